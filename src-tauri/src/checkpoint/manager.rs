@@ -7,6 +7,8 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::stream_json::{parse_line, ContentBlock, StreamEvent};
+
 use super::{
     storage::{self, CheckpointStorage},
     Checkpoint, CheckpointMetadata, CheckpointPaths, CheckpointResult, CheckpointStrategy,
@@ -66,19 +68,9 @@ impl CheckpointManager {
         messages.push(jsonl_message.clone());
 
         // Parse message to check for tool usage
-        if let Ok(msg) = serde_json::from_str::<serde_json::Value>(&jsonl_message) {
-            if let Some(content) = msg.get("message").and_then(|m| m.get("content")) {
-                if let Some(content_array) = content.as_array() {
-                    for item in content_array {
-                        if item.get("type").and_then(|t| t.as_str()) == Some("tool_use") {
-                            if let Some(tool_name) = item.get("name").and_then(|n| n.as_str()) {
-                                if let Some(input) = item.get("input") {
-                                    self.track_tool_operation(tool_name, input).await?;
-                                }
-                            }
-                        }
-                    }
-                }
+        if let Some(msg) = parse_line(&jsonl_message) {
+            for (tool_name, input) in msg.tool_uses() {
+                self.track_tool_operation(tool_name, input).await?;
             }
         }
 
@@ -313,85 +305,36 @@ impl CheckpointManager {
 
         // Iterate through messages in reverse to find the last user prompt
         for msg_str in messages.iter().rev() {
-            if let Ok(msg) = serde_json::from_str::<serde_json::Value>(msg_str) {
-                // Check for user message
-                if msg.get("type").and_then(|t| t.as_str()) == Some("user") {
-                    if let Some(content) = msg
-                        .get("message")
-                        .and_then(|m| m.get("content"))
-                        .and_then(|c| c.as_array())
-                    {
-                        for item in content {
-                            if item.get("type").and_then(|t| t.as_str()) == Some("text") {
-                                if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
-                                    user_prompt = text.to_string();
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                }
-
-                // Extract model info
-                if let Some(model) = msg.get("model").and_then(|m| m.as_str()) {
-                    model_used = model.to_string();
-                }
+            let Some(msg) = parse_line(msg_str) else {
+                continue;
+            };
 
-                // Also check for model in message.model (assistant messages)
-                if let Some(message) = msg.get("message") {
-                    if let Some(model) = message.get("model").and_then(|m| m.as_str()) {
-                        model_used = model.to_string();
-                    }
+            // Check for user message
+            if let StreamEvent::User(user) = &msg.event {
+                let text = user.message.content.iter().find_map(|block| match block {
+                    ContentBlock::Text { text } => Some(text),
+                    _ => None,
+                });
+                if let Some(text) = text {
+                    user_prompt = text.clone();
                 }
+            }
 
-                // Count tokens - check both top-level and nested usage
-                // First check for usage in message.usage (assistant messages)
-                if let Some(message) = msg.get("message") {
-                    if let Some(usage) = message.get("usage") {
-                        if let Some(input) = usage.get("input_tokens").and_then(|t| t.as_u64()) {
-                            total_tokens += input;
-                        }
-                        if let Some(output) = usage.get("output_tokens").and_then(|t| t.as_u64()) {
-                            total_tokens += output;
-                        }
-                        // Also count cache tokens
-                        if let Some(cache_creation) = usage
-                            .get("cache_creation_input_tokens")
-                            .and_then(|t| t.as_u64())
-                        {
-                            total_tokens += cache_creation;
-                        }
-                        if let Some(cache_read) = usage
-                            .get("cache_read_input_tokens")
-                            .and_then(|t| t.as_u64())
-                        {
-                            total_tokens += cache_read;
-                        }
-                    }
-                }
+            // Extract model info from the init line or assistant messages
+            let model = match &msg.event {
+                StreamEvent::System(system) => system.model.as_deref(),
+                _ => msg.message().and_then(|m| m.model.as_deref()),
+            };
+            if let Some(model) = model {
+                model_used = model.to_string();
+            }
 
-                // Then check for top-level usage (result messages)
-                if let Some(usage) = msg.get("usage") {
-                    if let Some(input) = usage.get("input_tokens").and_then(|t| t.as_u64()) {
-                        total_tokens += input;
-                    }
-                    if let Some(output) = usage.get("output_tokens").and_then(|t| t.as_u64()) {
-                        total_tokens += output;
-                    }
-                    // Also count cache tokens
-                    if let Some(cache_creation) = usage
-                        .get("cache_creation_input_tokens")
-                        .and_then(|t| t.as_u64())
-                    {
-                        total_tokens += cache_creation;
-                    }
-                    if let Some(cache_read) = usage
-                        .get("cache_read_input_tokens")
-                        .and_then(|t| t.as_u64())
-                    {
-                        total_tokens += cache_read;
-                    }
-                }
+            // Count tokens of messages and result lines, including cache tokens
+            if let Some(usage) = msg.usage() {
+                total_tokens += usage.input_tokens
+                    + usage.output_tokens
+                    + usage.cache_creation_input_tokens
+                    + usage.cache_read_input_tokens;
             }
         }
 
@@ -692,56 +635,25 @@ impl CheckpointManager {
             CheckpointStrategy::Manual => false,
             CheckpointStrategy::PerPrompt => {
                 // Check if message is a user prompt
-                if let Ok(msg) = serde_json::from_str::<serde_json::Value>(message) {
-                    msg.get("type").and_then(|t| t.as_str()) == Some("user")
-                } else {
-                    false
-                }
+                matches!(
+                    parse_line(message).map(|msg| msg.event),
+                    Some(StreamEvent::User(_))
+                )
             }
             CheckpointStrategy::PerToolUse => {
                 // Check if message contains tool use
-                if let Ok(msg) = serde_json::from_str::<serde_json::Value>(message) {
-                    if let Some(content) = msg
-                        .get("message")
-                        .and_then(|m| m.get("content"))
-                        .and_then(|c| c.as_array())
-                    {
-                        content.iter().any(|item| {
-                            item.get("type").and_then(|t| t.as_str()) == Some("tool_use")
-                        })
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                }
+                parse_line(message).is_some_and(|msg| msg.tool_uses().next().is_some())
             }
             CheckpointStrategy::Smart => {
                 // Smart strategy: checkpoint after destructive operations
-                if let Ok(msg) = serde_json::from_str::<serde_json::Value>(message) {
-                    if let Some(content) = msg
-                        .get("message")
-                        .and_then(|m| m.get("content"))
-                        .and_then(|c| c.as_array())
-                    {
-                        content.iter().any(|item| {
-                            if item.get("type").and_then(|t| t.as_str()) == Some("tool_use") {
-                                let tool_name =
-                                    item.get("name").and_then(|n| n.as_str()).unwrap_or("");
-                                matches!(
-                                    tool_name.to_lowercase().as_str(),
-                                    "write" | "edit" | "multiedit" | "bash" | "rm" | "delete"
-                                )
-                            } else {
-                                false
-                            }
-                        })
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                }
+                parse_line(message).is_some_and(|msg| {
+                    msg.tool_uses().any(|(tool_name, _)| {
+                        matches!(
+                            tool_name.to_lowercase().as_str(),
+                            "write" | "edit" | "multiedit" | "bash" | "rm" | "delete"
+                        )
+                    })
+                })
             }
         }
     }
@@ -0,0 +1,500 @@
+use log::{error, info, warn};
+use rusqlite::{params, Connection, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::Semaphore;
+
+use crate::commands::agents::{
+    execute_agent_with_context, get_agent, get_agent_run_with_metrics, wait_for_agent_run,
+    map_agent_run_row, AgentDb, AgentRun, AgentRunContext, AGENT_RUN_COLUMNS,
+};
+use crate::process::ProcessRegistryState;
+
+/// Concurrency used when the request doesn't set one
+const DEFAULT_BATCH_CONCURRENCY: usize = 4;
+
+/// Upper bound on concurrent runs in one batch
+const MAX_BATCH_CONCURRENCY: usize = 16;
+
+/// Request to run one agent and task across many projects
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentBatchRequest {
+    pub agent_id: i64,
+    pub task: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub project_paths: Vec<String>,
+    /// Glob whose matching directories are added to `project_paths`
+    #[serde(default)]
+    pub project_glob: Option<String>,
+    #[serde(default)]
+    pub max_concurrency: Option<usize>,
+    /// Values for the agent's declared template parameters, shared by every project
+    #[serde(default)]
+    pub parameters: Option<HashMap<String, JsonValue>>,
+}
+
+/// A batch of runs sharing one agent and task
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentBatch {
+    pub id: String,
+    pub agent_id: i64,
+    pub agent_name: String,
+    pub task: String,
+    pub model: Option<String>,
+    pub max_concurrency: i64,
+    pub status: String, // 'running', 'completed', 'cancelled'
+    pub created_at: String,
+    pub completed_at: Option<String>,
+    pub parameters: Option<HashMap<String, JsonValue>>,
+}
+
+/// Outcome of a batch run in one project
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchProjectSummary {
+    pub project_path: String,
+    pub run_id: i64,
+    pub status: String,
+    pub session_id: String,
+    pub cost_usd: Option<f64>,
+    pub duration_ms: Option<i64>,
+    pub total_tokens: Option<i64>,
+}
+
+/// Aggregated view of a batch
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentBatchSummary {
+    #[serde(flatten)]
+    pub batch: AgentBatch,
+    pub total: usize,
+    pub pending: usize,
+    pub running: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+    pub total_cost_usd: f64,
+    pub total_duration_ms: i64,
+    pub total_tokens: i64,
+    pub projects: Vec<BatchProjectSummary>,
+}
+
+const BATCH_COLUMNS: &str =
+    "id, agent_id, agent_name, task, model, max_concurrency, status, created_at, completed_at, parameters";
+
+fn map_batch_row(row: &rusqlite::Row) -> rusqlite::Result<AgentBatch> {
+    Ok(AgentBatch {
+        id: row.get(0)?,
+        agent_id: row.get(1)?,
+        agent_name: row.get(2)?,
+        task: row.get(3)?,
+        model: row.get(4)?,
+        max_concurrency: row.get(5)?,
+        status: row.get(6)?,
+        created_at: row.get(7)?,
+        completed_at: row.get(8)?,
+        parameters: crate::commands::agent_parameters::values_from_json(row.get(9)?),
+    })
+}
+
+/// Create the batch table
+pub fn init_batch_tables(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_batches (
+            id TEXT PRIMARY KEY,
+            agent_id INTEGER NOT NULL,
+            agent_name TEXT NOT NULL,
+            task TEXT NOT NULL,
+            model TEXT,
+            max_concurrency INTEGER NOT NULL,
+            status TEXT NOT NULL DEFAULT 'running',
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            completed_at TEXT,
+            parameters TEXT
+        )",
+        [],
+    )?;
+
+    // Queued runs of a batch interrupted by shutdown will never start
+    conn.execute(
+        "UPDATE agent_runs SET status = 'cancelled', completed_at = CURRENT_TIMESTAMP
+         WHERE status = 'pending' AND batch_id IN (SELECT id FROM agent_batches WHERE status = 'running')",
+        [],
+    )?;
+    conn.execute(
+        "UPDATE agent_batches SET status = 'completed', completed_at = CURRENT_TIMESTAMP WHERE status = 'running'",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Combine explicit project paths with directories matching the glob, without duplicates
+fn resolve_project_paths(request: &AgentBatchRequest) -> Result<Vec<String>, String> {
+    let mut paths: Vec<String> = Vec::new();
+    let mut push = |path: String| {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    };
+
+    for path in &request.project_paths {
+        if !std::path::Path::new(path).is_dir() {
+            return Err(format!("Project path does not exist: {}", path));
+        }
+        push(path.clone());
+    }
+
+    if let Some(pattern) = request.project_glob.as_deref().filter(|p| !p.trim().is_empty()) {
+        let entries = glob::glob(pattern).map_err(|e| format!("Invalid project glob: {}", e))?;
+        for entry in entries.flatten() {
+            if entry.is_dir() {
+                push(entry.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    if paths.is_empty() {
+        return Err("No project directories to run the batch in".to_string());
+    }
+    Ok(paths)
+}
+
+/// Run an agent across many projects with bounded concurrency; returns the batch ID
+#[tauri::command]
+pub async fn execute_agent_batch(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    request: AgentBatchRequest,
+) -> Result<String, String> {
+    let agent = get_agent(db.clone(), request.agent_id).await?;
+    let project_paths = resolve_project_paths(&request)?;
+    crate::commands::agent_parameters::check_values(agent.parameters.as_deref(), request.parameters.as_ref())?;
+    let parameters = crate::commands::agent_parameters::values_to_json(request.parameters.as_ref())?;
+    let max_concurrency = request
+        .max_concurrency
+        .unwrap_or(DEFAULT_BATCH_CONCURRENCY)
+        .clamp(1, MAX_BATCH_CONCURRENCY);
+    let batch_id = uuid::Uuid::new_v4().to_string();
+    let model = request.model.clone().unwrap_or_else(|| agent.model.clone());
+
+    // One pending row per project, so the whole batch is visible before any run starts
+    let queued: Vec<(i64, String)> = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO agent_batches (id, agent_id, agent_name, task, model, max_concurrency, parameters) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![batch_id, request.agent_id, agent.name, request.task, request.model, max_concurrency as i64, parameters],
+        )
+        .map_err(|e| e.to_string())?;
+
+        let mut queued = Vec::new();
+        for project_path in project_paths {
+            conn.execute(
+                "INSERT INTO agent_runs (agent_id, agent_name, agent_icon, task, model, project_path, session_id, status, batch_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, '', 'pending', ?7)",
+                params![request.agent_id, agent.name, agent.icon, request.task, model, project_path, batch_id],
+            )
+            .map_err(|e| e.to_string())?;
+            queued.push((conn.last_insert_rowid(), project_path));
+        }
+        queued
+    };
+
+    info!(
+        "Starting batch {} of agent '{}' across {} projects ({} at a time)",
+        batch_id,
+        agent.name,
+        queued.len(),
+        max_concurrency
+    );
+
+    let semaphore = Arc::new(Semaphore::new(max_concurrency));
+    let mut workers = Vec::new();
+    for (run_id, project_path) in queued {
+        let app = app.clone();
+        let semaphore = semaphore.clone();
+        let batch_id = batch_id.clone();
+        let request = request.clone();
+        workers.push(tauri::async_runtime::spawn(async move {
+            let Ok(_permit) = semaphore.acquire_owned().await else {
+                return;
+            };
+            run_batch_item(&app, &batch_id, run_id, project_path, &request).await;
+        }));
+    }
+
+    let app_for_batch = app.clone();
+    let batch_id_for_task = batch_id.clone();
+    tauri::async_runtime::spawn(async move {
+        for worker in workers {
+            let _ = worker.await;
+        }
+
+        {
+            let db = app_for_batch.state::<AgentDb>();
+            if let Ok(conn) = db.0.lock() {
+                let _ = conn.execute(
+                    "UPDATE agent_batches SET status = 'completed', completed_at = CURRENT_TIMESTAMP WHERE id = ?1 AND status = 'running'",
+                    params![batch_id_for_task],
+                );
+            };
+        }
+
+        match get_agent_batch_summary(app_for_batch.state::<AgentDb>(), batch_id_for_task.clone()).await {
+            Ok(summary) => {
+                info!(
+                    "Batch {} finished: {} completed, {} failed",
+                    batch_id_for_task, summary.completed, summary.failed
+                );
+                let _ = app_for_batch.emit(&format!("agent-batch-complete:{}", batch_id_for_task), &summary);
+            }
+            Err(e) => error!("Failed to summarize batch {}: {}", batch_id_for_task, e),
+        }
+    });
+
+    Ok(batch_id)
+}
+
+/// Start the queued run for one project and wait for it to finish
+async fn run_batch_item(
+    app: &AppHandle,
+    batch_id: &str,
+    run_id: i64,
+    project_path: String,
+    request: &AgentBatchRequest,
+) {
+    // Cancelled while waiting for a slot
+    let still_pending = {
+        let db = app.state::<AgentDb>();
+        let Ok(conn) = db.0.lock() else { return };
+        conn.query_row(
+            "SELECT status = 'pending' FROM agent_runs WHERE id = ?1",
+            params![run_id],
+            |row| row.get::<_, bool>(0),
+        )
+        .unwrap_or(false)
+    };
+    if !still_pending {
+        return;
+    }
+
+    let result = execute_agent_with_context(
+        app.clone(),
+        request.agent_id,
+        project_path.clone(),
+        request.task.clone(),
+        request.model.clone(),
+        AgentRunContext {
+            batch_id: Some(batch_id.to_string()),
+            parameters: request.parameters.clone(),
+            run_id: Some(run_id),
+            ..Default::default()
+        },
+        app.state::<AgentDb>(),
+        app.state::<ProcessRegistryState>(),
+    )
+    .await;
+
+    // Runs that stall are failed by `wait_for_agent_run`; anything else that goes wrong
+    // fails the item here, so the batch never waits on it
+    let outcome = match result {
+        Ok(_) => wait_for_agent_run(app, run_id).await.map(|_| ()),
+        Err(e) => Err(format!("failed to start: {}", e)),
+    };
+    if let Err(e) = outcome {
+        warn!("Batch {} run in {}: {}", batch_id, project_path, e);
+        let db = app.state::<AgentDb>();
+        if let Ok(conn) = db.0.lock() {
+            let _ = conn.execute(
+                "UPDATE agent_runs SET status = 'failed', completed_at = CURRENT_TIMESTAMP
+                 WHERE id = ?1 AND status IN ('pending', 'running')",
+                params![run_id],
+            );
+        };
+    }
+}
+
+/// List batches, most recent first
+#[tauri::command]
+pub async fn list_agent_batches(db: State<'_, AgentDb>) -> Result<Vec<AgentBatch>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM agent_batches ORDER BY created_at DESC",
+            BATCH_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let batches = stmt
+        .query_map([], map_batch_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(batches)
+}
+
+/// Summarize status, cost and duration per project for a batch
+#[tauri::command]
+pub async fn get_agent_batch_summary(
+    db: State<'_, AgentDb>,
+    batch_id: String,
+) -> Result<AgentBatchSummary, String> {
+    let (batch, runs): (AgentBatch, Vec<AgentRun>) = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let batch = conn
+            .query_row(
+                &format!("SELECT {} FROM agent_batches WHERE id = ?1", BATCH_COLUMNS),
+                params![batch_id],
+                map_batch_row,
+            )
+            .map_err(|e| e.to_string())?;
+
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM agent_runs WHERE batch_id = ?1 ORDER BY id",
+                AGENT_RUN_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let runs = stmt
+            .query_map(params![batch_id], map_agent_run_row)
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        (batch, runs)
+    };
+
+    let mut summary = AgentBatchSummary {
+        batch,
+        total: runs.len(),
+        pending: 0,
+        running: 0,
+        completed: 0,
+        failed: 0,
+        cancelled: 0,
+        total_cost_usd: 0.0,
+        total_duration_ms: 0,
+        total_tokens: 0,
+        projects: Vec::with_capacity(runs.len()),
+    };
+
+    for run in runs {
+        match run.status.as_str() {
+            "pending" => summary.pending += 1,
+            "running" => summary.running += 1,
+            "completed" => summary.completed += 1,
+            "cancelled" => summary.cancelled += 1,
+            _ => summary.failed += 1,
+        }
+
+        let run_id = run.id.unwrap_or_default();
+        let project_path = run.project_path.clone();
+        let status = run.status.clone();
+        let session_id = run.session_id.clone();
+        let metrics = if session_id.is_empty() {
+            None
+        } else {
+            get_agent_run_with_metrics(run).await.metrics
+        };
+
+        let cost_usd = metrics.as_ref().and_then(|m| m.cost_usd);
+        let duration_ms = metrics.as_ref().and_then(|m| m.duration_ms);
+        let total_tokens = metrics.as_ref().and_then(|m| m.total_tokens);
+        summary.total_cost_usd += cost_usd.unwrap_or(0.0);
+        summary.total_duration_ms += duration_ms.unwrap_or(0);
+        summary.total_tokens += total_tokens.unwrap_or(0);
+
+        summary.projects.push(BatchProjectSummary {
+            project_path,
+            run_id,
+            status,
+            session_id,
+            cost_usd,
+            duration_ms,
+            total_tokens,
+        });
+    }
+
+    Ok(summary)
+}
+
+/// Cancel a batch: queued runs never start and running ones are killed
+#[tauri::command]
+pub async fn cancel_agent_batch(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    registry: State<'_, ProcessRegistryState>,
+    batch_id: String,
+) -> Result<(), String> {
+    let running: Vec<i64> = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE agent_runs SET status = 'cancelled', completed_at = CURRENT_TIMESTAMP WHERE batch_id = ?1 AND status = 'pending'",
+            params![batch_id],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE agent_batches SET status = 'cancelled', completed_at = CURRENT_TIMESTAMP WHERE id = ?1 AND status = 'running'",
+            params![batch_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+        let mut stmt = conn
+            .prepare("SELECT id FROM agent_runs WHERE batch_id = ?1 AND status = 'running'")
+            .map_err(|e| e.to_string())?;
+        let ids = stmt
+            .query_map(params![batch_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        ids
+    };
+
+    for run_id in running {
+        if let Err(e) = crate::commands::agents::kill_agent_session(
+            app.clone(),
+            db.clone(),
+            registry.clone(),
+            run_id,
+        )
+        .await
+        {
+            warn!("Failed to kill batch run {}: {}", run_id, e);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_project_paths() {
+        let root = std::env::temp_dir().join(format!("claudia-batch-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("repo-a")).unwrap();
+        std::fs::create_dir_all(root.join("repo-b")).unwrap();
+        std::fs::write(root.join("notes.txt"), "").unwrap();
+
+        let repo_a = root.join("repo-a").to_string_lossy().to_string();
+        let request = AgentBatchRequest {
+            agent_id: 1,
+            task: "test".to_string(),
+            model: None,
+            project_paths: vec![repo_a.clone()],
+            project_glob: Some(format!("{}/*", root.to_string_lossy())),
+            max_concurrency: None,
+            parameters: None,
+        };
+
+        let paths = resolve_project_paths(&request).unwrap();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0], repo_a);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,254 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Type of a declared agent parameter
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentParameterType {
+    #[default]
+    String,
+    Number,
+    Boolean,
+    /// One of `options`
+    Enum,
+    /// A filesystem path, relative paths resolve against the project
+    Path,
+}
+
+/// A parameter an agent declares for its system prompt and task template
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AgentParameter {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub param_type: AgentParameterType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<JsonValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    /// Allowed values for `enum` parameters
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+}
+
+fn placeholder_regex() -> &'static Regex {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    PLACEHOLDER.get_or_init(|| {
+        Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").expect("valid placeholder regex")
+    })
+}
+
+fn name_regex() -> &'static Regex {
+    static NAME: OnceLock<Regex> = OnceLock::new();
+    NAME.get_or_init(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").expect("valid name regex"))
+}
+
+/// Parse the JSON stored in `agents.parameters`
+pub fn parse_parameters(json: Option<&str>) -> Result<Vec<AgentParameter>, String> {
+    match json.filter(|j| !j.trim().is_empty()) {
+        Some(json) => serde_json::from_str(json).map_err(|e| format!("Invalid agent parameters: {}", e)),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Check parameter declarations before they are stored
+pub fn validate_declarations(parameters: &[AgentParameter]) -> Result<(), String> {
+    let mut seen = Vec::new();
+
+    for parameter in parameters {
+        if !name_regex().is_match(&parameter.name) {
+            return Err(format!("Invalid parameter name '{}'", parameter.name));
+        }
+        if seen.contains(&&parameter.name) {
+            return Err(format!("Duplicate parameter '{}'", parameter.name));
+        }
+        seen.push(&parameter.name);
+
+        if parameter.param_type == AgentParameterType::Enum && parameter.options.is_empty() {
+            return Err(format!("Enum parameter '{}' needs options", parameter.name));
+        }
+        if let Some(default) = &parameter.default {
+            coerce_value(parameter, default, None)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a supplied value to its template text, checking it against the declared type.
+/// Relative paths are resolved against `project_path` when one is given.
+fn coerce_value(parameter: &AgentParameter, value: &JsonValue, project_path: Option<&Path>) -> Result<String, String> {
+    let invalid = |expected: &str| {
+        format!(
+            "Parameter '{}' must be {}, got {}",
+            parameter.name, expected, value
+        )
+    };
+
+    match parameter.param_type {
+        AgentParameterType::String => match value {
+            JsonValue::String(s) => Ok(s.clone()),
+            JsonValue::Number(n) => Ok(n.to_string()),
+            _ => Err(invalid("a string")),
+        },
+        AgentParameterType::Path => {
+            let path = match value {
+                JsonValue::String(s) => s.clone(),
+                JsonValue::Number(n) => n.to_string(),
+                _ => return Err(invalid("a path")),
+            };
+            match project_path {
+                Some(project) if Path::new(&path).is_relative() => {
+                    Ok(project.join(path).to_string_lossy().into_owned())
+                }
+                _ => Ok(path),
+            }
+        }
+        AgentParameterType::Number => match value {
+            JsonValue::Number(n) => Ok(n.to_string()),
+            JsonValue::String(s) if s.trim().parse::<f64>().is_ok() => Ok(s.trim().to_string()),
+            _ => Err(invalid("a number")),
+        },
+        AgentParameterType::Boolean => match value {
+            JsonValue::Bool(b) => Ok(b.to_string()),
+            JsonValue::String(s) if s == "true" || s == "false" => Ok(s.clone()),
+            _ => Err(invalid("a boolean")),
+        },
+        AgentParameterType::Enum => match value {
+            JsonValue::String(s) if parameter.options.contains(s) => Ok(s.clone()),
+            _ => Err(invalid(&format!("one of {}", parameter.options.join(", ")))),
+        },
+    }
+}
+
+/// Validate supplied values against the declarations and fill in defaults.
+///
+/// Returns the resolved values keyed by parameter name, in a stable order, with
+/// relative paths resolved against `project_path`.
+pub fn resolve_parameters(
+    declared: &[AgentParameter],
+    supplied: &HashMap<String, JsonValue>,
+    project_path: &Path,
+) -> Result<BTreeMap<String, String>, String> {
+    if let Some(unknown) = supplied
+        .keys()
+        .find(|name| !declared.iter().any(|p| &p.name == *name))
+    {
+        return Err(format!("Unknown parameter '{}'", unknown));
+    }
+
+    let mut resolved = BTreeMap::new();
+    for parameter in declared {
+        let value = supplied
+            .get(&parameter.name)
+            .filter(|v| !v.is_null() && v.as_str() != Some(""))
+            .or(parameter.default.as_ref());
+
+        match value {
+            Some(value) => {
+                resolved.insert(parameter.name.clone(), coerce_value(parameter, value, Some(project_path))?);
+            }
+            None if parameter.required => {
+                return Err(format!("Missing required parameter '{}'", parameter.name));
+            }
+            None => {
+                resolved.insert(parameter.name.clone(), String::new());
+            }
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// Check values saved for later runs against the declarations, so a schedule, batch or
+/// pipeline step missing a required value is rejected when it's saved
+pub fn check_values(
+    declared_json: Option<&str>,
+    supplied: Option<&HashMap<String, JsonValue>>,
+) -> Result<(), String> {
+    let declared = parse_parameters(declared_json)?;
+    if declared.is_empty() && supplied.is_none() {
+        return Ok(());
+    }
+    resolve_parameters(&declared, &supplied.cloned().unwrap_or_default(), Path::new("")).map(|_| ())
+}
+
+/// Serialize saved parameter values for a TEXT column
+pub fn values_to_json(values: Option<&HashMap<String, JsonValue>>) -> Result<Option<String>, String> {
+    values
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| format!("Failed to serialize parameters: {}", e))
+}
+
+/// Parse saved parameter values from a TEXT column
+pub fn values_from_json(json: Option<String>) -> Option<HashMap<String, JsonValue>> {
+    json.and_then(|json| serde_json::from_str(&json).ok())
+}
+
+/// Replace `{{name}}` placeholders with resolved values; unknown placeholders are left as-is
+pub fn substitute(template: &str, values: &BTreeMap<String, String>) -> String {
+    placeholder_regex()
+        .replace_all(template, |caps: &regex::Captures| {
+            values
+                .get(&caps[1])
+                .cloned()
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn declared() -> Vec<AgentParameter> {
+        serde_json::from_value(json!([
+            { "name": "target_dir", "type": "path", "required": true },
+            { "name": "severity", "type": "enum", "options": ["low", "high"], "default": "low" },
+            { "name": "max_files", "type": "number" }
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_and_substitute() {
+        let project = Path::new("/work/app");
+        let supplied = HashMap::from([("target_dir".to_string(), json!("src"))]);
+        let resolved = resolve_parameters(&declared(), &supplied, project).unwrap();
+
+        assert_eq!(
+            substitute("Scan {{ target_dir }} at {{severity}} {{other}}", &resolved),
+            format!("Scan {} at low {{{{other}}}}", project.join("src").display())
+        );
+
+        let absolute = HashMap::from([("target_dir".to_string(), json!("/srv/data"))]);
+        let resolved = resolve_parameters(&declared(), &absolute, project).unwrap();
+        assert_eq!(resolved["target_dir"], "/srv/data");
+    }
+
+    #[test]
+    fn test_resolve_rejects_invalid_values() {
+        let declared = declared();
+        let project = Path::new("/work/app");
+        assert!(resolve_parameters(&declared, &HashMap::new(), project).is_err());
+
+        let bad_enum = HashMap::from([
+            ("target_dir".to_string(), json!("src")),
+            ("severity".to_string(), json!("medium")),
+        ]);
+        assert!(resolve_parameters(&declared, &bad_enum, project).is_err());
+
+        let unknown = HashMap::from([
+            ("target_dir".to_string(), json!("src")),
+            ("verbose".to_string(), json!(true)),
+        ]);
+        assert!(resolve_parameters(&declared, &unknown, project).is_err());
+    }
+}
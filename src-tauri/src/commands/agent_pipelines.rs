@@ -0,0 +1,591 @@
+use log::{error, info, warn};
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::agent_results::load_run_result;
+use crate::commands::agents::{
+    execute_agent_with_context, get_agent_run, wait_for_agent_run, AgentDb, AgentRun,
+    AgentRunContext,
+};
+use crate::process::ProcessRegistryState;
+
+/// Placeholder in a step task that is replaced with the step input
+const INPUT_PLACEHOLDER: &str = "{{input}}";
+
+/// What a pipeline step receives from the step before it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StepInput {
+    /// Nothing; the task runs as written
+    #[default]
+    None,
+    /// The previous run's final assistant message
+    PreviousOutput,
+    /// A file the previous step wrote, relative to the project path
+    Artifact { path: String },
+}
+
+/// When a pipeline step runs, based on the outcome of the last step that ran
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StepCondition {
+    #[default]
+    OnSuccess,
+    OnFailure,
+    Always,
+}
+
+/// One agent invocation in a pipeline
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PipelineStep {
+    pub agent_id: i64,
+    /// Task to run instead of the agent's default task; `{{input}}` marks where the input goes
+    #[serde(default)]
+    pub task: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub input: StepInput,
+    #[serde(default)]
+    pub run_if: StepCondition,
+    /// Values for the agent's declared template parameters
+    #[serde(default)]
+    pub parameters: Option<HashMap<String, JsonValue>>,
+}
+
+/// A named chain of agent steps
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentPipeline {
+    pub id: Option<i64>,
+    pub name: String,
+    pub description: Option<String>,
+    pub steps: Vec<PipelineStep>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A single execution of a pipeline
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PipelineRun {
+    pub id: Option<i64>,
+    pub pipeline_id: i64,
+    pub pipeline_name: String,
+    pub project_path: String,
+    pub status: String, // 'running', 'completed', 'failed', 'cancelled'
+    pub current_step: Option<i64>,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub completed_at: Option<String>,
+}
+
+/// A pipeline run together with the agent runs of its steps
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PipelineRunWithSteps {
+    #[serde(flatten)]
+    pub run: PipelineRun,
+    pub step_runs: Vec<AgentRun>,
+}
+
+/// Progress event emitted as pipeline steps start and finish
+#[derive(Debug, Serialize, Clone)]
+struct PipelineStepEvent {
+    pipeline_run_id: i64,
+    step: usize,
+    status: String, // 'started', 'completed', 'failed', 'skipped'
+    agent_run_id: Option<i64>,
+}
+
+const PIPELINE_COLUMNS: &str = "id, name, description, steps, created_at, updated_at";
+const PIPELINE_RUN_COLUMNS: &str = "id, pipeline_id, pipeline_name, project_path, status, current_step, error, created_at, completed_at";
+
+fn map_pipeline_row(row: &rusqlite::Row) -> rusqlite::Result<AgentPipeline> {
+    let steps: String = row.get(3)?;
+    Ok(AgentPipeline {
+        id: Some(row.get(0)?),
+        name: row.get(1)?,
+        description: row.get(2)?,
+        steps: serde_json::from_str(&steps).unwrap_or_default(),
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+fn map_pipeline_run_row(row: &rusqlite::Row) -> rusqlite::Result<PipelineRun> {
+    Ok(PipelineRun {
+        id: Some(row.get(0)?),
+        pipeline_id: row.get(1)?,
+        pipeline_name: row.get(2)?,
+        project_path: row.get(3)?,
+        status: row.get(4)?,
+        current_step: row.get(5)?,
+        error: row.get(6)?,
+        created_at: row.get(7)?,
+        completed_at: row.get(8)?,
+    })
+}
+
+/// Create the pipeline definition and run tables
+pub fn init_pipeline_tables(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_pipelines (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            description TEXT,
+            steps TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_pipeline_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            pipeline_id INTEGER NOT NULL,
+            pipeline_name TEXT NOT NULL,
+            project_path TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'running',
+            current_step INTEGER,
+            error TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            completed_at TEXT
+        )",
+        [],
+    )?;
+
+    // Runs left 'running' by a previous app session can never finish
+    conn.execute(
+        "UPDATE agent_pipeline_runs SET status = 'failed', error = 'Interrupted by application shutdown', completed_at = CURRENT_TIMESTAMP WHERE status = 'running'",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn validate_steps(conn: &Connection, steps: &[PipelineStep]) -> Result<String, String> {
+    if steps.is_empty() {
+        return Err("A pipeline needs at least one step".to_string());
+    }
+    for (index, step) in steps.iter().enumerate() {
+        let declared: Option<String> = conn
+            .query_row(
+                "SELECT parameters FROM agents WHERE id = ?1",
+                params![step.agent_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Step {} refers to an unknown agent", index + 1))?;
+        super::agent_parameters::check_values(declared.as_deref(), step.parameters.as_ref())
+            .map_err(|e| format!("Step {}: {}", index + 1, e))?;
+    }
+    serde_json::to_string(steps).map_err(|e| format!("Failed to serialize steps: {}", e))
+}
+
+fn fetch_pipeline(conn: &Connection, id: i64) -> Result<AgentPipeline, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM agent_pipelines WHERE id = ?1", PIPELINE_COLUMNS),
+        params![id],
+        map_pipeline_row,
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn fetch_pipeline_run(conn: &Connection, id: i64) -> Result<PipelineRun, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM agent_pipeline_runs WHERE id = ?1", PIPELINE_RUN_COLUMNS),
+        params![id],
+        map_pipeline_run_row,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// List all pipelines
+#[tauri::command]
+pub async fn list_agent_pipelines(db: State<'_, AgentDb>) -> Result<Vec<AgentPipeline>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM agent_pipelines ORDER BY created_at DESC",
+            PIPELINE_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let pipelines = stmt
+        .query_map([], map_pipeline_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(pipelines)
+}
+
+/// Create a pipeline
+#[tauri::command]
+pub async fn create_agent_pipeline(
+    db: State<'_, AgentDb>,
+    name: String,
+    description: Option<String>,
+    steps: Vec<PipelineStep>,
+) -> Result<AgentPipeline, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let steps_json = validate_steps(&conn, &steps)?;
+
+    conn.execute(
+        "INSERT INTO agent_pipelines (name, description, steps) VALUES (?1, ?2, ?3)",
+        params![name, description, steps_json],
+    )
+    .map_err(|e| e.to_string())?;
+
+    fetch_pipeline(&conn, conn.last_insert_rowid())
+}
+
+/// Update a pipeline
+#[tauri::command]
+pub async fn update_agent_pipeline(
+    db: State<'_, AgentDb>,
+    id: i64,
+    name: String,
+    description: Option<String>,
+    steps: Vec<PipelineStep>,
+) -> Result<AgentPipeline, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let steps_json = validate_steps(&conn, &steps)?;
+
+    conn.execute(
+        "UPDATE agent_pipelines SET name = ?1, description = ?2, steps = ?3, updated_at = CURRENT_TIMESTAMP WHERE id = ?4",
+        params![name, description, steps_json, id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    fetch_pipeline(&conn, id)
+}
+
+/// Delete a pipeline; its past runs are kept
+#[tauri::command]
+pub async fn delete_agent_pipeline(db: State<'_, AgentDb>, id: i64) -> Result<(), String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM agent_pipelines WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Start a pipeline in a project; steps run one after another in the background
+#[tauri::command]
+pub async fn execute_agent_pipeline(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    pipeline_id: i64,
+    project_path: String,
+) -> Result<i64, String> {
+    let (pipeline, pipeline_run_id) = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let pipeline = fetch_pipeline(&conn, pipeline_id)?;
+        conn.execute(
+            "INSERT INTO agent_pipeline_runs (pipeline_id, pipeline_name, project_path) VALUES (?1, ?2, ?3)",
+            params![pipeline_id, pipeline.name, project_path],
+        )
+        .map_err(|e| e.to_string())?;
+        (pipeline, conn.last_insert_rowid())
+    };
+
+    info!("Starting pipeline '{}' as run {}", pipeline.name, pipeline_run_id);
+
+    tauri::async_runtime::spawn(async move {
+        let result = run_pipeline(&app, pipeline_run_id, &pipeline, &project_path).await;
+
+        let db = app.state::<AgentDb>();
+        let status = {
+            let Ok(conn) = db.0.lock() else {
+                error!("Failed to lock database to finish pipeline run {}", pipeline_run_id);
+                return;
+            };
+            let (status, error) = match &result {
+                Ok(status) => (status.clone(), None),
+                Err(e) => ("failed".to_string(), Some(e.clone())),
+            };
+            // A cancelled run keeps its status
+            let _ = conn.execute(
+                "UPDATE agent_pipeline_runs SET status = ?1, error = COALESCE(?2, error), completed_at = CURRENT_TIMESTAMP WHERE id = ?3 AND status = 'running'",
+                params![status, error, pipeline_run_id],
+            );
+            fetch_pipeline_run(&conn, pipeline_run_id)
+                .map(|run| run.status)
+                .unwrap_or(status)
+        };
+
+        info!("Pipeline run {} finished: {}", pipeline_run_id, status);
+        let _ = app.emit(&format!("pipeline-complete:{}", pipeline_run_id), &status);
+        let _ = app.emit("pipeline-complete", pipeline_run_id);
+    });
+
+    Ok(pipeline_run_id)
+}
+
+/// Run the pipeline steps in order, returning the final status
+async fn run_pipeline(
+    app: &AppHandle,
+    pipeline_run_id: i64,
+    pipeline: &AgentPipeline,
+    project_path: &str,
+) -> Result<String, String> {
+    let mut last_success: Option<bool> = None;
+    let mut last_output: Option<String> = None;
+    let mut last_run_id: Option<i64> = None;
+
+    for (index, step) in pipeline.steps.iter().enumerate() {
+        {
+            let db = app.state::<AgentDb>();
+            let conn = db.0.lock().map_err(|e| e.to_string())?;
+            if fetch_pipeline_run(&conn, pipeline_run_id)?.status == "cancelled" {
+                return Ok("cancelled".to_string());
+            }
+            conn.execute(
+                "UPDATE agent_pipeline_runs SET current_step = ?1 WHERE id = ?2",
+                params![index as i64, pipeline_run_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        // The first step always runs; later ones depend on the last step that ran
+        let should_run = match (step.run_if, last_success) {
+            (_, None) | (StepCondition::Always, _) => true,
+            (StepCondition::OnSuccess, Some(success)) => success,
+            (StepCondition::OnFailure, Some(success)) => !success,
+        };
+        if !should_run {
+            emit_step(app, pipeline_run_id, index, "skipped", None);
+            continue;
+        }
+
+        let (task, input) =
+            build_step_task(app, step, project_path, last_output.as_deref(), last_run_id).await?;
+        let agent_run_id = execute_agent_with_context(
+            app.clone(),
+            step.agent_id,
+            project_path.to_string(),
+            task,
+            step.model.clone(),
+            AgentRunContext {
+                pipeline_run_id: Some(pipeline_run_id),
+                pipeline_step: Some(index as i64),
+                parameters: step.parameters.clone(),
+                input,
+                ..Default::default()
+            },
+            app.state::<AgentDb>(),
+            app.state::<ProcessRegistryState>(),
+        )
+        .await;
+
+        let agent_run_id = match agent_run_id {
+            Ok(id) => id,
+            Err(e) => {
+                warn!("Pipeline run {} step {} failed to start: {}", pipeline_run_id, index, e);
+                emit_step(app, pipeline_run_id, index, "failed", None);
+                last_success = Some(false);
+                last_output = Some(e);
+                last_run_id = None;
+                continue;
+            }
+        };
+        emit_step(app, pipeline_run_id, index, "started", Some(agent_run_id));
+
+        let (success, output) = wait_for_step(app, agent_run_id).await?;
+        emit_step(
+            app,
+            pipeline_run_id,
+            index,
+            if success { "completed" } else { "failed" },
+            Some(agent_run_id),
+        );
+        last_success = Some(success);
+        last_output = output;
+        last_run_id = Some(agent_run_id);
+    }
+
+    Ok(if last_success.unwrap_or(false) {
+        "completed".to_string()
+    } else {
+        "failed".to_string()
+    })
+}
+
+fn emit_step(app: &AppHandle, pipeline_run_id: i64, step: usize, status: &str, agent_run_id: Option<i64>) {
+    let event = PipelineStepEvent {
+        pipeline_run_id,
+        step,
+        status: status.to_string(),
+        agent_run_id,
+    };
+    let _ = app.emit(&format!("pipeline-step:{}", pipeline_run_id), &event);
+}
+
+/// Resolve the step task and its input. The input is inserted only after the task's
+/// parameters are substituted, so placeholders in a previous step's output stay as written.
+async fn build_step_task(
+    app: &AppHandle,
+    step: &PipelineStep,
+    project_path: &str,
+    previous_output: Option<&str>,
+    previous_run_id: Option<i64>,
+) -> Result<(String, Option<String>), String> {
+    let task = match &step.task {
+        Some(task) if !task.trim().is_empty() => task.clone(),
+        _ => {
+            let db = app.state::<AgentDb>();
+            let conn = db.0.lock().map_err(|e| e.to_string())?;
+            conn.query_row(
+                "SELECT default_task FROM agents WHERE id = ?1",
+                params![step.agent_id],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .map_err(|e| e.to_string())?
+            .unwrap_or_default()
+        }
+    };
+
+    let input = match &step.input {
+        StepInput::None => return Ok((task, None)),
+        StepInput::PreviousOutput => previous_output.unwrap_or_default().to_string(),
+        StepInput::Artifact { path } => {
+            // A named artifact of the previous run wins over a project file of the same path
+            let named_artifact = match previous_run_id {
+                Some(run_id) => {
+                    let db = app.state::<AgentDb>();
+                    let conn = db.0.lock().map_err(|e| e.to_string())?;
+                    load_run_result(&conn, run_id)?.and_then(|result| {
+                        result
+                            .artifacts
+                            .into_iter()
+                            .find(|artifact| &artifact.name == path)
+                            .map(|artifact| std::path::PathBuf::from(artifact.path))
+                    })
+                }
+                None => None,
+            };
+            let artifact_path =
+                named_artifact.unwrap_or_else(|| std::path::Path::new(project_path).join(path));
+            tokio::fs::read_to_string(&artifact_path)
+                .await
+                .map_err(|e| format!("Failed to read artifact {}: {}", artifact_path.display(), e))?
+        }
+    };
+
+    Ok((task, Some(input)))
+}
+
+/// Put a step's input at the task's `{{input}}` placeholder, or after the task
+pub(crate) fn insert_input(task: &str, input: &str) -> String {
+    if task.contains(INPUT_PLACEHOLDER) {
+        task.replace(INPUT_PLACEHOLDER, input)
+    } else if task.trim().is_empty() {
+        input.to_string()
+    } else {
+        format!("{}\n\n{}", task, input)
+    }
+}
+
+/// Wait for an agent run to finish and return whether it succeeded and its final message
+async fn wait_for_step(app: &AppHandle, agent_run_id: i64) -> Result<(bool, Option<String>), String> {
+    let run = wait_for_agent_run(app, agent_run_id).await?;
+
+    let db = app.state::<AgentDb>();
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let result = load_run_result(&conn, agent_run_id)?;
+
+    let success = run.status == "completed"
+        && result
+            .as_ref()
+            .map(|r| !r.is_error && r.exit_code.unwrap_or(0) == 0)
+            .unwrap_or(true);
+    Ok((success, result.and_then(|r| r.result_message)))
+}
+
+/// List runs of a pipeline, most recent first
+#[tauri::command]
+pub async fn list_pipeline_runs(
+    db: State<'_, AgentDb>,
+    pipeline_id: Option<i64>,
+) -> Result<Vec<PipelineRun>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM agent_pipeline_runs WHERE ?1 IS NULL OR pipeline_id = ?1 ORDER BY created_at DESC",
+            PIPELINE_RUN_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let runs = stmt
+        .query_map(params![pipeline_id], map_pipeline_run_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(runs)
+}
+
+/// Get a pipeline run with the agent runs of its steps
+#[tauri::command]
+pub async fn get_pipeline_run(
+    db: State<'_, AgentDb>,
+    id: i64,
+) -> Result<PipelineRunWithSteps, String> {
+    let run_ids: Vec<i64> = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare("SELECT id FROM agent_runs WHERE pipeline_run_id = ?1 ORDER BY pipeline_step, id")
+            .map_err(|e| e.to_string())?;
+        let ids = stmt
+            .query_map(params![id], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        ids
+    };
+
+    let mut step_runs = Vec::new();
+    for run_id in run_ids {
+        step_runs.push(get_agent_run(db.clone(), run_id).await?);
+    }
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    Ok(PipelineRunWithSteps {
+        run: fetch_pipeline_run(&conn, id)?,
+        step_runs,
+    })
+}
+
+/// Cancel a pipeline run; the current step finishes but no further steps start
+#[tauri::command]
+pub async fn cancel_pipeline_run(db: State<'_, AgentDb>, id: i64) -> Result<(), String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE agent_pipeline_runs SET status = 'cancelled', completed_at = CURRENT_TIMESTAMP WHERE id = ?1 AND status = 'running'",
+        params![id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_input() {
+        assert_eq!(insert_input("Review: {{input}}", "diff"), "Review: diff");
+        assert_eq!(insert_input("Commit the changes", "ok"), "Commit the changes\n\nok");
+        assert_eq!(insert_input("", "ok"), "ok");
+
+        // Parameters are substituted first, leaving placeholders in the input alone
+        let resolved = std::collections::BTreeMap::from([("target".to_string(), "src".to_string())]);
+        let task = crate::commands::agent_parameters::substitute("Fix {{target}}: {{input}}", &resolved);
+        assert_eq!(insert_input(&task, "uses {{target}}"), "Fix src: uses {{target}}");
+    }
+}
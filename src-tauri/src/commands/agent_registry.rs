@@ -0,0 +1,709 @@
+use log::{info, warn};
+use rusqlite::{params, Connection, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::commands::agents::{Agent, AgentDb, GitHubAgentFile};
+
+/// Settings key under which the configured registries are stored
+const REGISTRIES_SETTING_KEY: &str = "agent_registries";
+
+/// Identifier of the built-in registry pointing at the upstream repository
+pub const DEFAULT_REGISTRY_ID: &str = "claudia-github";
+
+/// How long a cached registry listing is considered fresh
+const REGISTRY_CACHE_TTL_SECS: i64 = 60 * 60;
+
+/// Where a registry loads its agent listing from
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RegistrySource {
+    /// A directory in a GitHub repository, read through the contents API
+    Github {
+        owner: String,
+        repo: String,
+        path: String,
+        #[serde(default)]
+        branch: Option<String>,
+    },
+    /// A JSON index served over HTTPS
+    HttpIndex { url: String },
+    /// A local directory of `.claudia.json` files, or a local index JSON file
+    Local { path: String },
+}
+
+/// A configured agent registry
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentRegistry {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub source: RegistrySource,
+}
+
+/// An agent listed by a registry
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegistryAgentFile {
+    pub registry_id: String,
+    pub name: String,
+    pub path: String,
+    pub download_url: String,
+    pub size: i64,
+    pub sha: String,
+}
+
+/// An imported agent whose registry entry has changed since it was imported
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentUpdateInfo {
+    pub agent_id: i64,
+    pub agent_name: String,
+    pub registry_id: String,
+    pub path: String,
+    pub installed_sha: String,
+    pub latest_sha: String,
+    pub download_url: String,
+}
+
+/// Index format accepted from HTTPS and local index files
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RegistryIndex {
+    Wrapped { agents: Vec<RegistryIndexEntry> },
+    Bare(Vec<RegistryIndexEntry>),
+}
+
+#[derive(Debug, Deserialize)]
+struct RegistryIndexEntry {
+    name: String,
+    #[serde(default)]
+    path: Option<String>,
+    download_url: String,
+    #[serde(default)]
+    size: Option<i64>,
+    sha: String,
+}
+
+/// Represents the GitHub API response for directory contents
+#[derive(Debug, Deserialize)]
+struct GitHubApiResponse {
+    name: String,
+    path: String,
+    sha: String,
+    size: i64,
+    download_url: Option<String>,
+    #[serde(rename = "type")]
+    file_type: String,
+}
+
+impl AgentRegistry {
+    /// The registry that ships with the app
+    pub fn default_registry() -> Self {
+        Self {
+            id: DEFAULT_REGISTRY_ID.to_string(),
+            name: "Claudia (GitHub)".to_string(),
+            enabled: true,
+            source: RegistrySource::Github {
+                owner: "getAsterisk".to_string(),
+                repo: "claudia".to_string(),
+                path: "cc_agents".to_string(),
+                branch: None,
+            },
+        }
+    }
+}
+
+impl From<RegistryAgentFile> for GitHubAgentFile {
+    fn from(file: RegistryAgentFile) -> Self {
+        GitHubAgentFile {
+            name: file.name,
+            path: file.path,
+            download_url: file.download_url,
+            size: file.size,
+            sha: file.sha,
+        }
+    }
+}
+
+/// Create the tables used for registry caching and import tracking
+pub fn init_registry_tables(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_registry_cache (
+            registry_id TEXT PRIMARY KEY,
+            entries TEXT NOT NULL,
+            fetched_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Remembers where an imported agent came from so updates can be detected
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_registry_imports (
+            agent_id INTEGER PRIMARY KEY,
+            registry_id TEXT NOT NULL,
+            path TEXT NOT NULL,
+            sha TEXT NOT NULL,
+            imported_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (agent_id) REFERENCES agents(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Load the configured registries, falling back to the built-in one
+fn load_registries(conn: &Connection) -> Result<Vec<AgentRegistry>, String> {
+    match conn.query_row(
+        "SELECT value FROM app_settings WHERE key = ?1",
+        params![REGISTRIES_SETTING_KEY],
+        |row| row.get::<_, String>(0),
+    ) {
+        Ok(value) => serde_json::from_str(&value)
+            .map_err(|e| format!("Invalid agent registry settings: {}", e)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(vec![AgentRegistry::default_registry()]),
+        Err(e) => Err(format!("Failed to load agent registries: {}", e)),
+    }
+}
+
+fn find_registry(conn: &Connection, registry_id: &str) -> Result<AgentRegistry, String> {
+    load_registries(conn)?
+        .into_iter()
+        .find(|r| r.id == registry_id)
+        .ok_or_else(|| format!("Agent registry not found: {}", registry_id))
+}
+
+/// List the configured agent registries
+#[tauri::command]
+pub async fn list_agent_registries(db: State<'_, AgentDb>) -> Result<Vec<AgentRegistry>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_registries(&conn)
+}
+
+/// Replace the configured agent registries
+#[tauri::command]
+pub async fn save_agent_registries(
+    db: State<'_, AgentDb>,
+    registries: Vec<AgentRegistry>,
+) -> Result<(), String> {
+    let mut seen = std::collections::HashSet::new();
+    for registry in &registries {
+        if registry.id.trim().is_empty() {
+            return Err("Registry id cannot be empty".to_string());
+        }
+        if !seen.insert(registry.id.as_str()) {
+            return Err(format!("Duplicate registry id: {}", registry.id));
+        }
+        if let RegistrySource::HttpIndex { url } = &registry.source {
+            if !url.starts_with("https://") {
+                return Err(format!("Registry index must be served over HTTPS: {}", url));
+            }
+        }
+    }
+
+    let value = serde_json::to_string(&registries)
+        .map_err(|e| format!("Failed to serialize registries: {}", e))?;
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO app_settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = ?2",
+        params![REGISTRIES_SETTING_KEY, value],
+    )
+    .map_err(|e| format!("Failed to save agent registries: {}", e))?;
+
+    // Source definitions may have changed, so cached listings are stale
+    conn.execute("DELETE FROM agent_registry_cache", [])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Read a cached listing if it is younger than the TTL
+fn read_cache(conn: &Connection, registry_id: &str) -> Option<Vec<RegistryAgentFile>> {
+    let (entries, fetched_at): (String, String) = conn
+        .query_row(
+            "SELECT entries, fetched_at FROM agent_registry_cache WHERE registry_id = ?1",
+            params![registry_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok()?;
+
+    let fetched_at = chrono::DateTime::parse_from_rfc3339(&fetched_at).ok()?;
+    let age = chrono::Utc::now().signed_duration_since(fetched_at);
+    if age.num_seconds() > REGISTRY_CACHE_TTL_SECS {
+        return None;
+    }
+
+    serde_json::from_str(&entries).ok()
+}
+
+fn write_cache(conn: &Connection, registry_id: &str, files: &[RegistryAgentFile]) {
+    let entries = match serde_json::to_string(files) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Failed to serialize registry cache for {}: {}", registry_id, e);
+            return;
+        }
+    };
+    if let Err(e) = conn.execute(
+        "INSERT INTO agent_registry_cache (registry_id, entries, fetched_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(registry_id) DO UPDATE SET entries = ?2, fetched_at = ?3",
+        params![registry_id, entries, chrono::Utc::now().to_rfc3339()],
+    ) {
+        warn!("Failed to write registry cache for {}: {}", registry_id, e);
+    }
+}
+
+/// Hex-encoded SHA-256 of agent file content, used as the sha for local files
+fn content_sha(content: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content);
+    format!("{:x}", hasher.finalize())
+}
+
+fn file_url(path: &Path) -> String {
+    format!("file://{}", path.display())
+}
+
+/// Fetch the listing of a registry directly from its source
+async fn fetch_from_source(registry: &AgentRegistry) -> Result<Vec<RegistryAgentFile>, String> {
+    match &registry.source {
+        RegistrySource::Github {
+            owner,
+            repo,
+            path,
+            branch,
+        } => fetch_github_listing(&registry.id, owner, repo, path, branch.as_deref()).await,
+        RegistrySource::HttpIndex { url } => {
+            let client = reqwest::Client::new();
+            let response = client
+                .get(url)
+                .header("Accept", "application/json")
+                .header("User-Agent", "Claudia-App")
+                .send()
+                .await
+                .map_err(|e| format!("Failed to fetch registry index: {}", e))?;
+
+            if !response.status().is_success() {
+                return Err(format!(
+                    "Failed to fetch registry index: HTTP {}",
+                    response.status()
+                ));
+            }
+
+            let text = response
+                .text()
+                .await
+                .map_err(|e| format!("Failed to read registry index: {}", e))?;
+            let base = reqwest::Url::parse(url).map_err(|e| format!("Invalid index URL: {}", e))?;
+
+            parse_index(&registry.id, &text, |download_url| {
+                let resolved = base
+                    .join(download_url)
+                    .map_err(|e| format!("Invalid download URL {}: {}", download_url, e))?;
+                require_https(resolved.as_str())?;
+                Ok(resolved.to_string())
+            })
+        }
+        RegistrySource::Local { path } => {
+            let root = PathBuf::from(path);
+            if root.is_file() {
+                let text = std::fs::read_to_string(&root)
+                    .map_err(|e| format!("Failed to read registry index: {}", e))?;
+                let base_dir = root.parent().map(Path::to_path_buf).unwrap_or_default();
+                parse_index(&registry.id, &text, |download_url| {
+                    Ok(if download_url.contains("://") {
+                        download_url.to_string()
+                    } else {
+                        file_url(&base_dir.join(download_url))
+                    })
+                })
+            } else if root.is_dir() {
+                scan_local_directory(&registry.id, &root)
+            } else {
+                Err(format!("Registry path does not exist: {}", path))
+            }
+        }
+    }
+}
+
+async fn fetch_github_listing(
+    registry_id: &str,
+    owner: &str,
+    repo: &str,
+    path: &str,
+    branch: Option<&str>,
+) -> Result<Vec<RegistryAgentFile>, String> {
+    info!("Fetching agents from GitHub repository {}/{}/{}", owner, repo, path);
+
+    let client = reqwest::Client::new();
+    let mut url = reqwest::Url::parse(&format!(
+        "https://api.github.com/repos/{}/{}/contents/{}",
+        owner,
+        repo,
+        path.trim_matches('/')
+    ))
+    .map_err(|e| format!("Invalid GitHub repository: {}", e))?;
+    if let Some(branch) = branch {
+        url.query_pairs_mut().append_pair("ref", branch);
+    }
+
+    let response = client
+        .get(url)
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "Claudia-App")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch from GitHub: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("GitHub API error ({}): {}", status, error_text));
+    }
+
+    let api_files: Vec<GitHubApiResponse> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse GitHub response: {}", e))?;
+
+    // Filter only .claudia.json files
+    Ok(api_files
+        .into_iter()
+        .filter(|f| f.name.ends_with(".claudia.json") && f.file_type == "file")
+        .filter_map(|f| {
+            f.download_url.map(|download_url| RegistryAgentFile {
+                registry_id: registry_id.to_string(),
+                name: f.name,
+                path: f.path,
+                download_url,
+                size: f.size,
+                sha: f.sha,
+            })
+        })
+        .collect())
+}
+
+fn parse_index(
+    registry_id: &str,
+    text: &str,
+    resolve_url: impl Fn(&str) -> Result<String, String>,
+) -> Result<Vec<RegistryAgentFile>, String> {
+    let index: RegistryIndex =
+        serde_json::from_str(text).map_err(|e| format!("Invalid registry index: {}", e))?;
+    let entries = match index {
+        RegistryIndex::Wrapped { agents } => agents,
+        RegistryIndex::Bare(agents) => agents,
+    };
+
+    entries
+        .into_iter()
+        .map(|entry| {
+            Ok(RegistryAgentFile {
+                registry_id: registry_id.to_string(),
+                path: entry.path.unwrap_or_else(|| entry.name.clone()),
+                download_url: resolve_url(&entry.download_url)?,
+                size: entry.size.unwrap_or(0),
+                sha: entry.sha,
+                name: entry.name,
+            })
+        })
+        .collect()
+}
+
+/// Reject download URLs that aren't HTTPS, so a remote index or a caller
+/// can't point downloads at plain HTTP or at local files
+fn require_https(download_url: &str) -> Result<(), String> {
+    let url = reqwest::Url::parse(download_url)
+        .map_err(|e| format!("Invalid download URL {}: {}", download_url, e))?;
+    if url.scheme() != "https" {
+        return Err(format!(
+            "Remote registries may only download over HTTPS: {}",
+            download_url
+        ));
+    }
+    Ok(())
+}
+
+fn scan_local_directory(registry_id: &str, root: &Path) -> Result<Vec<RegistryAgentFile>, String> {
+    let entries = std::fs::read_dir(root)
+        .map_err(|e| format!("Failed to read registry directory: {}", e))?;
+
+    let mut files = Vec::new();
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let name = match path.file_name().and_then(|n| n.to_str()) {
+            Some(name) if name.ends_with(".claudia.json") && path.is_file() => name.to_string(),
+            _ => continue,
+        };
+        let content = match std::fs::read(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                warn!("Skipping unreadable registry file {:?}: {}", path, e);
+                continue;
+            }
+        };
+        files.push(RegistryAgentFile {
+            registry_id: registry_id.to_string(),
+            path: name.clone(),
+            name,
+            download_url: file_url(&path),
+            size: content.len() as i64,
+            sha: content_sha(&content),
+        });
+    }
+
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(files)
+}
+
+/// Get the listing of a registry, served from cache unless stale or forced
+pub async fn registry_listing(
+    db: &State<'_, AgentDb>,
+    registry: &AgentRegistry,
+    force_refresh: bool,
+) -> Result<Vec<RegistryAgentFile>, String> {
+    if !force_refresh {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        if let Some(cached) = read_cache(&conn, &registry.id) {
+            return Ok(cached);
+        }
+    }
+
+    let files = fetch_from_source(registry).await?;
+    info!("Found {} agents in registry {}", files.len(), registry.id);
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    write_cache(&conn, &registry.id, &files);
+    Ok(files)
+}
+
+/// Fetch agents from one registry, or from all enabled registries
+#[tauri::command]
+pub async fn fetch_registry_agents(
+    db: State<'_, AgentDb>,
+    registry_id: Option<String>,
+    force_refresh: Option<bool>,
+) -> Result<Vec<RegistryAgentFile>, String> {
+    let force_refresh = force_refresh.unwrap_or(false);
+    let registries = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        match &registry_id {
+            Some(id) => vec![find_registry(&conn, id)?],
+            None => load_registries(&conn)?
+                .into_iter()
+                .filter(|r| r.enabled)
+                .collect(),
+        }
+    };
+
+    let mut all_files = Vec::new();
+    for registry in &registries {
+        match registry_listing(&db, registry, force_refresh).await {
+            Ok(files) => all_files.extend(files),
+            // A single unreachable registry shouldn't hide the others
+            Err(e) if registry_id.is_none() => {
+                warn!("Failed to fetch registry {}: {}", registry.id, e)
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(all_files)
+}
+
+/// Read an agent file listed by `registry`; only local registries may list `file://` URLs
+async fn read_registry_agent_source(registry: &AgentRegistry, download_url: &str) -> Result<String, String> {
+    match (&registry.source, download_url.strip_prefix("file://")) {
+        (RegistrySource::Local { .. }, Some(path)) => {
+            std::fs::read_to_string(path).map_err(|e| format!("Failed to read agent file: {}", e))
+        }
+        _ => read_agent_source(download_url).await,
+    }
+}
+
+/// Read the raw content of an agent file from an HTTPS download URL
+pub async fn read_agent_source(download_url: &str) -> Result<String, String> {
+    require_https(download_url)?;
+
+    let client = reqwest::Client::new();
+    let response = client
+        .get(download_url)
+        .header("Accept", "application/json")
+        .header("User-Agent", "Claudia-App")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download agent: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to download agent: HTTP {}",
+            response.status()
+        ));
+    }
+
+    response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read response: {}", e))
+}
+
+/// Remember which registry entry an agent was imported from
+pub fn record_registry_import(
+    conn: &Connection,
+    agent_id: i64,
+    registry_id: &str,
+    path: &str,
+    sha: &str,
+) -> SqliteResult<()> {
+    conn.execute(
+        "INSERT INTO agent_registry_imports (agent_id, registry_id, path, sha, imported_at)
+         VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)
+         ON CONFLICT(agent_id) DO UPDATE SET registry_id = ?2, path = ?3, sha = ?4, imported_at = CURRENT_TIMESTAMP",
+        params![agent_id, registry_id, path, sha],
+    )?;
+    Ok(())
+}
+
+/// Import an agent from a registry entry and track it for update checks
+#[tauri::command]
+pub async fn import_agent_from_registry(
+    db: State<'_, AgentDb>,
+    file: RegistryAgentFile,
+) -> Result<Agent, String> {
+    info!("Importing agent {} from registry {}", file.path, file.registry_id);
+
+    let registry = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        find_registry(&conn, &file.registry_id)?
+    };
+    let json_data = read_registry_agent_source(&registry, &file.download_url).await?;
+    let signature = crate::commands::agent_trust::fetch_remote_signature(&file.download_url).await;
+    let agent =
+        crate::commands::agents::import_agent_with_source(db.clone(), json_data, None, signature)
+            .await?;
+
+    if let Some(agent_id) = agent.id {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        record_registry_import(&conn, agent_id, &file.registry_id, &file.path, &file.sha)
+            .map_err(|e| format!("Failed to record registry import: {}", e))?;
+    }
+
+    Ok(agent)
+}
+
+/// Compare imported agents against their registries and report newer versions
+#[tauri::command]
+pub async fn check_agent_updates(
+    db: State<'_, AgentDb>,
+    force_refresh: Option<bool>,
+) -> Result<Vec<AgentUpdateInfo>, String> {
+    let (imports, registries) = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT i.agent_id, a.name, i.registry_id, i.path, i.sha
+                 FROM agent_registry_imports i JOIN agents a ON a.id = i.agent_id",
+            )
+            .map_err(|e| e.to_string())?;
+        let imports = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        (imports, load_registries(&conn)?)
+    };
+
+    let mut listings: std::collections::HashMap<String, Vec<RegistryAgentFile>> =
+        std::collections::HashMap::new();
+    let mut updates = Vec::new();
+
+    for (agent_id, agent_name, registry_id, path, installed_sha) in imports {
+        if !listings.contains_key(&registry_id) {
+            let Some(registry) = registries.iter().find(|r| r.id == registry_id) else {
+                continue;
+            };
+            match registry_listing(&db, registry, force_refresh.unwrap_or(false)).await {
+                Ok(files) => {
+                    listings.insert(registry_id.clone(), files);
+                }
+                Err(e) => {
+                    warn!("Failed to check registry {} for updates: {}", registry_id, e);
+                    listings.insert(registry_id.clone(), Vec::new());
+                }
+            }
+        }
+
+        let latest = listings
+            .get(&registry_id)
+            .and_then(|files| files.iter().find(|f| f.path == path));
+        if let Some(latest) = latest {
+            if latest.sha != installed_sha {
+                updates.push(AgentUpdateInfo {
+                    agent_id,
+                    agent_name,
+                    registry_id: registry_id.clone(),
+                    path,
+                    installed_sha,
+                    latest_sha: latest.sha.clone(),
+                    download_url: latest.download_url.clone(),
+                });
+            }
+        }
+    }
+
+    Ok(updates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_index_resolves_relative_urls() {
+        let text = r#"{"agents":[{"name":"a.claudia.json","download_url":"agents/a.claudia.json","sha":"abc"}]}"#;
+        let files = parse_index("r", text, |u| Ok(format!("https://example.com/{}", u))).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "a.claudia.json");
+        assert_eq!(files[0].download_url, "https://example.com/agents/a.claudia.json");
+        assert_eq!(files[0].sha, "abc");
+    }
+
+    #[test]
+    fn test_remote_downloads_require_https() {
+        assert!(require_https("https://example.com/agents/a.claudia.json").is_ok());
+        assert!(require_https("http://example.com/agents/a.claudia.json").is_err());
+        assert!(require_https("file:///etc/passwd").is_err());
+
+        let base = reqwest::Url::parse("https://example.com/index.json").unwrap();
+        let text = r#"[{"name":"a","download_url":"file:///home/me/.ssh/id_rsa","sha":"abc"}]"#;
+        let resolved = parse_index("r", text, |u| {
+            let url = base.join(u).map_err(|e| e.to_string())?;
+            require_https(url.as_str())?;
+            Ok(url.to_string())
+        });
+        assert!(resolved.is_err());
+    }
+
+    #[test]
+    fn test_registry_source_serialization() {
+        let json = r#"{"type":"local","path":"/srv/agents"}"#;
+        let source: RegistrySource = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            source,
+            RegistrySource::Local {
+                path: "/srv/agents".to_string()
+            }
+        );
+    }
+}
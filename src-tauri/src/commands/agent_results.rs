@@ -0,0 +1,356 @@
+use log::{info, warn};
+use rusqlite::{params, Connection, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::commands::agents::AgentDb;
+use crate::stream_json::{parse_line, StreamEvent};
+
+/// Directory, relative to the project, under which each run gets its artifact folder
+pub const ARTIFACTS_DIR: &str = ".claudia/artifacts";
+
+/// Tools whose uses count as file changes, with the input field holding the path
+const FILE_CHANGING_TOOLS: &[(&str, &str)] = &[
+    ("Write", "file_path"),
+    ("Edit", "file_path"),
+    ("MultiEdit", "file_path"),
+    ("NotebookEdit", "notebook_path"),
+];
+
+/// Structured outcome of an agent run
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct AgentRunResult {
+    pub run_id: i64,
+    /// Text of the final `result` message, or the last assistant text if there was none
+    pub result_message: Option<String>,
+    pub is_error: bool,
+    /// Subtype of the final `result` message, e.g. "success" or "error_max_turns"
+    pub result_subtype: Option<String>,
+    pub num_turns: Option<i64>,
+    pub exit_code: Option<i32>,
+    pub files_changed: Vec<String>,
+    pub artifacts: Vec<AgentRunArtifact>,
+    pub created_at: String,
+}
+
+/// A named file the agent wrote to its artifact directory
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AgentRunArtifact {
+    pub name: String,
+    pub path: String,
+    pub size: i64,
+}
+
+/// Create the run result and artifact tables
+pub fn init_result_tables(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_run_results (
+            run_id INTEGER PRIMARY KEY,
+            result_message TEXT,
+            is_error BOOLEAN NOT NULL DEFAULT 0,
+            result_subtype TEXT,
+            num_turns INTEGER,
+            exit_code INTEGER,
+            files_changed TEXT NOT NULL DEFAULT '[]',
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (run_id) REFERENCES agent_runs(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_run_artifacts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            run_id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            path TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            UNIQUE(run_id, name),
+            FOREIGN KEY (run_id) REFERENCES agent_runs(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Artifact directory of a run inside its project
+pub fn artifacts_dir(project_path: &str, run_id: i64) -> PathBuf {
+    Path::new(project_path).join(ARTIFACTS_DIR).join(run_id.to_string())
+}
+
+/// Extract the result message and changed files from stream-json output
+pub fn parse_run_output(output: &str) -> AgentRunResult {
+    let mut result = AgentRunResult::default();
+    let mut last_assistant_text = None;
+
+    for line in output.lines() {
+        let Some(line) = parse_line(line) else {
+            continue;
+        };
+
+        match &line.event {
+            StreamEvent::Result(event) => {
+                result.is_error = event.is_error;
+                result.result_subtype = event.subtype.clone();
+                result.num_turns = event.num_turns;
+                result.result_message = event.result.clone();
+            }
+            StreamEvent::Assistant(_) => {
+                for (name, input) in line.tool_uses() {
+                    let path = FILE_CHANGING_TOOLS
+                        .iter()
+                        .find(|(tool, _)| *tool == name)
+                        .and_then(|(_, field)| input.get(field))
+                        .and_then(|p| p.as_str());
+                    if let Some(path) = path {
+                        if !result.files_changed.iter().any(|f| f == path) {
+                            result.files_changed.push(path.to_string());
+                        }
+                    }
+                }
+                if let Some(text) = line.text() {
+                    last_assistant_text = Some(text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if result.result_message.is_none() {
+        result.result_message = last_assistant_text;
+    }
+    result
+}
+
+/// List the files in a run's artifact directory, named by their path relative to it
+fn collect_artifacts(dir: &Path) -> Vec<AgentRunArtifact> {
+    if !dir.is_dir() {
+        return Vec::new();
+    }
+
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let name = entry
+                .path()
+                .strip_prefix(dir)
+                .ok()?
+                .to_string_lossy()
+                .replace('\\', "/");
+            Some(AgentRunArtifact {
+                name,
+                path: entry.path().to_string_lossy().to_string(),
+                size: entry.metadata().map(|m| m.len() as i64).unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+/// Parse a finished run's output and store its structured result and artifacts
+pub fn record_run_result(
+    conn: &Connection,
+    run_id: i64,
+    project_path: &str,
+    output: &str,
+    exit_code: Option<i32>,
+) -> Result<(), String> {
+    let result = parse_run_output(output);
+    let files_changed =
+        serde_json::to_string(&result.files_changed).map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT OR REPLACE INTO agent_run_results (run_id, result_message, is_error, result_subtype, num_turns, exit_code, files_changed)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            run_id,
+            result.result_message,
+            result.is_error,
+            result.result_subtype,
+            result.num_turns,
+            exit_code,
+            files_changed
+        ],
+    )
+    .map_err(|e| format!("Failed to store run result: {}", e))?;
+
+    let artifacts = collect_artifacts(&artifacts_dir(project_path, run_id));
+    for artifact in &artifacts {
+        conn.execute(
+            "INSERT OR REPLACE INTO agent_run_artifacts (run_id, name, path, size) VALUES (?1, ?2, ?3, ?4)",
+            params![run_id, artifact.name, artifact.path, artifact.size],
+        )
+        .map_err(|e| format!("Failed to store run artifact: {}", e))?;
+    }
+
+    info!(
+        "Recorded result for run {}: {} files changed, {} artifacts",
+        run_id,
+        result.files_changed.len(),
+        artifacts.len()
+    );
+    Ok(())
+}
+
+/// Store the result of a finished run using a fresh connection, as the spawn tasks do
+pub fn record_run_result_at(
+    db_path: &Path,
+    run_id: i64,
+    project_path: &str,
+    output: &str,
+    exit_code: Option<i32>,
+) {
+    match Connection::open(db_path) {
+        Ok(conn) => {
+            if let Err(e) = record_run_result(&conn, run_id, project_path, output, exit_code) {
+                warn!("Failed to record result for run {}: {}", run_id, e);
+            }
+        }
+        Err(e) => warn!("Failed to open database to record run {}: {}", run_id, e),
+    }
+}
+
+fn load_artifacts(conn: &Connection, run_id: i64) -> Result<Vec<AgentRunArtifact>, String> {
+    let mut stmt = conn
+        .prepare("SELECT name, path, size FROM agent_run_artifacts WHERE run_id = ?1 ORDER BY name")
+        .map_err(|e| e.to_string())?;
+    let artifacts = stmt
+        .query_map(params![run_id], |row| {
+            Ok(AgentRunArtifact {
+                name: row.get(0)?,
+                path: row.get(1)?,
+                size: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(artifacts)
+}
+
+fn map_result_row(row: &rusqlite::Row) -> rusqlite::Result<AgentRunResult> {
+    let files_changed: String = row.get(6)?;
+    Ok(AgentRunResult {
+        run_id: row.get(0)?,
+        result_message: row.get(1)?,
+        is_error: row.get(2)?,
+        result_subtype: row.get(3)?,
+        num_turns: row.get(4)?,
+        exit_code: row.get(5)?,
+        files_changed: serde_json::from_str(&files_changed).unwrap_or_default(),
+        artifacts: Vec::new(),
+        created_at: row.get(7)?,
+    })
+}
+
+const RESULT_COLUMNS: &str =
+    "r.run_id, r.result_message, r.is_error, r.result_subtype, r.num_turns, r.exit_code, r.files_changed, r.created_at";
+
+/// Load the stored result of a run, if it has finished
+pub fn load_run_result(conn: &Connection, run_id: i64) -> Result<Option<AgentRunResult>, String> {
+    let result = conn.query_row(
+        &format!("SELECT {} FROM agent_run_results r WHERE r.run_id = ?1", RESULT_COLUMNS),
+        params![run_id],
+        map_result_row,
+    );
+
+    match result {
+        Ok(mut result) => {
+            result.artifacts = load_artifacts(conn, run_id)?;
+            Ok(Some(result))
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Get the structured result of a run
+#[tauri::command]
+pub async fn get_agent_run_result(
+    db: State<'_, AgentDb>,
+    run_id: i64,
+) -> Result<Option<AgentRunResult>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_run_result(&conn, run_id)
+}
+
+/// List stored run results, optionally for one agent or only those touching a file
+#[tauri::command]
+pub async fn list_agent_run_results(
+    db: State<'_, AgentDb>,
+    agent_id: Option<i64>,
+    changed_file: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<AgentRunResult>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM agent_run_results r JOIN agent_runs a ON a.id = r.run_id
+             WHERE (?1 IS NULL OR a.agent_id = ?1)
+               AND (?2 IS NULL OR EXISTS (SELECT 1 FROM json_each(r.files_changed) WHERE value = ?2))
+             ORDER BY r.run_id DESC LIMIT ?3",
+            RESULT_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let mut results = stmt
+        .query_map(params![agent_id, changed_file, limit.unwrap_or(100)], map_result_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    for result in &mut results {
+        result.artifacts = load_artifacts(&conn, result.run_id)?;
+    }
+
+    Ok(results)
+}
+
+/// Read a named artifact of a run as text
+#[tauri::command]
+pub async fn read_agent_run_artifact(
+    db: State<'_, AgentDb>,
+    run_id: i64,
+    name: String,
+) -> Result<String, String> {
+    let path: String = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.query_row(
+            "SELECT path FROM agent_run_artifacts WHERE run_id = ?1 AND name = ?2",
+            params![run_id, name],
+            |row| row.get(0),
+        )
+        .map_err(|_| format!("Run {} has no artifact named '{}'", run_id, name))?
+    };
+
+    tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| format!("Failed to read artifact {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_run_output() {
+        let output = [
+            r#"{"type":"system","subtype":"init","session_id":"abc"}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Write","input":{"file_path":"/p/a.rs"}},{"type":"tool_use","name":"Read","input":{"file_path":"/p/b.rs"}}]}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Edit","input":{"file_path":"/p/a.rs"}},{"type":"text","text":"Done"}]}}"#,
+            r#"{"type":"result","subtype":"success","is_error":false,"num_turns":3,"result":"Fixed the bug"}"#,
+        ]
+        .join("\n");
+
+        let result = parse_run_output(&output);
+        assert_eq!(result.files_changed, vec!["/p/a.rs".to_string()]);
+        assert_eq!(result.result_message.as_deref(), Some("Fixed the bug"));
+        assert_eq!(result.result_subtype.as_deref(), Some("success"));
+        assert_eq!(result.num_turns, Some(3));
+        assert!(!result.is_error);
+    }
+}
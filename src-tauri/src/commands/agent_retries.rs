@@ -0,0 +1,483 @@
+use log::{info, warn};
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, HashMap};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::agent_results::{load_run_result, AgentRunResult};
+use crate::commands::agents::{
+    execute_agent_with_context, get_agent_run, map_agent_run_row, wait_for_agent_run, AgentDb,
+    AgentRun, AgentRunContext, AGENT_RUN_COLUMNS,
+};
+use crate::process::ProcessRegistryState;
+
+/// Prompt sent when resuming a run without an explicit follow-up
+const DEFAULT_RESUME_PROMPT: &str =
+    "The previous run was interrupted before it finished. Continue the task from where you left off.";
+
+/// Bounds of the factor the retry delay grows by
+const MIN_BACKOFF_MULTIPLIER: f64 = 1.0;
+const MAX_BACKOFF_MULTIPLIER: f64 = 10.0;
+
+/// Longest delay before a retry, however many attempts came before
+const MAX_BACKOFF_SECS: u64 = 24 * 60 * 60;
+
+/// Why an agent run failed, as far as its status and stored result tell
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureClass {
+    /// The API returned an error or was overloaded
+    ApiError,
+    /// The API rejected the request for rate limiting
+    RateLimit,
+    /// The process ended without reporting a result
+    Killed,
+    /// The run exceeded the execution timeout
+    Timeout,
+    /// The agent hit its turn limit
+    MaxTurns,
+    /// The run was cancelled by the user
+    Cancelled,
+    /// Any other error
+    Error,
+}
+
+/// Automatic retry settings of an agent
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AgentRetryPolicy {
+    /// Total attempts including the original run
+    pub max_attempts: i64,
+    /// Delay before the first retry
+    pub backoff_seconds: i64,
+    /// Factor the delay grows by on each further retry
+    #[serde(default = "default_backoff_multiplier")]
+    pub backoff_multiplier: f64,
+    /// Failure classes that trigger a retry
+    #[serde(default = "default_retry_on")]
+    pub retry_on: Vec<FailureClass>,
+}
+
+fn default_backoff_multiplier() -> f64 {
+    2.0
+}
+
+fn default_retry_on() -> Vec<FailureClass> {
+    vec![
+        FailureClass::ApiError,
+        FailureClass::RateLimit,
+        FailureClass::Killed,
+        FailureClass::Timeout,
+    ]
+}
+
+impl AgentRetryPolicy {
+    /// Delay before retrying after the given attempt failed
+    fn backoff_after(&self, attempt: i64) -> std::time::Duration {
+        let max_delay = std::time::Duration::from_secs(MAX_BACKOFF_SECS);
+        // Multipliers are validated on save, but a bad value must never stall or overflow the wait
+        let multiplier = if self.backoff_multiplier.is_finite() {
+            self.backoff_multiplier.clamp(MIN_BACKOFF_MULTIPLIER, MAX_BACKOFF_MULTIPLIER)
+        } else {
+            MIN_BACKOFF_MULTIPLIER
+        };
+        let factor = multiplier.powi((attempt - 1).clamp(0, i32::MAX as i64) as i32);
+        std::time::Duration::try_from_secs_f64(self.backoff_seconds.max(0) as f64 * factor)
+            .map_or(max_delay, |delay| delay.min(max_delay))
+    }
+}
+
+/// Create the retry policy table
+pub fn init_retry_tables(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_retry_policies (
+            agent_id INTEGER PRIMARY KEY,
+            max_attempts INTEGER NOT NULL,
+            backoff_seconds INTEGER NOT NULL,
+            backoff_multiplier REAL NOT NULL DEFAULT 2.0,
+            retry_on TEXT NOT NULL,
+            FOREIGN KEY (agent_id) REFERENCES agents(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Load an agent's retry policy, if it has one
+pub fn load_retry_policy(conn: &Connection, agent_id: i64) -> Result<Option<AgentRetryPolicy>, String> {
+    conn.query_row(
+        "SELECT max_attempts, backoff_seconds, backoff_multiplier, retry_on FROM agent_retry_policies WHERE agent_id = ?1",
+        params![agent_id],
+        |row| {
+            let retry_on: String = row.get(3)?;
+            Ok(AgentRetryPolicy {
+                max_attempts: row.get(0)?,
+                backoff_seconds: row.get(1)?,
+                backoff_multiplier: row.get(2)?,
+                retry_on: serde_json::from_str(&retry_on).unwrap_or_else(|_| default_retry_on()),
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Classify a finished run, returning `None` if it succeeded
+pub fn classify_failure(run: &AgentRun, result: Option<&AgentRunResult>) -> Option<FailureClass> {
+    match run.status.as_str() {
+        "cancelled" => return Some(FailureClass::Cancelled),
+        // Runs are only marked failed without a result when they stall: no output at
+        // start, or a process that outlived the wait or vanished
+        "failed" if result.is_none_or(|r| r.result_subtype.is_none()) => {
+            return Some(FailureClass::Timeout)
+        }
+        _ => {}
+    }
+
+    let Some(result) = result.filter(|r| r.result_subtype.is_some() || r.exit_code.is_some()) else {
+        return Some(FailureClass::Killed);
+    };
+
+    if result.result_subtype.as_deref() == Some("error_max_turns") {
+        return Some(FailureClass::MaxTurns);
+    }
+    if !result.is_error && result.exit_code.unwrap_or(0) == 0 && run.status == "completed" {
+        return None;
+    }
+    if result.result_subtype.is_none() && result.exit_code.is_none() {
+        return Some(FailureClass::Killed);
+    }
+
+    let message = result.result_message.as_deref().unwrap_or_default().to_lowercase();
+    if message.contains("rate limit") || message.contains("429") {
+        Some(FailureClass::RateLimit)
+    } else if ["api error", "overloaded", "529", "internal server error", "connection error"]
+        .iter()
+        .any(|needle| message.contains(needle))
+    {
+        Some(FailureClass::ApiError)
+    } else {
+        Some(FailureClass::Error)
+    }
+}
+
+/// Parameter values stored on a run, in the form `execute_agent_with_context` accepts
+fn stored_parameters(run: &AgentRun) -> Option<HashMap<String, JsonValue>> {
+    let resolved: BTreeMap<String, String> = serde_json::from_str(run.parameters.as_deref()?).ok()?;
+    Some(
+        resolved
+            .into_iter()
+            .map(|(name, value)| (name, JsonValue::String(value)))
+            .collect(),
+    )
+}
+
+/// Start a new attempt of a run, either fresh or continuing its Claude session
+async fn start_attempt(app: &AppHandle, run: &AgentRun, resume_prompt: Option<String>) -> Result<i64, String> {
+    let run_id = run.id.ok_or("Agent run has no ID")?;
+    let original_id = run.retry_of.unwrap_or(run_id);
+
+    let attempt = {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.query_row(
+            "SELECT COALESCE(MAX(attempt), 1) FROM agent_runs WHERE id = ?1 OR retry_of = ?1",
+            params![original_id],
+            |row| row.get::<_, i64>(0),
+        )
+        .map_err(|e| e.to_string())?
+            + 1
+    };
+
+    let (task, resume_session_id) = match resume_prompt {
+        Some(prompt) => {
+            if run.session_id.is_empty() {
+                return Err("Agent run has no Claude session to resume".to_string());
+            }
+            (prompt, Some(run.session_id.clone()))
+        }
+        // A resumed run's task is only its follow-up prompt, so start over from the original task
+        None if original_id != run_id => {
+            let db = app.state::<AgentDb>();
+            let conn = db.0.lock().map_err(|e| e.to_string())?;
+            let task = conn
+                .query_row(
+                    "SELECT task FROM agent_runs WHERE id = ?1",
+                    params![original_id],
+                    |row| row.get::<_, String>(0),
+                )
+                .map_err(|e| e.to_string())?;
+            (task, None)
+        }
+        None => (run.task.clone(), None),
+    };
+
+    execute_agent_with_context(
+        app.clone(),
+        run.agent_id,
+        run.project_path.clone(),
+        task,
+        Some(run.model.clone()),
+        AgentRunContext {
+            parameters: stored_parameters(run),
+            retry_of: Some(original_id),
+            attempt: Some(attempt),
+            resume_session_id,
+            ..Default::default()
+        },
+        app.state::<AgentDb>(),
+        app.state::<ProcessRegistryState>(),
+    )
+    .await
+}
+
+/// Spawn a watcher that retries the run if it fails in a way the agent's retry policy covers
+pub fn watch_for_auto_retry(app: &AppHandle, agent_id: i64, run_id: i64) {
+    let policy = {
+        let db = app.state::<AgentDb>();
+        let Ok(conn) = db.0.lock() else {
+            return;
+        };
+        match load_retry_policy(&conn, agent_id) {
+            Ok(Some(policy)) => policy,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Failed to load retry policy for agent {}: {}", agent_id, e);
+                return;
+            }
+        }
+    };
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = auto_retry(&app, run_id, &policy).await {
+            warn!("Automatic retry of agent run {} failed: {}", run_id, e);
+        }
+    });
+}
+
+async fn auto_retry(app: &AppHandle, run_id: i64, policy: &AgentRetryPolicy) -> Result<(), String> {
+    let run = wait_for_agent_run(app, run_id).await?;
+    let result = {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        load_run_result(&conn, run_id)?
+    };
+
+    let Some(class) = classify_failure(&run, result.as_ref()) else {
+        return Ok(());
+    };
+    if !policy.retry_on.contains(&class) || run.attempt >= policy.max_attempts {
+        return Ok(());
+    }
+
+    let delay = policy.backoff_after(run.attempt);
+    info!(
+        "Agent run {} failed ({:?}), retrying in {}s (attempt {} of {})",
+        run_id,
+        class,
+        delay.as_secs(),
+        run.attempt + 1,
+        policy.max_attempts
+    );
+    tokio::time::sleep(delay).await;
+
+    let new_run_id = start_attempt(app, &run, None).await?;
+    let _ = app.emit(&format!("agent-retry:{}", run_id), new_run_id);
+    Ok(())
+}
+
+/// Run a finished agent run again with the same task in a new session
+#[tauri::command]
+pub async fn retry_agent_run(app: AppHandle, db: State<'_, AgentDb>, run_id: i64) -> Result<i64, String> {
+    let run = get_agent_run(db, run_id).await?;
+    if run.status == "running" || run.status == "pending" {
+        return Err("Agent run is still in progress".to_string());
+    }
+    start_attempt(&app, &run, None).await
+}
+
+/// Continue a finished agent run's Claude session with a follow-up prompt
+#[tauri::command]
+pub async fn resume_agent_run(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    run_id: i64,
+    prompt: Option<String>,
+) -> Result<i64, String> {
+    let run = get_agent_run(db, run_id).await?;
+    if run.status == "running" || run.status == "pending" {
+        return Err("Agent run is still in progress".to_string());
+    }
+    let prompt = prompt
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_RESUME_PROMPT.to_string());
+    start_attempt(&app, &run, Some(prompt)).await
+}
+
+/// List a run's original run and all of its retries, oldest first
+#[tauri::command]
+pub async fn list_agent_run_attempts(db: State<'_, AgentDb>, run_id: i64) -> Result<Vec<AgentRun>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let original_id: i64 = conn
+        .query_row(
+            "SELECT COALESCE(retry_of, id) FROM agent_runs WHERE id = ?1",
+            params![run_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM agent_runs WHERE id = ?1 OR retry_of = ?1 ORDER BY attempt, id",
+            AGENT_RUN_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let runs = stmt
+        .query_map(params![original_id], map_agent_run_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(runs)
+}
+
+/// Get an agent's automatic retry policy
+#[tauri::command]
+pub async fn get_agent_retry_policy(
+    db: State<'_, AgentDb>,
+    agent_id: i64,
+) -> Result<Option<AgentRetryPolicy>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_retry_policy(&conn, agent_id)
+}
+
+/// Set or clear an agent's automatic retry policy
+#[tauri::command]
+pub async fn set_agent_retry_policy(
+    db: State<'_, AgentDb>,
+    agent_id: i64,
+    policy: Option<AgentRetryPolicy>,
+) -> Result<(), String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+
+    let Some(policy) = policy else {
+        conn.execute(
+            "DELETE FROM agent_retry_policies WHERE agent_id = ?1",
+            params![agent_id],
+        )
+        .map_err(|e| e.to_string())?;
+        return Ok(());
+    };
+
+    if policy.max_attempts < 1 {
+        return Err("Max attempts must be at least 1".to_string());
+    }
+    if policy.backoff_seconds < 0 {
+        return Err("Backoff must not be negative".to_string());
+    }
+    if !(MIN_BACKOFF_MULTIPLIER..=MAX_BACKOFF_MULTIPLIER).contains(&policy.backoff_multiplier) {
+        return Err(format!(
+            "Backoff multiplier must be between {} and {}",
+            MIN_BACKOFF_MULTIPLIER, MAX_BACKOFF_MULTIPLIER
+        ));
+    }
+    let retry_on = serde_json::to_string(&policy.retry_on).map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO agent_retry_policies (agent_id, max_attempts, backoff_seconds, backoff_multiplier, retry_on)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(agent_id) DO UPDATE SET max_attempts = ?2, backoff_seconds = ?3, backoff_multiplier = ?4, retry_on = ?5",
+        params![agent_id, policy.max_attempts, policy.backoff_seconds, policy.backoff_multiplier, retry_on],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finished_run(status: &str) -> AgentRun {
+        AgentRun {
+            id: Some(1),
+            agent_id: 1,
+            agent_name: "agent".to_string(),
+            agent_icon: "bot".to_string(),
+            task: "task".to_string(),
+            model: "sonnet".to_string(),
+            project_path: "/tmp".to_string(),
+            session_id: String::new(),
+            status: status.to_string(),
+            pid: None,
+            process_started_at: None,
+            created_at: String::new(),
+            completed_at: None,
+            agent_version: None,
+            schedule_id: None,
+            pipeline_run_id: None,
+            pipeline_step: None,
+            batch_id: None,
+            parameters: None,
+            retry_of: None,
+            attempt: 1,
+        }
+    }
+
+    fn result(subtype: &str, is_error: bool, message: &str) -> AgentRunResult {
+        AgentRunResult {
+            result_subtype: Some(subtype.to_string()),
+            is_error,
+            result_message: Some(message.to_string()),
+            exit_code: Some(if is_error { 1 } else { 0 }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_classify_failure() {
+        let completed = finished_run("completed");
+        assert_eq!(classify_failure(&completed, Some(&result("success", false, "done"))), None);
+        assert_eq!(
+            classify_failure(&completed, Some(&result("success", true, "API Error: 529 Overloaded"))),
+            Some(FailureClass::ApiError)
+        );
+        assert_eq!(
+            classify_failure(&completed, Some(&result("success", true, "Rate limit reached"))),
+            Some(FailureClass::RateLimit)
+        );
+        assert_eq!(
+            classify_failure(&completed, Some(&result("error_max_turns", true, ""))),
+            Some(FailureClass::MaxTurns)
+        );
+        assert_eq!(classify_failure(&completed, None), Some(FailureClass::Killed));
+        assert_eq!(classify_failure(&finished_run("failed"), None), Some(FailureClass::Timeout));
+        assert_eq!(classify_failure(&finished_run("cancelled"), None), Some(FailureClass::Cancelled));
+    }
+
+    #[test]
+    fn test_backoff_grows_per_attempt() {
+        let policy = AgentRetryPolicy {
+            max_attempts: 3,
+            backoff_seconds: 10,
+            backoff_multiplier: 2.0,
+            retry_on: default_retry_on(),
+        };
+        assert_eq!(policy.backoff_after(1).as_secs(), 10);
+        assert_eq!(policy.backoff_after(2).as_secs(), 20);
+        assert_eq!(policy.backoff_after(1000).as_secs(), MAX_BACKOFF_SECS);
+
+        let invalid = AgentRetryPolicy {
+            backoff_multiplier: f64::NAN,
+            ..policy.clone()
+        };
+        assert_eq!(invalid.backoff_after(3).as_secs(), 10);
+        let huge = AgentRetryPolicy {
+            backoff_seconds: i64::MAX,
+            backoff_multiplier: f64::INFINITY,
+            ..policy
+        };
+        assert_eq!(huge.backoff_after(2).as_secs(), MAX_BACKOFF_SECS);
+    }
+}
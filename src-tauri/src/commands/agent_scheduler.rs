@@ -0,0 +1,538 @@
+use chrono::{DateTime, Duration, Local, Utc};
+use cron::Schedule;
+use log::{error, info, warn};
+use rusqlite::{params, Connection, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::str::FromStr;
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::agents::{execute_agent_with_context, AgentDb, AgentRunContext};
+use crate::process::ProcessRegistryState;
+
+/// How often the scheduler checks for due schedules
+const SCHEDULER_TICK_SECS: u64 = 30;
+
+/// How late a run may fire before it counts as missed (e.g. after sleep or shutdown)
+const MISSED_RUN_GRACE_SECS: i64 = 5 * 60;
+
+/// What to do when a scheduled time passed while the app was asleep or closed
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedRunPolicy {
+    /// Run once to catch up, however many times were missed
+    #[default]
+    RunOnce,
+    /// Drop missed runs and wait for the next scheduled time
+    Skip,
+}
+
+impl MissedRunPolicy {
+    fn as_str(self) -> &'static str {
+        match self {
+            MissedRunPolicy::RunOnce => "run_once",
+            MissedRunPolicy::Skip => "skip",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "skip" => MissedRunPolicy::Skip,
+            _ => MissedRunPolicy::RunOnce,
+        }
+    }
+}
+
+/// A recurring agent run
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentSchedule {
+    pub id: Option<i64>,
+    pub agent_id: i64,
+    pub name: String,
+    /// Cron expression in local time; 5-field expressions are accepted
+    pub cron_expression: Option<String>,
+    /// Fixed interval in seconds, used when no cron expression is set
+    pub interval_seconds: Option<i64>,
+    pub project_path: String,
+    /// Task to run instead of the agent's default task
+    pub task: Option<String>,
+    /// Model to use instead of the agent's model
+    pub model: Option<String>,
+    /// Values for the agent's declared template parameters
+    pub parameters: Option<HashMap<String, JsonValue>>,
+    pub enabled: bool,
+    pub missed_run_policy: MissedRunPolicy,
+    pub next_run_at: Option<String>,
+    pub last_run_at: Option<String>,
+    pub last_run_id: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Fields accepted when creating or updating a schedule
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentScheduleInput {
+    pub agent_id: i64,
+    pub name: String,
+    pub cron_expression: Option<String>,
+    pub interval_seconds: Option<i64>,
+    pub project_path: String,
+    pub task: Option<String>,
+    pub model: Option<String>,
+    #[serde(default)]
+    pub parameters: Option<HashMap<String, JsonValue>>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub missed_run_policy: MissedRunPolicy,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+const SCHEDULE_COLUMNS: &str = "id, agent_id, name, cron_expression, interval_seconds, project_path, task, model, enabled, missed_run_policy, next_run_at, last_run_at, last_run_id, last_error, created_at, updated_at, parameters";
+
+fn map_schedule_row(row: &rusqlite::Row) -> rusqlite::Result<AgentSchedule> {
+    Ok(AgentSchedule {
+        id: Some(row.get(0)?),
+        agent_id: row.get(1)?,
+        name: row.get(2)?,
+        cron_expression: row.get(3)?,
+        interval_seconds: row.get(4)?,
+        project_path: row.get(5)?,
+        task: row.get(6)?,
+        model: row.get(7)?,
+        enabled: row.get::<_, bool>(8)?,
+        missed_run_policy: MissedRunPolicy::parse(&row.get::<_, String>(9)?),
+        next_run_at: row.get(10)?,
+        last_run_at: row.get(11)?,
+        last_run_id: row.get(12)?,
+        last_error: row.get(13)?,
+        created_at: row.get(14)?,
+        updated_at: row.get(15)?,
+        parameters: crate::commands::agent_parameters::values_from_json(row.get(16)?),
+    })
+}
+
+/// Create the schedules table
+pub fn init_schedule_tables(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_schedules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            agent_id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            cron_expression TEXT,
+            interval_seconds INTEGER,
+            project_path TEXT NOT NULL,
+            task TEXT,
+            model TEXT,
+            enabled BOOLEAN NOT NULL DEFAULT 1,
+            missed_run_policy TEXT NOT NULL DEFAULT 'run_once',
+            next_run_at TEXT,
+            last_run_at TEXT,
+            last_run_id INTEGER,
+            last_error TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            parameters TEXT,
+            FOREIGN KEY (agent_id) REFERENCES agents(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Parse a cron expression, accepting the common 5-field form without seconds
+fn parse_cron(expression: &str) -> Result<Schedule, String> {
+    let expression = expression.trim();
+    let normalized = if expression.split_whitespace().count() == 5 {
+        format!("0 {}", expression)
+    } else {
+        expression.to_string()
+    };
+    Schedule::from_str(&normalized).map_err(|e| format!("Invalid cron expression: {}", e))
+}
+
+/// Compute the first run time strictly after `after`
+fn next_run_after(
+    cron_expression: Option<&str>,
+    interval_seconds: Option<i64>,
+    after: DateTime<Utc>,
+) -> Result<DateTime<Utc>, String> {
+    match (cron_expression.filter(|e| !e.trim().is_empty()), interval_seconds) {
+        (Some(expression), _) => parse_cron(expression)?
+            .after(&after.with_timezone(&Local))
+            .next()
+            .map(|next| next.with_timezone(&Utc))
+            .ok_or_else(|| "Cron expression has no upcoming run".to_string()),
+        (None, Some(seconds)) if seconds > 0 => Ok(after + Duration::seconds(seconds)),
+        (None, Some(_)) => Err("Interval must be a positive number of seconds".to_string()),
+        (None, None) => Err("Schedule needs a cron expression or an interval".to_string()),
+    }
+}
+
+fn validate_input(input: &AgentScheduleInput) -> Result<DateTime<Utc>, String> {
+    if input.name.trim().is_empty() {
+        return Err("Schedule name is required".to_string());
+    }
+    if !std::path::Path::new(&input.project_path).is_dir() {
+        return Err(format!("Project path does not exist: {}", input.project_path));
+    }
+    next_run_after(
+        input.cron_expression.as_deref(),
+        input.interval_seconds,
+        Utc::now(),
+    )
+}
+
+/// Check the schedule's parameter values against the agent and serialize them for storage
+fn check_schedule_parameters(conn: &Connection, schedule: &AgentScheduleInput) -> Result<Option<String>, String> {
+    let declared: Option<String> = conn
+        .query_row(
+            "SELECT parameters FROM agents WHERE id = ?1",
+            params![schedule.agent_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    crate::commands::agent_parameters::check_values(declared.as_deref(), schedule.parameters.as_ref())?;
+    crate::commands::agent_parameters::values_to_json(schedule.parameters.as_ref())
+}
+
+fn fetch_schedule(conn: &Connection, id: i64) -> Result<AgentSchedule, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM agent_schedules WHERE id = ?1", SCHEDULE_COLUMNS),
+        params![id],
+        map_schedule_row,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// List schedules, optionally for a single agent
+#[tauri::command]
+pub async fn list_agent_schedules(
+    db: State<'_, AgentDb>,
+    agent_id: Option<i64>,
+) -> Result<Vec<AgentSchedule>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM agent_schedules WHERE ?1 IS NULL OR agent_id = ?1 ORDER BY created_at DESC",
+            SCHEDULE_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let schedules = stmt
+        .query_map(params![agent_id], map_schedule_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(schedules)
+}
+
+/// Create a schedule
+#[tauri::command]
+pub async fn create_agent_schedule(
+    db: State<'_, AgentDb>,
+    schedule: AgentScheduleInput,
+) -> Result<AgentSchedule, String> {
+    let next_run_at = validate_input(&schedule)?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let parameters = check_schedule_parameters(&conn, &schedule)?;
+
+    conn.execute(
+        "INSERT INTO agent_schedules (agent_id, name, cron_expression, interval_seconds, project_path, task, model, enabled, missed_run_policy, next_run_at, parameters)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            schedule.agent_id,
+            schedule.name,
+            schedule.cron_expression,
+            schedule.interval_seconds,
+            schedule.project_path,
+            schedule.task,
+            schedule.model,
+            schedule.enabled,
+            schedule.missed_run_policy.as_str(),
+            next_run_at.to_rfc3339(),
+            parameters,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    fetch_schedule(&conn, conn.last_insert_rowid())
+}
+
+/// Update a schedule; the next run time is recomputed from now
+#[tauri::command]
+pub async fn update_agent_schedule(
+    db: State<'_, AgentDb>,
+    id: i64,
+    schedule: AgentScheduleInput,
+) -> Result<AgentSchedule, String> {
+    let next_run_at = validate_input(&schedule)?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let parameters = check_schedule_parameters(&conn, &schedule)?;
+
+    conn.execute(
+        "UPDATE agent_schedules SET agent_id = ?1, name = ?2, cron_expression = ?3, interval_seconds = ?4, project_path = ?5, task = ?6, model = ?7, enabled = ?8, missed_run_policy = ?9, next_run_at = ?10, parameters = ?11, last_error = NULL, updated_at = CURRENT_TIMESTAMP
+         WHERE id = ?12",
+        params![
+            schedule.agent_id,
+            schedule.name,
+            schedule.cron_expression,
+            schedule.interval_seconds,
+            schedule.project_path,
+            schedule.task,
+            schedule.model,
+            schedule.enabled,
+            schedule.missed_run_policy.as_str(),
+            next_run_at.to_rfc3339(),
+            parameters,
+            id,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    fetch_schedule(&conn, id)
+}
+
+/// Delete a schedule; runs it produced keep their `schedule_id`
+#[tauri::command]
+pub async fn delete_agent_schedule(db: State<'_, AgentDb>, id: i64) -> Result<(), String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM agent_schedules WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Fire a schedule immediately without changing its next run time
+#[tauri::command]
+pub async fn run_agent_schedule_now(app: AppHandle, id: i64) -> Result<i64, String> {
+    let schedule = {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        fetch_schedule(&conn, id)?
+    };
+    fire_schedule(&app, &schedule).await
+}
+
+/// Start the agent and record the outcome on the schedule
+async fn fire_schedule(app: &AppHandle, schedule: &AgentSchedule) -> Result<i64, String> {
+    let schedule_id = schedule.id.unwrap_or_default();
+    let db = app.state::<AgentDb>();
+
+    let task = match &schedule.task {
+        Some(task) if !task.trim().is_empty() => Ok(task.clone()),
+        _ => {
+            let conn = db.0.lock().map_err(|e| e.to_string())?;
+            conn.query_row(
+                "SELECT default_task FROM agents WHERE id = ?1",
+                params![schedule.agent_id],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .map_err(|e| e.to_string())?
+            .filter(|task| !task.trim().is_empty())
+            .ok_or_else(|| "Schedule has no task and the agent has no default task".to_string())
+        }
+    };
+
+    let result = match task {
+        Ok(task) => {
+            execute_agent_with_context(
+                app.clone(),
+                schedule.agent_id,
+                schedule.project_path.clone(),
+                task,
+                schedule.model.clone(),
+                AgentRunContext {
+                    schedule_id: Some(schedule_id),
+                    parameters: schedule.parameters.clone(),
+                    ..Default::default()
+                },
+                app.state::<AgentDb>(),
+                app.state::<ProcessRegistryState>(),
+            )
+            .await
+        }
+        Err(e) => Err(e),
+    };
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    match &result {
+        Ok(run_id) => {
+            info!("Schedule {} started agent run {}", schedule_id, run_id);
+            conn.execute(
+                "UPDATE agent_schedules SET last_run_at = ?1, last_run_id = ?2, last_error = NULL WHERE id = ?3",
+                params![Utc::now().to_rfc3339(), run_id, schedule_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        Err(e) => {
+            error!("Schedule {} failed to start: {}", schedule_id, e);
+            conn.execute(
+                "UPDATE agent_schedules SET last_run_at = ?1, last_error = ?2 WHERE id = ?3",
+                params![Utc::now().to_rfc3339(), e, schedule_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+
+    result
+}
+
+/// Decide which due schedules to fire, advancing their next run time
+fn collect_due_schedules(conn: &Connection, now: DateTime<Utc>) -> Result<Vec<AgentSchedule>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM agent_schedules WHERE enabled = 1 AND next_run_at IS NOT NULL AND next_run_at <= ?1",
+            SCHEDULE_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let schedules = stmt
+        .query_map(params![now.to_rfc3339()], map_schedule_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut due = Vec::new();
+    for schedule in schedules {
+        let Some(id) = schedule.id else { continue };
+        let scheduled_at = schedule
+            .next_run_at
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or(now);
+        let missed = now - scheduled_at > Duration::seconds(MISSED_RUN_GRACE_SECS);
+
+        // Always advance from now so a long sleep never queues a burst of runs
+        match next_run_after(
+            schedule.cron_expression.as_deref(),
+            schedule.interval_seconds,
+            now,
+        ) {
+            Ok(next) => {
+                conn.execute(
+                    "UPDATE agent_schedules SET next_run_at = ?1 WHERE id = ?2",
+                    params![next.to_rfc3339(), id],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            Err(e) => {
+                warn!("Disabling schedule {}: {}", id, e);
+                conn.execute(
+                    "UPDATE agent_schedules SET enabled = 0, next_run_at = NULL, last_error = ?1 WHERE id = ?2",
+                    params![e, id],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+        }
+
+        if missed && schedule.missed_run_policy == MissedRunPolicy::Skip {
+            info!("Skipping missed run of schedule {} due at {}", id, scheduled_at);
+            continue;
+        }
+        due.push(schedule);
+    }
+
+    Ok(due)
+}
+
+/// Spawn the background task that fires due schedules
+pub fn start_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        info!("Agent scheduler started");
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(SCHEDULER_TICK_SECS));
+
+        loop {
+            interval.tick().await;
+
+            let due = {
+                let db = app.state::<AgentDb>();
+                let conn = match db.0.lock() {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        error!("Scheduler failed to lock database: {}", e);
+                        continue;
+                    }
+                };
+                match collect_due_schedules(&conn, Utc::now()) {
+                    Ok(due) => due,
+                    Err(e) => {
+                        error!("Scheduler failed to load schedules: {}", e);
+                        continue;
+                    }
+                }
+            };
+
+            for schedule in due {
+                let _ = fire_schedule(&app, &schedule).await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_run_after() {
+        let now = Utc::now();
+
+        let next = next_run_after(None, Some(3600), now).unwrap();
+        assert_eq!(next - now, Duration::seconds(3600));
+
+        let next = next_run_after(Some("*/5 * * * *"), None, now).unwrap();
+        assert!(next > now && next - now <= Duration::minutes(5));
+
+        assert!(next_run_after(Some("not a cron"), None, now).is_err());
+        assert!(next_run_after(None, Some(0), now).is_err());
+        assert!(next_run_after(None, None, now).is_err());
+    }
+
+    #[test]
+    fn test_schedule_parameters() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("CREATE TABLE agents (id INTEGER PRIMARY KEY, parameters TEXT)", [])
+            .unwrap();
+        conn.execute(
+            r#"INSERT INTO agents (id, parameters) VALUES (1, '[{"name": "target", "required": true}]')"#,
+            [],
+        )
+        .unwrap();
+        init_schedule_tables(&conn).unwrap();
+
+        let mut input = AgentScheduleInput {
+            agent_id: 1,
+            name: "nightly".to_string(),
+            cron_expression: None,
+            interval_seconds: Some(60),
+            project_path: "/tmp".to_string(),
+            task: None,
+            model: None,
+            parameters: None,
+            enabled: true,
+            missed_run_policy: MissedRunPolicy::RunOnce,
+        };
+        assert!(check_schedule_parameters(&conn, &input).is_err());
+
+        input.parameters = Some(HashMap::from([("target".to_string(), JsonValue::from("src"))]));
+        let parameters = check_schedule_parameters(&conn, &input).unwrap();
+        conn.execute(
+            "INSERT INTO agent_schedules (agent_id, name, interval_seconds, project_path, next_run_at, parameters) VALUES (1, 'nightly', 60, '/tmp', ?1, ?2)",
+            params![Utc::now().to_rfc3339(), parameters],
+        )
+        .unwrap();
+
+        let due = collect_due_schedules(&conn, Utc::now() + Duration::seconds(1)).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].parameters, input.parameters);
+    }
+}
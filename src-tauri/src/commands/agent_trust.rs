@@ -0,0 +1,360 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use log::{info, warn};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::commands::agents::AgentDb;
+
+/// Settings key holding the trusted signer keys
+const TRUSTED_KEYS_SETTING_KEY: &str = "agent_trusted_keys";
+
+/// Settings key holding the import policy
+const IMPORT_POLICY_SETTING_KEY: &str = "agent_import_policy";
+
+/// Extension of detached signature files, appended to the agent file name
+pub const SIGNATURE_EXTENSION: &str = ".sig";
+
+/// A public key whose signatures are trusted for agent imports
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrustedKey {
+    pub id: String,
+    pub name: String,
+    /// Base64-encoded 32-byte ed25519 public key
+    pub public_key: String,
+    pub added_at: String,
+}
+
+/// What to do with agents that are not signed by a trusted key
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportPolicy {
+    /// Import anything
+    AllowUnsigned,
+    /// Import, but flag agents that aren't signed by a trusted key
+    #[default]
+    Warn,
+    /// Refuse agents that aren't signed by a trusted key
+    RequireTrusted,
+}
+
+/// Outcome of checking an agent file's detached signature
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SignatureStatus {
+    /// No signature was provided
+    Unsigned,
+    /// The signature verifies against a trusted key
+    Verified { key_id: String, key_name: String },
+    /// A signature was provided but no trusted key verifies it
+    Unverified,
+}
+
+/// Signature status together with the policy decision, for display before import
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentTrustReport {
+    pub signature: SignatureStatus,
+    pub policy: ImportPolicy,
+    /// Whether the import will be accepted under the current policy
+    pub allowed: bool,
+}
+
+fn load_trusted_keys(conn: &Connection) -> Result<Vec<TrustedKey>, String> {
+    match conn.query_row(
+        "SELECT value FROM app_settings WHERE key = ?1",
+        params![TRUSTED_KEYS_SETTING_KEY],
+        |row| row.get::<_, String>(0),
+    ) {
+        Ok(value) => {
+            serde_json::from_str(&value).map_err(|e| format!("Invalid trusted keys setting: {}", e))
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to load trusted keys: {}", e)),
+    }
+}
+
+fn store_trusted_keys(conn: &Connection, keys: &[TrustedKey]) -> Result<(), String> {
+    let value =
+        serde_json::to_string(keys).map_err(|e| format!("Failed to serialize trusted keys: {}", e))?;
+    conn.execute(
+        "INSERT INTO app_settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = ?2",
+        params![TRUSTED_KEYS_SETTING_KEY, value],
+    )
+    .map_err(|e| format!("Failed to save trusted keys: {}", e))?;
+    Ok(())
+}
+
+fn load_import_policy(conn: &Connection) -> Result<ImportPolicy, String> {
+    match conn.query_row(
+        "SELECT value FROM app_settings WHERE key = ?1",
+        params![IMPORT_POLICY_SETTING_KEY],
+        |row| row.get::<_, String>(0),
+    ) {
+        Ok(value) => serde_json::from_value(serde_json::Value::String(value))
+            .map_err(|e| format!("Invalid import policy setting: {}", e)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(ImportPolicy::default()),
+        Err(e) => Err(format!("Failed to load import policy: {}", e)),
+    }
+}
+
+fn parse_public_key(public_key: &str) -> Result<VerifyingKey, String> {
+    let bytes = BASE64
+        .decode(public_key.trim())
+        .map_err(|e| format!("Public key is not valid base64: {}", e))?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| "Public key must be 32 bytes".to_string())?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| format!("Invalid ed25519 public key: {}", e))
+}
+
+/// Check a detached base64 ed25519 signature over the exact agent file bytes
+pub fn verify_signature(
+    content: &[u8],
+    signature: Option<&str>,
+    keys: &[TrustedKey],
+) -> SignatureStatus {
+    let Some(signature) = signature.map(str::trim).filter(|s| !s.is_empty()) else {
+        return SignatureStatus::Unsigned;
+    };
+
+    let signature = match BASE64
+        .decode(signature)
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+    {
+        Some(signature) => signature,
+        None => return SignatureStatus::Unverified,
+    };
+
+    for key in keys {
+        match parse_public_key(&key.public_key) {
+            Ok(verifying_key) => {
+                if verifying_key.verify(content, &signature).is_ok() {
+                    return SignatureStatus::Verified {
+                        key_id: key.id.clone(),
+                        key_name: key.name.clone(),
+                    };
+                }
+            }
+            Err(e) => warn!("Skipping malformed trusted key {}: {}", key.id, e),
+        }
+    }
+
+    SignatureStatus::Unverified
+}
+
+/// Evaluate an agent file against the trusted keys and import policy
+pub fn evaluate_trust(
+    conn: &Connection,
+    content: &[u8],
+    signature: Option<&str>,
+) -> Result<AgentTrustReport, String> {
+    let keys = load_trusted_keys(conn)?;
+    let policy = load_import_policy(conn)?;
+    let signature = verify_signature(content, signature, &keys);
+    let allowed = match policy {
+        ImportPolicy::AllowUnsigned | ImportPolicy::Warn => true,
+        ImportPolicy::RequireTrusted => matches!(signature, SignatureStatus::Verified { .. }),
+    };
+
+    Ok(AgentTrustReport {
+        signature,
+        policy,
+        allowed,
+    })
+}
+
+/// Enforce the import policy, returning an error if the agent must be rejected
+pub fn enforce_import_policy(
+    conn: &Connection,
+    content: &[u8],
+    signature: Option<&str>,
+) -> Result<AgentTrustReport, String> {
+    let report = evaluate_trust(conn, content, signature)?;
+
+    match (&report.signature, report.allowed) {
+        (SignatureStatus::Verified { key_name, .. }, _) => {
+            info!("Agent signature verified with trusted key '{}'", key_name);
+        }
+        (status, true) => {
+            if report.policy == ImportPolicy::Warn {
+                warn!("Importing agent that is not signed by a trusted key: {:?}", status);
+            }
+        }
+        (SignatureStatus::Unsigned, false) => {
+            return Err(
+                "Import policy requires agents to be signed by a trusted key, but this agent is unsigned"
+                    .to_string(),
+            );
+        }
+        (SignatureStatus::Unverified, false) => {
+            return Err(
+                "Import policy requires agents to be signed by a trusted key, but the signature could not be verified"
+                    .to_string(),
+            );
+        }
+    }
+
+    Ok(report)
+}
+
+/// Read the detached signature that sits next to a local agent file, if any
+pub fn read_local_signature(file_path: &str) -> Option<String> {
+    std::fs::read_to_string(format!("{}{}", file_path, SIGNATURE_EXTENSION)).ok()
+}
+
+/// Fetch the detached signature published next to a remote agent file, if any
+pub async fn fetch_remote_signature(download_url: &str) -> Option<String> {
+    if let Some(path) = download_url.strip_prefix("file://") {
+        return read_local_signature(path);
+    }
+
+    let response = reqwest::Client::new()
+        .get(format!("{}{}", download_url, SIGNATURE_EXTENSION))
+        .header("User-Agent", "Claudia-App")
+        .send()
+        .await
+        .ok()?;
+
+    if !response.status().is_success() {
+        return None;
+    }
+    response.text().await.ok()
+}
+
+/// List trusted signer keys
+#[tauri::command]
+pub async fn list_trusted_agent_keys(db: State<'_, AgentDb>) -> Result<Vec<TrustedKey>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_trusted_keys(&conn)
+}
+
+/// Add a trusted signer key
+#[tauri::command]
+pub async fn add_trusted_agent_key(
+    db: State<'_, AgentDb>,
+    name: String,
+    public_key: String,
+) -> Result<TrustedKey, String> {
+    let public_key = public_key.trim().to_string();
+    parse_public_key(&public_key)?;
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut keys = load_trusted_keys(&conn)?;
+    if keys.iter().any(|k| k.public_key == public_key) {
+        return Err("This key is already trusted".to_string());
+    }
+
+    let key = TrustedKey {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        public_key,
+        added_at: chrono::Utc::now().to_rfc3339(),
+    };
+    keys.push(key.clone());
+    store_trusted_keys(&conn, &keys)?;
+
+    Ok(key)
+}
+
+/// Remove a trusted signer key
+#[tauri::command]
+pub async fn remove_trusted_agent_key(db: State<'_, AgentDb>, id: String) -> Result<(), String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut keys = load_trusted_keys(&conn)?;
+    keys.retain(|k| k.id != id);
+    store_trusted_keys(&conn, &keys)
+}
+
+/// Get the agent import policy
+#[tauri::command]
+pub async fn get_agent_import_policy(db: State<'_, AgentDb>) -> Result<ImportPolicy, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_import_policy(&conn)
+}
+
+/// Set the agent import policy
+#[tauri::command]
+pub async fn set_agent_import_policy(
+    db: State<'_, AgentDb>,
+    policy: ImportPolicy,
+) -> Result<(), String> {
+    let value = match serde_json::to_value(policy) {
+        Ok(serde_json::Value::String(value)) => value,
+        _ => return Err("Failed to serialize import policy".to_string()),
+    };
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO app_settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = ?2",
+        params![IMPORT_POLICY_SETTING_KEY, value],
+    )
+    .map_err(|e| format!("Failed to save import policy: {}", e))?;
+
+    Ok(())
+}
+
+/// Check the signature of a local agent file before importing it
+#[tauri::command]
+pub async fn verify_agent_file_signature(
+    db: State<'_, AgentDb>,
+    file_path: String,
+) -> Result<AgentTrustReport, String> {
+    let content = std::fs::read(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let signature = read_local_signature(&file_path);
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    evaluate_trust(&conn, &content, signature.as_deref())
+}
+
+/// Check the signature of a remote agent file before importing it
+#[tauri::command]
+pub async fn verify_agent_source_signature(
+    db: State<'_, AgentDb>,
+    download_url: String,
+) -> Result<AgentTrustReport, String> {
+    let content = crate::commands::agent_registry::read_agent_source(&download_url).await?;
+    let signature = fetch_remote_signature(&download_url).await;
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    evaluate_trust(&conn, content.as_bytes(), signature.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn trusted_key(signing_key: &SigningKey) -> TrustedKey {
+        TrustedKey {
+            id: "k1".to_string(),
+            name: "Team".to_string(),
+            public_key: BASE64.encode(signing_key.verifying_key().to_bytes()),
+            added_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let content = br#"{"version":2}"#;
+        let signature = BASE64.encode(signing_key.sign(content).to_bytes());
+        let keys = vec![trusted_key(&signing_key)];
+
+        assert_eq!(
+            verify_signature(content, Some(&signature), &keys),
+            SignatureStatus::Verified {
+                key_id: "k1".to_string(),
+                key_name: "Team".to_string()
+            }
+        );
+        assert_eq!(
+            verify_signature(br#"{"version":1}"#, Some(&signature), &keys),
+            SignatureStatus::Unverified
+        );
+        assert_eq!(verify_signature(content, None, &keys), SignatureStatus::Unsigned);
+    }
+}
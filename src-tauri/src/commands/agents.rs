@@ -2,10 +2,10 @@ use anyhow::Result;
 use chrono;
 use dirs;
 use log::{debug, error, info, warn};
-use reqwest;
 use rusqlite::{params, Connection, Result as SqliteResult};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
+use std::collections::HashMap;
 use std::io::{BufRead, BufReader};
 use std::process::Stdio;
 use std::sync::Mutex;
@@ -15,6 +15,8 @@ use tauri_plugin_shell::process::CommandEvent;
 use tokio::io::{AsyncBufReadExt, BufReader as TokioBufReader};
 use tokio::process::Command;
 
+use super::mcp::MCPServerConfig;
+
 /// Finds the full path to the claude binary
 /// This is necessary because macOS apps have a limited PATH environment
 fn find_claude_binary(app_handle: &AppHandle) -> Result<String, String> {
@@ -37,6 +39,11 @@ pub struct Agent {
     pub source: Option<String>, // 'claudia', 'native', 'user', etc.
     pub created_at: String,
     pub updated_at: String,
+    pub allowed_tools: Option<String>, // JSON array of tool names passed via --allowedTools
+    pub dependencies: Option<String>,  // JSON of `AgentDependencies`
+    pub parameters: Option<String>,    // JSON array of `AgentParameter` declarations
+    #[serde(default)]
+    pub collect_artifacts: bool, // Point the agent at a per-run artifacts directory
 }
 
 /// Represents an agent execution run
@@ -55,6 +62,59 @@ pub struct AgentRun {
     pub process_started_at: Option<String>,
     pub created_at: String,
     pub completed_at: Option<String>,
+    pub agent_version: Option<i64>, // Agent definition version used for this run
+    pub schedule_id: Option<i64>,   // Schedule that started this run, if any
+    pub pipeline_run_id: Option<i64>, // Pipeline run this run is a step of, if any
+    pub pipeline_step: Option<i64>,   // Index of the pipeline step
+    pub batch_id: Option<String>,     // Batch this run belongs to, if any
+    pub parameters: Option<String>,   // JSON object of resolved template parameters
+    pub retry_of: Option<i64>,        // Original run this run retries or resumes, if any
+    pub attempt: i64,                 // 1 for an original run, 2+ for its retries
+}
+
+/// Represents a snapshot of an agent definition at a point in time
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentVersion {
+    pub id: Option<i64>,
+    pub agent_id: i64,
+    pub version: i64,
+    pub name: String,
+    pub icon: String,
+    pub system_prompt: String,
+    pub default_task: Option<String>,
+    pub model: String,
+    pub enable_file_read: bool,
+    pub enable_file_write: bool,
+    pub enable_network: bool,
+    pub hooks: Option<String>,
+    pub change_note: Option<String>, // e.g. "created", "updated", "rollback to v3"
+    pub created_at: String,
+    pub allowed_tools: Option<String>,
+    pub dependencies: Option<String>,
+    pub parameters: Option<String>,
+    #[serde(default)]
+    pub collect_artifacts: bool,
+}
+
+/// A single field that differs between two agent versions
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentFieldChange {
+    pub field: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Diff between two agent versions
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentVersionDiff {
+    pub agent_id: i64,
+    pub from_version: i64,
+    pub to_version: i64,
+    pub changed_fields: Vec<AgentFieldChange>,
+    /// Unified diff of the system prompt (None if unchanged)
+    pub system_prompt_diff: Option<String>,
+    /// Unified diff of the hooks JSON (None if unchanged)
+    pub hooks_diff: Option<String>,
 }
 
 /// Represents runtime metrics calculated from JSONL
@@ -75,6 +135,18 @@ pub struct AgentRunWithMetrics {
     pub output: Option<String>, // Real-time JSONL content
 }
 
+/// Export format version written by `export_agent`
+pub const AGENT_EXPORT_VERSION: u32 = 2;
+
+/// How often `wait_for_agent_run` checks whether a run has finished
+const RUN_POLL_INTERVAL_MS: u64 = 2000;
+
+/// Longest `wait_for_agent_run` waits for a run before failing it
+const RUN_WAIT_TIMEOUT_SECS: u64 = 6 * 60 * 60;
+
+/// How long a run may stay 'running' after its process is gone before it is failed
+const RUN_EXIT_GRACE_SECS: u64 = 30;
+
 /// Agent export format
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AgentExport {
@@ -84,6 +156,9 @@ pub struct AgentExport {
 }
 
 /// Agent data within export
+///
+/// Version 1 only carries the fields up to `hooks`; everything after is
+/// optional so that v1 files keep deserializing.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AgentData {
     pub name: String,
@@ -92,6 +167,50 @@ pub struct AgentData {
     pub default_task: Option<String>,
     pub model: String,
     pub hooks: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<AgentPermissions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_tools: Option<Vec<String>>,
+    /// MCP servers the agent expects to be configured (env values are stripped)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub required_mcp_servers: HashMap<String, MCPServerConfig>,
+    /// Environment variable keys the agent expects to be set (never values)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_env: Vec<String>,
+    /// Template parameters used in the system prompt and default task
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<super::agent_parameters::AgentParameter>,
+    /// Whether runs are pointed at a per-run artifacts directory
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub collect_artifacts: bool,
+}
+
+/// Permission flags carried by v2 exports
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentPermissions {
+    pub enable_file_read: bool,
+    pub enable_file_write: bool,
+    pub enable_network: bool,
+}
+
+/// External requirements of an agent, stored as JSON in `agents.dependencies`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AgentDependencies {
+    #[serde(default)]
+    pub mcp_servers: HashMap<String, MCPServerConfig>,
+    #[serde(default)]
+    pub env: Vec<String>,
+}
+
+/// Result of checking an agent export against the local setup before import
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentDependencyReport {
+    pub agent_name: String,
+    pub export_version: u32,
+    pub required_mcp_servers: HashMap<String, MCPServerConfig>,
+    pub required_env: Vec<String>,
+    pub missing_mcp_servers: Vec<String>,
+    pub missing_env: Vec<String>,
 }
 
 /// Represents an environment variable group
@@ -123,6 +242,152 @@ pub struct EnvironmentVariable {
 /// Database connection state
 pub struct AgentDb(pub Mutex<Connection>);
 
+/// Column list shared by every query that maps rows into `Agent`
+const AGENT_COLUMNS: &str = "id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, source, created_at, updated_at, allowed_tools, dependencies, parameters, collect_artifacts";
+
+/// Map a row selected with `AGENT_COLUMNS` into an `Agent`
+fn map_agent_row(row: &rusqlite::Row) -> rusqlite::Result<Agent> {
+    Ok(Agent {
+        id: Some(row.get(0)?),
+        name: row.get(1)?,
+        icon: row.get(2)?,
+        system_prompt: row.get(3)?,
+        default_task: row.get(4)?,
+        model: row
+            .get::<_, String>(5)
+            .unwrap_or_else(|_| "sonnet".to_string()),
+        enable_file_read: row.get::<_, bool>(6).unwrap_or(true),
+        enable_file_write: row.get::<_, bool>(7).unwrap_or(true),
+        enable_network: row.get::<_, bool>(8).unwrap_or(false),
+        hooks: row.get(9)?,
+        source: row.get(10)?,
+        created_at: row.get(11)?,
+        updated_at: row.get(12)?,
+        allowed_tools: row.get(13)?,
+        dependencies: row.get(14)?,
+        parameters: row.get(15)?,
+        collect_artifacts: row.get::<_, bool>(16).unwrap_or(false),
+    })
+}
+
+/// Column list shared by every query that maps rows into `AgentRun`
+pub(crate) const AGENT_RUN_COLUMNS: &str = "id, agent_id, agent_name, agent_icon, task, model, project_path, session_id, status, pid, process_started_at, created_at, completed_at, agent_version, schedule_id, pipeline_run_id, pipeline_step, batch_id, parameters, retry_of, attempt";
+
+/// Map a row selected with `AGENT_RUN_COLUMNS` into an `AgentRun`
+pub(crate) fn map_agent_run_row(row: &rusqlite::Row) -> rusqlite::Result<AgentRun> {
+    Ok(AgentRun {
+        id: Some(row.get(0)?),
+        agent_id: row.get(1)?,
+        agent_name: row.get(2)?,
+        agent_icon: row.get(3)?,
+        task: row.get(4)?,
+        model: row.get(5)?,
+        project_path: row.get(6)?,
+        session_id: row.get(7)?,
+        status: row
+            .get::<_, String>(8)
+            .unwrap_or_else(|_| "pending".to_string()),
+        pid: row
+            .get::<_, Option<i64>>(9)
+            .ok()
+            .flatten()
+            .map(|p| p as u32),
+        process_started_at: row.get(10)?,
+        created_at: row.get(11)?,
+        completed_at: row.get(12)?,
+        agent_version: row.get(13)?,
+        schedule_id: row.get(14)?,
+        pipeline_run_id: row.get(15)?,
+        pipeline_step: row.get(16)?,
+        batch_id: row.get(17)?,
+        parameters: row.get(18)?,
+        retry_of: row.get(19)?,
+        attempt: row.get::<_, Option<i64>>(20)?.unwrap_or(1),
+    })
+}
+
+/// Column list shared by every query that maps rows into `AgentVersion`
+const AGENT_VERSION_COLUMNS: &str = "id, agent_id, version, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, change_note, created_at, allowed_tools, dependencies, parameters, collect_artifacts";
+
+/// Map a row selected with `AGENT_VERSION_COLUMNS` into an `AgentVersion`
+fn map_agent_version_row(row: &rusqlite::Row) -> rusqlite::Result<AgentVersion> {
+    Ok(AgentVersion {
+        id: Some(row.get(0)?),
+        agent_id: row.get(1)?,
+        version: row.get(2)?,
+        name: row.get(3)?,
+        icon: row.get(4)?,
+        system_prompt: row.get(5)?,
+        default_task: row.get(6)?,
+        model: row.get(7)?,
+        enable_file_read: row.get::<_, bool>(8).unwrap_or(true),
+        enable_file_write: row.get::<_, bool>(9).unwrap_or(true),
+        enable_network: row.get::<_, bool>(10).unwrap_or(false),
+        hooks: row.get(11)?,
+        change_note: row.get(12)?,
+        created_at: row.get(13)?,
+        allowed_tools: row.get(14)?,
+        dependencies: row.get(15)?,
+        parameters: row.get(16)?,
+        collect_artifacts: row.get::<_, bool>(17).unwrap_or(false),
+    })
+}
+
+/// Snapshot the current definition of an agent into `agent_versions`.
+///
+/// A new version is only written when the definition differs from the latest
+/// stored version, so repeated saves without changes don't create noise.
+/// Returns the version number that now matches the agent row.
+pub fn record_agent_version(
+    conn: &Connection,
+    agent_id: i64,
+    change_note: Option<&str>,
+) -> SqliteResult<i64> {
+    let latest: Option<(i64, bool)> = conn
+        .query_row(
+            "SELECT v.version,
+                    v.name = a.name AND v.icon = a.icon AND v.system_prompt = a.system_prompt
+                    AND v.default_task IS a.default_task AND v.model = a.model
+                    AND v.enable_file_read = a.enable_file_read AND v.enable_file_write = a.enable_file_write
+                    AND v.enable_network = a.enable_network AND v.hooks IS a.hooks
+                    AND v.allowed_tools IS a.allowed_tools AND v.dependencies IS a.dependencies
+                    AND v.parameters IS a.parameters AND v.collect_artifacts = a.collect_artifacts
+             FROM agent_versions v JOIN agents a ON a.id = v.agent_id
+             WHERE v.agent_id = ?1
+             ORDER BY v.version DESC LIMIT 1",
+            params![agent_id],
+            |row| Ok((row.get(0)?, row.get::<_, Option<bool>>(1)?.unwrap_or(false))),
+        )
+        .map(Some)
+        .or_else(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Ok(None),
+            e => Err(e),
+        })?;
+
+    if let Some((version, true)) = latest {
+        return Ok(version);
+    }
+
+    let next_version = latest.map(|(v, _)| v + 1).unwrap_or(1);
+    conn.execute(
+        "INSERT INTO agent_versions (agent_id, version, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, allowed_tools, dependencies, parameters, collect_artifacts, change_note)
+         SELECT id, ?2, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, allowed_tools, dependencies, parameters, collect_artifacts, ?3
+         FROM agents WHERE id = ?1",
+        params![agent_id, next_version, change_note],
+    )?;
+
+    Ok(next_version)
+}
+
+/// Get the latest recorded version number of an agent
+fn current_agent_version(conn: &Connection, agent_id: i64) -> SqliteResult<Option<i64>> {
+    conn.query_row(
+        "SELECT MAX(version) FROM agent_versions WHERE agent_id = ?1",
+        params![agent_id],
+        |row| row.get(0),
+    )
+}
+
 /// Real-time JSONL reading and processing functions
 impl AgentRunMetrics {
     /// Calculate metrics from JSONL content
@@ -134,11 +399,11 @@ impl AgentRunMetrics {
         let mut end_time: Option<chrono::DateTime<chrono::Utc>> = None;
 
         for line in jsonl_content.lines() {
-            if let Ok(json) = serde_json::from_str::<JsonValue>(line) {
+            if let Some(line) = crate::stream_json::parse_line(line) {
                 message_count += 1;
 
                 // Track timestamps
-                if let Some(timestamp_str) = json.get("timestamp").and_then(|t| t.as_str()) {
+                if let Some(timestamp_str) = line.meta.timestamp.as_deref() {
                     if let Ok(timestamp) = chrono::DateTime::parse_from_rfc3339(timestamp_str) {
                         let utc_time = timestamp.with_timezone(&chrono::Utc);
                         if start_time.map_or(true, |st| utc_time < st) {
@@ -150,23 +415,13 @@ impl AgentRunMetrics {
                     }
                 }
 
-                // Extract token usage - check both top-level and nested message.usage
-                let usage = json
-                    .get("usage")
-                    .or_else(|| json.get("message").and_then(|m| m.get("usage")));
-
-                if let Some(usage) = usage {
-                    if let Some(input_tokens) = usage.get("input_tokens").and_then(|t| t.as_i64()) {
-                        total_tokens += input_tokens;
-                    }
-                    if let Some(output_tokens) = usage.get("output_tokens").and_then(|t| t.as_i64())
-                    {
-                        total_tokens += output_tokens;
-                    }
+                // Extract token usage from the message, or the run totals of a result line
+                if let Some(usage) = line.usage() {
+                    total_tokens += usage.total_tokens() as i64;
                 }
 
                 // Extract cost information
-                if let Some(cost) = json.get("cost").and_then(|c| c.as_f64()) {
+                if let Some(cost) = line.cost_usd() {
                     cost_usd += cost;
                 }
             }
@@ -196,14 +451,9 @@ impl AgentRunMetrics {
 
 /// Read JSONL content from a session file
 pub async fn read_session_jsonl(session_id: &str, project_path: &str) -> Result<String, String> {
-    let claude_dir = dirs::home_dir()
-        .ok_or("Failed to get home directory")?
-        .join(".claude")
-        .join("projects");
-
-    // Encode project path to match Claude Code's directory naming
-    let encoded_project = project_path.replace('/', "-");
-    let project_dir = claude_dir.join(&encoded_project);
+    // Find the directory Claude Code keeps the project's sessions in
+    let project_dir = crate::project_paths::project_dir_for_path(project_path)
+        .ok_or_else(|| format!("Project directory not found for {}", project_path))?;
     let session_file = project_dir.join(format!("{}.jsonl", session_id));
 
     if !session_file.exists() {
@@ -257,7 +507,12 @@ pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
 
     let db_path = app_dir.join("agents.db");
     let conn = Connection::open(db_path)?;
+    init_schema(&conn)?;
+    Ok(conn)
+}
 
+/// Create or migrate every table of the app database
+fn init_schema(conn: &Connection) -> SqliteResult<()> {
     // Create agents table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS agents (
@@ -285,6 +540,9 @@ pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
     );
     let _ = conn.execute("ALTER TABLE agents ADD COLUMN hooks TEXT", []);
     let _ = conn.execute("ALTER TABLE agents ADD COLUMN source TEXT DEFAULT 'claudia'", []);
+    let _ = conn.execute("ALTER TABLE agents ADD COLUMN allowed_tools TEXT", []);
+    let _ = conn.execute("ALTER TABLE agents ADD COLUMN dependencies TEXT", []);
+    let _ = conn.execute("ALTER TABLE agents ADD COLUMN parameters TEXT", []);
     let _ = conn.execute(
         "ALTER TABLE agents ADD COLUMN enable_file_read BOOLEAN DEFAULT 1",
         [],
@@ -297,6 +555,10 @@ pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
         "ALTER TABLE agents ADD COLUMN enable_network BOOLEAN DEFAULT 0",
         [],
     );
+    let _ = conn.execute(
+        "ALTER TABLE agents ADD COLUMN collect_artifacts BOOLEAN NOT NULL DEFAULT 0",
+        [],
+    );
 
     // Create agent_runs table
     conn.execute(
@@ -330,6 +592,14 @@ pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
         "ALTER TABLE agent_runs ADD COLUMN process_started_at TEXT",
         [],
     );
+    let _ = conn.execute("ALTER TABLE agent_runs ADD COLUMN agent_version INTEGER", []);
+    let _ = conn.execute("ALTER TABLE agent_runs ADD COLUMN schedule_id INTEGER", []);
+    let _ = conn.execute("ALTER TABLE agent_runs ADD COLUMN pipeline_run_id INTEGER", []);
+    let _ = conn.execute("ALTER TABLE agent_runs ADD COLUMN pipeline_step INTEGER", []);
+    let _ = conn.execute("ALTER TABLE agent_runs ADD COLUMN batch_id TEXT", []);
+    let _ = conn.execute("ALTER TABLE agent_runs ADD COLUMN parameters TEXT", []);
+    let _ = conn.execute("ALTER TABLE agent_runs ADD COLUMN retry_of INTEGER", []);
+    let _ = conn.execute("ALTER TABLE agent_runs ADD COLUMN attempt INTEGER DEFAULT 1", []);
 
     // Drop old columns that are no longer needed (data is now read from JSONL files)
     // Note: SQLite doesn't support DROP COLUMN, so we'll ignore errors for existing columns
@@ -344,6 +614,55 @@ pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
         [],
     );
 
+    // Usage analytics attribute session usage to runs by session ID
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_agent_runs_session ON agent_runs(session_id)",
+        [],
+    )?;
+
+    // Create agent_versions table to keep every revision of an agent definition
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_versions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            agent_id INTEGER NOT NULL,
+            version INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            icon TEXT NOT NULL,
+            system_prompt TEXT NOT NULL,
+            default_task TEXT,
+            model TEXT NOT NULL,
+            enable_file_read BOOLEAN NOT NULL DEFAULT 1,
+            enable_file_write BOOLEAN NOT NULL DEFAULT 1,
+            enable_network BOOLEAN NOT NULL DEFAULT 0,
+            hooks TEXT,
+            allowed_tools TEXT,
+            dependencies TEXT,
+            parameters TEXT,
+            collect_artifacts BOOLEAN NOT NULL DEFAULT 0,
+            change_note TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE(agent_id, version),
+            FOREIGN KEY (agent_id) REFERENCES agents(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    let _ = conn.execute("ALTER TABLE agent_versions ADD COLUMN allowed_tools TEXT", []);
+    let _ = conn.execute("ALTER TABLE agent_versions ADD COLUMN dependencies TEXT", []);
+    let _ = conn.execute("ALTER TABLE agent_versions ADD COLUMN parameters TEXT", []);
+    let _ = conn.execute(
+        "ALTER TABLE agent_versions ADD COLUMN collect_artifacts BOOLEAN NOT NULL DEFAULT 0",
+        [],
+    );
+
+    // Backfill an initial version for agents created before versioning existed
+    let _ = conn.execute(
+        "INSERT INTO agent_versions (agent_id, version, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, change_note, created_at)
+         SELECT id, 1, name, icon, system_prompt, default_task, COALESCE(model, 'sonnet'), COALESCE(enable_file_read, 1), COALESCE(enable_file_write, 1), COALESCE(enable_network, 0), hooks, 'initial', updated_at
+         FROM agents WHERE id NOT IN (SELECT agent_id FROM agent_versions)",
+        [],
+    );
+
     // Create trigger to update the updated_at timestamp
     conn.execute(
         "CREATE TRIGGER IF NOT EXISTS update_agent_timestamp
@@ -616,6 +935,43 @@ pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
     }
 
 
+    // Create agent registry cache and import tracking tables
+    super::agent_registry::init_registry_tables(conn)?;
+
+    // Create agent schedules table
+    super::agent_scheduler::init_schedule_tables(conn)?;
+
+    // Create agent pipeline tables
+    super::agent_pipelines::init_pipeline_tables(conn)?;
+
+    // Create agent batch table
+    super::agent_batches::init_batch_tables(conn)?;
+
+    // Create run result and artifact tables
+    super::agent_results::init_result_tables(conn)?;
+    super::agent_retries::init_retry_tables(conn)?;
+
+    // Create session search index tables
+    super::session_search::init_search_tables(conn)?;
+
+    // Create session metadata table
+    super::session_meta::init_session_meta_tables(conn)?;
+
+    // Create session summary tables
+    super::session_summaries::init_summary_tables(conn)?;
+
+    // Create session trash table
+    super::session_trash::init_trash_tables(conn)?;
+
+    // Create session fork table
+    super::session_forks::init_fork_tables(conn)?;
+
+    // Create usage index tables
+    super::usage_index::init_usage_index_tables(conn)?;
+
+    // Create budget tables
+    super::usage_budgets::init_budget_tables(conn)?;
+
     // Create trigger to update the updated_at timestamp
     conn.execute(
         "CREATE TRIGGER IF NOT EXISTS update_app_settings_timestamp
@@ -648,7 +1004,7 @@ pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
         [],
     )?;
 
-    Ok(conn)
+    Ok(())
 }
 
 /// List all agents
@@ -657,29 +1013,14 @@ pub async fn list_agents(db: State<'_, AgentDb>) -> Result<Vec<Agent>, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
 
     let mut stmt = conn
-        .prepare("SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, source, created_at, updated_at FROM agents ORDER BY created_at DESC")
+        .prepare(&format!(
+            "SELECT {} FROM agents ORDER BY created_at DESC",
+            AGENT_COLUMNS
+        ))
         .map_err(|e| e.to_string())?;
 
     let agents = stmt
-        .query_map([], |row| {
-            Ok(Agent {
-                id: Some(row.get(0)?),
-                name: row.get(1)?,
-                icon: row.get(2)?,
-                system_prompt: row.get(3)?,
-                default_task: row.get(4)?,
-                model: row
-                    .get::<_, String>(5)
-                    .unwrap_or_else(|_| "sonnet".to_string()),
-                enable_file_read: row.get::<_, bool>(6).unwrap_or(true),
-                enable_file_write: row.get::<_, bool>(7).unwrap_or(true),
-                enable_network: row.get::<_, bool>(8).unwrap_or(false),
-                hooks: row.get(9)?,
-                source: row.get(10)?,
-                created_at: row.get(11)?,
-                updated_at: row.get(12)?,
-            })
-        })
+        .query_map([], map_agent_row)
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
@@ -687,6 +1028,13 @@ pub async fn list_agents(db: State<'_, AgentDb>) -> Result<Vec<Agent>, String> {
     Ok(agents)
 }
 
+/// Validate parameter declarations, storing an empty list as NULL
+fn normalize_parameters(parameters: Option<String>) -> Result<Option<String>, String> {
+    let declared = super::agent_parameters::parse_parameters(parameters.as_deref())?;
+    super::agent_parameters::validate_declarations(&declared)?;
+    Ok(parameters.filter(|_| !declared.is_empty()))
+}
+
 /// Create a new agent
 #[tauri::command]
 pub async fn create_agent(
@@ -701,44 +1049,35 @@ pub async fn create_agent(
     enable_network: Option<bool>,
     hooks: Option<String>,
     source: Option<String>,
+    allowed_tools: Option<String>,
+    dependencies: Option<String>,
+    parameters: Option<String>,
+    collect_artifacts: Option<bool>,
 ) -> Result<Agent, String> {
+    let parameters = normalize_parameters(parameters)?;
     let conn = db.0.lock().map_err(|e| e.to_string())?;
     let model = model.unwrap_or_else(|| "sonnet-3-5".to_string());
     let enable_file_read = enable_file_read.unwrap_or(true);
     let enable_file_write = enable_file_write.unwrap_or(true);
     let enable_network = enable_network.unwrap_or(false);
+    let collect_artifacts = collect_artifacts.unwrap_or(false);
     let source = source.unwrap_or_else(|| "claudia".to_string());
 
     conn.execute(
-        "INSERT INTO agents (name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, source) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-        params![name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, source],
+        "INSERT INTO agents (name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, source, allowed_tools, dependencies, parameters, collect_artifacts) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+        params![name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, source, allowed_tools, dependencies, parameters, collect_artifacts],
     )
     .map_err(|e| e.to_string())?;
 
     let id = conn.last_insert_rowid();
+    record_agent_version(&conn, id, Some("created")).map_err(|e| e.to_string())?;
 
     // Fetch the created agent
     let agent = conn
         .query_row(
-            "SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, source, created_at, updated_at FROM agents WHERE id = ?1",
+            &format!("SELECT {} FROM agents WHERE id = ?1", AGENT_COLUMNS),
             params![id],
-            |row| {
-                Ok(Agent {
-                    id: Some(row.get(0)?),
-                    name: row.get(1)?,
-                    icon: row.get(2)?,
-                    system_prompt: row.get(3)?,
-                    default_task: row.get(4)?,
-                    model: row.get(5)?,
-                    enable_file_read: row.get(6)?,
-                    enable_file_write: row.get(7)?,
-                    enable_network: row.get(8)?,
-                    hooks: row.get(9)?,
-                    source: row.get(10)?,
-                    created_at: row.get(11)?,
-                    updated_at: row.get(12)?,
-                })
-            },
+            map_agent_row,
         )
         .map_err(|e| e.to_string())?;
 
@@ -759,6 +1098,10 @@ pub async fn update_agent(
     enable_file_write: Option<bool>,
     enable_network: Option<bool>,
     hooks: Option<String>,
+    allowed_tools: Option<String>,
+    dependencies: Option<String>,
+    parameters: Option<String>,
+    collect_artifacts: Option<bool>,
 ) -> Result<Agent, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
     let model = model.unwrap_or_else(|| "sonnet-3-5".to_string());
@@ -792,6 +1135,26 @@ pub async fn update_agent(
         query.push_str(&format!(", enable_network = ?{}", param_count));
         params_vec.push(Box::new(en));
     }
+    if let Some(tools) = allowed_tools {
+        param_count += 1;
+        query.push_str(&format!(", allowed_tools = ?{}", param_count));
+        params_vec.push(Box::new(Some(tools).filter(|t| !t.trim().is_empty())));
+    }
+    if let Some(deps) = dependencies {
+        param_count += 1;
+        query.push_str(&format!(", dependencies = ?{}", param_count));
+        params_vec.push(Box::new(Some(deps).filter(|d| !d.trim().is_empty())));
+    }
+    if let Some(params_json) = parameters {
+        param_count += 1;
+        query.push_str(&format!(", parameters = ?{}", param_count));
+        params_vec.push(Box::new(normalize_parameters(Some(params_json))?));
+    }
+    if let Some(ca) = collect_artifacts {
+        param_count += 1;
+        query.push_str(&format!(", collect_artifacts = ?{}", param_count));
+        params_vec.push(Box::new(ca));
+    }
 
     param_count += 1;
     query.push_str(&format!(" WHERE id = ?{}", param_count));
@@ -803,28 +1166,14 @@ pub async fn update_agent(
     )
     .map_err(|e| e.to_string())?;
 
+    record_agent_version(&conn, id, Some("updated")).map_err(|e| e.to_string())?;
+
     // Fetch the updated agent
     let agent = conn
         .query_row(
-            "SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, source, created_at, updated_at FROM agents WHERE id = ?1",
+            &format!("SELECT {} FROM agents WHERE id = ?1", AGENT_COLUMNS),
             params![id],
-            |row| {
-                Ok(Agent {
-                    id: Some(row.get(0)?),
-                    name: row.get(1)?,
-                    icon: row.get(2)?,
-                    system_prompt: row.get(3)?,
-                    default_task: row.get(4)?,
-                    model: row.get(5)?,
-                    enable_file_read: row.get(6)?,
-                    enable_file_write: row.get(7)?,
-                    enable_network: row.get(8)?,
-                    hooks: row.get(9)?,
-                    source: row.get(10)?,
-                    created_at: row.get(11)?,
-                    updated_at: row.get(12)?,
-                })
-            },
+            map_agent_row,
         )
         .map_err(|e| e.to_string())?;
 
@@ -838,6 +1187,14 @@ pub async fn delete_agent(db: State<'_, AgentDb>, id: i64) -> Result<(), String>
 
     conn.execute("DELETE FROM agents WHERE id = ?1", params![id])
         .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM agent_versions WHERE agent_id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM agent_registry_imports WHERE agent_id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM agent_schedules WHERE agent_id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM agent_retry_policies WHERE agent_id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
 
     Ok(())
 }
@@ -849,31 +1206,209 @@ pub async fn get_agent(db: State<'_, AgentDb>, id: i64) -> Result<Agent, String>
 
     let agent = conn
         .query_row(
-            "SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, source, created_at, updated_at FROM agents WHERE id = ?1",
+            &format!("SELECT {} FROM agents WHERE id = ?1", AGENT_COLUMNS),
             params![id],
-            |row| {
-                Ok(Agent {
-                    id: Some(row.get(0)?),
-                    name: row.get(1)?,
-                    icon: row.get(2)?,
-                    system_prompt: row.get(3)?,
-                    default_task: row.get(4)?,
-                    model: row.get::<_, String>(5).unwrap_or_else(|_| "sonnet-3-5".to_string()),
-                    enable_file_read: row.get::<_, bool>(6).unwrap_or(true),
-                    enable_file_write: row.get::<_, bool>(7).unwrap_or(true),
-                    enable_network: row.get::<_, bool>(8).unwrap_or(false),
-                    hooks: row.get(9)?,
-                    source: row.get(10)?,
-                    created_at: row.get(11)?,
-                    updated_at: row.get(12)?,
-                })
-            },
+            map_agent_row,
         )
         .map_err(|e| e.to_string())?;
 
     Ok(agent)
 }
 
+/// List all recorded versions of an agent, newest first
+#[tauri::command]
+pub async fn list_agent_versions(
+    db: State<'_, AgentDb>,
+    agent_id: i64,
+) -> Result<Vec<AgentVersion>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM agent_versions WHERE agent_id = ?1 ORDER BY version DESC",
+            AGENT_VERSION_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let versions = stmt
+        .query_map(params![agent_id], map_agent_version_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(versions)
+}
+
+/// Get a single version of an agent
+#[tauri::command]
+pub async fn get_agent_version(
+    db: State<'_, AgentDb>,
+    agent_id: i64,
+    version: i64,
+) -> Result<AgentVersion, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    fetch_agent_version(&conn, agent_id, version)
+}
+
+/// Fetch a version row, mapping a missing row to a readable error
+fn fetch_agent_version(conn: &Connection, agent_id: i64, version: i64) -> Result<AgentVersion, String> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM agent_versions WHERE agent_id = ?1 AND version = ?2",
+            AGENT_VERSION_COLUMNS
+        ),
+        params![agent_id, version],
+        map_agent_version_row,
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => {
+            format!("Version {} of agent {} not found", version, agent_id)
+        }
+        e => e.to_string(),
+    })
+}
+
+/// Build a unified diff between two texts, or None if they are identical
+fn unified_text_diff(from: &str, to: &str, from_label: &str, to_label: &str) -> Option<String> {
+    if from == to {
+        return None;
+    }
+    Some(
+        similar::TextDiff::from_lines(from, to)
+            .unified_diff()
+            .context_radius(3)
+            .header(from_label, to_label)
+            .to_string(),
+    )
+}
+
+/// Compare two versions of an agent
+#[tauri::command]
+pub async fn diff_agent_versions(
+    db: State<'_, AgentDb>,
+    agent_id: i64,
+    from_version: i64,
+    to_version: i64,
+) -> Result<AgentVersionDiff, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let from = fetch_agent_version(&conn, agent_id, from_version)?;
+    let to = fetch_agent_version(&conn, agent_id, to_version)?;
+    Ok(diff_versions(&from, &to))
+}
+
+/// Compare two versions of the same agent
+fn diff_versions(from: &AgentVersion, to: &AgentVersion) -> AgentVersionDiff {
+    let mut changed_fields = Vec::new();
+    let mut compare = |field: &str, a: Option<String>, b: Option<String>| {
+        if a != b {
+            changed_fields.push(AgentFieldChange {
+                field: field.to_string(),
+                from: a,
+                to: b,
+            });
+        }
+    };
+    compare("name", Some(from.name.clone()), Some(to.name.clone()));
+    compare("icon", Some(from.icon.clone()), Some(to.icon.clone()));
+    compare("system_prompt", Some(from.system_prompt.clone()), Some(to.system_prompt.clone()));
+    compare("default_task", from.default_task.clone(), to.default_task.clone());
+    compare("model", Some(from.model.clone()), Some(to.model.clone()));
+    compare(
+        "enable_file_read",
+        Some(from.enable_file_read.to_string()),
+        Some(to.enable_file_read.to_string()),
+    );
+    compare(
+        "enable_file_write",
+        Some(from.enable_file_write.to_string()),
+        Some(to.enable_file_write.to_string()),
+    );
+    compare(
+        "enable_network",
+        Some(from.enable_network.to_string()),
+        Some(to.enable_network.to_string()),
+    );
+    compare("hooks", from.hooks.clone(), to.hooks.clone());
+    compare("allowed_tools", from.allowed_tools.clone(), to.allowed_tools.clone());
+    compare("dependencies", from.dependencies.clone(), to.dependencies.clone());
+    compare("parameters", from.parameters.clone(), to.parameters.clone());
+    compare(
+        "collect_artifacts",
+        Some(from.collect_artifacts.to_string()),
+        Some(to.collect_artifacts.to_string()),
+    );
+
+    let from_label = format!("v{}", from.version);
+    let to_label = format!("v{}", to.version);
+
+    AgentVersionDiff {
+        agent_id: to.agent_id,
+        from_version: from.version,
+        to_version: to.version,
+        changed_fields,
+        system_prompt_diff: unified_text_diff(
+            &from.system_prompt,
+            &to.system_prompt,
+            &from_label,
+            &to_label,
+        ),
+        hooks_diff: unified_text_diff(
+            from.hooks.as_deref().unwrap_or(""),
+            to.hooks.as_deref().unwrap_or(""),
+            &from_label,
+            &to_label,
+        ),
+    }
+}
+
+/// Copy a stored version back into the agent row and record it as a new version,
+/// returning the new version number
+fn rollback_to_version(conn: &Connection, agent_id: i64, version: i64) -> Result<i64, String> {
+    let target = fetch_agent_version(conn, agent_id, version)?;
+
+    conn.execute(
+        "UPDATE agents SET name = ?1, icon = ?2, system_prompt = ?3, default_task = ?4, model = ?5, enable_file_read = ?6, enable_file_write = ?7, enable_network = ?8, hooks = ?9, allowed_tools = ?10, dependencies = ?11, parameters = ?12, collect_artifacts = ?13 WHERE id = ?14",
+        params![
+            target.name,
+            target.icon,
+            target.system_prompt,
+            target.default_task,
+            target.model,
+            target.enable_file_read,
+            target.enable_file_write,
+            target.enable_network,
+            target.hooks,
+            target.allowed_tools,
+            target.dependencies,
+            target.parameters,
+            target.collect_artifacts,
+            agent_id
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let note = format!("rollback to v{}", version);
+    record_agent_version(conn, agent_id, Some(&note)).map_err(|e| e.to_string())
+}
+
+/// Roll an agent back to a previous version.
+///
+/// The rollback is recorded as a new version so history stays linear.
+#[tauri::command]
+pub async fn rollback_agent_version(
+    db: State<'_, AgentDb>,
+    agent_id: i64,
+    version: i64,
+) -> Result<Agent, String> {
+    {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        rollback_to_version(&conn, agent_id, version)?;
+        info!("Rolled back agent {} to version {}", agent_id, version);
+    }
+
+    get_agent(db, agent_id).await
+}
+
 /// List agent runs (optionally filtered by agent_id)
 #[tauri::command]
 pub async fn list_agent_runs(
@@ -883,43 +1418,23 @@ pub async fn list_agent_runs(
     let conn = db.0.lock().map_err(|e| e.to_string())?;
 
     let query = if agent_id.is_some() {
-        "SELECT id, agent_id, agent_name, agent_icon, task, model, project_path, session_id, status, pid, process_started_at, created_at, completed_at
-         FROM agent_runs WHERE agent_id = ?1 ORDER BY created_at DESC"
+        format!(
+            "SELECT {} FROM agent_runs WHERE agent_id = ?1 ORDER BY created_at DESC",
+            AGENT_RUN_COLUMNS
+        )
     } else {
-        "SELECT id, agent_id, agent_name, agent_icon, task, model, project_path, session_id, status, pid, process_started_at, created_at, completed_at
-         FROM agent_runs ORDER BY created_at DESC"
+        format!(
+            "SELECT {} FROM agent_runs ORDER BY created_at DESC",
+            AGENT_RUN_COLUMNS
+        )
     };
 
-    let mut stmt = conn.prepare(query).map_err(|e| e.to_string())?;
-
-    let run_mapper = |row: &rusqlite::Row| -> rusqlite::Result<AgentRun> {
-        Ok(AgentRun {
-            id: Some(row.get(0)?),
-            agent_id: row.get(1)?,
-            agent_name: row.get(2)?,
-            agent_icon: row.get(3)?,
-            task: row.get(4)?,
-            model: row.get(5)?,
-            project_path: row.get(6)?,
-            session_id: row.get(7)?,
-            status: row
-                .get::<_, String>(8)
-                .unwrap_or_else(|_| "pending".to_string()),
-            pid: row
-                .get::<_, Option<i64>>(9)
-                .ok()
-                .flatten()
-                .map(|p| p as u32),
-            process_started_at: row.get(10)?,
-            created_at: row.get(11)?,
-            completed_at: row.get(12)?,
-        })
-    };
+    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
 
     let runs = if let Some(aid) = agent_id {
-        stmt.query_map(params![aid], run_mapper)
+        stmt.query_map(params![aid], map_agent_run_row)
     } else {
-        stmt.query_map(params![], run_mapper)
+        stmt.query_map(params![], map_agent_run_row)
     }
     .map_err(|e| e.to_string())?
     .collect::<Result<Vec<_>, _>>()
@@ -935,32 +1450,67 @@ pub async fn get_agent_run(db: State<'_, AgentDb>, id: i64) -> Result<AgentRun,
 
     let run = conn
         .query_row(
-            "SELECT id, agent_id, agent_name, agent_icon, task, model, project_path, session_id, status, pid, process_started_at, created_at, completed_at
-             FROM agent_runs WHERE id = ?1",
+            &format!("SELECT {} FROM agent_runs WHERE id = ?1", AGENT_RUN_COLUMNS),
             params![id],
-            |row| {
-                Ok(AgentRun {
-                    id: Some(row.get(0)?),
-                    agent_id: row.get(1)?,
-                    agent_name: row.get(2)?,
-                    agent_icon: row.get(3)?,
-                    task: row.get(4)?,
-                    model: row.get(5)?,
-                    project_path: row.get(6)?,
-                    session_id: row.get(7)?,
-                    status: row.get::<_, String>(8).unwrap_or_else(|_| "pending".to_string()),
-                    pid: row.get::<_, Option<i64>>(9).ok().flatten().map(|p| p as u32),
-                    process_started_at: row.get(10)?,
-                    created_at: row.get(11)?,
-                    completed_at: row.get(12)?,
-                })
-            },
+            map_agent_run_row,
         )
         .map_err(|e| e.to_string())?;
 
     Ok(run)
 }
 
+/// Why to stop waiting for an unfinished run, given how long it has been waited for and
+/// how long its process has been gone, if it has
+fn stalled_run_reason(waited: std::time::Duration, exited_for: Option<std::time::Duration>) -> Option<String> {
+    if exited_for.is_some_and(|exited_for| exited_for.as_secs() >= RUN_EXIT_GRACE_SECS) {
+        Some("its process exited without finishing the run".to_string())
+    } else if waited.as_secs() >= RUN_WAIT_TIMEOUT_SECS {
+        Some(format!("it did not finish within {} seconds", RUN_WAIT_TIMEOUT_SECS))
+    } else {
+        None
+    }
+}
+
+/// Wait until an agent run leaves the 'pending'/'running' states and return it.
+///
+/// A run that outlives `RUN_WAIT_TIMEOUT_SECS`, or whose process went away without
+/// finishing it, is killed and marked failed so pipelines and batches waiting on it move on.
+pub async fn wait_for_agent_run(app: &AppHandle, run_id: i64) -> Result<AgentRun, String> {
+    let started = std::time::Instant::now();
+    let mut exited_at: Option<std::time::Instant> = None;
+    loop {
+        let run = get_agent_run(app.state::<AgentDb>(), run_id).await?;
+        if run.status != "running" && run.status != "pending" {
+            return Ok(run);
+        }
+
+        let registry = app.state::<crate::process::ProcessRegistryState>();
+        if run.status == "running" && !registry.0.is_process_running(run_id).await? {
+            exited_at.get_or_insert_with(std::time::Instant::now);
+        } else {
+            exited_at = None;
+        }
+
+        if let Some(reason) = stalled_run_reason(started.elapsed(), exited_at.map(|at| at.elapsed())) {
+            warn!("Failing agent run {}: {}", run_id, reason);
+            let _ = registry.0.kill_process(run_id).await;
+            {
+                let db = app.state::<AgentDb>();
+                let conn = db.0.lock().map_err(|e| e.to_string())?;
+                conn.execute(
+                    "UPDATE agent_runs SET status = 'failed', completed_at = CURRENT_TIMESTAMP
+                     WHERE id = ?1 AND status IN ('pending', 'running')",
+                    params![run_id],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            return get_agent_run(app.state::<AgentDb>(), run_id).await;
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(RUN_POLL_INTERVAL_MS)).await;
+    }
+}
+
 /// Get agent run with real-time metrics from JSONL
 #[tauri::command]
 pub async fn get_agent_run_with_real_time_metrics(
@@ -996,15 +1546,84 @@ pub async fn execute_agent(
     project_path: String,
     task: String,
     model: Option<String>,
+    parameters: Option<HashMap<String, JsonValue>>,
+    db: State<'_, AgentDb>,
+    registry: State<'_, crate::process::ProcessRegistryState>,
+) -> Result<i64, String> {
+    execute_agent_with_context(
+        app,
+        agent_id,
+        project_path,
+        task,
+        model,
+        AgentRunContext {
+            parameters,
+            ..Default::default()
+        },
+        db,
+        registry,
+    )
+    .await
+}
+
+/// Where an agent run originated, recorded on its `agent_runs` row
+#[derive(Debug, Clone, Default)]
+pub struct AgentRunContext {
+    pub schedule_id: Option<i64>,
+    pub pipeline_run_id: Option<i64>,
+    pub pipeline_step: Option<i64>,
+    pub batch_id: Option<String>,
+    /// Values for the agent's declared template parameters
+    pub parameters: Option<HashMap<String, JsonValue>>,
+    /// Pending `agent_runs` row created ahead of time, used instead of inserting a new one
+    pub run_id: Option<i64>,
+    /// Original run this run retries or resumes
+    pub retry_of: Option<i64>,
+    /// Attempt number within the original run's retries, 1 when unset
+    pub attempt: Option<i64>,
+    /// Claude session to continue with `--resume`; the task is sent as the follow-up prompt
+    pub resume_session_id: Option<String>,
+    /// Pipeline step input, inserted into the task after its parameters are substituted
+    pub input: Option<String>,
+}
+
+/// Execute an agent, recording the context that triggered the run
+pub async fn execute_agent_with_context(
+    app: AppHandle,
+    agent_id: i64,
+    project_path: String,
+    mut task: String,
+    model: Option<String>,
+    context: AgentRunContext,
     db: State<'_, AgentDb>,
     registry: State<'_, crate::process::ProcessRegistryState>,
 ) -> Result<i64, String> {
     info!("Executing agent {} with task: {}", agent_id, task);
 
+    // Refuse to launch past an exhausted hard budget
+    super::usage_budgets::check_launch_budget(&db, &project_path).await?;
+
     // Get the agent from database
-    let agent = get_agent(db.clone(), agent_id).await?;
+    let mut agent = get_agent(db.clone(), agent_id).await?;
     let execution_model = model.unwrap_or(agent.model.clone());
 
+    // Validate parameter values and fill the system prompt and task templates
+    let declared = super::agent_parameters::parse_parameters(agent.parameters.as_deref())?;
+    let mut resolved_parameters = None;
+    if !declared.is_empty() || context.parameters.is_some() {
+        let resolved = super::agent_parameters::resolve_parameters(
+            &declared,
+            &context.parameters.clone().unwrap_or_default(),
+            std::path::Path::new(&project_path),
+        )?;
+        agent.system_prompt = super::agent_parameters::substitute(&agent.system_prompt, &resolved);
+        task = super::agent_parameters::substitute(&task, &resolved);
+        resolved_parameters = Some(serde_json::to_string(&resolved).map_err(|e| e.to_string())?);
+    }
+    if let Some(input) = &context.input {
+        task = super::agent_pipelines::insert_input(&task, input);
+    }
+
     // Create .claude/settings.json with agent hooks if it doesn't exist
     if let Some(hooks_json) = &agent.hooks {
         let claude_dir = std::path::Path::new(&project_path).join(".claude");
@@ -1044,12 +1663,22 @@ pub async fn execute_agent(
     // Create a new run record
     let run_id = {
         let conn = db.0.lock().map_err(|e| e.to_string())?;
-        conn.execute(
-            "INSERT INTO agent_runs (agent_id, agent_name, agent_icon, task, model, project_path, session_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            params![agent_id, agent.name, agent.icon, task, execution_model, project_path, ""],
-        )
-        .map_err(|e| e.to_string())?;
-        conn.last_insert_rowid()
+        let agent_version = current_agent_version(&conn, agent_id).map_err(|e| e.to_string())?;
+        if let Some(run_id) = context.run_id {
+            conn.execute(
+                "UPDATE agent_runs SET task = ?1, model = ?2, project_path = ?3, agent_version = ?4, parameters = ?5 WHERE id = ?6",
+                params![task, execution_model, project_path, agent_version, resolved_parameters, run_id],
+            )
+            .map_err(|e| e.to_string())?;
+            run_id
+        } else {
+            conn.execute(
+                "INSERT INTO agent_runs (agent_id, agent_name, agent_icon, task, model, project_path, session_id, agent_version, schedule_id, pipeline_run_id, pipeline_step, batch_id, parameters, retry_of, attempt) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+                params![agent_id, agent.name, agent.icon, task, execution_model, project_path, "", agent_version, context.schedule_id, context.pipeline_run_id, context.pipeline_step, context.batch_id, resolved_parameters, context.retry_of, context.attempt.unwrap_or(1)],
+            )
+            .map_err(|e| e.to_string())?;
+            conn.last_insert_rowid()
+        }
     };
 
     // Find Claude binary
@@ -1062,12 +1691,23 @@ pub async fn execute_agent(
         }
     };
 
+    // Agents that opt in are told where named artifacts go; they're collected when the run finishes
+    let system_prompt = if agent.collect_artifacts {
+        format!(
+            "{}\n\nIf you produce named artifacts (reports, summaries, patches), write them to {}",
+            agent.system_prompt,
+            super::agent_results::artifacts_dir(&project_path, run_id).display()
+        )
+    } else {
+        agent.system_prompt.clone()
+    };
+
     // Build arguments
-    let args = vec![
+    let mut args = vec![
         "-p".to_string(),
         task.clone(),
         "--system-prompt".to_string(),
-        agent.system_prompt.clone(),
+        system_prompt,
         "--model".to_string(),
         execution_model.clone(),
         "--output-format".to_string(),
@@ -1076,12 +1716,36 @@ pub async fn execute_agent(
         "--dangerously-skip-permissions".to_string(),
     ];
 
+    // Continue an earlier Claude session instead of starting a new one
+    if let Some(session_id) = &context.resume_session_id {
+        args.push("--resume".to_string());
+        args.push(session_id.clone());
+    }
+
+    // Restrict the agent to its declared tools, if any
+    if let Some(tools_json) = &agent.allowed_tools {
+        match serde_json::from_str::<Vec<String>>(tools_json) {
+            Ok(tools) if !tools.is_empty() => {
+                args.push("--allowedTools".to_string());
+                args.push(tools.join(","));
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Ignoring invalid allowed tools for agent {}: {}", agent_id, e),
+        }
+    }
+
     // Execute based on whether we should use sidecar or system binary
-    if should_use_sidecar(&claude_path) {
-        spawn_agent_sidecar(app, run_id, agent_id, agent.name.clone(), args, project_path, task, execution_model, db, registry).await
+    let result = if should_use_sidecar(&claude_path) {
+        spawn_agent_sidecar(app.clone(), run_id, agent_id, agent.name.clone(), args, project_path, task, execution_model, db, registry).await
     } else {
-        spawn_agent_system(app, run_id, agent_id, agent.name.clone(), claude_path, args, project_path, task, execution_model, db, registry).await
+        spawn_agent_system(app.clone(), run_id, agent_id, agent.name.clone(), claude_path, args, project_path, task, execution_model, db, registry).await
+    };
+
+    // Watch the run for failures the agent's retry policy covers
+    if result.is_ok() {
+        super::agent_retries::watch_for_auto_retry(&app, agent_id, run_id);
     }
+    result
 }
 
 /// Determines whether to use sidecar or system binary execution for agents
@@ -1214,6 +1878,7 @@ async fn spawn_agent_sidecar(
     let first_output = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
     let first_output_clone = first_output.clone();
     let db_path_for_sidecar = db_path.clone();
+    let project_path_for_sidecar = project_path.clone();
 
     tokio::spawn(async move {
         info!("📖 Starting to read Claude sidecar events...");
@@ -1250,32 +1915,27 @@ async fn spawn_agent_sidecar(
                     let _ = registry_clone.append_live_output(run_id, &line);
 
                     // Extract session ID from JSONL output
-                    if let Ok(json) = serde_json::from_str::<JsonValue>(&line) {
-                        // Claude Code uses "session_id" (underscore), not "sessionId"
-                        if json.get("type").and_then(|t| t.as_str()) == Some("system") &&
-                           json.get("subtype").and_then(|s| s.as_str()) == Some("init") {
-                            if let Some(sid) = json.get("session_id").and_then(|s| s.as_str()) {
-                                if let Ok(mut current_session_id) = session_id_clone.lock() {
-                                    if current_session_id.is_empty() {
-                                        *current_session_id = sid.to_string();
-                                        info!("🔑 Extracted session ID: {}", sid);
-
-                                        // Update database immediately with session ID
-                                        if let Ok(conn) = Connection::open(&db_path_for_sidecar) {
-                                            match conn.execute(
-                                                "UPDATE agent_runs SET session_id = ?1 WHERE id = ?2",
-                                                params![sid, run_id],
-                                            ) {
-                                                Ok(rows) => {
-                                                    if rows > 0 {
-                                                        info!("✅ Updated agent run {} with session ID immediately", run_id);
-                                                    }
-                                                }
-                                                Err(e) => {
-                                                    error!("❌ Failed to update session ID immediately: {}", e);
-                                                }
+                    let parsed = crate::stream_json::parse_line(&line);
+                    if let Some(sid) = parsed.as_ref().and_then(|line| line.init_session_id()) {
+                        if let Ok(mut current_session_id) = session_id_clone.lock() {
+                            if current_session_id.is_empty() {
+                                *current_session_id = sid.to_string();
+                                info!("🔑 Extracted session ID: {}", sid);
+
+                                // Update database immediately with session ID
+                                if let Ok(conn) = Connection::open(&db_path_for_sidecar) {
+                                    match conn.execute(
+                                        "UPDATE agent_runs SET session_id = ?1 WHERE id = ?2",
+                                        params![sid, run_id],
+                                    ) {
+                                        Ok(rows) => {
+                                            if rows > 0 {
+                                                info!("✅ Updated agent run {} with session ID immediately", run_id);
                                             }
                                         }
+                                        Err(e) => {
+                                            error!("❌ Failed to update session ID immediately: {}", e);
+                                        }
                                     }
                                 }
                             }
@@ -1284,6 +1944,9 @@ async fn spawn_agent_sidecar(
 
                     // Emit the line to the frontend
                     let _ = app_handle.emit(&format!("agent-output:{}", run_id), &line);
+                    if let Some(event) = &parsed {
+                        let _ = app_handle.emit(&format!("agent-event:{}", run_id), event.to_json_with_raw(&line));
+                    }
                     let _ = app_handle.emit("agent-output", &line);
                 }
                 CommandEvent::Stderr(line_bytes) => {
@@ -1302,6 +1965,17 @@ async fn spawn_agent_sidecar(
                         String::new()
                     };
 
+                    // Store the structured result before the run is marked finished
+                    let output = live_output_clone.lock().map(|o| o.clone()).unwrap_or_default();
+                    super::agent_results::record_run_result_at(
+                        &db_path,
+                        run_id,
+                        &project_path_for_sidecar,
+                        &output,
+                        payload.code,
+                    );
+                    let _ = registry_clone.mark_process_exited(run_id);
+
                     // Update database with completion
                     if let Ok(conn) = Connection::open(&db_path) {
                         let _ = conn.execute(
@@ -1429,32 +2103,27 @@ async fn spawn_agent_system(
             let _ = registry_clone.append_live_output(run_id, &line);
 
             // Extract session ID from JSONL output
-            if let Ok(json) = serde_json::from_str::<JsonValue>(&line) {
-                // Claude Code uses "session_id" (underscore), not "sessionId"
-                if json.get("type").and_then(|t| t.as_str()) == Some("system") &&
-                   json.get("subtype").and_then(|s| s.as_str()) == Some("init") {
-                    if let Some(sid) = json.get("session_id").and_then(|s| s.as_str()) {
-                        if let Ok(mut current_session_id) = session_id_clone.lock() {
-                            if current_session_id.is_empty() {
-                                *current_session_id = sid.to_string();
-                                info!("🔑 Extracted session ID: {}", sid);
-
-                                // Update database immediately with session ID
-                                if let Ok(conn) = Connection::open(&db_path_for_stdout) {
-                                    match conn.execute(
-                                        "UPDATE agent_runs SET session_id = ?1 WHERE id = ?2",
-                                        params![sid, run_id],
-                                    ) {
-                                        Ok(rows) => {
-                                            if rows > 0 {
-                                                info!("✅ Updated agent run {} with session ID immediately", run_id);
-                                            }
-                                        }
-                                        Err(e) => {
-                                            error!("❌ Failed to update session ID immediately: {}", e);
-                                        }
+            let parsed = crate::stream_json::parse_line(&line);
+            if let Some(sid) = parsed.as_ref().and_then(|line| line.init_session_id()) {
+                if let Ok(mut current_session_id) = session_id_clone.lock() {
+                    if current_session_id.is_empty() {
+                        *current_session_id = sid.to_string();
+                        info!("🔑 Extracted session ID: {}", sid);
+
+                        // Update database immediately with session ID
+                        if let Ok(conn) = Connection::open(&db_path_for_stdout) {
+                            match conn.execute(
+                                "UPDATE agent_runs SET session_id = ?1 WHERE id = ?2",
+                                params![sid, run_id],
+                            ) {
+                                Ok(rows) => {
+                                    if rows > 0 {
+                                        info!("✅ Updated agent run {} with session ID immediately", run_id);
                                     }
                                 }
+                                Err(e) => {
+                                    error!("❌ Failed to update session ID immediately: {}", e);
+                                }
                             }
                         }
                     }
@@ -1463,6 +2132,9 @@ async fn spawn_agent_system(
 
             // Emit the line to the frontend with run_id for isolation
             let _ = app_handle.emit(&format!("agent-output:{}", run_id), &line);
+            if let Some(event) = &parsed {
+                let _ = app_handle.emit(&format!("agent-event:{}", run_id), event.to_json_with_raw(&line));
+            }
             // Also emit to the generic event for backward compatibility
             let _ = app_handle.emit("agent-output", &line);
         }
@@ -1525,6 +2197,8 @@ async fn spawn_agent_system(
     info!("📋 Registered process in registry");
 
     let db_path_for_monitor = db_path.clone(); // Clone for the monitor task
+    let registry_for_monitor = registry.0.clone();
+    let project_path_for_monitor = project_path.clone();
 
     // Monitor process status and wait for completion
     tokio::spawn(async move {
@@ -1576,6 +2250,13 @@ async fn spawn_agent_system(
                 }
 
                 // Update database
+                super::agent_results::record_run_result_at(
+                    &db_path_for_monitor,
+                    run_id,
+                    &project_path_for_monitor,
+                    "",
+                    None,
+                );
                 if let Ok(conn) = Connection::open(&db_path_for_monitor) {
                     let _ = conn.execute(
                         "UPDATE agent_runs SET status = 'failed', completed_at = CURRENT_TIMESTAMP WHERE id = ?1",
@@ -1609,6 +2290,19 @@ async fn spawn_agent_system(
         // Wait for process completion and update status
         info!("✅ Claude process execution monitoring complete");
 
+        // Store the structured result before the run is marked finished
+        let exit_code = registry_for_monitor
+            .wait_for_exit_code(run_id, std::time::Duration::from_secs(5))
+            .await;
+        let output = live_output.lock().map(|o| o.clone()).unwrap_or_default();
+        super::agent_results::record_run_result_at(
+            &db_path_for_monitor,
+            run_id,
+            &project_path_for_monitor,
+            &output,
+            exit_code,
+        );
+
         // Update the run record with session ID and mark as completed - open a new connection
         if let Ok(conn) = Connection::open(&db_path_for_monitor) {
             info!("🔄 Updating database with extracted session ID: {}", extracted_session_id);
@@ -1649,35 +2343,15 @@ pub async fn list_running_sessions(
     let conn = db.0.lock().map_err(|e| e.to_string())?;
 
     // First get all running sessions from the database
-    let mut stmt = conn.prepare(
-        "SELECT id, agent_id, agent_name, agent_icon, task, model, project_path, session_id, status, pid, process_started_at, created_at, completed_at
-         FROM agent_runs WHERE status = 'running' ORDER BY process_started_at DESC"
-    ).map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM agent_runs WHERE status = 'running' ORDER BY process_started_at DESC",
+            AGENT_RUN_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
 
     let mut runs = stmt
-        .query_map([], |row| {
-            Ok(AgentRun {
-                id: Some(row.get(0)?),
-                agent_id: row.get(1)?,
-                agent_name: row.get(2)?,
-                agent_icon: row.get(3)?,
-                task: row.get(4)?,
-                model: row.get(5)?,
-                project_path: row.get(6)?,
-                session_id: row.get(7)?,
-                status: row
-                    .get::<_, String>(8)
-                    .unwrap_or_else(|_| "pending".to_string()),
-                pid: row
-                    .get::<_, Option<i64>>(9)
-                    .ok()
-                    .flatten()
-                    .map(|p| p as u32),
-                process_started_at: row.get(10)?,
-                created_at: row.get(11)?,
-                completed_at: row.get(12)?,
-            })
-        })
+        .query_map([], map_agent_run_row)
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
@@ -1950,13 +2624,58 @@ pub async fn get_session_output(
     }
 }
 
-/// Stream real-time session output by watching the JSONL file
+/// How long the session stream waits for a filesystem event before checking the file anyway
+const SESSION_STREAM_FALLBACK_POLL_MS: u64 = 2000;
+
+/// New complete lines appended to a session JSONL file
+#[derive(Debug, Serialize, Clone)]
+pub struct SessionOutputChunk {
+    pub run_id: i64,
+    /// Byte offset in the session file just past the last emitted line
+    pub offset: u64,
+    /// Typed lines with the original fields kept, see `StreamLine::to_json_with_raw`
+    pub lines: Vec<serde_json::Value>,
+}
+
+/// Read the complete lines appended to a file since `offset`.
+///
+/// Returns the lines and the offset just past the last newline; a trailing partial
+/// line is left for the next read. A file shorter than `offset` is read from the start.
+pub fn read_complete_lines(path: &std::path::Path, offset: u64) -> std::io::Result<(Vec<String>, u64)> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path)?;
+    let offset = if file.metadata()?.len() < offset { 0 } else { offset };
+    file.seek(SeekFrom::Start(offset))?;
+
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)?;
+    let Some(last_newline) = buffer.iter().rposition(|b| *b == b'\n') else {
+        return Ok((Vec::new(), offset));
+    };
+
+    let lines = String::from_utf8_lossy(&buffer[..last_newline])
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect();
+    Ok((lines, offset + last_newline as u64 + 1))
+}
+
+/// Stream real-time session output by tailing the JSONL file.
+///
+/// New complete lines are emitted as `session-output-update:{run_id}` events, starting
+/// at `offset` (the start of the file by default), until the run's process exits.
 #[tauri::command]
 pub async fn stream_session_output(
     app: AppHandle,
     db: State<'_, AgentDb>,
+    registry: State<'_, crate::process::ProcessRegistryState>,
     run_id: i64,
+    offset: Option<u64>,
 ) -> Result<(), String> {
+    use notify::Watcher;
+
     // Get the session information
     let run = get_agent_run(db, run_id).await?;
 
@@ -1967,71 +2686,78 @@ pub async fn stream_session_output(
 
     let session_id = run.session_id.clone();
     let project_path = run.project_path.clone();
+    let registry = registry.0.clone();
 
-    // Spawn a task to monitor the file
+    // Spawn a task to tail the file
     tokio::spawn(async move {
-        let claude_dir = match dirs::home_dir() {
-            Some(home) => home.join(".claude").join("projects"),
-            None => return,
+        let project_dir = match crate::project_paths::project_dir_for_path(&project_path) {
+            Some(dir) => dir,
+            None => {
+                debug!("Project directory not found for {}, not streaming", project_path);
+                return;
+            }
         };
-
-        let encoded_project = project_path.replace('/', "-");
-        let project_dir = claude_dir.join(&encoded_project);
         let session_file = project_dir.join(format!("{}.jsonl", session_id));
 
-        let mut last_size = 0u64;
+        // Wake up on changes in the project directory, with a slow poll as fallback
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |_: notify::Result<notify::Event>| {
+            let _ = tx.send(());
+        })
+        .map_err(|e| debug!("File watcher unavailable, polling session file instead: {}", e))
+        .ok();
+        let mut watching = false;
+        let mut offset = offset.unwrap_or(0);
 
-        // Monitor file changes continuously while session is running
         loop {
+            if !watching {
+                if let Some(watcher) = watcher.as_mut() {
+                    watching = watcher
+                        .watch(&project_dir, notify::RecursiveMode::NonRecursive)
+                        .is_ok();
+                }
+            }
+
+            // Check before reading so lines written just before the process exits are still emitted
+            let running = registry.is_process_running(run_id).await.unwrap_or(false);
+
             if session_file.exists() {
-                if let Ok(metadata) = tokio::fs::metadata(&session_file).await {
-                    let current_size = metadata.len();
-
-                    if current_size > last_size {
-                        // File has grown, read new content
-                        if let Ok(content) = tokio::fs::read_to_string(&session_file).await {
-                            let _ = app
-                                .emit("session-output-update", &format!("{}:{}", run_id, content));
+                match read_complete_lines(&session_file, offset) {
+                    Ok((lines, new_offset)) => {
+                        offset = new_offset;
+                        let lines: Vec<_> = lines
+                            .iter()
+                            .filter_map(|line| {
+                                crate::stream_json::parse_line(line)
+                                    .map(|parsed| parsed.to_json_with_raw(line))
+                            })
+                            .collect();
+                        if !lines.is_empty() {
+                            let _ = app.emit(
+                                &format!("session-output-update:{}", run_id),
+                                SessionOutputChunk { run_id, offset, lines },
+                            );
                         }
-                        last_size = current_size;
                     }
+                    Err(e) => debug!("Failed to read session file for {}: {}", run_id, e),
                 }
-            } else {
-                // If session file doesn't exist yet, keep waiting
-                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                continue;
             }
 
-            // Check if the session is still running by querying the database
-            // If the session is no longer running, stop streaming
-            if let Ok(app_dir) = app.path().app_data_dir() {
-                if let Ok(conn) = rusqlite::Connection::open(app_dir.join("agents.db")) {
-                if let Ok(status) = conn.query_row(
-                    "SELECT status FROM agent_runs WHERE id = ?1",
-                    rusqlite::params![run_id],
-                    |row| row.get::<_, String>(0),
-                ) {
-                    if status != "running" {
-                        debug!("Session {} is no longer running, stopping stream", run_id);
-                        break;
-                    }
-                } else {
-                    // If we can't query the status, assume it's still running
-                    debug!(
-                        "Could not query session status for {}, continuing stream",
-                        run_id
-                    );
-                }
-                } else {
-                    debug!("Could not open database connection for session status check");
-                }
-            } else {
-                debug!("Could not get app data dir for session status check");
+            if !running {
+                debug!("Session {} is no longer running, stopping stream", run_id);
+                break;
             }
 
-            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+            let _ = tokio::time::timeout(
+                tokio::time::Duration::from_millis(SESSION_STREAM_FALLBACK_POLL_MS),
+                rx.recv(),
+            )
+            .await;
+            // Events often arrive in bursts, one read covers them all
+            while rx.try_recv().is_ok() {}
         }
 
+        let _ = app.emit(&format!("session-output-complete:{}", run_id), offset);
         debug!("Stopped streaming for session {}", run_id);
     });
 
@@ -2042,35 +2768,69 @@ pub async fn stream_session_output(
 #[tauri::command]
 pub async fn export_agent(db: State<'_, AgentDb>, id: i64) -> Result<String, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let export_data = build_agent_export(&conn, id)?;
+
+    // Convert to pretty JSON string
+    serde_json::to_string_pretty(&export_data)
+        .map_err(|e| format!("Failed to serialize agent: {}", e))
+}
 
+/// Build the current export of an agent, without MCP server secrets
+fn build_agent_export(conn: &Connection, id: i64) -> Result<AgentExport, String> {
     // Fetch the agent
     let agent = conn
         .query_row(
-            "SELECT name, icon, system_prompt, default_task, model, hooks FROM agents WHERE id = ?1",
+            &format!("SELECT {} FROM agents WHERE id = ?1", AGENT_COLUMNS),
             params![id],
-            |row| {
-                Ok(serde_json::json!({
-                    "name": row.get::<_, String>(0)?,
-                    "icon": row.get::<_, String>(1)?,
-                    "system_prompt": row.get::<_, String>(2)?,
-                    "default_task": row.get::<_, Option<String>>(3)?,
-                    "model": row.get::<_, String>(4)?,
-                    "hooks": row.get::<_, Option<String>>(5)?
-                }))
-            },
+            map_agent_row,
         )
         .map_err(|e| format!("Failed to fetch agent: {}", e))?;
 
-    // Create the export wrapper
-    let export_data = serde_json::json!({
-        "version": 1,
-        "exported_at": chrono::Utc::now().to_rfc3339(),
-        "agent": agent
-    });
+    let allowed_tools = match agent.allowed_tools.as_deref() {
+        Some(tools) => Some(
+            serde_json::from_str::<Vec<String>>(tools)
+                .map_err(|e| format!("Invalid allowed tools for agent: {}", e))?,
+        ),
+        None => None,
+    };
+    let mut dependencies = match agent.dependencies.as_deref() {
+        Some(deps) => serde_json::from_str::<AgentDependencies>(deps)
+            .map_err(|e| format!("Invalid dependencies for agent: {}", e))?,
+        None => AgentDependencies::default(),
+    };
 
-    // Convert to pretty JSON string
-    serde_json::to_string_pretty(&export_data)
-        .map_err(|e| format!("Failed to serialize agent: {}", e))
+    let parameters = super::agent_parameters::parse_parameters(agent.parameters.as_deref())?;
+
+    // Never export MCP server secrets, only the variable names they expect
+    for server in dependencies.mcp_servers.values_mut() {
+        for value in server.env.values_mut() {
+            value.clear();
+        }
+    }
+
+    // Create the export wrapper
+    Ok(AgentExport {
+        version: AGENT_EXPORT_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        agent: AgentData {
+            name: agent.name,
+            icon: agent.icon,
+            system_prompt: agent.system_prompt,
+            default_task: agent.default_task,
+            model: agent.model,
+            hooks: agent.hooks,
+            permissions: Some(AgentPermissions {
+                enable_file_read: agent.enable_file_read,
+                enable_file_write: agent.enable_file_write,
+                enable_network: agent.enable_network,
+            }),
+            allowed_tools,
+            required_mcp_servers: dependencies.mcp_servers,
+            required_env: dependencies.env,
+            parameters,
+            collect_artifacts: agent.collect_artifacts,
+        },
+    })
 }
 
 /// Export agent to file with native dialog
@@ -2258,7 +3018,7 @@ pub async fn list_claude_installations(
 
 /// Helper function to create a tokio Command with proper environment variables
 /// This ensures commands like Claude can find Node.js and other dependencies
-fn create_command_with_env(program: &str) -> Command {
+pub fn create_command_with_env(program: &str) -> Command {
     // Convert std::process::Command to tokio::process::Command
     let _std_cmd = crate::claude_binary::create_command_with_env(program);
 
@@ -2322,24 +3082,48 @@ fn create_command_with_env(program: &str) -> Command {
     tokio_cmd
 }
 
-/// Import an agent from JSON data
-#[tauri::command]
-pub async fn import_agent(db: State<'_, AgentDb>, json_data: String) -> Result<Agent, String> {
-    // Parse the JSON data
+/// Parse an agent export and make sure its format version is supported
+fn parse_agent_export(json_data: &str) -> Result<AgentExport, String> {
     let export_data: AgentExport =
-        serde_json::from_str(&json_data).map_err(|e| format!("Invalid JSON format: {}", e))?;
+        serde_json::from_str(json_data).map_err(|e| format!("Invalid JSON format: {}", e))?;
 
-    // Validate version
-    if export_data.version != 1 {
+    if export_data.version == 0 || export_data.version > AGENT_EXPORT_VERSION {
         return Err(format!(
-            "Unsupported export version: {}. This version of the app only supports version 1.",
-            export_data.version
+            "Unsupported export version: {}. This version of the app supports versions 1 to {}.",
+            export_data.version, AGENT_EXPORT_VERSION
         ));
     }
 
-    let agent_data = export_data.agent;
+    Ok(export_data)
+}
+
+/// Import an agent from JSON data, with an optional detached signature over it
+#[tauri::command]
+pub async fn import_agent(
+    db: State<'_, AgentDb>,
+    json_data: String,
+    signature: Option<String>,
+) -> Result<Agent, String> {
+    import_agent_with_source(db, json_data, None, signature).await
+}
+
+/// Import an agent from JSON data with source, enforcing the import trust policy
+pub async fn import_agent_with_source(
+    db: State<'_, AgentDb>,
+    json_data: String,
+    source: Option<String>,
+    signature: Option<String>,
+) -> Result<Agent, String> {
+    let export_data = parse_agent_export(&json_data)?;
+
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    super::agent_trust::enforce_import_policy(&conn, json_data.as_bytes(), signature.as_deref())?;
+    let source = source.unwrap_or_else(|| "claudia".to_string());
+    insert_imported_agent(&conn, export_data.agent, &source)
+}
 
+/// Create an agent from imported agent data, renaming it if the name is taken
+fn insert_imported_agent(conn: &Connection, agent_data: AgentData, source: &str) -> Result<Agent, String> {
     // Check if an agent with the same name already exists
     let existing_count: i64 = conn
         .query_row(
@@ -2356,128 +3140,141 @@ pub async fn import_agent(db: State<'_, AgentDb>, json_data: String) -> Result<A
         agent_data.name
     };
 
+    // v1 exports carry no permissions; keep the historical defaults for them
+    let permissions = agent_data.permissions.unwrap_or(AgentPermissions {
+        enable_file_read: true,
+        enable_file_write: true,
+        enable_network: false,
+    });
+    let allowed_tools = agent_data
+        .allowed_tools
+        .map(|tools| serde_json::to_string(&tools))
+        .transpose()
+        .map_err(|e| format!("Failed to serialize allowed tools: {}", e))?;
+    let dependencies = if agent_data.required_mcp_servers.is_empty() && agent_data.required_env.is_empty() {
+        None
+    } else {
+        Some(
+            serde_json::to_string(&AgentDependencies {
+                mcp_servers: agent_data.required_mcp_servers,
+                env: agent_data.required_env,
+            })
+            .map_err(|e| format!("Failed to serialize dependencies: {}", e))?,
+        )
+    };
+
+    super::agent_parameters::validate_declarations(&agent_data.parameters)?;
+    let parameters = if agent_data.parameters.is_empty() {
+        None
+    } else {
+        Some(
+            serde_json::to_string(&agent_data.parameters)
+                .map_err(|e| format!("Failed to serialize parameters: {}", e))?,
+        )
+    };
+
     // Create the agent
     conn.execute(
-        "INSERT INTO agents (name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, source) VALUES (?1, ?2, ?3, ?4, ?5, 1, 1, 0, ?6, ?7)",
+        "INSERT INTO agents (name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, source, allowed_tools, dependencies, parameters, collect_artifacts) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
         params![
             final_name,
             agent_data.icon,
             agent_data.system_prompt,
             agent_data.default_task,
             agent_data.model,
+            permissions.enable_file_read,
+            permissions.enable_file_write,
+            permissions.enable_network,
             agent_data.hooks,
-            "claudia"
+            source,
+            allowed_tools,
+            dependencies,
+            parameters,
+            agent_data.collect_artifacts
         ],
     )
     .map_err(|e| format!("Failed to create agent: {}", e))?;
 
     let id = conn.last_insert_rowid();
+    record_agent_version(conn, id, Some("imported"))
+        .map_err(|e| format!("Failed to record agent version: {}", e))?;
 
     // Fetch the created agent
     let agent = conn
         .query_row(
-            "SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, source, created_at, updated_at FROM agents WHERE id = ?1",
+            &format!("SELECT {} FROM agents WHERE id = ?1", AGENT_COLUMNS),
             params![id],
-            |row| {
-                Ok(Agent {
-                    id: Some(row.get(0)?),
-                    name: row.get(1)?,
-                    icon: row.get(2)?,
-                    system_prompt: row.get(3)?,
-                    default_task: row.get(4)?,
-                    model: row.get(5)?,
-                    enable_file_read: row.get(6)?,
-                    enable_file_write: row.get(7)?,
-                    enable_network: row.get(8)?,
-                    hooks: row.get(9)?,
-                    source: row.get(10)?,
-                    created_at: row.get(11)?,
-                    updated_at: row.get(12)?,
-                })
-            },
+            map_agent_row,
         )
         .map_err(|e| format!("Failed to fetch created agent: {}", e))?;
 
     Ok(agent)
 }
 
-/// Import an agent from JSON data with source
-pub async fn import_agent_with_source(db: State<'_, AgentDb>, json_data: String, source: Option<String>) -> Result<Agent, String> {
-    // Parse the JSON data
-    let export_data: AgentExport =
-        serde_json::from_str(&json_data).map_err(|e| format!("Invalid JSON format: {}", e))?;
-
-    // Validate version
-    if export_data.version != 1 {
-        return Err(format!(
-            "Unsupported export version: {}. This version of the app only supports version 1.",
-            export_data.version
-        ));
-    }
-
+/// Check which MCP servers and environment variables required by an agent
+/// export are missing locally, so the user can be asked before importing
+#[tauri::command]
+pub async fn check_agent_import_dependencies(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    json_data: String,
+) -> Result<AgentDependencyReport, String> {
+    let export_data = parse_agent_export(&json_data)?;
     let agent_data = export_data.agent;
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
-    let source = source.unwrap_or_else(|| "claudia".to_string());
-
-    // Check if an agent with the same name already exists
-    let existing_count: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM agents WHERE name = ?1",
-            params![agent_data.name],
-            |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
 
-    // If agent with same name exists, append a suffix
-    let final_name = if existing_count > 0 {
-        format!("{} (Imported)", agent_data.name)
+    let missing_mcp_servers = if agent_data.required_mcp_servers.is_empty() {
+        Vec::new()
     } else {
-        agent_data.name
+        let configured: std::collections::HashSet<String> =
+            match super::mcp::mcp_list(app).await {
+                Ok(servers) => servers.into_iter().map(|s| s.name).collect(),
+                Err(e) => {
+                    warn!("Could not list MCP servers for dependency check: {}", e);
+                    std::collections::HashSet::new()
+                }
+            };
+        let mut missing: Vec<String> = agent_data
+            .required_mcp_servers
+            .keys()
+            .filter(|name| !configured.contains(*name))
+            .cloned()
+            .collect();
+        missing.sort();
+        missing
     };
 
-    // Create the agent
-    conn.execute(
-        "INSERT INTO agents (name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, source) VALUES (?1, ?2, ?3, ?4, ?5, 1, 1, 0, ?6, ?7)",
-        params![
-            final_name,
-            agent_data.icon,
-            agent_data.system_prompt,
-            agent_data.default_task,
-            agent_data.model,
-            agent_data.hooks,
-            source
-        ],
-    )
-    .map_err(|e| format!("Failed to create agent: {}", e))?;
-
-    let id = conn.last_insert_rowid();
+    let missing_env = if agent_data.required_env.is_empty() {
+        Vec::new()
+    } else {
+        let configured = get_enabled_environment_variables(db).await?;
+        agent_data
+            .required_env
+            .iter()
+            .filter(|key| !configured.contains_key(*key) && std::env::var(key).is_err())
+            .cloned()
+            .collect()
+    };
 
-    // Fetch the created agent
-    let agent = conn
-        .query_row(
-            "SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, source, created_at, updated_at FROM agents WHERE id = ?1",
-            params![id],
-            |row| {
-                Ok(Agent {
-                    id: Some(row.get(0)?),
-                    name: row.get(1)?,
-                    icon: row.get(2)?,
-                    system_prompt: row.get(3)?,
-                    default_task: row.get(4)?,
-                    model: row.get(5)?,
-                    enable_file_read: row.get(6)?,
-                    enable_file_write: row.get(7)?,
-                    enable_network: row.get(8)?,
-                    hooks: row.get(9)?,
-                    source: row.get(10)?,
-                    created_at: row.get(11)?,
-                    updated_at: row.get(12)?,
-                })
-            },
-        )
-        .map_err(|e| format!("Failed to fetch created agent: {}", e))?;
+    Ok(AgentDependencyReport {
+        agent_name: agent_data.name,
+        export_version: export_data.version,
+        required_mcp_servers: agent_data.required_mcp_servers,
+        required_env: agent_data.required_env,
+        missing_mcp_servers,
+        missing_env,
+    })
+}
 
-    Ok(agent)
+/// Check the dependencies of an agent export file
+#[tauri::command]
+pub async fn check_agent_file_dependencies(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    file_path: String,
+) -> Result<AgentDependencyReport, String> {
+    let json_data =
+        std::fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    check_agent_import_dependencies(app, db, json_data).await
 }
 
 /// Import agent from file
@@ -2487,12 +3284,13 @@ pub async fn import_agent_from_file(
     file_path: String,
     source: Option<String>,
 ) -> Result<Agent, String> {
-    // Read the file
+    // Read the file and its detached signature, if one sits next to it
     let json_data =
         std::fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let signature = super::agent_trust::read_local_signature(&file_path);
 
     // Import the agent with source
-    import_agent_with_source(db, json_data, source).await
+    import_agent_with_source(db, json_data, source, signature).await
 }
 
 // GitHub Agent Import functionality
@@ -2507,62 +3305,12 @@ pub struct GitHubAgentFile {
     pub sha: String,
 }
 
-/// Represents the GitHub API response for directory contents
-#[derive(Debug, Deserialize)]
-struct GitHubApiResponse {
-    name: String,
-    path: String,
-    sha: String,
-    size: i64,
-    download_url: Option<String>,
-    #[serde(rename = "type")]
-    file_type: String,
-}
-
-/// Fetch list of agents from GitHub repository
+/// Fetch list of agents from the built-in GitHub registry
 #[tauri::command]
-pub async fn fetch_github_agents() -> Result<Vec<GitHubAgentFile>, String> {
-    info!("Fetching agents from GitHub repository...");
-
-    let client = reqwest::Client::new();
-    let url = "https://api.github.com/repos/getAsterisk/claudia/contents/cc_agents";
-
-    let response = client
-        .get(url)
-        .header("Accept", "application/vnd.github+json")
-        .header("User-Agent", "Claudia-App")
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch from GitHub: {}", e))?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("GitHub API error ({}): {}", status, error_text));
-    }
-
-    let api_files: Vec<GitHubApiResponse> = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse GitHub response: {}", e))?;
-
-    // Filter only .claudia.json files
-    let agent_files: Vec<GitHubAgentFile> = api_files
-        .into_iter()
-        .filter(|f| f.name.ends_with(".claudia.json") && f.file_type == "file")
-        .filter_map(|f| {
-            f.download_url.map(|download_url| GitHubAgentFile {
-                name: f.name,
-                path: f.path,
-                download_url,
-                size: f.size,
-                sha: f.sha,
-            })
-        })
-        .collect();
-
-    info!("Found {} agents on GitHub", agent_files.len());
-    Ok(agent_files)
+pub async fn fetch_github_agents(db: State<'_, AgentDb>) -> Result<Vec<GitHubAgentFile>, String> {
+    let registry = super::agent_registry::AgentRegistry::default_registry();
+    let files = super::agent_registry::registry_listing(&db, &registry, false).await?;
+    Ok(files.into_iter().map(GitHubAgentFile::from).collect())
 }
 
 /// Fetch and preview a specific agent from GitHub
@@ -2570,40 +3318,10 @@ pub async fn fetch_github_agents() -> Result<Vec<GitHubAgentFile>, String> {
 pub async fn fetch_github_agent_content(download_url: String) -> Result<AgentExport, String> {
     info!("Fetching agent content from: {}", download_url);
 
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&download_url)
-        .header("Accept", "application/json")
-        .header("User-Agent", "Claudia-App")
-        .send()
-        .await
-        .map_err(|e| format!("Failed to download agent: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!(
-            "Failed to download agent: HTTP {}",
-            response.status()
-        ));
-    }
-
-    let json_text = response
-        .text()
-        .await
-        .map_err(|e| format!("Failed to read response: {}", e))?;
+    let json_text = super::agent_registry::read_agent_source(&download_url).await?;
 
     // Parse and validate the agent data
-    let export_data: AgentExport = serde_json::from_str(&json_text)
-        .map_err(|e| format!("Invalid agent JSON format: {}", e))?;
-
-    // Validate version
-    if export_data.version != 1 {
-        return Err(format!(
-            "Unsupported agent version: {}",
-            export_data.version
-        ));
-    }
-
-    Ok(export_data)
+    parse_agent_export(&json_text)
 }
 
 /// Import an agent directly from GitHub
@@ -2611,18 +3329,32 @@ pub async fn fetch_github_agent_content(download_url: String) -> Result<AgentExp
 pub async fn import_agent_from_github(
     db: State<'_, AgentDb>,
     download_url: String,
+    path: Option<String>,
+    sha: Option<String>,
 ) -> Result<Agent, String> {
     info!("Importing agent from GitHub: {}", download_url);
 
-    // First, fetch the agent content
-    let export_data = fetch_github_agent_content(download_url).await?;
-
-    // Convert to JSON string and use existing import logic
-    let json_data = serde_json::to_string(&export_data)
-        .map_err(|e| format!("Failed to serialize agent data: {}", e))?;
+    // Fetch the raw agent file so the signature is checked against the published bytes
+    let json_data = super::agent_registry::read_agent_source(&download_url).await?;
+    let signature = super::agent_trust::fetch_remote_signature(&download_url).await;
 
     // Import using existing function
-    import_agent(db, json_data).await
+    let agent = import_agent_with_source(db.clone(), json_data, None, signature).await?;
+
+    // Track the upstream file so update checks can compare its sha later
+    if let (Some(agent_id), Some(path), Some(sha)) = (agent.id, path, sha) {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        super::agent_registry::record_registry_import(
+            &conn,
+            agent_id,
+            super::agent_registry::DEFAULT_REGISTRY_ID,
+            &path,
+            &sha,
+        )
+        .map_err(|e| format!("Failed to record registry import: {}", e))?;
+    }
+
+    Ok(agent)
 }
 
 /// Load agent session history from JSONL file
@@ -2803,6 +3535,10 @@ pub async fn list_native_agents() -> Result<Vec<Agent>, String> {
                             source: Some("native".to_string()),
                             created_at: chrono::Utc::now().to_rfc3339(),
                             updated_at: chrono::Utc::now().to_rfc3339(),
+                            allowed_tools: None,
+                            dependencies: None,
+                            parameters: None,
+                            collect_artifacts: false,
                         });
                         agent_id += 1;
                     }
@@ -2891,6 +3627,9 @@ pub async fn import_native_agents(db: State<'_, AgentDb>) -> Result<u32, String>
                             ],
                         ).map_err(|e| format!("Failed to insert agent '{}': {}", name, e))?;
 
+                        record_agent_version(&conn, conn.last_insert_rowid(), Some("imported"))
+                            .map_err(|e| format!("Failed to record agent version: {}", e))?;
+
                         imported_count += 1;
                         info!("Successfully imported agent: {}", name);
                     }
@@ -2943,6 +3682,12 @@ pub async fn delete_native_agents(db: State<'_, AgentDb>) -> Result<u32, String>
         .execute("DELETE FROM agents WHERE source = 'native'", [])
         .map_err(|e| e.to_string())?;
 
+    conn.execute(
+        "DELETE FROM agent_versions WHERE agent_id NOT IN (SELECT id FROM agents)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
     info!("Deleted {} native agents from database", deleted);
     Ok(deleted as u32)
 }
@@ -3370,3 +4115,115 @@ pub async fn delete_environment_variable_group(db: State<'_, AgentDb>, id: i64)
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::time::Duration;
+
+    #[test]
+    fn test_stalled_run_reason() {
+        assert_eq!(stalled_run_reason(Duration::from_secs(60), None), None);
+        assert_eq!(stalled_run_reason(Duration::from_secs(60), Some(Duration::from_secs(5))), None);
+        assert!(stalled_run_reason(Duration::from_secs(60), Some(Duration::from_secs(RUN_EXIT_GRACE_SECS))).is_some());
+        assert!(stalled_run_reason(Duration::from_secs(RUN_WAIT_TIMEOUT_SECS), None).is_some());
+    }
+
+    #[test]
+    fn test_agent_versions_skip_unchanged_diff_and_roll_back() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO agents (name, icon, system_prompt, model) VALUES ('reviewer', 'bot', ?1, 'sonnet')",
+            params!["Review the diff.\nBe terse.\n"],
+        )
+        .unwrap();
+        let id = conn.last_insert_rowid();
+        assert_eq!(record_agent_version(&conn, id, Some("created")).unwrap(), 1);
+        // Saving an unchanged definition doesn't add a version
+        assert_eq!(record_agent_version(&conn, id, Some("updated")).unwrap(), 1);
+
+        conn.execute(
+            "UPDATE agents SET system_prompt = ?1, model = 'opus' WHERE id = ?2",
+            params!["Review the diff.\nExplain each finding.\n", id],
+        )
+        .unwrap();
+        assert_eq!(record_agent_version(&conn, id, Some("updated")).unwrap(), 2);
+
+        let v1 = fetch_agent_version(&conn, id, 1).unwrap();
+        let diff = diff_versions(&v1, &fetch_agent_version(&conn, id, 2).unwrap());
+        let fields: Vec<&str> = diff.changed_fields.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["system_prompt", "model"]);
+        assert_eq!(diff.changed_fields[1].from.as_deref(), Some("sonnet"));
+        let prompt_diff = diff.system_prompt_diff.unwrap();
+        assert!(prompt_diff.contains("-Be terse.") && prompt_diff.contains("+Explain each finding."));
+        assert!(diff.hooks_diff.is_none());
+
+        // A rollback is recorded as a new version matching the old one
+        assert_eq!(rollback_to_version(&conn, id, 1).unwrap(), 3);
+        let v3 = fetch_agent_version(&conn, id, 3).unwrap();
+        assert_eq!(v3.change_note.as_deref(), Some("rollback to v1"));
+        assert!(diff_versions(&v1, &v3).changed_fields.is_empty());
+        let model: String = conn
+            .query_row("SELECT model FROM agents WHERE id = ?1", params![id], |row| row.get(0))
+            .unwrap();
+        assert_eq!(model, "sonnet");
+    }
+
+    #[test]
+    fn test_agent_export_round_trips_and_imports_v1() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+
+        let v1 = r#"{"version":1,"exported_at":"2024-01-01T00:00:00Z","agent":{"name":"old","icon":"bot","system_prompt":"Help.","model":"sonnet"}}"#;
+        let imported = insert_imported_agent(&conn, parse_agent_export(v1).unwrap().agent, "claudia").unwrap();
+        assert_eq!(imported.name, "old");
+        assert!(imported.enable_file_read && imported.enable_file_write && !imported.enable_network);
+        assert!(imported.allowed_tools.is_none() && imported.dependencies.is_none() && imported.parameters.is_none());
+        assert!(parse_agent_export(&v1.replace("\"version\":1", "\"version\":3")).is_err());
+
+        conn.execute(
+            "INSERT INTO agents (name, icon, system_prompt, model, enable_network, allowed_tools, dependencies, parameters)
+             VALUES ('deployer', 'rocket', 'Deploy {{env}}.', 'opus', 1, ?1, ?2, ?3)",
+            params![
+                r#"["Bash","Read"]"#,
+                r#"{"mcp_servers":{"github":{"command":"gh-mcp","args":[],"env":{"GITHUB_TOKEN":"ghp_secret"}}},"env":["DEPLOY_KEY"]}"#,
+                r#"[{"name":"env","type":"enum","options":["staging","prod"],"required":true}]"#,
+            ],
+        )
+        .unwrap();
+        let id = conn.last_insert_rowid();
+
+        let json = serde_json::to_string_pretty(&build_agent_export(&conn, id).unwrap()).unwrap();
+        assert!(!json.contains("ghp_secret"));
+        assert!(json.contains("GITHUB_TOKEN"));
+
+        let export = parse_agent_export(&json).unwrap();
+        assert_eq!(export.version, AGENT_EXPORT_VERSION);
+        let copy = insert_imported_agent(&conn, export.agent, "claudia").unwrap();
+        assert_eq!(copy.name, "deployer (Imported)");
+        assert_eq!((copy.system_prompt.as_str(), copy.model.as_str()), ("Deploy {{env}}.", "opus"));
+        assert!(copy.enable_network);
+        assert_eq!(copy.allowed_tools.as_deref(), Some(r#"["Bash","Read"]"#));
+        let deps: AgentDependencies = serde_json::from_str(copy.dependencies.as_deref().unwrap()).unwrap();
+        assert_eq!(deps.mcp_servers["github"].env["GITHUB_TOKEN"], "");
+        assert_eq!(deps.env, vec!["DEPLOY_KEY"]);
+        let parameters = crate::commands::agent_parameters::parse_parameters(copy.parameters.as_deref()).unwrap();
+        assert_eq!(parameters[0].options, vec!["staging", "prod"]);
+    }
+
+    #[test]
+    fn test_read_complete_lines_leaves_partial_line() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, "{{\"a\":1}}\n{{\"b\":2}}\n{{\"c\"").unwrap();
+
+        let (lines, offset) = read_complete_lines(file.path(), 0).unwrap();
+        assert_eq!(lines, vec!["{\"a\":1}", "{\"b\":2}"]);
+
+        writeln!(file, ":3}}").unwrap();
+        let (lines, next_offset) = read_complete_lines(file.path(), offset).unwrap();
+        assert_eq!(lines, vec!["{\"c\":3}"]);
+        assert_eq!(next_offset, file.as_file().metadata().unwrap().len());
+    }
+}
@@ -15,6 +15,13 @@ use tauri_plugin_shell::ShellExt;
 use tauri_plugin_shell::process::CommandEvent;
 use regex;
 use crate::{debug_log, info_log, error_log};
+use crate::commands::session_meta::{
+    apply_meta, archived_session_path, filter_and_sort_sessions,
+    load_project_meta, ArchiveFilter, SessionListQuery, ARCHIVE_DIR,
+};
+use crate::commands::session_summaries::load_project_summaries;
+use crate::commands::session_trash::trash_session;
+use crate::commands::usage_budgets::ensure_launch_budget;
 
 /// Global state to track current Claude process
 pub struct ClaudeProcessState {
@@ -55,10 +62,26 @@ pub struct Session {
     pub todo_data: Option<serde_json::Value>,
     /// Unix timestamp when the session file was created
     pub created_at: u64,
+    /// Unix timestamp when the session file was last written
+    pub modified_at: u64,
     /// First user message content (if available)
     pub first_message: Option<String>,
     /// Timestamp of the first user message (if available)
     pub message_timestamp: Option<String>,
+    /// User-assigned title
+    pub title: Option<String>,
+    /// User-assigned tags
+    pub tags: Vec<String>,
+    /// Whether the session is pinned to the top of the listing
+    pub pinned: bool,
+    /// Whether the session has been moved out of the active listing
+    pub archived: bool,
+    /// User notes
+    pub notes: Option<String>,
+    /// Title generated by the session summarizer
+    pub summary_title: Option<String>,
+    /// Paragraph summary generated by the session summarizer
+    pub summary: Option<String>,
 }
 
 /// Represents a message entry in the JSONL file
@@ -542,80 +565,6 @@ fn get_claude_dir() -> Result<PathBuf> {
         .context("Could not find ~/.claude directory")
 }
 
-/// Gets the actual project path by reading the cwd from JSONL entries
-fn get_project_path_from_sessions(project_dir: &PathBuf) -> Result<String, String> {
-    // Try to read any JSONL file in the directory
-    let entries = fs::read_dir(project_dir)
-        .map_err(|e| format!("Failed to read project directory: {}", e))?;
-
-    for entry in entries {
-        if let Ok(entry) = entry {
-            let path = entry.path();
-            if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
-                // Read the JSONL file and look for a line with cwd
-                if let Ok(file) = fs::File::open(&path) {
-                    let reader = BufReader::new(file);
-                    // Read up to 10 lines to find one with cwd
-                    for line in reader.lines().take(10) {
-                        if let Ok(line_content) = line {
-                            // Parse the JSON and extract cwd
-                            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&line_content) {
-                                if let Some(cwd) = json.get("cwd").and_then(|v| v.as_str()) {
-                                    return Ok(cwd.to_string());
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    Err("Could not determine project path from session files".to_string())
-}
-
-/// Decodes a project directory name back to its original path
-/// The directory names in ~/.claude/projects are encoded paths
-/// DEPRECATED: Use get_project_path_from_sessions instead when possible
-fn decode_project_path(encoded: &str) -> String {
-    // This is a fallback - the encoding isn't reversible when paths contain hyphens
-    // For example: -Users-mufeedvh-dev-jsonl-viewer could be /Users/mufeedvh/dev/jsonl-viewer
-    // or /Users/mufeedvh/dev/jsonl/viewer
-
-    #[cfg(target_os = "windows")]
-    {
-        // On Windows, the encoded path looks like: E--Data-claude-code-projects-test
-        // We need to convert it to: E:\Data\claude_code\projects\test
-        // Note: underscores in path names are also encoded as hyphens, making this ambiguous
-
-        let mut result = encoded.to_string();
-
-        // First, handle the drive letter pattern (e.g., "E--" -> "E:\")
-        // Windows drive letters are single uppercase letters followed by --
-        if result.len() >= 3 {
-            let first_char = result.chars().next().unwrap();
-            if first_char.is_ascii_uppercase() && result.starts_with(&format!("{}--", first_char)) {
-                result = format!("{}:\\{}", first_char, &result[3..]);
-            }
-        }
-
-        // Replace remaining double hyphens with single backslash (directory separators)
-        // Note: This is still ambiguous as some hyphens might be underscores in the original path
-        result = result.replace("--", "\\");
-
-        // Replace single hyphens with backslash (assuming they are path separators)
-        // This loses information about underscores vs path separators
-        result = result.replace('-', "\\");
-
-        result
-    }
-
-    #[cfg(not(target_os = "windows"))]
-    {
-        encoded.replace('-', "/")
-    }
-}
-
 /// Extracts the first valid user message from a JSONL file
 fn extract_first_user_message(jsonl_path: &PathBuf) -> (Option<String>, Option<String>) {
     let file = match fs::File::open(jsonl_path) {
@@ -1027,14 +976,11 @@ pub async fn list_projects() -> Result<Vec<Project>, String> {
                 .unwrap_or_default()
                 .as_secs();
 
-            // Get the actual project path from JSONL files
-            let project_path = match get_project_path_from_sessions(&path) {
-                Ok(path) => path,
-                Err(e) => {
-                    log::warn!("Failed to get project path from sessions for {}: {}, falling back to decode", dir_name, e);
-                    decode_project_path(dir_name)
-                }
-            };
+            // Get the actual project path from JSONL files or the filesystem
+            let project_path = crate::project_paths::resolve_project_path(&path);
+            if crate::commands::session_summaries::is_summarizer_project(&project_path) {
+                continue;
+            }
 
             // List all JSONL files (sessions) in this project directory
             let mut sessions = Vec::new();
@@ -1073,7 +1019,11 @@ pub async fn list_projects() -> Result<Vec<Project>, String> {
 
 /// Gets sessions for a specific project
 #[tauri::command]
-pub async fn get_project_sessions(project_id: String) -> Result<Vec<Session>, String> {
+pub async fn get_project_sessions(
+    db: tauri::State<'_, crate::commands::agents::AgentDb>,
+    project_id: String,
+    query: Option<SessionListQuery>,
+) -> Result<Vec<Session>, String> {
     log::info!("Getting sessions for project: {}", project_id);
 
     let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
@@ -1084,71 +1034,96 @@ pub async fn get_project_sessions(project_id: String) -> Result<Vec<Session>, St
         return Err(format!("Project directory not found: {}", project_id));
     }
 
-    // Get the actual project path from JSONL files
-    let project_path = match get_project_path_from_sessions(&project_dir) {
-        Ok(path) => path,
-        Err(e) => {
-            log::warn!(
-                "Failed to get project path from sessions for {}: {}, falling back to decode",
-                project_id,
-                e
-            );
-            decode_project_path(&project_id)
-        }
-    };
+    let query = query.unwrap_or_default();
+
+    // Get the actual project path from JSONL files or the filesystem
+    let project_path = crate::project_paths::resolve_project_path(&project_dir);
+
+    let mut session_dirs = Vec::new();
+    if query.archived != ArchiveFilter::Archived {
+        session_dirs.push((project_dir.clone(), false));
+    }
+    if query.archived != ArchiveFilter::Active {
+        session_dirs.push((project_dir.join(ARCHIVE_DIR), true));
+    }
 
     let mut sessions = Vec::new();
 
-    // Read all JSONL files in the project directory
-    let entries = fs::read_dir(&project_dir)
-        .map_err(|e| format!("Failed to read project directory: {}", e))?;
+    // Read all JSONL files in the project directory, and its archive if requested
+    for (dir, archived) in session_dirs {
+        if !dir.exists() {
+            continue;
+        }
+        let entries =
+            fs::read_dir(&dir).map_err(|e| format!("Failed to read project directory: {}", e))?;
 
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-        let path = entry.path();
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+            let path = entry.path();
 
-        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
-            if let Some(session_id) = path.file_stem().and_then(|s| s.to_str()) {
-                // Get file creation time
-                let metadata = fs::metadata(&path)
-                    .map_err(|e| format!("Failed to read file metadata: {}", e))?;
-
-                let created_at = metadata
-                    .created()
-                    .or_else(|_| metadata.modified())
-                    .unwrap_or(SystemTime::UNIX_EPOCH)
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs();
-
-                // Extract first user message and timestamp
-                let (first_message, message_timestamp) = extract_first_user_message(&path);
-
-                // Try to load associated todo data
-                let todo_path = todos_dir.join(format!("{}.json", session_id));
-                let todo_data = if todo_path.exists() {
-                    fs::read_to_string(&todo_path)
-                        .ok()
-                        .and_then(|content| serde_json::from_str(&content).ok())
-                } else {
-                    None
-                };
+            if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
+                if let Some(session_id) = path.file_stem().and_then(|s| s.to_str()) {
+                    // Get file creation and modification times
+                    let metadata = fs::metadata(&path)
+                        .map_err(|e| format!("Failed to read file metadata: {}", e))?;
 
-                sessions.push(Session {
-                    id: session_id.to_string(),
-                    project_id: project_id.clone(),
-                    project_path: project_path.clone(),
-                    todo_data,
-                    created_at,
-                    first_message,
-                    message_timestamp,
-                });
+                    let unix_secs = |time: std::io::Result<SystemTime>| {
+                        time.unwrap_or(SystemTime::UNIX_EPOCH)
+                            .duration_since(SystemTime::UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_secs()
+                    };
+                    let created_at = unix_secs(metadata.created().or_else(|_| metadata.modified()));
+                    let modified_at = unix_secs(metadata.modified());
+
+                    // Extract first user message and timestamp
+                    let (first_message, message_timestamp) = extract_first_user_message(&path);
+
+                    // Try to load associated todo data
+                    let todo_path = todos_dir.join(format!("{}.json", session_id));
+                    let todo_data = if todo_path.exists() {
+                        fs::read_to_string(&todo_path)
+                            .ok()
+                            .and_then(|content| serde_json::from_str(&content).ok())
+                    } else {
+                        None
+                    };
+
+                    sessions.push(Session {
+                        id: session_id.to_string(),
+                        project_id: project_id.clone(),
+                        project_path: project_path.clone(),
+                        todo_data,
+                        created_at,
+                        modified_at,
+                        first_message,
+                        message_timestamp,
+                        title: None,
+                        tags: Vec::new(),
+                        pinned: false,
+                        archived,
+                        notes: None,
+                        summary_title: None,
+                        summary: None,
+                    });
+                }
             }
         }
     }
 
-    // Sort sessions by creation time (newest first)
-    sessions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    // Attach user metadata and summaries, then filter and sort (pinned first, newest first by default)
+    let (meta, summaries) = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        (load_project_meta(&conn, &project_id)?, load_project_summaries(&conn, &project_id)?)
+    };
+    for session in &mut sessions {
+        apply_meta(session, meta.get(&session.id));
+        if let Some(summary) = summaries.get(&session.id) {
+            session.summary_title = Some(summary.title.clone());
+            session.summary = Some(summary.summary.clone());
+        }
+    }
+    let sessions = filter_and_sort_sessions(sessions, &query);
 
     log::info!(
         "Found {} sessions for project {}",
@@ -1593,6 +1568,12 @@ pub async fn load_session_history(
         .join(&project_id)
         .join(format!("{}.jsonl", session_id));
 
+    // Archived sessions can still be viewed
+    let session_path = if session_path.exists() {
+        session_path
+    } else {
+        archived_session_path(&claude_dir.join("projects").join(&project_id), &session_id)
+    };
     if !session_path.exists() {
         return Err(format!("Session file not found: {}", session_id));
     }
@@ -1631,6 +1612,9 @@ pub async fn execute_claude_code(
         model
     );
 
+    // Refuse to launch past an exhausted hard budget
+    ensure_launch_budget(&app, &project_path).await?;
+
     // Get enabled environment variables from database
     let env_vars = match get_enabled_environment_variables(app.state::<AgentDb>()).await {
         Ok(vars) => {
@@ -1687,6 +1671,9 @@ pub async fn continue_claude_code(
         model
     );
 
+    // Refuse to launch past an exhausted hard budget
+    ensure_launch_budget(&app, &project_path).await?;
+
     // Get enabled environment variables from database
     let env_vars = match get_enabled_environment_variables(app.state::<AgentDb>()).await {
         Ok(vars) => vars,
@@ -1740,6 +1727,9 @@ pub async fn resume_claude_code(
         model
     );
 
+    // Refuse to launch past an exhausted hard budget
+    ensure_launch_budget(&app, &project_path).await?;
+
     // Get enabled environment variables from database
     let env_vars = match get_enabled_environment_variables(app.state::<AgentDb>()).await {
         Ok(vars) => vars,
@@ -2005,43 +1995,40 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
             log::debug!("Claude stdout: {}", line);
 
             // Parse the line to check for init message with session ID
-            if let Ok(msg) = serde_json::from_str::<serde_json::Value>(&line) {
-                if msg["type"] == "system" && msg["subtype"] == "init" {
-                    if let Some(claude_session_id) = msg["session_id"].as_str() {
-                        let mut session_id_guard = match session_id_holder_clone.lock() {
-                            Ok(guard) => guard,
-                            Err(e) => {
-                                error_log!("Failed to lock session_id_holder: {}", e);
-                                return;
-                            }
-                        };
-                        if session_id_guard.is_none() {
-                            *session_id_guard = Some(claude_session_id.to_string());
-                            log::info!("Extracted Claude session ID: {}", claude_session_id);
-
-                            // Now register with ProcessRegistry using Claude's session ID
-                            match registry_clone.register_claude_session(
-                                claude_session_id.to_string(),
-                                pid,
-                                project_path_clone.clone(),
-                                prompt_clone.clone(),
-                                model_clone.clone(),
-                            ) {
-                                Ok(run_id) => {
-                                    log::info!("Registered Claude session with run_id: {}", run_id);
-                                    let mut run_id_guard = match run_id_holder_clone.lock() {
-                                        Ok(guard) => guard,
-                                        Err(e) => {
-                                            error_log!("Failed to lock run_id_holder: {}", e);
-                                            return;
-                                        }
-                                    };
-                                    *run_id_guard = Some(run_id);
-                                }
+            let parsed = crate::stream_json::parse_line(&line);
+            if let Some(claude_session_id) = parsed.as_ref().and_then(|msg| msg.init_session_id()) {
+                let mut session_id_guard = match session_id_holder_clone.lock() {
+                    Ok(guard) => guard,
+                    Err(e) => {
+                        error_log!("Failed to lock session_id_holder: {}", e);
+                        return;
+                    }
+                };
+                if session_id_guard.is_none() {
+                    *session_id_guard = Some(claude_session_id.to_string());
+                    log::info!("Extracted Claude session ID: {}", claude_session_id);
+
+                    // Now register with ProcessRegistry using Claude's session ID
+                    match registry_clone.register_claude_session(
+                        claude_session_id.to_string(),
+                        pid,
+                        project_path_clone.clone(),
+                        prompt_clone.clone(),
+                        model_clone.clone(),
+                    ) {
+                        Ok(run_id) => {
+                            log::info!("Registered Claude session with run_id: {}", run_id);
+                            let mut run_id_guard = match run_id_holder_clone.lock() {
+                                Ok(guard) => guard,
                                 Err(e) => {
-                                    log::error!("Failed to register Claude session: {}", e);
+                                    error_log!("Failed to lock run_id_holder: {}", e);
+                                    return;
                                 }
-                            }
+                            };
+                            *run_id_guard = Some(run_id);
+                        }
+                        Err(e) => {
+                            log::error!("Failed to register Claude session: {}", e);
                         }
                     }
                 }
@@ -2061,6 +2048,9 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
             // Emit the line to the frontend with session isolation if we have session ID
             if let Some(ref session_id) = *session_id_holder_clone.lock().unwrap() {
                 let _ = app_handle.emit(&format!("claude-output:{}", session_id), &line);
+                if let Some(event) = &parsed {
+                    let _ = app_handle.emit(&format!("claude-event:{}", session_id), event.to_json_with_raw(&line));
+                }
             }
             // Also emit to the generic event for backward compatibility
             let _ = app_handle.emit("claude-output", &line);
@@ -2197,33 +2187,30 @@ async fn spawn_claude_sidecar(
                         log::debug!("Claude sidecar stdout: {}", line_str);
 
                         // Parse the line to check for init message with session ID
-                        if let Ok(msg) = serde_json::from_str::<serde_json::Value>(line_str) {
-                            if msg["type"] == "system" && msg["subtype"] == "init" {
-                                if let Some(claude_session_id) = msg["session_id"].as_str() {
-                                    if let Ok(mut session_id_guard) = session_id_holder_clone.lock() {
-                                        if session_id_guard.is_none() {
-                                            *session_id_guard = Some(claude_session_id.to_string());
-                                            log::info!("Extracted Claude session ID: {}", claude_session_id);
-
-                                            // Register with ProcessRegistry using Claude's session ID
-                                            match registry_clone.register_claude_session(
-                                                claude_session_id.to_string(),
-                                                pid,
-                                                project_path_clone.clone(),
-                                                prompt_clone.clone(),
-                                                model_clone.clone(),
-                                            ) {
-                                                Ok(run_id) => {
-                                                    log::info!("Registered Claude sidecar session with run_id: {}", run_id);
-                                                    if let Ok(mut run_id_guard) = run_id_holder_clone.lock() {
-                                                        *run_id_guard = Some(run_id);
-                                                    }
-                                                }
-                                                Err(e) => {
-                                                    log::error!("Failed to register Claude sidecar session: {}", e);
-                                                }
+                        let parsed = crate::stream_json::parse_line(line_str);
+                        if let Some(claude_session_id) = parsed.as_ref().and_then(|msg| msg.init_session_id()) {
+                            if let Ok(mut session_id_guard) = session_id_holder_clone.lock() {
+                                if session_id_guard.is_none() {
+                                    *session_id_guard = Some(claude_session_id.to_string());
+                                    log::info!("Extracted Claude session ID: {}", claude_session_id);
+
+                                    // Register with ProcessRegistry using Claude's session ID
+                                    match registry_clone.register_claude_session(
+                                        claude_session_id.to_string(),
+                                        pid,
+                                        project_path_clone.clone(),
+                                        prompt_clone.clone(),
+                                        model_clone.clone(),
+                                    ) {
+                                        Ok(run_id) => {
+                                            log::info!("Registered Claude sidecar session with run_id: {}", run_id);
+                                            if let Ok(mut run_id_guard) = run_id_holder_clone.lock() {
+                                                *run_id_guard = Some(run_id);
                                             }
                                         }
+                                        Err(e) => {
+                                            log::error!("Failed to register Claude sidecar session: {}", e);
+                                        }
                                     }
                                 }
                             }
@@ -2239,6 +2226,9 @@ async fn spawn_claude_sidecar(
                         // Emit the line to the frontend with session isolation if we have session ID
                         if let Some(ref session_id) = *session_id_holder_clone.lock().unwrap() {
                             let _ = app_handle.emit(&format!("claude-output:{}", session_id), line_str);
+                            if let Some(event) = &parsed {
+                                let _ = app_handle.emit(&format!("claude-event:{}", session_id), event);
+                            }
                         }
                         // Also emit to the generic event for backward compatibility
                         let _ = app_handle.emit("claude-output", line_str);
@@ -3136,61 +3126,34 @@ pub async fn validate_hook_command(command: String) -> Result<serde_json::Value,
     }
 }
 
-/// Deletes a session file and its associated data
+/// Moves a session, its todos and its checkpoint timeline to the trash
 #[tauri::command]
 pub async fn delete_session(
     app: tauri::State<'_, crate::checkpoint::state::CheckpointState>,
+    db: tauri::State<'_, crate::commands::agents::AgentDb>,
     session_id: String,
     project_id: String,
 ) -> Result<(), String> {
     log::info!("Deleting session: {} from project: {}", session_id, project_id);
 
     let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
-    let project_dir = claude_dir.join("projects").join(&project_id);
-    let session_file = project_dir.join(format!("{}.jsonl", session_id));
+    let session_file = claude_dir
+        .join("projects")
+        .join(&project_id)
+        .join(format!("{}.jsonl", session_id));
 
     // Check if session file exists
     if !session_file.exists() {
         return Err(format!("Session file not found: {}", session_id));
     }
 
-    // Delete the session file
-    fs::remove_file(&session_file)
-        .map_err(|e| format!("Failed to delete session file: {}", e))?;
-
-    log::info!("Successfully deleted session file: {:?}", session_file);
-
     // Clear checkpoint manager for this session if it exists
     app.remove_manager(&session_id).await;
 
-    // Also try to delete any associated todo data
-    let todos_dir = claude_dir.join("todos");
-    let todo_file = todos_dir.join(format!("{}.json", session_id));
-    if todo_file.exists() {
-        if let Err(e) = fs::remove_file(&todo_file) {
-            log::warn!("Failed to delete todo file for session {}: {}", session_id, e);
-        } else {
-            log::info!("Successfully deleted todo file: {:?}", todo_file);
-        }
-    }
-
-    // Check if this was the last session in the project
-    let remaining_sessions = fs::read_dir(&project_dir)
-        .map_err(|e| format!("Failed to read project directory: {}", e))?
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| {
-            entry.path().is_file() && 
-            entry.path().extension().and_then(|s| s.to_str()) == Some("jsonl")
-        })
-        .count();
-
-    // If no sessions remain, delete the entire project directory
-    if remaining_sessions == 0 {
-        log::info!("No sessions remaining in project {}, deleting project directory", project_id);
-        fs::remove_dir_all(&project_dir)
-            .map_err(|e| format!("Failed to delete project directory: {}", e))?;
-        log::info!("Successfully deleted project directory: {:?}", project_dir);
-    }
+    // Keep the first message so the trash listing can say what the session was
+    let (first_message, _) = extract_first_user_message(&session_file);
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    trash_session(&conn, &session_id, &project_id, first_message)?;
 
     Ok(())
 }
@@ -7,11 +7,30 @@
 /// ## Module Structure
 /// 
 /// - `agents` - Agent management and execution commands
+/// - `agent_batches` - Fan-out of one agent across many projects
+/// - `agent_parameters` - Typed template parameters for agent prompts and tasks
+/// - `agent_pipelines` - Multi-step pipelines that chain agent runs
+/// - `agent_registry` - Configurable sources for importing agents
+/// - `agent_results` - Structured results and artifacts of finished agent runs
+/// - `agent_retries` - Retrying and resuming failed agent runs, with per-agent retry policies
+/// - `agent_scheduler` - Scheduled and recurring agent runs
+/// - `agent_trust` - Signature verification and import policy for agents
 /// - `claude` - Claude Code integration and session management  
 /// - `mcp` - Model Context Protocol server management
+/// - `session_export` - Rendering sessions as Markdown, HTML or JSON for sharing
+/// - `session_forks` - Forking sessions from any message and the resulting session trees
+/// - `session_meta` - User titles, tags, pinning and archiving of sessions
+/// - `session_search` - Full-text search index over Claude session files
+/// - `session_summaries` - Generated titles and summaries of finished sessions
+/// - `session_transfer` - Moving sessions between machines as JSONL files or bundles
+/// - `session_trash` - Recoverable deletion of sessions with a retention period
 /// - `slash_commands` - Slash command discovery and management
 /// - `storage` - Database operations and data management
 /// - `usage` - Usage statistics and cost tracking
+/// - `usage_export` - Exporting usage entries or their totals as CSV or JSON
+/// - `usage_pricing` - Model prices with effective dates, user overrides and imports
+/// - `usage_budgets` - Daily and monthly spend budgets with alerts and launch blocking
+/// - `usage_index` - Incremental SQLite index of the token usage recorded in sessions
 /// 
 /// ## Security
 /// 
@@ -19,9 +38,28 @@
 /// for database operations to prevent SQL injection attacks.
 
 pub mod agents;
+pub mod agent_batches;
+pub mod agent_parameters;
+pub mod agent_pipelines;
+pub mod agent_registry;
+pub mod agent_results;
+pub mod agent_retries;
+pub mod agent_scheduler;
+pub mod agent_trust;
 pub mod claude;
 pub mod mcp;
+pub mod session_export;
+pub mod session_forks;
+pub mod session_meta;
+pub mod session_search;
+pub mod session_summaries;
+pub mod session_transfer;
+pub mod session_trash;
 pub mod usage;
+pub mod usage_budgets;
+pub mod usage_export;
+pub mod usage_index;
+pub mod usage_pricing;
 pub mod storage;
 pub mod slash_commands;
 pub mod proxy;
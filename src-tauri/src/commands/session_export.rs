@@ -0,0 +1,703 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::sync::OnceLock;
+use tauri::State;
+
+use crate::commands::agents::{get_enabled_environment_variables, AgentDb};
+use crate::commands::usage_pricing::{load_pricing, PricingTable};
+use crate::project_paths::{claude_projects_dir, resolve_project_path};
+use crate::stream_json::{parse_line, ContentBlock, StreamEvent};
+
+/// Version of the normalized JSON export format
+const SESSION_EXPORT_VERSION: u32 = 1;
+
+/// Tools whose inputs carry file contents, with the fields holding them
+const FILE_CONTENT_FIELDS: &[(&str, &[&str])] = &[
+    ("Write", &["content"]),
+    ("Edit", &["old_string", "new_string"]),
+    ("MultiEdit", &["edits"]),
+    ("NotebookEdit", &["new_source"]),
+];
+
+/// Tools whose results are file contents
+const FILE_READING_TOOLS: &[&str] = &["Read", "NotebookRead"];
+
+/// Environment variable names whose values are treated as secrets
+const SENSITIVE_ENV_NAMES: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD", "PASSWD", "CREDENTIAL", "AUTH"];
+
+/// Environment values shorter than this aren't redacted, to avoid mangling ordinary text
+const MIN_REDACTED_ENV_VALUE_LEN: usize = 6;
+
+const REDACTED_FILE_CONTENTS: &str = "[file contents redacted]";
+const REDACTED_SECRET: &str = "[secret redacted]";
+
+/// Output format of a session export
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionExportFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+/// What a session export renders and what it redacts
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionExportOptions {
+    pub format: SessionExportFormat,
+    #[serde(default = "default_true")]
+    pub include_tool_calls: bool,
+    #[serde(default)]
+    pub include_thinking: bool,
+    /// Replace file contents written by edit tools and returned by read tools
+    #[serde(default)]
+    pub redact_file_contents: bool,
+    /// Replace API keys, tokens, private keys and password assignments
+    #[serde(default)]
+    pub redact_secrets: bool,
+    /// Replace values of configured and sensitive environment variables with `${NAME}`
+    #[serde(default)]
+    pub redact_env: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A session in the normalized export format
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExportedSession {
+    pub version: u32,
+    pub exported_at: String,
+    pub session_id: String,
+    pub project_id: String,
+    pub project_path: String,
+    /// Title Claude Code recorded for the session, if any
+    pub title: Option<String>,
+    pub started_at: Option<String>,
+    pub ended_at: Option<String>,
+    pub usage: ExportedUsage,
+    pub usage_by_model: BTreeMap<String, ExportedUsage>,
+    pub messages: Vec<ExportedMessage>,
+}
+
+/// Token counts and cost
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ExportedUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// One message of an exported session
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExportedMessage {
+    /// Position of the message in the session history as `load_session_history` returns it
+    pub index: i64,
+    /// "user", "assistant", or "tool" for user messages that only carry tool results
+    pub role: String,
+    pub timestamp: Option<String>,
+    pub model: Option<String>,
+    pub blocks: Vec<ExportedBlock>,
+}
+
+/// A block of an exported message
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExportedBlock {
+    Text {
+        text: String,
+    },
+    Thinking {
+        text: String,
+    },
+    ToolCall {
+        id: String,
+        name: String,
+        input: JsonValue,
+    },
+    ToolResult {
+        tool_use_id: String,
+        /// Name of the tool whose call this answers
+        tool_name: Option<String>,
+        content: String,
+        is_error: bool,
+    },
+}
+
+impl ExportedUsage {
+    fn add(&mut self, other: &ExportedUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_tokens += other.cache_creation_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cost_usd += other.cost_usd;
+    }
+}
+
+/// Flatten tool result content, a string or a list of content blocks, to text
+fn tool_result_text(content: &JsonValue) -> String {
+    match content {
+        JsonValue::String(text) => text.clone(),
+        JsonValue::Array(blocks) => blocks
+            .iter()
+            .map(|block| match block.get("type").and_then(|t| t.as_str()) {
+                Some("text") => block.get("text").and_then(|t| t.as_str()).unwrap_or_default().to_string(),
+                Some(other) => format!("[{}]", other),
+                None => block.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        JsonValue::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Build the normalized form of a session from its JSONL lines
+pub fn build_exported_session(
+    session_id: &str,
+    project_id: &str,
+    project_path: &str,
+    lines: &[&str],
+    pricing: &PricingTable,
+) -> ExportedSession {
+    let mut session = ExportedSession {
+        version: SESSION_EXPORT_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        session_id: session_id.to_string(),
+        project_id: project_id.to_string(),
+        project_path: project_path.to_string(),
+        title: None,
+        started_at: None,
+        ended_at: None,
+        usage: ExportedUsage::default(),
+        usage_by_model: BTreeMap::new(),
+        messages: Vec::new(),
+    };
+    let mut tool_names: HashMap<String, String> = HashMap::new();
+    let mut counted = HashSet::new();
+    let mut index = 0;
+
+    for raw in lines {
+        // Count lines the way `load_session_history` does, so indexes point into its result
+        if serde_json::from_str::<JsonValue>(raw).is_err() {
+            continue;
+        }
+        index += 1;
+        let Some(line) = parse_line(raw) else {
+            continue;
+        };
+
+        if let StreamEvent::Summary(summary) = &line.event {
+            session.title = Some(summary.summary.clone());
+            continue;
+        }
+        let role = match &line.event {
+            StreamEvent::User(event) if !event.is_meta => "user",
+            StreamEvent::Assistant(_) => "assistant",
+            _ => continue,
+        };
+        let Some(message) = line.message() else {
+            continue;
+        };
+
+        if let Some(timestamp) = &line.meta.timestamp {
+            session.started_at.get_or_insert_with(|| timestamp.clone());
+            session.ended_at = Some(timestamp.clone());
+        }
+
+        // Usage is repeated on every line of a streamed message, so count each once
+        let first_sighting = match (&message.id, &line.meta.request_id) {
+            (Some(id), Some(request_id)) => counted.insert(format!("{}:{}", id, request_id)),
+            _ => true,
+        };
+        if let Some(usage) = message.usage.filter(|usage| !usage.is_empty() && first_sighting) {
+            let model = message.model.clone().unwrap_or_else(|| "unknown".to_string());
+            let usage = ExportedUsage {
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+                cache_creation_tokens: usage.cache_creation_input_tokens,
+                cache_read_tokens: usage.cache_read_input_tokens,
+                cost_usd: line.meta.cost_usd.unwrap_or_else(|| {
+                    pricing
+                        .cost(&model, line.meta.timestamp.as_deref(), &usage)
+                        .unwrap_or(0.0)
+                }),
+            };
+            session.usage.add(&usage);
+            session.usage_by_model.entry(model).or_default().add(&usage);
+        }
+
+        let blocks: Vec<ExportedBlock> = message
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } if !text.trim().is_empty() => {
+                    Some(ExportedBlock::Text { text: text.clone() })
+                }
+                ContentBlock::Thinking { thinking } => Some(ExportedBlock::Thinking { text: thinking.clone() }),
+                ContentBlock::ToolUse { id, name, input } => {
+                    tool_names.insert(id.clone(), name.clone());
+                    Some(ExportedBlock::ToolCall { id: id.clone(), name: name.clone(), input: input.clone() })
+                }
+                ContentBlock::ToolResult { tool_use_id, content, is_error } => Some(ExportedBlock::ToolResult {
+                    tool_use_id: tool_use_id.clone(),
+                    tool_name: tool_names.get(tool_use_id).cloned(),
+                    content: tool_result_text(content),
+                    is_error: *is_error,
+                }),
+                _ => None,
+            })
+            .collect();
+        if blocks.is_empty() {
+            continue;
+        }
+
+        let only_results = blocks.iter().all(|b| matches!(b, ExportedBlock::ToolResult { .. }));
+        session.messages.push(ExportedMessage {
+            index: index - 1,
+            role: if role == "user" && only_results { "tool" } else { role }.to_string(),
+            timestamp: line.meta.timestamp.clone(),
+            model: message.model.clone(),
+            blocks,
+        });
+    }
+
+    session
+}
+
+fn secret_patterns() -> &'static [Regex] {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            r"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----",
+            r"\bsk-(?:ant-)?[A-Za-z0-9_\-]{20,}",
+            r"\b(?:ghp|gho|ghu|ghs|ghr|github_pat)_[A-Za-z0-9_]{20,}",
+            r"\bxox[abposr]-[A-Za-z0-9\-]{10,}",
+            r"\bAKIA[0-9A-Z]{16}\b",
+            r"\bAIza[0-9A-Za-z_\-]{35}\b",
+            r"(?i)\bbearer\s+[A-Za-z0-9_\-\.=]{16,}",
+        ]
+        .iter()
+        .map(|pattern| Regex::new(pattern).expect("valid secret pattern"))
+        .collect()
+    })
+}
+
+/// `password = value` style assignments, redacting only the value
+fn secret_assignment_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r#"(?i)\b([A-Z0-9_]*(?:password|passwd|secret|api_?key|access_?token|auth_?token)[A-Z0-9_]*["']?\s*[:=]\s*)["']?[^\s"',;]{4,}["']?"#)
+            .expect("valid assignment pattern")
+    })
+}
+
+/// Replaces sensitive content in an exported session
+struct Redactor {
+    file_contents: bool,
+    secrets: bool,
+    /// Environment values to hide, longest first, with the variable names they're shown as
+    env_values: Vec<(String, String)>,
+}
+
+impl Redactor {
+    fn new(options: &SessionExportOptions, env: &HashMap<String, String>) -> Self {
+        let mut env_values: Vec<(String, String)> = if options.redact_env {
+            env.iter()
+                .filter(|(_, value)| value.len() >= MIN_REDACTED_ENV_VALUE_LEN)
+                .map(|(name, value)| (value.clone(), name.clone()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        env_values.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.1.cmp(&b.1)));
+
+        Self {
+            file_contents: options.redact_file_contents,
+            secrets: options.redact_secrets,
+            env_values,
+        }
+    }
+
+    fn redact_text(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (value, name) in &self.env_values {
+            if text.contains(value.as_str()) {
+                text = text.replace(value.as_str(), &format!("${{{}}}", name));
+            }
+        }
+        if self.secrets {
+            for pattern in secret_patterns() {
+                text = pattern.replace_all(&text, REDACTED_SECRET).into_owned();
+            }
+            text = secret_assignment_pattern()
+                .replace_all(&text, format!("${{1}}{}", REDACTED_SECRET).as_str())
+                .into_owned();
+        }
+        text
+    }
+
+    fn redact_json(&self, value: &mut JsonValue) {
+        match value {
+            JsonValue::String(text) => *text = self.redact_text(text),
+            JsonValue::Array(items) => items.iter_mut().for_each(|item| self.redact_json(item)),
+            JsonValue::Object(fields) => fields.values_mut().for_each(|field| self.redact_json(field)),
+            _ => {}
+        }
+    }
+
+    fn redact_block(&self, block: &mut ExportedBlock) {
+        match block {
+            ExportedBlock::Text { text } | ExportedBlock::Thinking { text } => *text = self.redact_text(text),
+            ExportedBlock::ToolCall { name, input, .. } => {
+                if self.file_contents {
+                    let fields = FILE_CONTENT_FIELDS
+                        .iter()
+                        .find(|(tool, _)| tool == name)
+                        .map(|(_, fields)| *fields)
+                        .unwrap_or_default();
+                    for field in fields {
+                        if let Some(value) = input.get_mut(*field) {
+                            *value = JsonValue::String(REDACTED_FILE_CONTENTS.to_string());
+                        }
+                    }
+                }
+                self.redact_json(input);
+            }
+            ExportedBlock::ToolResult { tool_name, content, .. } => {
+                if self.file_contents && tool_name.as_deref().is_some_and(|name| FILE_READING_TOOLS.contains(&name)) {
+                    *content = REDACTED_FILE_CONTENTS.to_string();
+                } else {
+                    *content = self.redact_text(content);
+                }
+            }
+        }
+    }
+
+    fn redact(&self, session: &mut ExportedSession) {
+        if let Some(title) = &mut session.title {
+            *title = self.redact_text(title);
+        }
+        for message in &mut session.messages {
+            message.blocks.iter_mut().for_each(|block| self.redact_block(block));
+        }
+    }
+}
+
+/// Drop blocks the options exclude, and messages left empty by that
+fn filter_blocks(session: &mut ExportedSession, options: &SessionExportOptions) {
+    for message in &mut session.messages {
+        message.blocks.retain(|block| match block {
+            ExportedBlock::Thinking { .. } => options.include_thinking,
+            ExportedBlock::ToolCall { .. } | ExportedBlock::ToolResult { .. } => options.include_tool_calls,
+            ExportedBlock::Text { .. } => true,
+        });
+    }
+    session.messages.retain(|message| !message.blocks.is_empty());
+}
+
+/// A Markdown code fence longer than any backtick run in the content
+fn fence(content: &str) -> String {
+    let longest = content
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn pretty_json(value: &JsonValue) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+fn session_title(session: &ExportedSession) -> String {
+    session
+        .title
+        .clone()
+        .unwrap_or_else(|| format!("Session {}", session.session_id))
+}
+
+fn role_heading(message: &ExportedMessage) -> String {
+    let role = match message.role.as_str() {
+        "user" => "User",
+        "assistant" => "Assistant",
+        _ => "Tool results",
+    };
+    match &message.model {
+        Some(model) if message.role == "assistant" => format!("{} ({})", role, model),
+        _ => role.to_string(),
+    }
+}
+
+fn usage_summary(usage: &ExportedUsage) -> String {
+    format!(
+        "{} input, {} output, {} cache write, {} cache read tokens, ${:.4}",
+        usage.input_tokens, usage.output_tokens, usage.cache_creation_tokens, usage.cache_read_tokens, usage.cost_usd
+    )
+}
+
+/// Render a session as Markdown
+pub fn render_markdown(session: &ExportedSession) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", session_title(session));
+    let _ = writeln!(out, "- **Project:** `{}`", session.project_path);
+    let _ = writeln!(out, "- **Session:** `{}`", session.session_id);
+    if let (Some(start), Some(end)) = (&session.started_at, &session.ended_at) {
+        let _ = writeln!(out, "- **Time:** {} – {}", start, end);
+    }
+    let _ = writeln!(out, "- **Usage:** {}", usage_summary(&session.usage));
+    for (model, usage) in &session.usage_by_model {
+        let _ = writeln!(out, "  - `{}`: {}", model, usage_summary(usage));
+    }
+
+    for message in &session.messages {
+        let _ = write!(out, "\n## {}", role_heading(message));
+        if let Some(timestamp) = &message.timestamp {
+            let _ = write!(out, " · {}", timestamp);
+        }
+        out.push_str("\n\n");
+
+        for block in &message.blocks {
+            match block {
+                ExportedBlock::Text { text } => {
+                    let _ = writeln!(out, "{}\n", text.trim_end());
+                }
+                ExportedBlock::Thinking { text } => {
+                    for line in text.trim_end().lines() {
+                        let _ = writeln!(out, "> {}", line);
+                    }
+                    out.push('\n');
+                }
+                ExportedBlock::ToolCall { name, input, .. } => {
+                    let input = pretty_json(input);
+                    let fence = fence(&input);
+                    let _ = writeln!(out, "**Tool call:** `{}`\n\n{}json\n{}\n{}\n", name, fence, input, fence);
+                }
+                ExportedBlock::ToolResult { tool_name, content, is_error, .. } => {
+                    let label = if *is_error { "Tool error" } else { "Tool result" };
+                    let fence = fence(content);
+                    let _ = writeln!(
+                        out,
+                        "**{}:** `{}`\n\n{}\n{}\n{}\n",
+                        label,
+                        tool_name.as_deref().unwrap_or("unknown"),
+                        fence,
+                        content.trim_end(),
+                        fence
+                    );
+                }
+            }
+        }
+    }
+
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const HTML_STYLE: &str = "body{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;max-width:920px;margin:2rem auto;padding:0 1rem;color:#1f2328;line-height:1.5}\
+header{border-bottom:1px solid #d0d7de;margin-bottom:1.5rem}\
+dl{display:grid;grid-template-columns:max-content 1fr;gap:.25rem 1rem}dt{font-weight:600}dd{margin:0}\
+.message{border:1px solid #d0d7de;border-radius:8px;padding:.75rem 1rem;margin:1rem 0}\
+.message.user{background:#f6f8fa}.message.tool{background:#fbfbfb}\
+.role{font-weight:600;margin-bottom:.5rem}.role time{font-weight:400;color:#656d76;margin-left:.5rem}\
+.text{white-space:pre-wrap}.thinking{white-space:pre-wrap;color:#656d76;font-style:italic}\
+details{margin:.5rem 0}summary{cursor:pointer;font-family:monospace}details.error summary{color:#cf222e}\
+pre{background:#f6f8fa;border-radius:6px;padding:.75rem;overflow-x:auto;white-space:pre-wrap;word-break:break-word}";
+
+/// Render a session as a self-contained HTML page with collapsible tool calls
+pub fn render_html(session: &ExportedSession) -> String {
+    let title = escape_html(&session_title(session));
+    let mut out = String::new();
+    let _ = writeln!(out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>",
+        title, HTML_STYLE
+    );
+
+    let _ = writeln!(out, "<header>\n<h1>{}</h1>\n<dl>", title);
+    let _ = writeln!(out, "<dt>Project</dt><dd><code>{}</code></dd>", escape_html(&session.project_path));
+    let _ = writeln!(out, "<dt>Session</dt><dd><code>{}</code></dd>", escape_html(&session.session_id));
+    if let (Some(start), Some(end)) = (&session.started_at, &session.ended_at) {
+        let _ = writeln!(out, "<dt>Time</dt><dd>{} – {}</dd>", escape_html(start), escape_html(end));
+    }
+    let _ = writeln!(out, "<dt>Usage</dt><dd>{}</dd>", escape_html(&usage_summary(&session.usage)));
+    for (model, usage) in &session.usage_by_model {
+        let _ = writeln!(out,
+            "<dt><code>{}</code></dt><dd>{}</dd>",
+            escape_html(model),
+            escape_html(&usage_summary(usage))
+        );
+    }
+    out.push_str("</dl>\n</header>\n<main>\n");
+
+    for message in &session.messages {
+        let _ = write!(
+            out,
+            "<section class=\"message {}\" id=\"message-{}\">\n<div class=\"role\">{}",
+            escape_html(&message.role),
+            message.index,
+            escape_html(&role_heading(message))
+        );
+        if let Some(timestamp) = &message.timestamp {
+            let _ = write!(out, "<time>{}</time>", escape_html(timestamp));
+        }
+        out.push_str("</div>\n");
+
+        for block in &message.blocks {
+            match block {
+                ExportedBlock::Text { text } => {
+                    let _ = writeln!(out, "<div class=\"text\">{}</div>", escape_html(text.trim_end()));
+                }
+                ExportedBlock::Thinking { text } => {
+                    let _ = writeln!(out, "<div class=\"thinking\">{}</div>", escape_html(text.trim_end()));
+                }
+                ExportedBlock::ToolCall { name, input, .. } => {
+                    let _ = writeln!(out,
+                        "<details class=\"tool-call\"><summary>Tool call: {}</summary><pre>{}</pre></details>",
+                        escape_html(name),
+                        escape_html(&pretty_json(input))
+                    );
+                }
+                ExportedBlock::ToolResult { tool_name, content, is_error, .. } => {
+                    let _ = writeln!(out,
+                        "<details class=\"tool-result{}\"><summary>{}: {}</summary><pre>{}</pre></details>",
+                        if *is_error { " error" } else { "" },
+                        if *is_error { "Tool error" } else { "Tool result" },
+                        escape_html(tool_name.as_deref().unwrap_or("unknown")),
+                        escape_html(content.trim_end())
+                    );
+                }
+            }
+        }
+        out.push_str("</section>\n");
+    }
+
+    out.push_str("</main>\n</body>\n</html>\n");
+    out
+}
+
+/// Export a session as Markdown, HTML or normalized JSON
+#[tauri::command]
+pub async fn export_session(
+    db: State<'_, AgentDb>,
+    session_id: String,
+    project_id: String,
+    options: SessionExportOptions,
+) -> Result<String, String> {
+    let project_dir = claude_projects_dir()
+        .ok_or("Could not find home directory")?
+        .join(&project_id);
+    let session_path = project_dir.join(format!("{}.jsonl", session_id));
+    let content = fs::read_to_string(&session_path)
+        .map_err(|e| format!("Failed to read session file {}: {}", session_id, e))?;
+
+    let pricing = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        load_pricing(&conn)?
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    let mut session = build_exported_session(
+        &session_id,
+        &project_id,
+        &resolve_project_path(&project_dir),
+        &lines,
+        &pricing,
+    );
+    filter_blocks(&mut session, &options);
+
+    if options.redact_file_contents || options.redact_secrets || options.redact_env {
+        let mut env = HashMap::new();
+        if options.redact_env {
+            env.extend(std::env::vars().filter(|(name, _)| {
+                let name = name.to_uppercase();
+                SENSITIVE_ENV_NAMES.iter().any(|sensitive| name.contains(sensitive))
+            }));
+            env.extend(get_enabled_environment_variables(db).await?);
+        }
+        Redactor::new(&options, &env).redact(&mut session);
+    }
+
+    match options.format {
+        SessionExportFormat::Markdown => Ok(render_markdown(&session)),
+        SessionExportFormat::Html => Ok(render_html(&session)),
+        SessionExportFormat::Json => serde_json::to_string_pretty(&session)
+            .map_err(|e| format!("Failed to serialize session: {}", e)),
+    }
+}
+
+/// Export a session to a file
+#[tauri::command]
+pub async fn export_session_to_file(
+    db: State<'_, AgentDb>,
+    session_id: String,
+    project_id: String,
+    options: SessionExportOptions,
+    file_path: String,
+) -> Result<(), String> {
+    let exported = export_session(db, session_id, project_id, options).await?;
+    fs::write(&file_path, exported).map_err(|e| format!("Failed to write file: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &[&str] = &[
+        r#"{"type":"summary","summary":"Fix login"}"#,
+        r#"{"type":"user","timestamp":"2025-03-01T10:00:00Z","message":{"role":"user","content":"Use key sk-ant-REDACTED to log in"}}"#,
+        r#"{"type":"assistant","requestId":"r1","timestamp":"2025-03-01T10:00:05Z","message":{"id":"m1","model":"claude-sonnet-4","content":[{"type":"tool_use","id":"t1","name":"Read","input":{"file_path":"/srv/app/.env"}}],"usage":{"input_tokens":1000,"output_tokens":10}}}"#,
+        r#"{"type":"assistant","requestId":"r1","timestamp":"2025-03-01T10:00:05Z","message":{"id":"m1","model":"claude-sonnet-4","content":[{"type":"text","text":"Token is hunter2hunter2"}],"usage":{"input_tokens":1000,"output_tokens":10}}}"#,
+        r#"{"type":"user","timestamp":"2025-03-01T10:00:06Z","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"DB_PASSWORD=hunter2hunter2"}]}}"#,
+    ];
+
+    fn options(format: SessionExportFormat) -> SessionExportOptions {
+        SessionExportOptions {
+            format,
+            include_tool_calls: true,
+            include_thinking: false,
+            redact_file_contents: true,
+            redact_secrets: true,
+            redact_env: true,
+        }
+    }
+
+    #[test]
+    fn test_export_counts_usage_once_and_redacts() {
+        let mut session = build_exported_session("s1", "-srv-app", "/srv/app", SESSION, &PricingTable::new(Vec::new()));
+        assert_eq!(session.usage.input_tokens, 1000);
+        assert_eq!(session.messages.iter().map(|m| m.index).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(session.messages[3].role, "tool");
+
+        let env = HashMap::from([("DB_PASSWORD".to_string(), "hunter2hunter2".to_string())]);
+        Redactor::new(&options(SessionExportFormat::Markdown), &env).redact(&mut session);
+
+        let markdown = render_markdown(&session);
+        assert!(markdown.starts_with("# Fix login"));
+        assert!(!markdown.contains("sk-ant-"));
+        assert!(!markdown.contains("hunter2"));
+        assert!(markdown.contains("Token is ${DB_PASSWORD}"));
+        assert!(markdown.contains(REDACTED_FILE_CONTENTS));
+
+        let html = render_html(&session);
+        assert!(html.contains("<details class=\"tool-call\"><summary>Tool call: Read</summary>"));
+    }
+}
@@ -0,0 +1,401 @@
+use log::info;
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::commands::agents::AgentDb;
+use crate::commands::session_meta::archived_session_path;
+use crate::project_paths::{claude_projects_dir, resolve_project_path};
+
+/// Fields of a session line that hold the ID of another line
+const PARENT_UUID_FIELDS: &[&str] = &["parentUuid", "logicalParentUuid"];
+
+/// A session forked from a message of another session
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SessionFork {
+    pub session_id: String,
+    pub project_id: String,
+    pub project_path: String,
+    pub parent_session_id: String,
+    /// Index of the last message copied from the parent
+    pub fork_message_index: i64,
+    /// `uuid` of that message in the parent
+    pub fork_message_uuid: Option<String>,
+    pub created_at: String,
+}
+
+/// A session and the sessions forked from it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SessionTreeNode {
+    pub session_id: String,
+    /// Where the session was forked from its parent, if it is a fork
+    pub fork_message_index: Option<i64>,
+    pub fork_message_uuid: Option<String>,
+    pub created_at: Option<String>,
+    /// False once the session was deleted
+    pub exists: bool,
+    pub children: Vec<SessionTreeNode>,
+}
+
+/// The lines of a forked session
+#[derive(Debug, PartialEq)]
+struct ForkedLines {
+    content: String,
+    fork_message_uuid: Option<String>,
+}
+
+/// Create the session fork table
+pub fn init_fork_tables(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_forks (
+            session_id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            parent_session_id TEXT NOT NULL,
+            fork_message_index INTEGER NOT NULL,
+            fork_message_uuid TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_session_forks_parent ON session_forks(parent_session_id)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn project_dir(project_id: &str) -> Result<PathBuf, String> {
+    claude_projects_dir()
+        .map(|dir| dir.join(project_id))
+        .ok_or_else(|| "Could not find home directory".to_string())
+}
+
+/// The session's file, active or archived
+fn session_file(project_dir: &Path, session_id: &str) -> Option<PathBuf> {
+    let active = project_dir.join(format!("{}.jsonl", session_id));
+    if active.exists() {
+        return Some(active);
+    }
+    let archived = archived_session_path(project_dir, session_id);
+    archived.exists().then_some(archived)
+}
+
+/// IDs of the tool calls a message makes and of the calls it answers
+fn tool_ids(message: &Value) -> (Vec<&str>, Vec<&str>) {
+    let mut uses = Vec::new();
+    let mut results = Vec::new();
+    let blocks = message
+        .pointer("/message/content")
+        .and_then(|content| content.as_array())
+        .map(|blocks| blocks.as_slice())
+        .unwrap_or_default();
+    for block in blocks {
+        match block.get("type").and_then(|t| t.as_str()) {
+            Some("tool_use") => uses.extend(block.get("id").and_then(|id| id.as_str())),
+            Some("tool_result") => results.extend(block.get("tool_use_id").and_then(|id| id.as_str())),
+            _ => {}
+        }
+    }
+    (uses, results)
+}
+
+/// Copy the messages of a session up to and including `message_index` under a new
+/// session ID.
+///
+/// Messages are counted among the lines that parse as JSON, like the session history
+/// the UI shows. Tool results answering calls made up to the fork point are copied too,
+/// so the fork never ends on an unanswered tool call. Every message gets a fresh `uuid`
+/// and parent links are rewritten to match; summaries of conversation leaves that weren't
+/// copied are dropped.
+fn fork_lines(content: &str, new_session_id: &str, message_index: usize) -> Result<ForkedLines, String> {
+    let mut messages: Vec<Value> = Vec::new();
+    let mut pending_tool_uses: HashSet<String> = HashSet::new();
+    let mut fork_message_uuid = None;
+
+    for (index, line) in content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .enumerate()
+    {
+        let (uses, results) = tool_ids(&line);
+        if index > message_index {
+            let answers_pending = results.iter().any(|id| pending_tool_uses.contains(*id));
+            if !answers_pending {
+                break;
+            }
+        }
+        for id in results {
+            pending_tool_uses.remove(id);
+        }
+        pending_tool_uses.extend(uses.into_iter().map(String::from));
+        if index == message_index {
+            fork_message_uuid = line.get("uuid").and_then(|u| u.as_str()).map(String::from);
+        }
+        messages.push(line);
+    }
+
+    if messages.len() <= message_index {
+        return Err(format!(
+            "Message {} is out of range; the session has {} messages",
+            message_index,
+            messages.len()
+        ));
+    }
+
+    let uuids: HashMap<String, String> = messages
+        .iter()
+        .filter_map(|message| message.get("uuid").and_then(|u| u.as_str()))
+        .map(|uuid| (uuid.to_string(), uuid::Uuid::new_v4().to_string()))
+        .collect();
+
+    let mut out = String::new();
+    for mut message in messages {
+        let Some(fields) = message.as_object_mut() else {
+            continue;
+        };
+        if let Some(leaf) = fields.get("leafUuid").and_then(|u| u.as_str()) {
+            match uuids.get(leaf) {
+                Some(new_leaf) => {
+                    fields.insert("leafUuid".to_string(), Value::String(new_leaf.clone()));
+                }
+                None => continue,
+            }
+        }
+        for key in ["sessionId", "session_id"] {
+            if fields.contains_key(key) {
+                fields.insert(key.to_string(), Value::String(new_session_id.to_string()));
+            }
+        }
+        if let Some(new_uuid) = fields.get("uuid").and_then(|u| u.as_str()).and_then(|u| uuids.get(u)) {
+            fields.insert("uuid".to_string(), Value::String(new_uuid.clone()));
+        }
+        for key in PARENT_UUID_FIELDS {
+            if let Some(parent) = fields.get(*key).and_then(|u| u.as_str()) {
+                let new_parent = uuids.get(parent).cloned().map(Value::String).unwrap_or(Value::Null);
+                fields.insert(key.to_string(), new_parent);
+            }
+        }
+        out.push_str(&message.to_string());
+        out.push('\n');
+    }
+
+    Ok(ForkedLines { content: out, fork_message_uuid })
+}
+
+fn map_fork_row(row: &rusqlite::Row) -> rusqlite::Result<SessionFork> {
+    Ok(SessionFork {
+        session_id: row.get(0)?,
+        project_id: row.get(1)?,
+        project_path: String::new(),
+        parent_session_id: row.get(2)?,
+        fork_message_index: row.get(3)?,
+        fork_message_uuid: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+const SESSION_FORK_COLUMNS: &str =
+    "session_id, project_id, parent_session_id, fork_message_index, fork_message_uuid, created_at";
+
+fn load_project_forks(conn: &Connection, project_id: &str) -> Result<Vec<SessionFork>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM session_forks WHERE project_id = ?1 ORDER BY created_at",
+            SESSION_FORK_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let forks = stmt
+        .query_map(params![project_id], map_fork_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(forks)
+}
+
+/// Forget where a session was forked from. Forks of it keep pointing at it.
+pub fn delete_session_fork(conn: &Connection, session_id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM session_forks WHERE session_id = ?1", params![session_id])
+        .map_err(|e| format!("Failed to delete session fork: {}", e))?;
+    Ok(())
+}
+
+/// Build the tree of forks containing `session_id`, from its root session down
+fn build_session_tree(
+    forks: &[SessionFork],
+    session_id: &str,
+    exists: &dyn Fn(&str) -> bool,
+) -> SessionTreeNode {
+    let by_session: HashMap<&str, &SessionFork> =
+        forks.iter().map(|fork| (fork.session_id.as_str(), fork)).collect();
+    let mut children: HashMap<&str, Vec<&SessionFork>> = HashMap::new();
+    for fork in forks {
+        children.entry(fork.parent_session_id.as_str()).or_default().push(fork);
+    }
+
+    let mut root = session_id;
+    let mut seen = HashSet::from([root]);
+    while let Some(fork) = by_session.get(root) {
+        if !seen.insert(fork.parent_session_id.as_str()) {
+            break;
+        }
+        root = fork.parent_session_id.as_str();
+    }
+
+    fn node(
+        session_id: &str,
+        by_session: &HashMap<&str, &SessionFork>,
+        children: &HashMap<&str, Vec<&SessionFork>>,
+        exists: &dyn Fn(&str) -> bool,
+        visited: &mut HashSet<String>,
+    ) -> SessionTreeNode {
+        visited.insert(session_id.to_string());
+        let fork = by_session.get(session_id);
+        let kids = children
+            .get(session_id)
+            .map(|kids| kids.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .filter(|kid| !visited.contains(&kid.session_id))
+            .map(|kid| kid.session_id.clone())
+            .collect::<Vec<_>>();
+        SessionTreeNode {
+            session_id: session_id.to_string(),
+            fork_message_index: fork.map(|f| f.fork_message_index),
+            fork_message_uuid: fork.and_then(|f| f.fork_message_uuid.clone()),
+            created_at: fork.map(|f| f.created_at.clone()),
+            exists: exists(session_id),
+            children: kids
+                .iter()
+                .map(|kid| node(kid, by_session, children, exists, visited))
+                .collect(),
+        }
+    }
+
+    node(root, &by_session, &children, exists, &mut HashSet::new())
+}
+
+/// Fork a session from one of its messages into a new session of the same project.
+///
+/// The new session can be continued with `resume_claude_code`.
+#[tauri::command]
+pub async fn fork_session(
+    db: State<'_, AgentDb>,
+    session_id: String,
+    project_id: String,
+    message_index: usize,
+) -> Result<SessionFork, String> {
+    let project_dir = project_dir(&project_id)?;
+    let source = session_file(&project_dir, &session_id)
+        .ok_or_else(|| format!("Session file not found: {}", session_id))?;
+    let content = fs::read_to_string(&source).map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    let new_session_id = uuid::Uuid::new_v4().to_string();
+    let forked = fork_lines(&content, &new_session_id, message_index)?;
+    fs::write(project_dir.join(format!("{}.jsonl", new_session_id)), forked.content)
+        .map_err(|e| format!("Failed to write forked session: {}", e))?;
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO session_forks (session_id, project_id, parent_session_id, fork_message_index, fork_message_uuid)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![new_session_id, project_id, session_id, message_index as i64, forked.fork_message_uuid],
+    )
+    .map_err(|e| format!("Failed to record session fork: {}", e))?;
+
+    info!(
+        "Forked session {} at message {} into {}",
+        session_id, message_index, new_session_id
+    );
+    let fork = conn
+        .query_row(
+            &format!("SELECT {} FROM session_forks WHERE session_id = ?1", SESSION_FORK_COLUMNS),
+            params![new_session_id],
+            map_fork_row,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Session fork not found: {}", new_session_id))?;
+    Ok(SessionFork {
+        project_path: resolve_project_path(&project_dir),
+        ..fork
+    })
+}
+
+/// List the sessions of a project that were forked from another session
+#[tauri::command]
+pub async fn list_session_forks(db: State<'_, AgentDb>, project_id: String) -> Result<Vec<SessionFork>, String> {
+    let project_path = resolve_project_path(&project_dir(&project_id)?);
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    Ok(load_project_forks(&conn, &project_id)?
+        .into_iter()
+        .map(|fork| SessionFork {
+            project_path: project_path.clone(),
+            ..fork
+        })
+        .collect())
+}
+
+/// Get the tree of forks a session belongs to, starting at the session all of them
+/// descend from
+#[tauri::command]
+pub async fn get_session_tree(
+    db: State<'_, AgentDb>,
+    session_id: String,
+    project_id: String,
+) -> Result<SessionTreeNode, String> {
+    let forks = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        load_project_forks(&conn, &project_id)?
+    };
+    let project_dir = project_dir(&project_id)?;
+    Ok(build_session_tree(&forks, &session_id, &|id| {
+        session_file(&project_dir, id).is_some()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fork_lines_rewrites_ids_and_keeps_tool_results() {
+        let content = [
+            r#"{"type":"user","uuid":"a","parentUuid":null,"sessionId":"old","message":{"role":"user","content":"hi"}}"#,
+            "not json",
+            r#"{"type":"assistant","uuid":"b","parentUuid":"a","sessionId":"old","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Read","input":{}}]}}"#,
+            r#"{"type":"user","uuid":"c","parentUuid":"b","sessionId":"old","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"ok"}]}}"#,
+            r#"{"type":"assistant","uuid":"d","parentUuid":"c","sessionId":"old","message":{"role":"assistant","content":[{"type":"text","text":"done"}]}}"#,
+            r#"{"type":"summary","summary":"Greeting","leafUuid":"d"}"#,
+        ]
+        .join("\n");
+
+        let forked = fork_lines(&content, "new", 1).unwrap();
+        assert_eq!(forked.fork_message_uuid.as_deref(), Some("b"));
+        let lines: Vec<Value> = forked
+            .content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        // The tool result answering the fork point's call comes along
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|line| line["sessionId"] == "new"));
+        assert_eq!(lines[0]["parentUuid"], Value::Null);
+        assert_ne!(lines[1]["uuid"], "b");
+        assert_eq!(lines[1]["parentUuid"], lines[0]["uuid"]);
+        assert_eq!(lines[2]["parentUuid"], lines[1]["uuid"]);
+
+        // A summary of a copied leaf follows its leaf's new ID
+        let content = content.replace(r#""leafUuid":"d""#, r#""leafUuid":"a""#);
+        let forked = fork_lines(&content, "new", 4).unwrap();
+        let lines: Vec<Value> = forked.content.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines[4]["leafUuid"], lines[0]["uuid"]);
+
+        assert!(fork_lines(&content, "new", 5).is_err());
+    }
+}
@@ -0,0 +1,385 @@
+use log::{info, warn};
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::checkpoint::state::CheckpointState;
+use crate::commands::agents::AgentDb;
+use crate::commands::claude::Session;
+use crate::project_paths::claude_projects_dir;
+
+/// Directory inside a project directory that archived sessions are moved to
+pub const ARCHIVE_DIR: &str = ".archive";
+
+/// Directory inside a project directory holding session timelines
+const TIMELINES_DIR: &str = ".timelines";
+
+/// User-assigned metadata of a session
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SessionMeta {
+    pub session_id: String,
+    pub project_id: String,
+    pub title: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub pinned: bool,
+    /// Set by `archive_session`, not by `set_session_meta`
+    #[serde(default)]
+    pub archived: bool,
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+/// Which sessions a listing includes by archive state
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveFilter {
+    #[default]
+    Active,
+    Archived,
+    All,
+}
+
+/// What a session listing is sorted by; pinned sessions always come first
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionSortKey {
+    #[default]
+    CreatedAt,
+    LastActivity,
+    Title,
+}
+
+/// Filters and ordering for `get_project_sessions`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SessionListQuery {
+    #[serde(default)]
+    pub archived: ArchiveFilter,
+    /// Only sessions carrying all of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub pinned_only: bool,
+    /// Case-insensitive match against titles, first message, notes and summary
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub sort_by: SessionSortKey,
+    /// Reverse the default order of newest first, or A–Z for titles
+    #[serde(default)]
+    pub reverse: bool,
+}
+
+/// Create the session metadata table
+pub fn init_session_meta_tables(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_meta (
+            session_id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            title TEXT,
+            tags TEXT NOT NULL DEFAULT '[]',
+            pinned BOOLEAN NOT NULL DEFAULT 0,
+            archived BOOLEAN NOT NULL DEFAULT 0,
+            notes TEXT,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_session_meta_project ON session_meta(project_id)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn map_session_meta_row(row: &rusqlite::Row) -> rusqlite::Result<SessionMeta> {
+    let tags: String = row.get(3)?;
+    Ok(SessionMeta {
+        session_id: row.get(0)?,
+        project_id: row.get(1)?,
+        title: row.get(2)?,
+        tags: serde_json::from_str(&tags).unwrap_or_default(),
+        pinned: row.get(4)?,
+        archived: row.get(5)?,
+        notes: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+const SESSION_META_COLUMNS: &str = "session_id, project_id, title, tags, pinned, archived, notes, updated_at";
+
+/// Load the metadata of every session in a project, keyed by session ID
+pub fn load_project_meta(conn: &Connection, project_id: &str) -> Result<HashMap<String, SessionMeta>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM session_meta WHERE project_id = ?1",
+            SESSION_META_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let meta = stmt
+        .query_map(params![project_id], map_session_meta_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(meta.into_iter().map(|m| (m.session_id.clone(), m)).collect())
+}
+
+fn load_session_meta(conn: &Connection, session_id: &str) -> Result<Option<SessionMeta>, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM session_meta WHERE session_id = ?1", SESSION_META_COLUMNS),
+        params![session_id],
+        map_session_meta_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Remove a session's metadata, e.g. once the session is deleted
+pub fn delete_session_meta(conn: &Connection, session_id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM session_meta WHERE session_id = ?1", params![session_id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Path of a session's JSONL file once archived
+pub fn archived_session_path(project_dir: &Path, session_id: &str) -> PathBuf {
+    project_dir.join(ARCHIVE_DIR).join(format!("{}.jsonl", session_id))
+}
+
+/// Copy metadata onto a listed session
+pub fn apply_meta(session: &mut Session, meta: Option<&SessionMeta>) {
+    if let Some(meta) = meta {
+        session.title = meta.title.clone();
+        session.tags = meta.tags.clone();
+        session.pinned = meta.pinned;
+        session.notes = meta.notes.clone();
+    }
+}
+
+/// Filter and sort listed sessions, pinned sessions first
+pub fn filter_and_sort_sessions(mut sessions: Vec<Session>, query: &SessionListQuery) -> Vec<Session> {
+    let text = query
+        .text
+        .as_deref()
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty());
+
+    sessions.retain(|session| {
+        let archive_matches = match query.archived {
+            ArchiveFilter::Active => !session.archived,
+            ArchiveFilter::Archived => session.archived,
+            ArchiveFilter::All => true,
+        };
+        let text_matches = text.as_ref().is_none_or(|text| {
+            [&session.title, &session.summary_title, &session.first_message, &session.notes, &session.summary]
+                .iter()
+                .any(|field| field.as_deref().is_some_and(|f| f.to_lowercase().contains(text)))
+        });
+        archive_matches
+            && text_matches
+            && (!query.pinned_only || session.pinned)
+            && query.tags.iter().all(|tag| session.tags.contains(tag))
+    });
+
+    sessions.sort_by(|a, b| {
+        let order = match query.sort_by {
+            SessionSortKey::CreatedAt => b.created_at.cmp(&a.created_at),
+            SessionSortKey::LastActivity => b.modified_at.cmp(&a.modified_at),
+            SessionSortKey::Title => {
+                let title = |s: &Session| {
+                    s.title
+                        .clone()
+                        .or_else(|| s.summary_title.clone())
+                        .or_else(|| s.first_message.clone())
+                        .unwrap_or_default()
+                        .to_lowercase()
+                };
+                title(a).cmp(&title(b))
+            }
+        };
+        let order = if query.reverse { order.reverse() } else { order };
+        b.pinned.cmp(&a.pinned).then(order)
+    });
+
+    sessions
+}
+
+/// Move a file or directory, creating the destination's parent
+pub(crate) fn move_path(from: &Path, to: &Path) -> Result<(), String> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+    fs::rename(from, to).map_err(|e| format!("Failed to move {:?} to {:?}: {}", from, to, e))
+}
+
+/// Record a session's archive state, keeping any other metadata
+fn set_archived(conn: &Connection, session_id: &str, project_id: &str, archived: bool) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO session_meta (session_id, project_id, archived) VALUES (?1, ?2, ?3)
+         ON CONFLICT(session_id) DO UPDATE SET archived = ?3, updated_at = CURRENT_TIMESTAMP",
+        params![session_id, project_id, archived],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Get a session's metadata
+#[tauri::command]
+pub async fn get_session_meta(db: State<'_, AgentDb>, session_id: String) -> Result<Option<SessionMeta>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_session_meta(&conn, &session_id)
+}
+
+/// Set a session's title, tags, pinned flag and notes
+#[tauri::command]
+pub async fn set_session_meta(db: State<'_, AgentDb>, meta: SessionMeta) -> Result<SessionMeta, String> {
+    let title = meta.title.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    let notes = meta.notes.as_deref().filter(|n| !n.trim().is_empty());
+    let tags: BTreeSet<&str> = meta.tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()).collect();
+    let tags = serde_json::to_string(&tags).map_err(|e| e.to_string())?;
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO session_meta (session_id, project_id, title, tags, pinned, notes) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(session_id) DO UPDATE SET project_id = ?2, title = ?3, tags = ?4, pinned = ?5, notes = ?6,
+             updated_at = CURRENT_TIMESTAMP",
+        params![meta.session_id, meta.project_id, title, tags, meta.pinned, notes],
+    )
+    .map_err(|e| e.to_string())?;
+
+    load_session_meta(&conn, &meta.session_id)?.ok_or_else(|| "Session metadata not saved".to_string())
+}
+
+/// List every tag in use, alphabetically
+#[tauri::command]
+pub async fn list_session_tags(db: State<'_, AgentDb>) -> Result<Vec<String>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT tags FROM session_meta WHERE tags != '[]'")
+        .map_err(|e| e.to_string())?;
+    let tags = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|tags| tags.ok())
+        .flat_map(|tags| serde_json::from_str::<Vec<String>>(&tags).unwrap_or_default())
+        .collect::<BTreeSet<_>>();
+    Ok(tags.into_iter().collect())
+}
+
+/// Move a session and its timeline out of the active listing without deleting them
+#[tauri::command]
+pub async fn archive_session(
+    db: State<'_, AgentDb>,
+    checkpoints: State<'_, CheckpointState>,
+    session_id: String,
+    project_id: String,
+) -> Result<(), String> {
+    let project_dir = claude_projects_dir()
+        .ok_or("Could not find home directory")?
+        .join(&project_id);
+    let session_file = project_dir.join(format!("{}.jsonl", session_id));
+    if !session_file.exists() {
+        return Err(format!("Session file not found: {}", session_id));
+    }
+
+    move_path(&session_file, &archived_session_path(&project_dir, &session_id))?;
+    let timeline = project_dir.join(TIMELINES_DIR).join(&session_id);
+    if timeline.exists() {
+        if let Err(e) = move_path(&timeline, &project_dir.join(ARCHIVE_DIR).join(TIMELINES_DIR).join(&session_id)) {
+            warn!("Failed to archive timeline of session {}: {}", session_id, e);
+        }
+    }
+    checkpoints.remove_manager(&session_id).await;
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    set_archived(&conn, &session_id, &project_id, true)?;
+    info!("Archived session {} of project {}", session_id, project_id);
+    Ok(())
+}
+
+/// Move an archived session and its timeline back into the active listing
+#[tauri::command]
+pub async fn unarchive_session(
+    db: State<'_, AgentDb>,
+    session_id: String,
+    project_id: String,
+) -> Result<(), String> {
+    let project_dir = claude_projects_dir()
+        .ok_or("Could not find home directory")?
+        .join(&project_id);
+    let archived = archived_session_path(&project_dir, &session_id);
+    if !archived.exists() {
+        return Err(format!("Archived session not found: {}", session_id));
+    }
+    let session_file = project_dir.join(format!("{}.jsonl", session_id));
+    if session_file.exists() {
+        return Err(format!("Session {} already exists in the active listing", session_id));
+    }
+
+    move_path(&archived, &session_file)?;
+    let timeline = project_dir.join(ARCHIVE_DIR).join(TIMELINES_DIR).join(&session_id);
+    if timeline.exists() {
+        if let Err(e) = move_path(&timeline, &project_dir.join(TIMELINES_DIR).join(&session_id)) {
+            warn!("Failed to restore timeline of session {}: {}", session_id, e);
+        }
+    }
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    set_archived(&conn, &session_id, &project_id, false)?;
+    info!("Unarchived session {} of project {}", session_id, project_id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, created_at: u64, title: Option<&str>) -> Session {
+        Session {
+            id: id.to_string(),
+            project_id: "p".to_string(),
+            project_path: "/p".to_string(),
+            todo_data: None,
+            created_at,
+            modified_at: created_at,
+            first_message: Some(format!("message {}", id)),
+            message_timestamp: None,
+            title: title.map(str::to_string),
+            tags: Vec::new(),
+            pinned: false,
+            archived: false,
+            notes: None,
+            summary_title: None,
+            summary: None,
+        }
+    }
+
+    #[test]
+    fn test_filter_and_sort_sessions() {
+        let mut pinned = session("a", 1, Some("Zebra"));
+        pinned.pinned = true;
+        let mut tagged = session("b", 3, Some("apple"));
+        tagged.tags = vec!["bug".to_string()];
+        let mut archived = session("c", 2, None);
+        archived.archived = true;
+        let sessions = vec![tagged, archived, pinned];
+
+        let ids = |sessions: Vec<Session>| sessions.into_iter().map(|s| s.id).collect::<Vec<_>>();
+        assert_eq!(ids(filter_and_sort_sessions(sessions.clone(), &SessionListQuery::default())), vec!["a", "b"]);
+
+        let by_title = SessionListQuery { archived: ArchiveFilter::All, sort_by: SessionSortKey::Title, ..Default::default() };
+        assert_eq!(ids(filter_and_sort_sessions(sessions.clone(), &by_title)), vec!["a", "b", "c"]);
+
+        let tagged_only = SessionListQuery { tags: vec!["bug".to_string()], ..Default::default() };
+        assert_eq!(ids(filter_and_sort_sessions(sessions.clone(), &tagged_only)), vec!["b"]);
+
+        let archived_text = SessionListQuery { archived: ArchiveFilter::Archived, text: Some("MESSAGE".to_string()), ..Default::default() };
+        assert_eq!(ids(filter_and_sort_sessions(sessions, &archived_text)), vec!["c"]);
+    }
+}
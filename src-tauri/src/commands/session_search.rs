@@ -0,0 +1,624 @@
+use log::{debug, info, warn};
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::commands::agents::{read_complete_lines, AgentDb};
+use crate::commands::session_summaries::is_summarizer_project;
+use crate::project_paths::{claude_projects_dir, resolve_project_path};
+use crate::stream_json::{parse_line, ContentBlock, StreamEvent};
+
+/// Tool input fields that hold a file path
+const FILE_PATH_FIELDS: &[&str] = &["file_path", "notebook_path", "path"];
+
+/// Markers around matched terms in snippets
+pub const SNIPPET_MATCH_START: &str = "<mark>";
+pub const SNIPPET_MATCH_END: &str = "</mark>";
+
+/// Number of tokens in each snippet
+const SNIPPET_TOKENS: i64 = 16;
+
+const DEFAULT_SEARCH_LIMIT: i64 = 50;
+const MAX_SEARCH_LIMIT: i64 = 500;
+
+/// A search over indexed session messages
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SessionSearchQuery {
+    /// Terms to match; every term must appear, the last one as a prefix
+    pub query: String,
+    /// Only messages from sessions of this project path
+    #[serde(default)]
+    pub project_path: Option<String>,
+    /// Only messages at or after this RFC 3339 timestamp or date
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Only messages at or before this RFC 3339 timestamp or date
+    #[serde(default)]
+    pub to: Option<String>,
+    /// Only sessions that used a model whose name contains this
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+/// A message matching a search
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SessionSearchHit {
+    pub project_id: String,
+    pub project_path: String,
+    pub session_id: String,
+    /// Position of the message in the session history as `load_session_history` returns it;
+    /// 0 for summary hits
+    pub message_index: i64,
+    /// "user", "assistant", or "summary" for matches in a generated session summary
+    pub role: String,
+    pub model: Option<String>,
+    pub timestamp: Option<String>,
+    /// Matched text with terms wrapped in `SNIPPET_MATCH_START` and `SNIPPET_MATCH_END`
+    pub snippet: String,
+    pub tool_names: Vec<String>,
+    pub file_paths: Vec<String>,
+}
+
+/// Outcome of bringing the index up to date
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SessionIndexStats {
+    pub files_scanned: usize,
+    pub files_updated: usize,
+    pub files_removed: usize,
+    pub messages_added: usize,
+}
+
+/// Searchable parts of one session message
+#[derive(Debug, Default, PartialEq)]
+struct IndexedMessage {
+    message_index: i64,
+    role: String,
+    model: Option<String>,
+    timestamp: Option<String>,
+    content: String,
+    tool_names: Vec<String>,
+    file_paths: Vec<String>,
+}
+
+/// Create the session search tables
+pub fn init_search_tables(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_index_files (
+            path TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            project_path TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            byte_offset INTEGER NOT NULL DEFAULT 0,
+            message_count INTEGER NOT NULL DEFAULT 0,
+            indexed_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_messages (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_path TEXT NOT NULL,
+            project_id TEXT NOT NULL,
+            project_path TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            message_index INTEGER NOT NULL,
+            role TEXT NOT NULL,
+            model TEXT,
+            timestamp TEXT,
+            content TEXT NOT NULL,
+            tool_names TEXT NOT NULL,
+            file_paths TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_session_messages_file ON session_messages(file_path)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_session_messages_session ON session_messages(session_id)",
+        [],
+    )?;
+
+    // Full-text index over the message table, kept in sync by triggers
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS session_messages_fts USING fts5(
+            content, tool_names, file_paths,
+            content = 'session_messages', content_rowid = 'id'
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS session_messages_fts_insert AFTER INSERT ON session_messages BEGIN
+             INSERT INTO session_messages_fts(rowid, content, tool_names, file_paths)
+             VALUES (new.id, new.content, new.tool_names, new.file_paths);
+         END",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS session_messages_fts_delete AFTER DELETE ON session_messages BEGIN
+             INSERT INTO session_messages_fts(session_messages_fts, rowid, content, tool_names, file_paths)
+             VALUES ('delete', old.id, old.content, old.tool_names, old.file_paths);
+         END",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Extract the searchable message from one session line, if it has any searchable content
+fn index_line(line: &str, message_index: i64) -> Option<IndexedMessage> {
+    let parsed = parse_line(line)?;
+    let role = match &parsed.event {
+        StreamEvent::User(event) if !event.is_meta => "user",
+        StreamEvent::Assistant(_) => "assistant",
+        _ => return None,
+    };
+    let message = parsed.message()?;
+
+    let mut texts = Vec::new();
+    let mut tool_names = Vec::new();
+    let mut file_paths = Vec::new();
+    for block in &message.content {
+        match block {
+            ContentBlock::Text { text } => texts.push(text.as_str()),
+            ContentBlock::ToolUse { name, input, .. } => {
+                tool_names.push(name.clone());
+                file_paths.extend(
+                    FILE_PATH_FIELDS
+                        .iter()
+                        .filter_map(|field| input.get(field).and_then(|v| v.as_str()))
+                        .map(str::to_string),
+                );
+            }
+            _ => {}
+        }
+    }
+    if texts.is_empty() && tool_names.is_empty() {
+        return None;
+    }
+
+    Some(IndexedMessage {
+        message_index,
+        role: role.to_string(),
+        model: message.model.clone(),
+        timestamp: parsed.meta.timestamp.clone(),
+        content: texts.join("\n"),
+        tool_names,
+        file_paths,
+    })
+}
+
+/// Drop a session file and its messages from the index
+fn remove_file(conn: &Connection, path: &str) -> SqliteResult<()> {
+    conn.execute("DELETE FROM session_messages WHERE file_path = ?1", params![path])?;
+    conn.execute("DELETE FROM session_index_files WHERE path = ?1", params![path])?;
+    Ok(())
+}
+
+/// Index the lines appended to a session file since it was last indexed, returning
+/// the number of messages added. A file that shrank is indexed again from the start.
+fn index_file(
+    conn: &mut Connection,
+    path: &Path,
+    project_id: &str,
+    project_path: &str,
+) -> Result<usize, String> {
+    let key = path.to_string_lossy().to_string();
+    let session_id = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let len = fs::metadata(path).map_err(|e| e.to_string())?.len();
+
+    let indexed: Option<(u64, i64)> = conn
+        .query_row(
+            "SELECT byte_offset, message_count FROM session_index_files WHERE path = ?1",
+            params![key],
+            |row| Ok((row.get::<_, i64>(0)? as u64, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    let (offset, mut message_count) = match indexed {
+        Some((offset, _)) if len < offset => {
+            debug!("Session file {} shrank, reindexing", key);
+            remove_file(conn, &key).map_err(|e| e.to_string())?;
+            (0, 0)
+        }
+        Some((offset, _)) if len == offset => return Ok(0),
+        Some(indexed) => indexed,
+        None => (0, 0),
+    };
+
+    let (lines, new_offset) = read_complete_lines(path, offset).map_err(|e| e.to_string())?;
+    let mut messages = Vec::new();
+    for line in &lines {
+        // Count lines the way `load_session_history` does, so indexes point into its result
+        if serde_json::from_str::<serde_json::Value>(line).is_err() {
+            continue;
+        }
+        messages.extend(index_line(line, message_count));
+        message_count += 1;
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for message in &messages {
+        tx.execute(
+            "INSERT INTO session_messages (file_path, project_id, project_path, session_id, message_index, role, model, timestamp, content, tool_names, file_paths)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                key,
+                project_id,
+                project_path,
+                session_id,
+                message.message_index,
+                message.role,
+                message.model,
+                message.timestamp,
+                message.content,
+                message.tool_names.join(" "),
+                message.file_paths.join("\n"),
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.execute(
+        "INSERT INTO session_index_files (path, project_id, project_path, session_id, byte_offset, message_count, indexed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, CURRENT_TIMESTAMP)
+         ON CONFLICT(path) DO UPDATE SET project_path = ?3, byte_offset = ?5, message_count = ?6, indexed_at = CURRENT_TIMESTAMP",
+        params![key, project_id, project_path, session_id, new_offset as i64, message_count],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(messages.len())
+}
+
+/// Bring the index up to date with every session file under `projects_dir`, dropping
+/// files that no longer exist
+pub fn update_index(conn: &mut Connection, projects_dir: &Path) -> Result<SessionIndexStats, String> {
+    let mut stats = SessionIndexStats::default();
+    let mut seen = HashSet::new();
+
+    if let Ok(projects) = fs::read_dir(projects_dir) {
+        for project in projects.filter_map(|entry| entry.ok()) {
+            let project_dir = project.path();
+            if !project_dir.is_dir() {
+                continue;
+            }
+            let project_id = project.file_name().to_string_lossy().to_string();
+            let project_path = resolve_project_path(&project_dir);
+            if is_summarizer_project(&project_path) {
+                continue;
+            }
+
+            let Ok(files) = fs::read_dir(&project_dir) else {
+                continue;
+            };
+            for file in files.filter_map(|entry| entry.ok()) {
+                let path = file.path();
+                if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("jsonl") {
+                    continue;
+                }
+                stats.files_scanned += 1;
+                seen.insert(path.to_string_lossy().to_string());
+
+                match index_file(conn, &path, &project_id, &project_path) {
+                    Ok(0) => {}
+                    Ok(added) => {
+                        stats.files_updated += 1;
+                        stats.messages_added += added;
+                    }
+                    Err(e) => warn!("Failed to index session file {:?}: {}", path, e),
+                }
+            }
+        }
+    }
+
+    let indexed: Vec<String> = {
+        let mut stmt = conn
+            .prepare("SELECT path FROM session_index_files")
+            .map_err(|e| e.to_string())?;
+        let paths = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        paths
+    };
+    for path in indexed.iter().filter(|path| !seen.contains(*path)) {
+        remove_file(conn, path).map_err(|e| e.to_string())?;
+        stats.files_removed += 1;
+    }
+
+    Ok(stats)
+}
+
+/// Turn free text into an FTS5 query matching every term, the last one as a prefix
+fn fts_query(query: &str) -> Option<String> {
+    let mut terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    let last = terms.pop()?;
+    terms.push(format!("{}*", last));
+    Some(terms.join(" "))
+}
+
+/// Search the indexed messages, best matches first
+pub fn search(conn: &Connection, query: &SessionSearchQuery) -> Result<Vec<SessionSearchHit>, String> {
+    let Some(fts) = fts_query(&query.query) else {
+        return Ok(Vec::new());
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT m.project_id, m.project_path, m.session_id, m.message_index, m.role, m.model, m.timestamp,
+                    snippet(session_messages_fts, -1, ?7, ?8, '…', ?9), m.tool_names, m.file_paths
+             FROM session_messages_fts
+             JOIN session_messages m ON m.id = session_messages_fts.rowid
+             WHERE session_messages_fts MATCH ?1
+               AND (?2 IS NULL OR m.project_path = ?2)
+               AND (?3 IS NULL OR m.timestamp >= ?3)
+               AND (?4 IS NULL OR substr(m.timestamp, 1, length(?4)) <= ?4)
+               AND (?5 IS NULL OR m.session_id IN (
+                   SELECT session_id FROM session_messages WHERE model LIKE '%' || ?5 || '%'))
+             ORDER BY bm25(session_messages_fts), m.timestamp DESC
+             LIMIT ?6",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let hits = stmt
+        .query_map(
+            params![
+                fts,
+                query.project_path.as_deref().map(|p| p.trim_end_matches(['/', '\\'])),
+                query.from,
+                query.to,
+                query.model,
+                limit,
+                SNIPPET_MATCH_START,
+                SNIPPET_MATCH_END,
+                SNIPPET_TOKENS,
+            ],
+            |row| {
+                let tool_names: String = row.get(8)?;
+                let file_paths: String = row.get(9)?;
+                Ok(SessionSearchHit {
+                    project_id: row.get(0)?,
+                    project_path: row.get(1)?,
+                    session_id: row.get(2)?,
+                    message_index: row.get(3)?,
+                    role: row.get(4)?,
+                    model: row.get(5)?,
+                    timestamp: row.get(6)?,
+                    snippet: row.get(7)?,
+                    tool_names: tool_names.split_whitespace().map(str::to_string).collect(),
+                    file_paths: file_paths.lines().map(str::to_string).collect(),
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(hits)
+}
+
+/// Search generated session summaries, best matches first
+pub fn search_summaries(conn: &Connection, query: &SessionSearchQuery) -> Result<Vec<SessionSearchHit>, String> {
+    let Some(fts) = fts_query(&query.query) else {
+        return Ok(Vec::new());
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.project_id, s.project_path, s.session_id, s.ended_at,
+                    snippet(session_summaries_fts, -1, ?7, ?8, '…', ?9), s.models
+             FROM session_summaries_fts
+             JOIN session_summaries s ON s.id = session_summaries_fts.rowid
+             WHERE session_summaries_fts MATCH ?1
+               AND (?2 IS NULL OR s.project_path = ?2)
+               AND (?3 IS NULL OR s.ended_at >= ?3)
+               AND (?4 IS NULL OR substr(s.started_at, 1, length(?4)) <= ?4)
+               AND (?5 IS NULL OR s.models LIKE '%' || ?5 || '%')
+             ORDER BY bm25(session_summaries_fts)
+             LIMIT ?6",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let hits = stmt
+        .query_map(
+            params![
+                fts,
+                query.project_path.as_deref().map(|p| p.trim_end_matches(['/', '\\'])),
+                query.from,
+                query.to,
+                query.model,
+                limit,
+                SNIPPET_MATCH_START,
+                SNIPPET_MATCH_END,
+                SNIPPET_TOKENS,
+            ],
+            |row| {
+                let models: String = row.get(5)?;
+                Ok(SessionSearchHit {
+                    project_id: row.get(0)?,
+                    project_path: row.get(1)?,
+                    session_id: row.get(2)?,
+                    message_index: 0,
+                    role: "summary".to_string(),
+                    model: models.split_whitespace().last().map(str::to_string),
+                    timestamp: row.get(3)?,
+                    snippet: row.get(4)?,
+                    tool_names: Vec::new(),
+                    file_paths: Vec::new(),
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(hits)
+}
+
+/// Held while the index is being updated, so updates run one at a time
+static UPDATING: Mutex<()> = Mutex::new(());
+
+/// The app database file and the projects directory the index is built from
+fn index_paths(db: &AgentDb) -> Result<(PathBuf, PathBuf), String> {
+    let db_path = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.path()
+            .map(PathBuf::from)
+            .ok_or("The session index needs a database file")?
+    };
+    let projects_dir = claude_projects_dir().ok_or("Could not find home directory")?;
+    Ok((db_path, projects_dir))
+}
+
+/// Run `update` on a connection of its own to the app database, on a blocking thread
+/// and one at a time, so reading session files doesn't hold the shared connection
+async fn run_index_update<F>(db: &AgentDb, update: F) -> Result<SessionIndexStats, String>
+where
+    F: FnOnce(&mut Connection, &Path) -> Result<SessionIndexStats, String> + Send + 'static,
+{
+    let (db_path, projects_dir) = index_paths(db)?;
+    tokio::task::spawn_blocking(move || {
+        let _updating = UPDATING.lock().map_err(|e| e.to_string())?;
+        let mut conn = Connection::open(&db_path).map_err(|e| e.to_string())?;
+        update(&mut conn, &projects_dir)
+    })
+    .await
+    .map_err(|e| format!("Session index update failed: {}", e))?
+}
+
+/// Search all Claude sessions and their summaries.
+///
+/// Searches what is already indexed and updates the index in the background, so
+/// sessions changed since the last update show up in later searches.
+/// Summary hits come first since they match a session as a whole.
+#[tauri::command]
+pub async fn search_sessions(
+    db: State<'_, AgentDb>,
+    query: SessionSearchQuery,
+) -> Result<Vec<SessionSearchHit>, String> {
+    let (db_path, projects_dir) = index_paths(&db)?;
+    tokio::task::spawn_blocking(move || {
+        // An update already running covers this search too
+        let Ok(_updating) = UPDATING.try_lock() else {
+            return;
+        };
+        let updated = Connection::open(&db_path)
+            .map_err(|e| e.to_string())
+            .and_then(|mut conn| update_index(&mut conn, &projects_dir));
+        match updated {
+            Ok(stats) => debug!(
+                "Session index updated: {} of {} files, {} messages added",
+                stats.files_updated, stats.files_scanned, stats.messages_added
+            ),
+            Err(e) => warn!("Failed to update session index: {}", e),
+        }
+    });
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT) as usize;
+    let mut hits = search_summaries(&conn, &query)?;
+    hits.extend(search(&conn, &query)?);
+    hits.truncate(limit);
+    Ok(hits)
+}
+
+/// Index session files that are new or have grown since the last update
+#[tauri::command]
+pub async fn update_session_index(db: State<'_, AgentDb>) -> Result<SessionIndexStats, String> {
+    let stats = run_index_update(&db, update_index).await?;
+    info!(
+        "Session index updated: {} of {} files, {} messages added, {} files removed",
+        stats.files_updated, stats.files_scanned, stats.messages_added, stats.files_removed
+    );
+    Ok(stats)
+}
+
+/// Drop the whole index and build it again from the session files
+#[tauri::command]
+pub async fn rebuild_session_index(db: State<'_, AgentDb>) -> Result<SessionIndexStats, String> {
+    run_index_update(&db, |conn, projects_dir| {
+        conn.execute("DELETE FROM session_messages", [])
+            .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM session_index_files", [])
+            .map_err(|e| e.to_string())?;
+        update_index(conn, projects_dir)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_index_and_search_incrementally() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_search_tables(&conn).unwrap();
+
+        let projects = tempfile::tempdir().unwrap();
+        let project_dir = projects.path().join("-tmp-app");
+        fs::create_dir_all(&project_dir).unwrap();
+        let session = project_dir.join("s1.jsonl");
+        let mut file = fs::File::create(&session).unwrap();
+        writeln!(file, r#"{{"type":"summary","summary":"Parser work"}}"#).unwrap();
+        writeln!(
+            file,
+            r#"{{"type":"user","cwd":"/tmp/app","timestamp":"2025-03-01T10:00:00Z","message":{{"role":"user","content":"fix the tokenizer"}}}}"#
+        )
+        .unwrap();
+        file.flush().unwrap();
+
+        let stats = update_index(&mut conn, projects.path()).unwrap();
+        assert_eq!(stats.messages_added, 1);
+
+        writeln!(
+            file,
+            r#"{{"type":"assistant","timestamp":"2025-03-02T10:00:00Z","message":{{"model":"claude-opus-4","content":[{{"type":"text","text":"Updated the tokenizer"}},{{"type":"tool_use","id":"t1","name":"Edit","input":{{"file_path":"src/lexer.rs"}}}}]}}}}"#
+        )
+        .unwrap();
+        file.flush().unwrap();
+        let stats = update_index(&mut conn, projects.path()).unwrap();
+        assert_eq!((stats.files_updated, stats.messages_added), (1, 1));
+
+        let hits = search(&conn, &SessionSearchQuery { query: "tokeniz".to_string(), ..Default::default() }).unwrap();
+        assert_eq!(hits.len(), 2);
+
+        let hits = search(
+            &conn,
+            &SessionSearchQuery { query: "lexer".to_string(), to: Some("2025-03-02".to_string()), ..Default::default() },
+        )
+        .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].message_index, hits[0].session_id.as_str()), (2, "s1"));
+        assert_eq!(hits[0].tool_names, vec!["Edit"]);
+
+        let hits = search(
+            &conn,
+            &SessionSearchQuery { query: "tokenizer".to_string(), model: Some("sonnet".to_string()), ..Default::default() },
+        )
+        .unwrap();
+        assert!(hits.is_empty());
+
+        fs::remove_file(&session).unwrap();
+        assert_eq!(update_index(&mut conn, projects.path()).unwrap().files_removed, 1);
+    }
+}
@@ -0,0 +1,382 @@
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use zstd::stream::{decode_all, encode_all};
+
+use crate::commands::session_meta::archived_session_path;
+use crate::project_paths::{claude_dir, claude_projects_dir, encode_project_path, project_dir_for_path, resolve_project_path};
+
+/// Version of the session bundle format
+const SESSION_BUNDLE_VERSION: u32 = 1;
+
+/// Magic number at the start of zstd frames
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// A session packed with its todos for moving it to another machine
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SessionBundle {
+    pub version: u32,
+    pub session_id: String,
+    /// Project path the session was recorded in
+    pub project_path: String,
+    pub exported_at: String,
+    /// The session's JSONL
+    pub session: String,
+    pub todos: Option<Value>,
+}
+
+/// What to do when the imported session's ID is already taken
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionIdConflict {
+    /// Refuse to import
+    #[default]
+    Fail,
+    /// Import under a new session ID
+    Rename,
+    /// Overwrite the existing session, if it belongs to the target project and isn't archived
+    Replace,
+}
+
+/// Options of a session import
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SessionImportOptions {
+    /// Local project path to remap the recorded `cwd` to; defaults to the recorded path
+    pub project_path: Option<String>,
+    #[serde(default)]
+    pub on_conflict: SessionIdConflict,
+}
+
+/// What a file would import, for choosing the import options
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SessionImportPreview {
+    pub session_id: String,
+    /// Project path the session was recorded in
+    pub recorded_project_path: Option<String>,
+    pub message_count: usize,
+    pub has_todos: bool,
+    /// Project already holding a session with the same ID
+    pub existing_project_id: Option<String>,
+}
+
+/// A session placed under `~/.claude/projects` by an import
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SessionImportResult {
+    pub session_id: String,
+    /// ID the session had in the imported file
+    pub original_session_id: String,
+    pub project_id: String,
+    pub project_path: String,
+    pub recorded_project_path: Option<String>,
+    pub message_count: usize,
+    pub has_todos: bool,
+}
+
+/// A session read from an import file
+#[derive(Debug, PartialEq)]
+struct ImportedSession {
+    session_id: String,
+    recorded_project_path: Option<String>,
+    lines: Vec<Value>,
+    todos: Option<Value>,
+}
+
+/// Parse a session JSONL or session bundle, compressed or not
+fn read_import(bytes: &[u8], file_stem: &str) -> Result<ImportedSession, String> {
+    let bytes = if bytes.starts_with(&ZSTD_MAGIC) {
+        decode_all(bytes).map_err(|e| format!("Failed to decompress session bundle: {}", e))?
+    } else {
+        bytes.to_vec()
+    };
+    let text = String::from_utf8(bytes).map_err(|e| format!("Session file is not UTF-8: {}", e))?;
+
+    let (session, bundle) = match serde_json::from_str::<SessionBundle>(&text) {
+        Ok(bundle) if bundle.version <= SESSION_BUNDLE_VERSION => (bundle.session.clone(), Some(bundle)),
+        Ok(bundle) => return Err(format!("Unsupported session bundle version: {}", bundle.version)),
+        Err(_) => (text, None),
+    };
+
+    let lines: Vec<Value> = session
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    if lines.is_empty() {
+        return Err("No session messages found".to_string());
+    }
+
+    let field = |key: &str| {
+        lines
+            .iter()
+            .find_map(|line| line.get(key).and_then(|v| v.as_str()))
+            .map(String::from)
+    };
+    let session_id = bundle
+        .as_ref()
+        .map(|b| b.session_id.clone())
+        .or_else(|| field("sessionId"))
+        .unwrap_or_else(|| file_stem.to_string());
+    // The ID becomes a file name, so it must not be able to point anywhere else
+    uuid::Uuid::parse_str(&session_id).map_err(|_| format!("Invalid session ID: {}", session_id))?;
+
+    // A bundle names its project; the first `cwd` may be a subdirectory of it
+    let recorded_project_path = bundle
+        .as_ref()
+        .map(|b| b.project_path.clone())
+        .filter(|path| !path.is_empty())
+        .or_else(|| field("cwd"));
+
+    Ok(ImportedSession {
+        session_id,
+        recorded_project_path,
+        lines,
+        todos: bundle.and_then(|b| b.todos),
+    })
+}
+
+/// Map a path recorded under `from` to the same place under `to`
+fn remap_path(path: &str, from: &str, to: &str) -> Option<String> {
+    let rest = path.strip_prefix(from.trim_end_matches(['/', '\\']))?;
+    if rest.is_empty() || rest.starts_with(['/', '\\']) {
+        Some(format!("{}{}", to.trim_end_matches(['/', '\\']), rest))
+    } else {
+        None
+    }
+}
+
+/// Render the session's lines with a new session ID and `cwd`s moved to `project_path`
+fn rewrite_lines(session: &ImportedSession, session_id: &str, project_path: &str) -> String {
+    let mut out = String::new();
+    for line in &session.lines {
+        let mut line = line.clone();
+        if let Some(fields) = line.as_object_mut() {
+            if fields.contains_key("sessionId") {
+                fields.insert("sessionId".to_string(), Value::String(session_id.to_string()));
+            }
+            let cwd = fields.get("cwd").and_then(|v| v.as_str()).zip(session.recorded_project_path.as_deref());
+            if let Some(cwd) = cwd.and_then(|(cwd, from)| remap_path(cwd, from, project_path)) {
+                fields.insert("cwd".to_string(), Value::String(cwd));
+            }
+        }
+        out.push_str(&line.to_string());
+        out.push('\n');
+    }
+    out
+}
+
+/// Find the project holding a session with this ID, active or archived
+fn find_session_project(projects_dir: &Path, session_id: &str) -> Option<String> {
+    fs::read_dir(projects_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|dir| {
+            dir.join(format!("{}.jsonl", session_id)).exists()
+                || archived_session_path(dir, session_id).exists()
+        })
+        .and_then(|dir| dir.file_name().map(|name| name.to_string_lossy().to_string()))
+}
+
+/// Place an imported session in `project_dir`, the directory of `project_path`
+fn place_session(
+    claude_dir: &Path,
+    project_dir: &Path,
+    session: &ImportedSession,
+    project_path: &str,
+    on_conflict: SessionIdConflict,
+) -> Result<SessionImportResult, String> {
+    let projects_dir = claude_dir.join("projects");
+    let project_id = project_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut session_id = session.session_id.clone();
+    if let Some(existing) = find_session_project(&projects_dir, &session_id) {
+        match on_conflict {
+            SessionIdConflict::Fail => {
+                return Err(format!("Session {} already exists in project {}", session_id, existing))
+            }
+            SessionIdConflict::Rename => session_id = uuid::Uuid::new_v4().to_string(),
+            // Writing an active copy next to an archived one would list the session twice
+            SessionIdConflict::Replace if existing == project_id => {
+                if archived_session_path(project_dir, &session_id).exists() {
+                    return Err(format!(
+                        "Session {} is archived; unarchive it before replacing it",
+                        session_id
+                    ));
+                }
+            }
+            SessionIdConflict::Replace => {
+                return Err(format!(
+                    "Session {} belongs to another project ({}) and can't be replaced",
+                    session_id, existing
+                ))
+            }
+        }
+    }
+
+    fs::create_dir_all(project_dir).map_err(|e| format!("Failed to create project directory: {}", e))?;
+    fs::write(
+        project_dir.join(format!("{}.jsonl", session_id)),
+        rewrite_lines(session, &session_id, project_path),
+    )
+    .map_err(|e| format!("Failed to write session file: {}", e))?;
+
+    if let Some(todos) = &session.todos {
+        let todos_dir = claude_dir.join("todos");
+        fs::create_dir_all(&todos_dir).map_err(|e| format!("Failed to create todos directory: {}", e))?;
+        let todos = serde_json::to_string_pretty(todos).map_err(|e| e.to_string())?;
+        fs::write(todos_dir.join(format!("{}.json", session_id)), todos)
+            .map_err(|e| format!("Failed to write todos: {}", e))?;
+    }
+
+    Ok(SessionImportResult {
+        session_id,
+        original_session_id: session.session_id.clone(),
+        project_id,
+        project_path: project_path.to_string(),
+        recorded_project_path: session.recorded_project_path.clone(),
+        message_count: session.lines.len(),
+        has_todos: session.todos.is_some(),
+    })
+}
+
+fn read_import_file(path: &str) -> Result<ImportedSession, String> {
+    let path = Path::new(path);
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    let file_stem = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default()
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    read_import(&bytes, &file_stem)
+}
+
+/// Show what a session file would import
+#[tauri::command]
+pub async fn preview_session_import(path: String) -> Result<SessionImportPreview, String> {
+    let session = read_import_file(&path)?;
+    let projects_dir = claude_projects_dir().ok_or("Could not find home directory")?;
+    Ok(SessionImportPreview {
+        existing_project_id: find_session_project(&projects_dir, &session.session_id),
+        session_id: session.session_id,
+        recorded_project_path: session.recorded_project_path,
+        message_count: session.lines.len(),
+        has_todos: session.todos.is_some(),
+    })
+}
+
+/// Import a session JSONL or session bundle into the project it belongs to locally
+#[tauri::command]
+pub async fn import_session(
+    path: String,
+    options: Option<SessionImportOptions>,
+) -> Result<SessionImportResult, String> {
+    let options = options.unwrap_or_default();
+    let session = read_import_file(&path)?;
+
+    let project_path = options
+        .project_path
+        .or_else(|| session.recorded_project_path.clone())
+        .ok_or("The session doesn't record its project path; choose one")?;
+    if !Path::new(&project_path).is_dir() {
+        return Err(format!("Project path does not exist: {}", project_path));
+    }
+
+    let claude_dir = claude_dir().ok_or("Could not find home directory")?;
+    let project_dir = project_dir_for_path(&project_path).unwrap_or_else(|| {
+        claude_dir
+            .join("projects")
+            .join(encode_project_path(project_path.trim_end_matches(['/', '\\'])))
+    });
+    let result = place_session(&claude_dir, &project_dir, &session, &project_path, options.on_conflict)?;
+    info!(
+        "Imported session {} as {} into project {}",
+        result.original_session_id, result.session_id, result.project_id
+    );
+    Ok(result)
+}
+
+/// Pack a session and its todos into a compressed bundle for importing elsewhere
+#[tauri::command]
+pub async fn export_session_bundle(session_id: String, project_id: String, path: String) -> Result<(), String> {
+    let claude_dir = claude_dir().ok_or("Could not find home directory")?;
+    let project_dir = claude_dir.join("projects").join(&project_id);
+    let session_file = [
+        project_dir.join(format!("{}.jsonl", session_id)),
+        archived_session_path(&project_dir, &session_id),
+    ]
+    .into_iter()
+    .find(|file| file.exists())
+    .ok_or_else(|| format!("Session file not found: {}", session_id))?;
+
+    let todos = fs::read_to_string(claude_dir.join("todos").join(format!("{}.json", session_id)))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+    let bundle = SessionBundle {
+        version: SESSION_BUNDLE_VERSION,
+        project_path: resolve_project_path(&project_dir),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        session: fs::read_to_string(&session_file).map_err(|e| format!("Failed to read session file: {}", e))?,
+        session_id,
+        todos,
+    };
+
+    let json = serde_json::to_vec(&bundle).map_err(|e| e.to_string())?;
+    let compressed = encode_all(json.as_slice(), 3).map_err(|e| format!("Failed to compress session bundle: {}", e))?;
+    fs::write(&path, compressed).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION_ID: &str = "0b9e1f4e-3c55-4b8a-9d3e-2f1a7c6d5e4b";
+
+    #[test]
+    fn test_import_bundle_remaps_cwd_and_renames_on_collision() {
+        let session = format!(
+            "{}\n{}\n",
+            format_args!(r#"{{"type":"user","sessionId":"{}","cwd":"/home/ci/app/src","message":{{"content":"hi"}}}}"#, SESSION_ID),
+            format_args!(r#"{{"type":"assistant","sessionId":"{}","cwd":"/home/ci/app","message":{{"content":"ok"}}}}"#, SESSION_ID),
+        );
+        let bundle = SessionBundle {
+            version: SESSION_BUNDLE_VERSION,
+            session_id: SESSION_ID.to_string(),
+            project_path: "/home/ci/app".to_string(),
+            exported_at: "2026-01-01T00:00:00Z".to_string(),
+            session,
+            todos: Some(serde_json::json!([{"content": "ship", "status": "pending"}])),
+        };
+        let bytes = encode_all(serde_json::to_vec(&bundle).unwrap().as_slice(), 3).unwrap();
+        let imported = read_import(&bytes, "bundle").unwrap();
+        assert_eq!(imported.recorded_project_path.as_deref(), Some("/home/ci/app"));
+
+        let claude_dir = tempfile::tempdir().unwrap();
+        let claude_dir = claude_dir.path();
+        let project_dir = claude_dir.join("projects").join("-Users-me-app");
+        let first = place_session(claude_dir, &project_dir, &imported, "/Users/me/app", SessionIdConflict::Fail).unwrap();
+        assert_eq!(first.project_id, "-Users-me-app");
+        let written = fs::read_to_string(project_dir.join(format!("{}.jsonl", SESSION_ID))).unwrap();
+        assert!(written.contains(r#""cwd":"/Users/me/app""#));
+        assert!(written.contains(r#""cwd":"/Users/me/app/src""#));
+        assert!(claude_dir.join("todos").join(format!("{}.json", SESSION_ID)).exists());
+
+        assert!(place_session(claude_dir, &project_dir, &imported, "/Users/me/app", SessionIdConflict::Fail).is_err());
+        let second = place_session(claude_dir, &project_dir, &imported, "/Users/me/app", SessionIdConflict::Rename).unwrap();
+        assert_ne!(second.session_id, SESSION_ID);
+        assert_eq!(second.original_session_id, SESSION_ID);
+
+        place_session(claude_dir, &project_dir, &imported, "/Users/me/app", SessionIdConflict::Replace).unwrap();
+        let archived = archived_session_path(&project_dir, SESSION_ID);
+        fs::create_dir_all(archived.parent().unwrap()).unwrap();
+        fs::rename(project_dir.join(format!("{}.jsonl", SESSION_ID)), &archived).unwrap();
+        assert!(place_session(claude_dir, &project_dir, &imported, "/Users/me/app", SessionIdConflict::Replace).is_err());
+        assert!(!project_dir.join(format!("{}.jsonl", SESSION_ID)).exists());
+    }
+}
@@ -0,0 +1,418 @@
+use log::{info, warn};
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::checkpoint::state::CheckpointState;
+use crate::commands::agents::AgentDb;
+use crate::commands::session_forks::delete_session_fork;
+use crate::commands::session_meta::{delete_session_meta, move_path};
+use crate::commands::session_summaries::delete_summary;
+use crate::project_paths::{claude_dir, claude_projects_dir};
+
+/// Trash directory under `~/.claude`, on the same filesystem as the sessions so
+/// trashing and restoring are plain renames
+const TRASH_DIR: &str = ".claudia-trash";
+
+/// `app_settings` key holding the trash retention in days
+const TRASH_RETENTION_SETTING_KEY: &str = "session_trash_retention_days";
+
+const DEFAULT_TRASH_RETENTION_DAYS: i64 = 30;
+
+/// How often expired trash is purged while the app runs
+const TRASH_PURGE_INTERVAL_SECS: u64 = 6 * 60 * 60;
+
+/// Names of the parts of a session inside its trash entry
+const TRASHED_SESSION_FILE: &str = "session.jsonl";
+const TRASHED_TODOS_FILE: &str = "todos.json";
+const TRASHED_TIMELINE_DIR: &str = "timeline";
+
+/// A deleted session waiting in the trash
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TrashedSession {
+    pub id: String,
+    pub session_id: String,
+    pub project_id: String,
+    pub first_message: Option<String>,
+    pub has_todos: bool,
+    pub has_timeline: bool,
+    pub deleted_at: String,
+    /// When the session is purged for good
+    pub expires_at: String,
+}
+
+/// Which parts of a session were moved into a trash entry
+#[derive(Debug, Default, PartialEq)]
+struct TrashContents {
+    has_todos: bool,
+    has_timeline: bool,
+}
+
+/// Create the session trash table
+pub fn init_trash_tables(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_trash (
+            id TEXT PRIMARY KEY,
+            session_id TEXT NOT NULL,
+            project_id TEXT NOT NULL,
+            first_message TEXT,
+            has_todos BOOLEAN NOT NULL DEFAULT 0,
+            has_timeline BOOLEAN NOT NULL DEFAULT 0,
+            deleted_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            expires_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn load_retention_days(conn: &Connection) -> Result<i64, String> {
+    match conn.query_row(
+        "SELECT value FROM app_settings WHERE key = ?1",
+        params![TRASH_RETENTION_SETTING_KEY],
+        |row| row.get::<_, String>(0),
+    ) {
+        Ok(value) => value
+            .parse()
+            .map_err(|e| format!("Invalid trash retention setting: {}", e)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(DEFAULT_TRASH_RETENTION_DAYS),
+        Err(e) => Err(format!("Failed to load trash retention: {}", e)),
+    }
+}
+
+/// Move a session's JSONL, todos and timeline into `entry_dir`
+fn move_to_trash(claude_dir: &Path, entry_dir: &Path, session_id: &str, project_id: &str) -> Result<TrashContents, String> {
+    let project_dir = claude_dir.join("projects").join(project_id);
+    let session_file = project_dir.join(format!("{}.jsonl", session_id));
+    if !session_file.exists() {
+        return Err(format!("Session file not found: {}", session_id));
+    }
+
+    move_path(&session_file, &entry_dir.join(TRASHED_SESSION_FILE))?;
+    let mut contents = TrashContents::default();
+
+    let todo_file = claude_dir.join("todos").join(format!("{}.json", session_id));
+    if todo_file.exists() {
+        match move_path(&todo_file, &entry_dir.join(TRASHED_TODOS_FILE)) {
+            Ok(()) => contents.has_todos = true,
+            Err(e) => warn!("Failed to trash todos of session {}: {}", session_id, e),
+        }
+    }
+
+    let timelines_dir = project_dir.join(".timelines");
+    let timeline = timelines_dir.join(session_id);
+    if timeline.exists() {
+        match move_path(&timeline, &entry_dir.join(TRASHED_TIMELINE_DIR)) {
+            Ok(()) => contents.has_timeline = true,
+            Err(e) => warn!("Failed to trash timeline of session {}: {}", session_id, e),
+        }
+    }
+
+    // Drop directories the session leaves empty; anything else in them stays
+    let _ = fs::remove_dir(&timelines_dir);
+    let _ = fs::remove_dir(&project_dir);
+
+    Ok(contents)
+}
+
+/// Move a trashed session's parts back where they came from
+fn restore_from_trash(claude_dir: &Path, entry_dir: &Path, session_id: &str, project_id: &str) -> Result<(), String> {
+    let project_dir = claude_dir.join("projects").join(project_id);
+    let session_file = project_dir.join(format!("{}.jsonl", session_id));
+    if session_file.exists() {
+        return Err(format!("Session {} already exists", session_id));
+    }
+
+    move_path(&entry_dir.join(TRASHED_SESSION_FILE), &session_file)?;
+
+    let todos = entry_dir.join(TRASHED_TODOS_FILE);
+    let todo_file = claude_dir.join("todos").join(format!("{}.json", session_id));
+    if todos.exists() && !todo_file.exists() {
+        if let Err(e) = move_path(&todos, &todo_file) {
+            warn!("Failed to restore todos of session {}: {}", session_id, e);
+        }
+    }
+
+    let timeline = entry_dir.join(TRASHED_TIMELINE_DIR);
+    let timeline_dir = project_dir.join(".timelines").join(session_id);
+    if timeline.exists() && !timeline_dir.exists() {
+        if let Err(e) = move_path(&timeline, &timeline_dir) {
+            warn!("Failed to restore timeline of session {}: {}", session_id, e);
+        }
+    }
+
+    fs::remove_dir_all(entry_dir).map_err(|e| format!("Failed to remove trash entry: {}", e))
+}
+
+const TRASHED_SESSION_COLUMNS: &str =
+    "id, session_id, project_id, first_message, has_todos, has_timeline, deleted_at, expires_at";
+
+fn map_trashed_session_row(row: &rusqlite::Row) -> rusqlite::Result<TrashedSession> {
+    Ok(TrashedSession {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        project_id: row.get(2)?,
+        first_message: row.get(3)?,
+        has_todos: row.get(4)?,
+        has_timeline: row.get(5)?,
+        deleted_at: row.get(6)?,
+        expires_at: row.get(7)?,
+    })
+}
+
+fn load_trashed_session(conn: &Connection, id: &str) -> Result<TrashedSession, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM session_trash WHERE id = ?1", TRASHED_SESSION_COLUMNS),
+        params![id],
+        map_trashed_session_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Trashed session not found: {}", id))
+}
+
+/// Delete a trash entry for good, along with the session's metadata, summary and fork record
+fn purge_entry(conn: &Connection, trash_dir: &Path, entry: &TrashedSession) -> Result<(), String> {
+    let entry_dir = trash_dir.join(&entry.id);
+    if entry_dir.exists() {
+        fs::remove_dir_all(&entry_dir).map_err(|e| format!("Failed to purge {:?}: {}", entry_dir, e))?;
+    }
+    conn.execute("DELETE FROM session_trash WHERE id = ?1", params![entry.id])
+        .map_err(|e| e.to_string())?;
+
+    // The same session may have been trashed, restored and trashed again
+    let still_trashed: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM session_trash WHERE session_id = ?1)",
+            params![entry.session_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let restored = claude_projects_dir()
+        .ok_or("Could not find home directory")?
+        .join(&entry.project_id)
+        .join(format!("{}.jsonl", entry.session_id))
+        .exists();
+    if !still_trashed && !restored {
+        delete_session_meta(conn, &entry.session_id)?;
+        delete_summary(conn, &entry.session_id)?;
+        delete_session_fork(conn, &entry.session_id)?;
+    }
+    Ok(())
+}
+
+/// Purge trash entries past their retention, returning how many were purged
+fn purge_expired(conn: &Connection) -> Result<usize, String> {
+    let trash_dir = claude_dir().ok_or("Could not find home directory")?.join(TRASH_DIR);
+    let expired = {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM session_trash WHERE expires_at <= datetime('now')",
+                TRASHED_SESSION_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], map_trashed_session_row)
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+
+    for entry in &expired {
+        purge_entry(conn, &trash_dir, entry)?;
+    }
+    Ok(expired.len())
+}
+
+/// Move a session, its todos and its checkpoint timeline into the trash
+pub fn trash_session(
+    conn: &Connection,
+    session_id: &str,
+    project_id: &str,
+    first_message: Option<String>,
+) -> Result<TrashedSession, String> {
+    let claude_dir = claude_dir().ok_or("Could not find home directory")?;
+    let retention_days = load_retention_days(conn)?;
+    let id = uuid::Uuid::new_v4().to_string();
+    let entry_dir = claude_dir.join(TRASH_DIR).join(&id);
+    let contents = move_to_trash(&claude_dir, &entry_dir, session_id, project_id)?;
+
+    let inserted = conn.execute(
+        "INSERT INTO session_trash (id, session_id, project_id, first_message, has_todos, has_timeline, expires_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now', ?7))",
+        params![
+            id,
+            session_id,
+            project_id,
+            first_message,
+            contents.has_todos,
+            contents.has_timeline,
+            format!("+{} days", retention_days),
+        ],
+    );
+    if let Err(e) = inserted {
+        // Don't leave the session in a trash entry nothing refers to
+        if let Err(restore_err) = restore_from_trash(&claude_dir, &entry_dir, session_id, project_id) {
+            warn!("Failed to move session {} back from trash entry {}: {}", session_id, id, restore_err);
+        }
+        return Err(format!("Failed to record trashed session: {}", e));
+    }
+
+    info!("Moved session {} of project {} to trash entry {}", session_id, project_id, id);
+    load_trashed_session(conn, &id)
+}
+
+/// Periodically purge trash entries past their retention
+pub fn start_trash_purger(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(TRASH_PURGE_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let db = app.state::<AgentDb>();
+            let Ok(conn) = db.0.lock() else {
+                continue;
+            };
+            match purge_expired(&conn) {
+                Ok(0) => {}
+                Ok(purged) => info!("Purged {} expired sessions from trash", purged),
+                Err(e) => warn!("Failed to purge expired trash: {}", e),
+            }
+        }
+    });
+}
+
+/// List sessions in the trash, most recently deleted first
+#[tauri::command]
+pub async fn list_trashed_sessions(db: State<'_, AgentDb>) -> Result<Vec<TrashedSession>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    purge_expired(&conn)?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM session_trash ORDER BY deleted_at DESC",
+            TRASHED_SESSION_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let entries = stmt
+        .query_map([], map_trashed_session_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(entries)
+}
+
+/// Move a trashed session back into its project
+#[tauri::command]
+pub async fn restore_trashed_session(
+    db: State<'_, AgentDb>,
+    checkpoints: State<'_, CheckpointState>,
+    trash_id: String,
+) -> Result<TrashedSession, String> {
+    let entry = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let entry = load_trashed_session(&conn, &trash_id)?;
+        let claude_dir = claude_dir().ok_or("Could not find home directory")?;
+        restore_from_trash(
+            &claude_dir,
+            &claude_dir.join(TRASH_DIR).join(&trash_id),
+            &entry.session_id,
+            &entry.project_id,
+        )?;
+        conn.execute("DELETE FROM session_trash WHERE id = ?1", params![trash_id])
+            .map_err(|e| e.to_string())?;
+        entry
+    };
+
+    // Any manager still cached for the session predates the restored timeline
+    checkpoints.remove_manager(&entry.session_id).await;
+    info!("Restored session {} from trash", entry.session_id);
+    Ok(entry)
+}
+
+/// Delete a trashed session for good
+#[tauri::command]
+pub async fn purge_trashed_session(db: State<'_, AgentDb>, trash_id: String) -> Result<(), String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let entry = load_trashed_session(&conn, &trash_id)?;
+    purge_entry(&conn, &claude_dir().ok_or("Could not find home directory")?.join(TRASH_DIR), &entry)
+}
+
+/// Delete every trashed session for good, returning how many were purged
+#[tauri::command]
+pub async fn empty_session_trash(db: State<'_, AgentDb>) -> Result<usize, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let trash_dir = claude_dir().ok_or("Could not find home directory")?.join(TRASH_DIR);
+    let entries = {
+        let mut stmt = conn
+            .prepare(&format!("SELECT {} FROM session_trash", TRASHED_SESSION_COLUMNS))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], map_trashed_session_row)
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+
+    for entry in &entries {
+        purge_entry(&conn, &trash_dir, entry)?;
+    }
+    Ok(entries.len())
+}
+
+/// Get how many days trashed sessions are kept
+#[tauri::command]
+pub async fn get_session_trash_retention(db: State<'_, AgentDb>) -> Result<i64, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_retention_days(&conn)
+}
+
+/// Set how many days newly trashed sessions are kept
+#[tauri::command]
+pub async fn set_session_trash_retention(db: State<'_, AgentDb>, days: i64) -> Result<(), String> {
+    if days < 1 {
+        return Err("Retention must be at least one day".to_string());
+    }
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO app_settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = ?2",
+        params![TRASH_RETENTION_SETTING_KEY, days.to_string()],
+    )
+    .map_err(|e| format!("Failed to save trash retention: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trash_and_restore_session() {
+        let claude_dir = tempfile::tempdir().unwrap();
+        let claude_dir = claude_dir.path();
+        let project_dir = claude_dir.join("projects").join("-tmp-app");
+        fs::create_dir_all(project_dir.join(".timelines").join("s1").join("checkpoints")).unwrap();
+        fs::create_dir_all(claude_dir.join("todos")).unwrap();
+        fs::write(project_dir.join("s1.jsonl"), "{}\n").unwrap();
+        fs::write(claude_dir.join("todos").join("s1.json"), "[]").unwrap();
+
+        let entry_dir = claude_dir.join(TRASH_DIR).join("entry");
+        let contents = move_to_trash(claude_dir, &entry_dir, "s1", "-tmp-app").unwrap();
+        assert_eq!(contents, TrashContents { has_todos: true, has_timeline: true });
+        // The project held nothing else, so it's gone, but only because it was empty
+        assert!(!project_dir.exists());
+        assert!(entry_dir.join(TRASHED_TIMELINE_DIR).join("checkpoints").exists());
+
+        restore_from_trash(claude_dir, &entry_dir, "s1", "-tmp-app").unwrap();
+        assert!(project_dir.join("s1.jsonl").exists());
+        assert!(project_dir.join(".timelines").join("s1").join("checkpoints").exists());
+        assert!(claude_dir.join("todos").join("s1.json").exists());
+        assert!(!entry_dir.exists());
+    }
+}
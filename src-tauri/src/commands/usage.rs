@@ -1,10 +1,12 @@
 use chrono::{DateTime, Local, NaiveDate};
+use rusqlite::{params, Connection, ToSql};
 use serde::{Deserialize, Serialize};
-use serde_json;
-use std::collections::{HashMap, HashSet};
-use std::fs;
-use std::path::PathBuf;
-use tauri::command;
+use std::sync::MutexGuard;
+use tauri::{command, State};
+
+use crate::commands::agents::AgentDb;
+use crate::commands::usage_index::refresh_usage_index;
+use crate::commands::usage_pricing::load_pricing;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UsageEntry {
@@ -31,6 +33,13 @@ pub struct UsageStats {
     by_model: Vec<ModelUsage>,
     by_date: Vec<DailyUsage>,
     by_project: Vec<ProjectUsage>,
+    by_agent: Vec<AgentUsage>,
+    /// Usage of manual sessions and of agent runs, in that order
+    by_source: Vec<SourceUsage>,
+    /// Models without a price, whose usage is counted at no cost
+    unknown_models: Vec<String>,
+    /// Version of the prices the costs were computed with
+    pricing_version: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -63,435 +72,267 @@ pub struct ProjectUsage {
     last_used: String,
 }
 
-// Claude model pricing constants (per million tokens)
-// Based on https://docs.anthropic.com/zh-CN/docs/about-claude/pricing
-// Updated: 2025-01-19
-
-// Claude 3.5 Haiku pricing
-const HAIKU_3_5_INPUT_PRICE: f64 = 0.8;
-const HAIKU_3_5_OUTPUT_PRICE: f64 = 4.0;
-const HAIKU_3_5_CACHE_WRITE_PRICE: f64 = 1.0;
-const HAIKU_3_5_CACHE_READ_PRICE: f64 = 0.08;
-
-// Claude 3.5 Sonnet pricing
-const SONNET_3_5_INPUT_PRICE: f64 = 3.0;
-const SONNET_3_5_OUTPUT_PRICE: f64 = 15.0;
-const SONNET_3_5_CACHE_WRITE_PRICE: f64 = 3.75;
-const SONNET_3_5_CACHE_READ_PRICE: f64 = 0.30;
-
-// Claude 3.7 Sonnet pricing
-const SONNET_3_7_INPUT_PRICE: f64 = 4.0;
-const SONNET_3_7_OUTPUT_PRICE: f64 = 20.0;
-const SONNET_3_7_CACHE_WRITE_PRICE: f64 = 5.0;
-const SONNET_3_7_CACHE_READ_PRICE: f64 = 0.40;
-
-// Claude 3 Opus pricing (legacy)
-const OPUS_3_INPUT_PRICE: f64 = 15.0;
-const OPUS_3_OUTPUT_PRICE: f64 = 75.0;
-const OPUS_3_CACHE_WRITE_PRICE: f64 = 18.75;
-const OPUS_3_CACHE_READ_PRICE: f64 = 1.50;
-
-// Legacy Claude 4 pricing constants (for backward compatibility)
-const OPUS_4_INPUT_PRICE: f64 = 15.0;
-const OPUS_4_OUTPUT_PRICE: f64 = 75.0;
-const OPUS_4_CACHE_WRITE_PRICE: f64 = 18.75;
-const OPUS_4_CACHE_READ_PRICE: f64 = 1.50;
-
-const SONNET_4_INPUT_PRICE: f64 = 3.0;
-const SONNET_4_OUTPUT_PRICE: f64 = 15.0;
-const SONNET_4_CACHE_WRITE_PRICE: f64 = 3.75;
-const SONNET_4_CACHE_READ_PRICE: f64 = 0.30;
-
-#[derive(Debug, Deserialize)]
-struct JsonlEntry {
-    timestamp: String,
-    message: Option<MessageData>,
-    #[serde(rename = "sessionId")]
-    session_id: Option<String>,
-    #[serde(rename = "requestId")]
-    request_id: Option<String>,
-    #[serde(rename = "costUSD")]
-    cost_usd: Option<f64>,
-}
-
-#[derive(Debug, Deserialize)]
-struct MessageData {
-    id: Option<String>,
-    model: Option<String>,
-    usage: Option<UsageData>,
-}
-
-#[derive(Debug, Deserialize)]
-struct UsageData {
-    input_tokens: Option<u64>,
-    output_tokens: Option<u64>,
-    cache_creation_input_tokens: Option<u64>,
-    cache_read_input_tokens: Option<u64>,
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AgentUsage {
+    agent_id: i64,
+    agent_name: String,
+    agent_icon: String,
+    total_cost: f64,
+    total_tokens: u64,
+    run_count: u64,
+    avg_cost_per_run: f64,
+    last_used: String,
 }
 
-fn calculate_cost(model: &str, usage: &UsageData) -> f64 {
-    let input_tokens = usage.input_tokens.unwrap_or(0) as f64;
-    let output_tokens = usage.output_tokens.unwrap_or(0) as f64;
-    let cache_creation_tokens = usage.cache_creation_input_tokens.unwrap_or(0) as f64;
-    let cache_read_tokens = usage.cache_read_input_tokens.unwrap_or(0) as f64;
-
-    // Calculate cost based on model
-    let (input_price, output_price, cache_write_price, cache_read_price) = match model {
-        // Claude 3.5 Haiku
-        m if m.contains("claude-3-5-haiku") || m == "haiku" => (
-            HAIKU_3_5_INPUT_PRICE,
-            HAIKU_3_5_OUTPUT_PRICE,
-            HAIKU_3_5_CACHE_WRITE_PRICE,
-            HAIKU_3_5_CACHE_READ_PRICE,
-        ),
-        // Claude 3.5 Sonnet
-        m if m.contains("claude-3-5-sonnet") || m == "sonnet-3-5" => (
-            SONNET_3_5_INPUT_PRICE,
-            SONNET_3_5_OUTPUT_PRICE,
-            SONNET_3_5_CACHE_WRITE_PRICE,
-            SONNET_3_5_CACHE_READ_PRICE,
-        ),
-        // Claude 3.7 Sonnet
-        m if m.contains("claude-3-7-sonnet") || m == "sonnet-3-7" => (
-            SONNET_3_7_INPUT_PRICE,
-            SONNET_3_7_OUTPUT_PRICE,
-            SONNET_3_7_CACHE_WRITE_PRICE,
-            SONNET_3_7_CACHE_READ_PRICE,
-        ),
-        // Claude 3 Opus
-        m if m.contains("claude-3-opus") || m.contains("opus-3") => (
-            OPUS_3_INPUT_PRICE,
-            OPUS_3_OUTPUT_PRICE,
-            OPUS_3_CACHE_WRITE_PRICE,
-            OPUS_3_CACHE_READ_PRICE,
-        ),
-        // Legacy Claude 4 Opus (backward compatibility)
-        m if m.contains("opus-4") || m.contains("claude-opus-4") || m == "opus" => (
-            OPUS_4_INPUT_PRICE,
-            OPUS_4_OUTPUT_PRICE,
-            OPUS_4_CACHE_WRITE_PRICE,
-            OPUS_4_CACHE_READ_PRICE,
-        ),
-        // Legacy Claude 4 Sonnet (backward compatibility)
-        m if m.contains("sonnet-4") || m.contains("claude-sonnet-4") || m == "sonnet" => (
-            SONNET_4_INPUT_PRICE,
-            SONNET_4_OUTPUT_PRICE,
-            SONNET_4_CACHE_WRITE_PRICE,
-            SONNET_4_CACHE_READ_PRICE,
-        ),
-        // Unknown models - return 0 to avoid incorrect cost estimations
-        _ => (0.0, 0.0, 0.0, 0.0),
-    };
-
-    // Calculate cost (prices are per million tokens)
-    let cost = (input_tokens * input_price / 1_000_000.0)
-        + (output_tokens * output_price / 1_000_000.0)
-        + (cache_creation_tokens * cache_write_price / 1_000_000.0)
-        + (cache_read_tokens * cache_read_price / 1_000_000.0);
-
-    cost
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SourceUsage {
+    /// `manual` for sessions started by the user, `agent` for agent runs
+    source: String,
+    total_cost: f64,
+    total_tokens: u64,
+    session_count: u64,
 }
 
-fn parse_jsonl_file(
-    path: &PathBuf,
-    encoded_project_name: &str,
-    processed_hashes: &mut HashSet<String>,
-) -> Vec<UsageEntry> {
-    let mut entries = Vec::new();
-    let mut actual_project_path: Option<String> = None;
-
-    if let Ok(content) = fs::read_to_string(path) {
-        // Extract session ID from the file path
-        let session_id = path
-            .parent()
-            .and_then(|p| p.file_name())
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        for line in content.lines() {
-            if line.trim().is_empty() {
-                continue;
-            }
-
-            if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(line) {
-                // Extract the actual project path from cwd if we haven't already
-                if actual_project_path.is_none() {
-                    if let Some(cwd) = json_value.get("cwd").and_then(|v| v.as_str()) {
-                        actual_project_path = Some(cwd.to_string());
-                    }
-                }
-
-                // Try to parse as JsonlEntry for usage data
-                if let Ok(entry) = serde_json::from_value::<JsonlEntry>(json_value) {
-                    if let Some(message) = &entry.message {
-                        // Deduplication based on message ID and request ID
-                        if let (Some(msg_id), Some(req_id)) = (&message.id, &entry.request_id) {
-                            let unique_hash = format!("{}:{}", msg_id, req_id);
-                            if processed_hashes.contains(&unique_hash) {
-                                continue; // Skip duplicate entry
-                            }
-                            processed_hashes.insert(unique_hash);
-                        }
-
-                        if let Some(usage) = &message.usage {
-                            // Skip entries without meaningful token usage
-                            if usage.input_tokens.unwrap_or(0) == 0
-                                && usage.output_tokens.unwrap_or(0) == 0
-                                && usage.cache_creation_input_tokens.unwrap_or(0) == 0
-                                && usage.cache_read_input_tokens.unwrap_or(0) == 0
-                            {
-                                continue;
-                            }
-
-                            let cost = entry.cost_usd.unwrap_or_else(|| {
-                                if let Some(model_str) = &message.model {
-                                    calculate_cost(model_str, usage)
-                                } else {
-                                    0.0
-                                }
-                            });
-
-                            // Use actual project path if found, otherwise use encoded name
-                            let project_path = actual_project_path
-                                .clone()
-                                .unwrap_or_else(|| encoded_project_name.to_string());
-
-                            entries.push(UsageEntry {
-                                timestamp: entry.timestamp,
-                                model: message
-                                    .model
-                                    .clone()
-                                    .unwrap_or_else(|| "unknown".to_string()),
-                                input_tokens: usage.input_tokens.unwrap_or(0),
-                                output_tokens: usage.output_tokens.unwrap_or(0),
-                                cache_creation_tokens: usage
-                                    .cache_creation_input_tokens
-                                    .unwrap_or(0),
-                                cache_read_tokens: usage.cache_read_input_tokens.unwrap_or(0),
-                                cost,
-                                session_id: entry.session_id.unwrap_or_else(|| session_id.clone()),
-                                project_path,
-                            });
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    entries
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AgentRunUsage {
+    run_id: i64,
+    agent_id: i64,
+    agent_name: String,
+    agent_icon: String,
+    task: String,
+    model: String,
+    status: String,
+    project_path: String,
+    session_id: String,
+    created_at: String,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    total_tokens: u64,
+    total_cost: f64,
 }
 
-fn get_earliest_timestamp(path: &PathBuf) -> Option<String> {
-    if let Ok(content) = fs::read_to_string(path) {
-        let mut earliest_timestamp: Option<String> = None;
-        for line in content.lines() {
-            if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(line) {
-                if let Some(timestamp_str) = json_value.get("timestamp").and_then(|v| v.as_str()) {
-                    if let Some(current_earliest) = &earliest_timestamp {
-                        if timestamp_str < current_earliest.as_str() {
-                            earliest_timestamp = Some(timestamp_str.to_string());
-                        }
-                    } else {
-                        earliest_timestamp = Some(timestamp_str.to_string());
-                    }
-                }
-            }
-        }
-        return earliest_timestamp;
-    }
-    None
+/// Bring the usage index up to date, then lock the database for querying it
+pub(crate) async fn indexed_usage_db<'a>(db: &'a State<'_, AgentDb>) -> Result<MutexGuard<'a, Connection>, String> {
+    refresh_usage_index(db).await?;
+    db.0.lock().map_err(|e| e.to_string())
 }
 
-fn get_all_usage_entries(claude_path: &PathBuf) -> Vec<UsageEntry> {
-    let mut all_entries = Vec::new();
-    let mut processed_hashes = HashSet::new();
-    let projects_dir = claude_path.join("projects");
-
-    let mut files_to_process: Vec<(PathBuf, String)> = Vec::new();
-
-    if let Ok(projects) = fs::read_dir(&projects_dir) {
-        for project in projects.flatten() {
-            if project.file_type().map(|t| t.is_dir()).unwrap_or(false) {
-                let project_name = project.file_name().to_string_lossy().to_string();
-                let project_path = project.path();
-
-                walkdir::WalkDir::new(&project_path)
-                    .into_iter()
-                    .filter_map(Result::ok)
-                    .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("jsonl"))
-                    .for_each(|entry| {
-                        files_to_process.push((entry.path().to_path_buf(), project_name.clone()));
-                    });
-            }
-        }
-    }
-
-    // Sort files by their earliest timestamp to ensure chronological processing
-    // and deterministic deduplication.
-    files_to_process.sort_by_cached_key(|(path, _)| get_earliest_timestamp(path));
-
-    for (path, project_name) in files_to_process {
-        let entries = parse_jsonl_file(&path, &project_name, &mut processed_hashes);
-        all_entries.extend(entries);
-    }
-
-    // Sort by timestamp
-    all_entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-
-    all_entries
+/// An `attributed` common table expression of the usage entries matching `filter`,
+/// each with the agent run it was spent by. Entries belong to the latest run of their
+/// session started before them, and to no run when they come from a manual session
+fn attributed_usage(filter: &str) -> String {
+    format!(
+        "WITH attributed AS (
+             SELECT e.timestamp, e.session_id, e.cost, e.input_tokens, e.output_tokens,
+                    e.cache_creation_tokens, e.cache_read_tokens,
+                    (SELECT r.id FROM agent_runs r
+                     WHERE r.session_id = e.session_id AND r.session_id != ''
+                       AND julianday(r.created_at) <= julianday(e.timestamp)
+                     ORDER BY julianday(r.created_at) DESC, r.id DESC LIMIT 1) AS run_id
+             FROM usage_entries e WHERE {}
+         )",
+        filter
+    )
 }
 
-#[command]
-pub fn get_usage_stats(days: Option<u32>) -> Result<UsageStats, String> {
-    let claude_path = dirs::home_dir()
-        .ok_or("Failed to get home directory")?
-        .join(".claude");
-
-    let all_entries = get_all_usage_entries(&claude_path);
-
-    if all_entries.is_empty() {
-        return Ok(UsageStats {
-            total_cost: 0.0,
-            total_tokens: 0,
-            total_input_tokens: 0,
-            total_output_tokens: 0,
-            total_cache_creation_tokens: 0,
-            total_cache_read_tokens: 0,
-            total_sessions: 0,
-            by_model: vec![],
-            by_date: vec![],
-            by_project: vec![],
-        });
-    }
+/// Aggregate the indexed usage entries matching `filter`, an SQL condition over
+/// `usage_entries` using `params`
+fn query_usage_stats(conn: &Connection, filter: &str, params: &[&dyn ToSql]) -> Result<UsageStats, String> {
+    let pricing_version = load_pricing(conn)?.version;
 
-    // Filter by days if specified
-    let filtered_entries = if let Some(days) = days {
-        let cutoff = Local::now().naive_local().date() - chrono::Duration::days(days as i64);
-        all_entries
-            .into_iter()
-            .filter(|e| {
-                if let Ok(dt) = DateTime::parse_from_rfc3339(&e.timestamp) {
-                    dt.naive_local().date() >= cutoff
-                } else {
-                    false
-                }
+    let (
+        total_cost,
+        total_input_tokens,
+        total_output_tokens,
+        total_cache_creation_tokens,
+        total_cache_read_tokens,
+        total_sessions,
+    ) = conn
+        .query_row(
+            &format!(
+                "SELECT COALESCE(SUM(cost), 0), COALESCE(SUM(input_tokens), 0), COALESCE(SUM(output_tokens), 0),
+                        COALESCE(SUM(cache_creation_tokens), 0), COALESCE(SUM(cache_read_tokens), 0), COUNT(*)
+                 FROM usage_entries WHERE {}",
+                filter
+            ),
+            params,
+            |row| {
+                Ok((
+                    row.get::<_, f64>(0)?,
+                    row.get::<_, i64>(1)? as u64,
+                    row.get::<_, i64>(2)? as u64,
+                    row.get::<_, i64>(3)? as u64,
+                    row.get::<_, i64>(4)? as u64,
+                    row.get::<_, i64>(5)? as u64,
+                ))
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT model, SUM(cost), SUM(input_tokens), SUM(output_tokens), SUM(cache_creation_tokens),
+                    SUM(cache_read_tokens), COUNT(*)
+             FROM usage_entries WHERE {}
+             GROUP BY model ORDER BY SUM(cost) DESC",
+            filter
+        ))
+        .map_err(|e| e.to_string())?;
+    let by_model = stmt
+        .query_map(params, |row| {
+            let input_tokens = row.get::<_, i64>(2)? as u64;
+            let output_tokens = row.get::<_, i64>(3)? as u64;
+            Ok(ModelUsage {
+                model: row.get(0)?,
+                total_cost: row.get(1)?,
+                total_tokens: input_tokens + output_tokens,
+                input_tokens,
+                output_tokens,
+                cache_creation_tokens: row.get::<_, i64>(4)? as u64,
+                cache_read_tokens: row.get::<_, i64>(5)? as u64,
+                session_count: row.get::<_, i64>(6)? as u64,
             })
-            .collect()
-    } else {
-        all_entries
-    };
-
-    // Calculate aggregated stats
-    let mut total_cost = 0.0;
-    let mut total_input_tokens = 0u64;
-    let mut total_output_tokens = 0u64;
-    let mut total_cache_creation_tokens = 0u64;
-    let mut total_cache_read_tokens = 0u64;
-
-    let mut model_stats: HashMap<String, ModelUsage> = HashMap::new();
-    let mut daily_stats: HashMap<String, DailyUsage> = HashMap::new();
-    let mut project_stats: HashMap<String, ProjectUsage> = HashMap::new();
-
-    for entry in &filtered_entries {
-        // Update totals
-        total_cost += entry.cost;
-        total_input_tokens += entry.input_tokens;
-        total_output_tokens += entry.output_tokens;
-        total_cache_creation_tokens += entry.cache_creation_tokens;
-        total_cache_read_tokens += entry.cache_read_tokens;
-
-        // Update model stats
-        let model_stat = model_stats
-            .entry(entry.model.clone())
-            .or_insert(ModelUsage {
-                model: entry.model.clone(),
-                total_cost: 0.0,
-                total_tokens: 0,
-                input_tokens: 0,
-                output_tokens: 0,
-                cache_creation_tokens: 0,
-                cache_read_tokens: 0,
-                session_count: 0,
-            });
-        model_stat.total_cost += entry.cost;
-        model_stat.input_tokens += entry.input_tokens;
-        model_stat.output_tokens += entry.output_tokens;
-        model_stat.cache_creation_tokens += entry.cache_creation_tokens;
-        model_stat.cache_read_tokens += entry.cache_read_tokens;
-        model_stat.total_tokens = model_stat.input_tokens + model_stat.output_tokens;
-        model_stat.session_count += 1;
-
-        // Update daily stats
-        let date = entry
-            .timestamp
-            .split('T')
-            .next()
-            .unwrap_or(&entry.timestamp)
-            .to_string();
-        let daily_stat = daily_stats.entry(date.clone()).or_insert(DailyUsage {
-            date,
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT substr(timestamp, 1, 10) AS date, SUM(cost),
+                    SUM(input_tokens + output_tokens + cache_creation_tokens + cache_read_tokens),
+                    group_concat(DISTINCT model)
+             FROM usage_entries WHERE {}
+             GROUP BY date ORDER BY date DESC",
+            filter
+        ))
+        .map_err(|e| e.to_string())?;
+    let by_date = stmt
+        .query_map(params, |row| {
+            let models: String = row.get(3)?;
+            Ok(DailyUsage {
+                date: row.get(0)?,
+                total_cost: row.get(1)?,
+                total_tokens: row.get::<_, i64>(2)? as u64,
+                models_used: models.split(',').map(String::from).collect(),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT project_path, SUM(cost),
+                    SUM(input_tokens + output_tokens + cache_creation_tokens + cache_read_tokens),
+                    COUNT(*), MAX(timestamp)
+             FROM usage_entries WHERE {}
+             GROUP BY project_path ORDER BY SUM(cost) DESC",
+            filter
+        ))
+        .map_err(|e| e.to_string())?;
+    let by_project = stmt
+        .query_map(params, |row| {
+            let project_path: String = row.get(0)?;
+            Ok(ProjectUsage {
+                project_name: project_path
+                    .split('/')
+                    .next_back()
+                    .unwrap_or(&project_path)
+                    .to_string(),
+                project_path,
+                total_cost: row.get(1)?,
+                total_tokens: row.get::<_, i64>(2)? as u64,
+                session_count: row.get::<_, i64>(3)? as u64,
+                last_used: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{}
+             SELECT r.agent_id, COALESCE(a.name, MAX(r.agent_name)), COALESCE(a.icon, MAX(r.agent_icon)),
+                    SUM(attributed.cost),
+                    SUM(attributed.input_tokens + attributed.output_tokens
+                        + attributed.cache_creation_tokens + attributed.cache_read_tokens),
+                    COUNT(DISTINCT attributed.run_id), MAX(attributed.timestamp)
+             FROM attributed
+             JOIN agent_runs r ON r.id = attributed.run_id
+             LEFT JOIN agents a ON a.id = r.agent_id
+             GROUP BY r.agent_id ORDER BY SUM(attributed.cost) DESC",
+            attributed_usage(filter)
+        ))
+        .map_err(|e| e.to_string())?;
+    let by_agent = stmt
+        .query_map(params, |row| {
+            let total_cost: f64 = row.get(3)?;
+            let run_count = row.get::<_, i64>(5)? as u64;
+            Ok(AgentUsage {
+                agent_id: row.get(0)?,
+                agent_name: row.get(1)?,
+                agent_icon: row.get(2)?,
+                total_cost,
+                total_tokens: row.get::<_, i64>(4)? as u64,
+                run_count,
+                avg_cost_per_run: total_cost / run_count as f64,
+                last_used: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut by_source: Vec<SourceUsage> = ["manual", "agent"]
+        .iter()
+        .map(|source| SourceUsage {
+            source: source.to_string(),
             total_cost: 0.0,
             total_tokens: 0,
-            models_used: vec![],
-        });
-        daily_stat.total_cost += entry.cost;
-        daily_stat.total_tokens += entry.input_tokens
-            + entry.output_tokens
-            + entry.cache_creation_tokens
-            + entry.cache_read_tokens;
-        if !daily_stat.models_used.contains(&entry.model) {
-            daily_stat.models_used.push(entry.model.clone());
-        }
-
-        // Update project stats
-        let project_stat =
-            project_stats
-                .entry(entry.project_path.clone())
-                .or_insert(ProjectUsage {
-                    project_path: entry.project_path.clone(),
-                    project_name: entry
-                        .project_path
-                        .split('/')
-                        .last()
-                        .unwrap_or(&entry.project_path)
-                        .to_string(),
-                    total_cost: 0.0,
-                    total_tokens: 0,
-                    session_count: 0,
-                    last_used: entry.timestamp.clone(),
-                });
-        project_stat.total_cost += entry.cost;
-        project_stat.total_tokens += entry.input_tokens
-            + entry.output_tokens
-            + entry.cache_creation_tokens
-            + entry.cache_read_tokens;
-        project_stat.session_count += 1;
-        if entry.timestamp > project_stat.last_used {
-            project_stat.last_used = entry.timestamp.clone();
-        }
+            session_count: 0,
+        })
+        .collect();
+    let mut stmt = conn
+        .prepare(&format!(
+            "{}
+             SELECT run_id IS NOT NULL AS from_agent, SUM(cost),
+                    SUM(input_tokens + output_tokens + cache_creation_tokens + cache_read_tokens),
+                    COUNT(DISTINCT session_id)
+             FROM attributed GROUP BY from_agent",
+            attributed_usage(filter)
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt.query(params).map_err(|e| e.to_string())?;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let from_agent: bool = row.get(0).map_err(|e| e.to_string())?;
+        let usage = &mut by_source[from_agent as usize];
+        usage.total_cost = row.get(1).map_err(|e| e.to_string())?;
+        usage.total_tokens = row.get::<_, i64>(2).map_err(|e| e.to_string())? as u64;
+        usage.session_count = row.get::<_, i64>(3).map_err(|e| e.to_string())? as u64;
     }
 
-    let total_tokens = total_input_tokens
-        + total_output_tokens
-        + total_cache_creation_tokens
-        + total_cache_read_tokens;
-    let total_sessions = filtered_entries.len() as u64;
-
-    // Convert hashmaps to sorted vectors
-    let mut by_model: Vec<ModelUsage> = model_stats.into_values().collect();
-    by_model.sort_by(|a, b| b.total_cost.partial_cmp(&a.total_cost).unwrap());
-
-    let mut by_date: Vec<DailyUsage> = daily_stats.into_values().collect();
-    by_date.sort_by(|a, b| b.date.cmp(&a.date));
-
-    let mut by_project: Vec<ProjectUsage> = project_stats.into_values().collect();
-    by_project.sort_by(|a, b| b.total_cost.partial_cmp(&a.total_cost).unwrap());
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT DISTINCT model FROM usage_entries WHERE priced = 0 AND {} ORDER BY model",
+            filter
+        ))
+        .map_err(|e| e.to_string())?;
+    let unknown_models = stmt
+        .query_map(params, |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| e.to_string())?;
 
     Ok(UsageStats {
         total_cost,
-        total_tokens,
+        total_tokens: total_input_tokens
+            + total_output_tokens
+            + total_cache_creation_tokens
+            + total_cache_read_tokens,
         total_input_tokens,
         total_output_tokens,
         total_cache_creation_tokens,
@@ -500,270 +341,271 @@ pub fn get_usage_stats(days: Option<u32>) -> Result<UsageStats, String> {
         by_model,
         by_date,
         by_project,
+        by_agent,
+        by_source,
+        unknown_models,
+        pricing_version,
     })
 }
 
-#[command]
-pub fn get_usage_by_date_range(start_date: String, end_date: String) -> Result<UsageStats, String> {
-    let claude_path = dirs::home_dir()
-        .ok_or("Failed to get home directory")?
-        .join(".claude");
-
-    let all_entries = get_all_usage_entries(&claude_path);
-
-    // Parse dates
-    let start = NaiveDate::parse_from_str(&start_date, "%Y-%m-%d").or_else(|_| {
-        // Try parsing ISO datetime format
-        DateTime::parse_from_rfc3339(&start_date)
-            .map(|dt| dt.naive_local().date())
-            .map_err(|e| format!("Invalid start date: {}", e))
-    })?;
-    let end = NaiveDate::parse_from_str(&end_date, "%Y-%m-%d").or_else(|_| {
+/// Parse a `YYYY-MM-DD` date or an RFC 3339 timestamp into a date
+pub(crate) fn parse_date_arg(value: &str, name: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").or_else(|_| {
         // Try parsing ISO datetime format
-        DateTime::parse_from_rfc3339(&end_date)
+        DateTime::parse_from_rfc3339(value)
             .map(|dt| dt.naive_local().date())
-            .map_err(|e| format!("Invalid end date: {}", e))
-    })?;
-
-    // Filter entries by date range
-    let filtered_entries: Vec<_> = all_entries
-        .into_iter()
-        .filter(|e| {
-            if let Ok(dt) = DateTime::parse_from_rfc3339(&e.timestamp) {
-                let date = dt.naive_local().date();
-                date >= start && date <= end
-            } else {
-                false
-            }
+            .map_err(|e| format!("Invalid {} date: {}", name, e))
+    })
+}
+
+/// Usage of each agent run since `cutoff`, optionally for one agent only
+fn query_agent_run_usage(
+    conn: &Connection,
+    agent_id: Option<i64>,
+    cutoff: Option<String>,
+) -> Result<Vec<AgentRunUsage>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{}
+             SELECT r.id, r.agent_id, r.agent_name, r.agent_icon, r.task, r.model, r.status,
+                    r.project_path, r.session_id, r.created_at,
+                    SUM(attributed.input_tokens), SUM(attributed.output_tokens),
+                    SUM(attributed.cache_creation_tokens), SUM(attributed.cache_read_tokens),
+                    SUM(attributed.cost)
+             FROM attributed
+             JOIN agent_runs r ON r.id = attributed.run_id
+             WHERE ?2 IS NULL OR r.agent_id = ?2
+             GROUP BY r.id ORDER BY r.created_at DESC, r.id DESC",
+            attributed_usage("?1 IS NULL OR substr(timestamp, 1, 10) >= ?1")
+        ))
+        .map_err(|e| e.to_string())?;
+    let runs = stmt
+        .query_map(params![cutoff, agent_id], |row| {
+            let input_tokens = row.get::<_, i64>(10)? as u64;
+            let output_tokens = row.get::<_, i64>(11)? as u64;
+            let cache_creation_tokens = row.get::<_, i64>(12)? as u64;
+            let cache_read_tokens = row.get::<_, i64>(13)? as u64;
+            Ok(AgentRunUsage {
+                run_id: row.get(0)?,
+                agent_id: row.get(1)?,
+                agent_name: row.get(2)?,
+                agent_icon: row.get(3)?,
+                task: row.get(4)?,
+                model: row.get(5)?,
+                status: row.get(6)?,
+                project_path: row.get(7)?,
+                session_id: row.get(8)?,
+                created_at: row.get(9)?,
+                input_tokens,
+                output_tokens,
+                cache_creation_tokens,
+                cache_read_tokens,
+                total_tokens: input_tokens + output_tokens + cache_creation_tokens + cache_read_tokens,
+                total_cost: row.get(14)?,
+            })
         })
-        .collect();
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
 
-    if filtered_entries.is_empty() {
-        return Ok(UsageStats {
-            total_cost: 0.0,
-            total_tokens: 0,
-            total_input_tokens: 0,
-            total_output_tokens: 0,
-            total_cache_creation_tokens: 0,
-            total_cache_read_tokens: 0,
-            total_sessions: 0,
-            by_model: vec![],
-            by_date: vec![],
-            by_project: vec![],
-        });
-    }
+    Ok(runs)
+}
 
-    // Calculate aggregated stats (same logic as get_usage_stats)
-    let mut total_cost = 0.0;
-    let mut total_input_tokens = 0u64;
-    let mut total_output_tokens = 0u64;
-    let mut total_cache_creation_tokens = 0u64;
-    let mut total_cache_read_tokens = 0u64;
-
-    let mut model_stats: HashMap<String, ModelUsage> = HashMap::new();
-    let mut daily_stats: HashMap<String, DailyUsage> = HashMap::new();
-    let mut project_stats: HashMap<String, ProjectUsage> = HashMap::new();
-
-    for entry in &filtered_entries {
-        // Update totals
-        total_cost += entry.cost;
-        total_input_tokens += entry.input_tokens;
-        total_output_tokens += entry.output_tokens;
-        total_cache_creation_tokens += entry.cache_creation_tokens;
-        total_cache_read_tokens += entry.cache_read_tokens;
-
-        // Update model stats
-        let model_stat = model_stats
-            .entry(entry.model.clone())
-            .or_insert(ModelUsage {
-                model: entry.model.clone(),
-                total_cost: 0.0,
-                total_tokens: 0,
-                input_tokens: 0,
-                output_tokens: 0,
-                cache_creation_tokens: 0,
-                cache_read_tokens: 0,
-                session_count: 0,
-            });
-        model_stat.total_cost += entry.cost;
-        model_stat.input_tokens += entry.input_tokens;
-        model_stat.output_tokens += entry.output_tokens;
-        model_stat.cache_creation_tokens += entry.cache_creation_tokens;
-        model_stat.cache_read_tokens += entry.cache_read_tokens;
-        model_stat.total_tokens = model_stat.input_tokens + model_stat.output_tokens;
-        model_stat.session_count += 1;
-
-        // Update daily stats
-        let date = entry
-            .timestamp
-            .split('T')
-            .next()
-            .unwrap_or(&entry.timestamp)
-            .to_string();
-        let daily_stat = daily_stats.entry(date.clone()).or_insert(DailyUsage {
-            date,
-            total_cost: 0.0,
-            total_tokens: 0,
-            models_used: vec![],
-        });
-        daily_stat.total_cost += entry.cost;
-        daily_stat.total_tokens += entry.input_tokens
-            + entry.output_tokens
-            + entry.cache_creation_tokens
-            + entry.cache_read_tokens;
-        if !daily_stat.models_used.contains(&entry.model) {
-            daily_stat.models_used.push(entry.model.clone());
-        }
+#[command]
+pub async fn get_usage_stats(db: State<'_, AgentDb>, days: Option<u32>) -> Result<UsageStats, String> {
+    let conn = indexed_usage_db(&db).await?;
 
-        // Update project stats
-        let project_stat =
-            project_stats
-                .entry(entry.project_path.clone())
-                .or_insert(ProjectUsage {
-                    project_path: entry.project_path.clone(),
-                    project_name: entry
-                        .project_path
-                        .split('/')
-                        .last()
-                        .unwrap_or(&entry.project_path)
-                        .to_string(),
-                    total_cost: 0.0,
-                    total_tokens: 0,
-                    session_count: 0,
-                    last_used: entry.timestamp.clone(),
-                });
-        project_stat.total_cost += entry.cost;
-        project_stat.total_tokens += entry.input_tokens
-            + entry.output_tokens
-            + entry.cache_creation_tokens
-            + entry.cache_read_tokens;
-        project_stat.session_count += 1;
-        if entry.timestamp > project_stat.last_used {
-            project_stat.last_used = entry.timestamp.clone();
+    // Filter by days if specified
+    match days {
+        Some(days) => {
+            let cutoff = Local::now().naive_local().date() - chrono::Duration::days(days as i64);
+            query_usage_stats(
+                &conn,
+                "substr(timestamp, 1, 10) >= ?1",
+                &[&cutoff.format("%Y-%m-%d").to_string()],
+            )
         }
+        None => query_usage_stats(&conn, "1", &[]),
     }
+}
 
-    let total_tokens = total_input_tokens
-        + total_output_tokens
-        + total_cache_creation_tokens
-        + total_cache_read_tokens;
-    let total_sessions = filtered_entries.len() as u64;
-
-    // Convert hashmaps to sorted vectors
-    let mut by_model: Vec<ModelUsage> = model_stats.into_values().collect();
-    by_model.sort_by(|a, b| b.total_cost.partial_cmp(&a.total_cost).unwrap());
-
-    let mut by_date: Vec<DailyUsage> = daily_stats.into_values().collect();
-    by_date.sort_by(|a, b| b.date.cmp(&a.date));
-
-    let mut by_project: Vec<ProjectUsage> = project_stats.into_values().collect();
-    by_project.sort_by(|a, b| b.total_cost.partial_cmp(&a.total_cost).unwrap());
-
-    Ok(UsageStats {
-        total_cost,
-        total_tokens,
-        total_input_tokens,
-        total_output_tokens,
-        total_cache_creation_tokens,
-        total_cache_read_tokens,
-        total_sessions,
-        by_model,
-        by_date,
-        by_project,
-    })
+#[command]
+pub async fn get_usage_by_date_range(
+    db: State<'_, AgentDb>,
+    start_date: String,
+    end_date: String,
+) -> Result<UsageStats, String> {
+    let start = parse_date_arg(&start_date, "start")?;
+    let end = parse_date_arg(&end_date, "end")?;
+
+    let conn = indexed_usage_db(&db).await?;
+    query_usage_stats(
+        &conn,
+        "substr(timestamp, 1, 10) BETWEEN ?1 AND ?2",
+        &[
+            &start.format("%Y-%m-%d").to_string(),
+            &end.format("%Y-%m-%d").to_string(),
+        ],
+    )
 }
 
 #[command]
-pub fn get_usage_details(
+pub async fn get_usage_details(
+    db: State<'_, AgentDb>,
     project_path: Option<String>,
     date: Option<String>,
 ) -> Result<Vec<UsageEntry>, String> {
-    let claude_path = dirs::home_dir()
-        .ok_or("Failed to get home directory")?
-        .join(".claude");
-
-    let mut all_entries = get_all_usage_entries(&claude_path);
-
-    // Filter by project if specified
-    if let Some(project) = project_path {
-        all_entries.retain(|e| e.project_path == project);
-    }
-
-    // Filter by date if specified
-    if let Some(date) = date {
-        all_entries.retain(|e| e.timestamp.starts_with(&date));
-    }
+    let conn = indexed_usage_db(&db).await?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT timestamp, model, input_tokens, output_tokens, cache_creation_tokens, cache_read_tokens,
+                    cost, session_id, project_path
+             FROM usage_entries
+             WHERE (?1 IS NULL OR project_path = ?1)
+               AND (?2 IS NULL OR substr(timestamp, 1, length(?2)) = ?2)
+             ORDER BY timestamp, id",
+        )
+        .map_err(|e| e.to_string())?;
+    let entries = stmt
+        .query_map(params![project_path, date], |row| {
+            Ok(UsageEntry {
+                timestamp: row.get(0)?,
+                model: row.get(1)?,
+                input_tokens: row.get::<_, i64>(2)? as u64,
+                output_tokens: row.get::<_, i64>(3)? as u64,
+                cache_creation_tokens: row.get::<_, i64>(4)? as u64,
+                cache_read_tokens: row.get::<_, i64>(5)? as u64,
+                cost: row.get(6)?,
+                session_id: row.get(7)?,
+                project_path: row.get(8)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
 
-    Ok(all_entries)
+    Ok(entries)
 }
 
 #[command]
-pub fn get_session_stats(
+pub async fn get_session_stats(
+    db: State<'_, AgentDb>,
     since: Option<String>,
     until: Option<String>,
     order: Option<String>,
 ) -> Result<Vec<ProjectUsage>, String> {
-    let claude_path = dirs::home_dir()
-        .ok_or("Failed to get home directory")?
-        .join(".claude");
-
-    let all_entries = get_all_usage_entries(&claude_path);
-
-    let since_date = since.and_then(|s| NaiveDate::parse_from_str(&s, "%Y%m%d").ok());
-    let until_date = until.and_then(|s| NaiveDate::parse_from_str(&s, "%Y%m%d").ok());
-
-    let filtered_entries: Vec<_> = all_entries
-        .into_iter()
-        .filter(|e| {
-            if let Ok(dt) = DateTime::parse_from_rfc3339(&e.timestamp) {
-                let date = dt.date_naive();
-                let is_after_since = since_date.map_or(true, |s| date >= s);
-                let is_before_until = until_date.map_or(true, |u| date <= u);
-                is_after_since && is_before_until
-            } else {
-                false
-            }
+    let as_date = |value: Option<String>| {
+        value
+            .and_then(|s| NaiveDate::parse_from_str(&s, "%Y%m%d").ok())
+            .map(|date| date.format("%Y-%m-%d").to_string())
+    };
+    let since_date = as_date(since);
+    let until_date = as_date(until);
+
+    // Sort by last_used date, descending by default
+    let direction = if order.as_deref() == Some("asc") { "ASC" } else { "DESC" };
+
+    let conn = indexed_usage_db(&db).await?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT project_path, session_id, SUM(cost),
+                    SUM(input_tokens + output_tokens + cache_creation_tokens + cache_read_tokens),
+                    COUNT(*), MAX(timestamp) AS last_used
+             FROM usage_entries
+             WHERE (?1 IS NULL OR substr(timestamp, 1, 10) >= ?1)
+               AND (?2 IS NULL OR substr(timestamp, 1, 10) <= ?2)
+             GROUP BY project_path, session_id
+             ORDER BY last_used {}",
+            direction
+        ))
+        .map_err(|e| e.to_string())?;
+    let by_session = stmt
+        .query_map(params![since_date, until_date], |row| {
+            Ok(ProjectUsage {
+                project_path: row.get(0)?,
+                // Using session_id as project_name for session view
+                project_name: row.get(1)?,
+                total_cost: row.get(2)?,
+                total_tokens: row.get::<_, i64>(3)? as u64,
+                // In this context, this counts entries per session
+                session_count: row.get::<_, i64>(4)? as u64,
+                last_used: row.get(5)?,
+            })
         })
-        .collect();
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
 
-    let mut session_stats: HashMap<String, ProjectUsage> = HashMap::new();
-    for entry in &filtered_entries {
-        let session_key = format!("{}/{}", entry.project_path, entry.session_id);
-        let project_stat = session_stats
-            .entry(session_key)
-            .or_insert_with(|| ProjectUsage {
-                project_path: entry.project_path.clone(),
-                project_name: entry.session_id.clone(), // Using session_id as project_name for session view
-                total_cost: 0.0,
-                total_tokens: 0,
-                session_count: 0, // In this context, this will count entries per session
-                last_used: " ".to_string(),
-            });
-
-        project_stat.total_cost += entry.cost;
-        project_stat.total_tokens += entry.input_tokens
-            + entry.output_tokens
-            + entry.cache_creation_tokens
-            + entry.cache_read_tokens;
-        project_stat.session_count += 1;
-        if entry.timestamp > project_stat.last_used {
-            project_stat.last_used = entry.timestamp.clone();
-        }
-    }
+    Ok(by_session)
+}
 
-    let mut by_session: Vec<ProjectUsage> = session_stats.into_values().collect();
+/// Cost of each agent run with usage, latest first
+#[command]
+pub async fn get_agent_run_usage(
+    db: State<'_, AgentDb>,
+    agent_id: Option<i64>,
+    days: Option<u32>,
+) -> Result<Vec<AgentRunUsage>, String> {
+    let cutoff = days.map(|days| {
+        (Local::now().naive_local().date() - chrono::Duration::days(days as i64))
+            .format("%Y-%m-%d")
+            .to_string()
+    });
+
+    let conn = indexed_usage_db(&db).await?;
+    query_agent_run_usage(&conn, agent_id, cutoff)
+}
 
-    // Sort by last_used date
-    if let Some(order_str) = order {
-        if order_str == "asc" {
-            by_session.sort_by(|a, b| a.last_used.cmp(&b.last_used));
-        } else {
-            by_session.sort_by(|a, b| b.last_used.cmp(&a.last_used));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::usage_index::init_usage_index_tables;
+
+    #[test]
+    fn test_usage_is_attributed_to_agent_runs() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE app_settings (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+             CREATE TABLE agents (id INTEGER PRIMARY KEY, name TEXT NOT NULL, icon TEXT NOT NULL);
+             CREATE TABLE agent_runs (id INTEGER PRIMARY KEY, agent_id INTEGER NOT NULL, agent_name TEXT NOT NULL,
+                 agent_icon TEXT NOT NULL, task TEXT NOT NULL, model TEXT NOT NULL, project_path TEXT NOT NULL,
+                 session_id TEXT NOT NULL, status TEXT NOT NULL, created_at TEXT NOT NULL);
+             INSERT INTO agents VALUES (7, 'Reviewer', 'bot');
+             INSERT INTO agent_runs VALUES (1, 7, 'Reviewer', 'bot', 'review', 'sonnet', '/work/app', 's1', 'completed', '2025-03-01 09:00:00');
+             -- A later run resuming the same session
+             INSERT INTO agent_runs VALUES (2, 7, 'Reviewer', 'bot', 'again', 'sonnet', '/work/app', 's1', 'completed', '2025-03-01 12:00:00');
+             INSERT INTO agent_runs VALUES (3, 7, 'Reviewer', 'bot', 'pending', 'sonnet', '/work/app', '', 'pending', '2025-03-01 12:00:00');",
+        )
+        .unwrap();
+        init_usage_index_tables(&conn).unwrap();
+        for (timestamp, session, cost) in [
+            ("2025-03-01T08:00:00.000Z", "m1", 1.0),
+            ("2025-03-01T10:00:00.000Z", "s1", 2.0),
+            ("2025-03-01T13:00:00.000Z", "s1", 3.0),
+        ] {
+            conn.execute(
+                "INSERT INTO usage_entries (file_path, timestamp, model, input_tokens, output_tokens, cost, session_id, project_path)
+                 VALUES ('f', ?1, 'claude-sonnet-4', 10, 10, ?2, ?3, '/work/app')",
+                params![timestamp, cost, session],
+            )
+            .unwrap();
         }
-    } else {
-        // Default to descending
-        by_session.sort_by(|a, b| b.last_used.cmp(&a.last_used));
-    }
 
-    Ok(by_session)
+        let stats = query_usage_stats(&conn, "1", &[]).unwrap();
+        assert_eq!(stats.by_agent.len(), 1);
+        assert_eq!((stats.by_agent[0].total_cost, stats.by_agent[0].run_count), (5.0, 2));
+        assert_eq!(stats.by_agent[0].avg_cost_per_run, 2.5);
+        let by_source: Vec<(&str, f64, u64)> = stats
+            .by_source
+            .iter()
+            .map(|usage| (usage.source.as_str(), usage.total_cost, usage.session_count))
+            .collect();
+        assert_eq!(by_source, vec![("manual", 1.0, 1), ("agent", 5.0, 1)]);
+
+        let runs = query_agent_run_usage(&conn, Some(7), None).unwrap();
+        let costs: Vec<(i64, f64)> = runs.iter().map(|run| (run.run_id, run.total_cost)).collect();
+        assert_eq!(costs, vec![(2, 3.0), (1, 2.0)]);
+        assert!(query_agent_run_usage(&conn, Some(8), None).unwrap().is_empty());
+    }
 }
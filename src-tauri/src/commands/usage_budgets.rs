@@ -0,0 +1,578 @@
+use chrono::{DateTime, Datelike, Local, SecondsFormat, Utc};
+use log::{info, warn};
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::commands::agents::AgentDb;
+use crate::commands::usage_index::refresh_usage_index;
+
+/// How often budgets are checked against the spend while the app runs
+const BUDGET_CHECK_INTERVAL_SECS: u64 = 5 * 60;
+
+/// `app_settings` key holding when the override of hard budgets ends
+const BUDGET_OVERRIDE_SETTING_KEY: &str = "budget_override_until";
+
+/// Percentages of a budget that trigger a notification by default
+const DEFAULT_BUDGET_THRESHOLDS: &[u32] = &[50, 80, 100];
+
+/// The period a budget's spend is counted over, in local time
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Daily,
+    Monthly,
+}
+
+impl BudgetPeriod {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BudgetPeriod::Daily => "daily",
+            BudgetPeriod::Monthly => "monthly",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "daily" => BudgetPeriod::Daily,
+            _ => BudgetPeriod::Monthly,
+        }
+    }
+
+    /// Start of the period containing `now`
+    fn start(&self, now: DateTime<Local>) -> DateTime<Utc> {
+        let day = match self {
+            BudgetPeriod::Daily => now.date_naive(),
+            BudgetPeriod::Monthly => now.date_naive().with_day(1).unwrap_or(now.date_naive()),
+        };
+        day.and_hms_opt(0, 0, 0)
+            .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+            .unwrap_or(now)
+            .with_timezone(&Utc)
+    }
+}
+
+fn default_thresholds() -> Vec<u32> {
+    DEFAULT_BUDGET_THRESHOLDS.to_vec()
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A spend limit over a day or month, for all usage or one project's
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Budget {
+    pub id: Option<i64>,
+    /// Project the budget applies to, or all projects when unset
+    pub project_path: Option<String>,
+    pub period: BudgetPeriod,
+    pub limit_usd: f64,
+    /// Percentages of the limit at which to notify
+    #[serde(default = "default_thresholds")]
+    pub thresholds: Vec<u32>,
+    /// Block new Claude Code and agent launches once the limit is reached
+    #[serde(default)]
+    pub hard_limit: bool,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+/// A budget with its spend over the current period
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BudgetStatus {
+    pub budget: Budget,
+    /// Start of the current period, in UTC
+    pub period_start: String,
+    pub spent_usd: f64,
+    pub percent_used: f64,
+    pub exceeded: bool,
+    /// Whether the budget currently blocks launches
+    pub blocking: bool,
+}
+
+/// A threshold of a budget crossed for the first time in a period
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BudgetAlert {
+    pub budget_id: i64,
+    pub project_path: Option<String>,
+    pub period: BudgetPeriod,
+    pub threshold: u32,
+    pub spent_usd: f64,
+    pub limit_usd: f64,
+}
+
+/// Create the budget tables
+pub fn init_budget_tables(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS usage_budgets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_path TEXT,
+            period TEXT NOT NULL,
+            limit_usd REAL NOT NULL,
+            thresholds TEXT NOT NULL DEFAULT '[50,80,100]',
+            hard_limit BOOLEAN NOT NULL DEFAULT 0,
+            enabled BOOLEAN NOT NULL DEFAULT 1,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Thresholds already notified, so each fires once per period
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS usage_budget_alerts (
+            budget_id INTEGER NOT NULL,
+            period_start TEXT NOT NULL,
+            threshold INTEGER NOT NULL,
+            notified_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (budget_id, period_start, threshold),
+            FOREIGN KEY (budget_id) REFERENCES usage_budgets(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Format an instant the way session timestamps are, so they compare as strings
+fn session_timestamp(instant: DateTime<Utc>) -> String {
+    instant.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn normalize_project_path(path: &str) -> String {
+    path.trim_end_matches(['/', '\\']).to_string()
+}
+
+const BUDGET_COLUMNS: &str =
+    "id, project_path, period, limit_usd, thresholds, hard_limit, enabled, created_at, updated_at";
+
+fn map_budget_row(row: &rusqlite::Row) -> rusqlite::Result<Budget> {
+    let thresholds: String = row.get(4)?;
+    Ok(Budget {
+        id: row.get(0)?,
+        project_path: row.get(1)?,
+        period: BudgetPeriod::parse(&row.get::<_, String>(2)?),
+        limit_usd: row.get(3)?,
+        thresholds: serde_json::from_str(&thresholds).unwrap_or_else(|_| default_thresholds()),
+        hard_limit: row.get(5)?,
+        enabled: row.get(6)?,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+fn load_budgets(conn: &Connection, enabled_only: bool) -> Result<Vec<Budget>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM usage_budgets WHERE enabled = 1 OR ?1 = 0 ORDER BY project_path IS NOT NULL, project_path, period",
+            BUDGET_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let budgets = stmt
+        .query_map(params![enabled_only], map_budget_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(budgets)
+}
+
+/// When the override of hard budgets ends, if one is active
+fn load_override_until(conn: &Connection, now: DateTime<Utc>) -> Result<Option<String>, String> {
+    let until: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            params![BUDGET_OVERRIDE_SETTING_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to load budget override: {}", e))?;
+    Ok(until.filter(|until| {
+        DateTime::parse_from_rfc3339(until).is_ok_and(|until| until.with_timezone(&Utc) > now)
+    }))
+}
+
+/// The spend of each budget over its current period, from the usage index
+fn compute_statuses(
+    conn: &Connection,
+    budgets: Vec<Budget>,
+    now: DateTime<Local>,
+    overridden: bool,
+) -> Result<Vec<BudgetStatus>, String> {
+    budgets
+        .into_iter()
+        .map(|budget| {
+            let period_start = session_timestamp(budget.period.start(now));
+            let spent_usd: f64 = conn
+                .query_row(
+                    "SELECT COALESCE(SUM(cost), 0) FROM usage_entries
+                     WHERE timestamp >= ?1 AND (?2 IS NULL OR rtrim(project_path, '/\\') = ?2)",
+                    params![period_start, budget.project_path],
+                    |row| row.get(0),
+                )
+                .map_err(|e| e.to_string())?;
+            let percent_used = if budget.limit_usd > 0.0 {
+                spent_usd / budget.limit_usd * 100.0
+            } else {
+                0.0
+            };
+            let exceeded = spent_usd >= budget.limit_usd;
+            Ok(BudgetStatus {
+                blocking: exceeded && budget.hard_limit && budget.enabled && !overridden,
+                budget,
+                period_start,
+                spent_usd,
+                percent_used,
+                exceeded,
+            })
+        })
+        .collect()
+}
+
+/// Record the thresholds crossed for the first time in their period
+fn take_new_alerts(conn: &Connection, statuses: &[BudgetStatus]) -> Result<Vec<BudgetAlert>, String> {
+    let mut alerts = Vec::new();
+    for status in statuses {
+        let Some(budget_id) = status.budget.id else {
+            continue;
+        };
+        // Only the highest threshold crossed is worth a notification
+        let crossed = status
+            .budget
+            .thresholds
+            .iter()
+            .filter(|threshold| status.percent_used >= **threshold as f64)
+            .max();
+        let Some(threshold) = crossed else {
+            continue;
+        };
+        let inserted = conn
+            .execute(
+                "INSERT OR IGNORE INTO usage_budget_alerts (budget_id, period_start, threshold) VALUES (?1, ?2, ?3)",
+                params![budget_id, status.period_start, threshold],
+            )
+            .map_err(|e| e.to_string())?;
+        // Lower thresholds crossed along the way don't fire later
+        for lower in status.budget.thresholds.iter().filter(|t| *t < threshold) {
+            conn.execute(
+                "INSERT OR IGNORE INTO usage_budget_alerts (budget_id, period_start, threshold) VALUES (?1, ?2, ?3)",
+                params![budget_id, status.period_start, lower],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        if inserted > 0 {
+            alerts.push(BudgetAlert {
+                budget_id,
+                project_path: status.budget.project_path.clone(),
+                period: status.budget.period,
+                threshold: *threshold,
+                spent_usd: status.spent_usd,
+                limit_usd: status.budget.limit_usd,
+            });
+        }
+    }
+    Ok(alerts)
+}
+
+/// How a budget is named in messages, such as "The daily budget of app"
+fn budget_scope(project_path: Option<&str>, period: BudgetPeriod) -> String {
+    match project_path {
+        Some(path) => {
+            let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+            format!("The {} budget of {}", period.as_str(), name)
+        }
+        None => format!("The {} budget", period.as_str()),
+    }
+}
+
+/// The enabled hard budgets covering `project_path`, none while hard budgets are overridden
+fn launch_budgets(conn: &Connection, project_path: &str) -> Result<Vec<Budget>, String> {
+    if load_override_until(conn, Utc::now())?.is_some() {
+        return Ok(Vec::new());
+    }
+    let project_path = normalize_project_path(project_path);
+    Ok(load_budgets(conn, true)?
+        .into_iter()
+        .filter(|budget| budget.hard_limit)
+        .filter(|budget| budget.project_path.as_ref().is_none_or(|path| *path == project_path))
+        .collect())
+}
+
+/// Refuse to launch Claude Code or an agent in `project_path` while a hard budget
+/// covering it is exceeded, unless hard budgets are overridden
+pub async fn check_launch_budget(db: &AgentDb, project_path: &str) -> Result<(), String> {
+    let hard_budgets = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        launch_budgets(&conn, project_path)?
+    };
+    if hard_budgets.is_empty() {
+        return Ok(());
+    }
+
+    refresh_usage_index(db).await?;
+    let statuses = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        compute_statuses(&conn, hard_budgets, Local::now(), false)?
+    };
+    match statuses.iter().find(|status| status.blocking) {
+        Some(status) => Err(format!(
+            "{} is exhausted: ${:.2} spent of ${:.2}. Override the budget to launch anyway.",
+            budget_scope(status.budget.project_path.as_deref(), status.budget.period),
+            status.spent_usd,
+            status.budget.limit_usd
+        )),
+        None => Ok(()),
+    }
+}
+
+/// `check_launch_budget` for launches that only have the app handle
+pub async fn ensure_launch_budget(app: &AppHandle, project_path: &str) -> Result<(), String> {
+    check_launch_budget(&app.state::<AgentDb>(), project_path).await
+}
+
+/// Check budgets against the spend and return the thresholds newly crossed
+async fn check_budgets(db: &AgentDb) -> Result<Vec<BudgetAlert>, String> {
+    let budgets = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        load_budgets(&conn, true)?
+    };
+    if budgets.is_empty() {
+        return Ok(Vec::new());
+    }
+    refresh_usage_index(db).await?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let statuses = compute_statuses(&conn, budgets, Local::now(), false)?;
+    take_new_alerts(&conn, &statuses)
+}
+
+/// Periodically check budgets and notify when their thresholds are crossed
+pub fn start_budget_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(BUDGET_CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let alerts = match check_budgets(&app.state::<AgentDb>()).await {
+                Ok(alerts) => alerts,
+                Err(e) => {
+                    warn!("Failed to check budgets: {}", e);
+                    continue;
+                }
+            };
+
+            for alert in alerts {
+                let body = format!(
+                    "{} is {}% used: ${:.2} of ${:.2}",
+                    budget_scope(alert.project_path.as_deref(), alert.period),
+                    alert.threshold,
+                    alert.spent_usd,
+                    alert.limit_usd
+                );
+                info!("Budget alert: {}", body);
+                if let Err(e) = app.notification().builder().title("Claude spend").body(&body).show() {
+                    warn!("Failed to show budget notification: {}", e);
+                }
+                let _ = app.emit("budget-alert", &alert);
+            }
+        }
+    });
+}
+
+/// List all budgets, global ones first
+#[tauri::command]
+pub async fn list_budgets(db: State<'_, AgentDb>) -> Result<Vec<Budget>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_budgets(&conn, false)
+}
+
+/// Create a budget, or update the one with the same ID
+#[tauri::command]
+pub async fn save_budget(db: State<'_, AgentDb>, mut budget: Budget) -> Result<Budget, String> {
+    if !budget.limit_usd.is_finite() || budget.limit_usd <= 0.0 {
+        return Err("The budget limit must be greater than zero".to_string());
+    }
+    budget.thresholds.sort_unstable();
+    budget.thresholds.dedup();
+    if budget.thresholds.iter().any(|threshold| *threshold == 0 || *threshold > 1000) {
+        return Err("Thresholds must be between 1% and 1000%".to_string());
+    }
+    budget.project_path = budget
+        .project_path
+        .as_deref()
+        .map(normalize_project_path)
+        .filter(|path| !path.is_empty());
+    let thresholds = serde_json::to_string(&budget.thresholds).map_err(|e| e.to_string())?;
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let id = match budget.id {
+        Some(id) => {
+            let updated = conn
+                .execute(
+                    "UPDATE usage_budgets
+                     SET project_path = ?1, period = ?2, limit_usd = ?3, thresholds = ?4, hard_limit = ?5, enabled = ?6,
+                         updated_at = CURRENT_TIMESTAMP
+                     WHERE id = ?7",
+                    params![
+                        budget.project_path,
+                        budget.period.as_str(),
+                        budget.limit_usd,
+                        thresholds,
+                        budget.hard_limit,
+                        budget.enabled,
+                        id
+                    ],
+                )
+                .map_err(|e| format!("Failed to update budget: {}", e))?;
+            if updated == 0 {
+                return Err(format!("Budget not found: {}", id));
+            }
+            // Thresholds are counted afresh against the new limit
+            conn.execute("DELETE FROM usage_budget_alerts WHERE budget_id = ?1", params![id])
+                .map_err(|e| e.to_string())?;
+            id
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO usage_budgets (project_path, period, limit_usd, thresholds, hard_limit, enabled)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    budget.project_path,
+                    budget.period.as_str(),
+                    budget.limit_usd,
+                    thresholds,
+                    budget.hard_limit,
+                    budget.enabled
+                ],
+            )
+            .map_err(|e| format!("Failed to create budget: {}", e))?;
+            conn.last_insert_rowid()
+        }
+    };
+
+    conn.query_row(
+        &format!("SELECT {} FROM usage_budgets WHERE id = ?1", BUDGET_COLUMNS),
+        params![id],
+        map_budget_row,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Delete a budget
+#[tauri::command]
+pub async fn delete_budget(db: State<'_, AgentDb>, id: i64) -> Result<(), String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM usage_budget_alerts WHERE budget_id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM usage_budgets WHERE id = ?1", params![id])
+        .map_err(|e| format!("Failed to delete budget: {}", e))?;
+    Ok(())
+}
+
+/// Get the spend of every budget over its current period
+#[tauri::command]
+pub async fn get_budget_statuses(db: State<'_, AgentDb>) -> Result<Vec<BudgetStatus>, String> {
+    refresh_usage_index(&db).await?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let overridden = load_override_until(&conn, Utc::now())?.is_some();
+    let budgets = load_budgets(&conn, false)?;
+    compute_statuses(&conn, budgets, Local::now(), overridden)
+}
+
+/// Get when the override of hard budgets ends, if one is active
+#[tauri::command]
+pub async fn get_budget_override(db: State<'_, AgentDb>) -> Result<Option<String>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_override_until(&conn, Utc::now())
+}
+
+/// Let launches through exceeded hard budgets for the next `hours`, or stop letting
+/// them through when `hours` is unset. Returns when the override ends.
+#[tauri::command]
+pub async fn set_budget_override(db: State<'_, AgentDb>, hours: Option<u32>) -> Result<Option<String>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let Some(hours) = hours.filter(|hours| *hours > 0) else {
+        conn.execute(
+            "DELETE FROM app_settings WHERE key = ?1",
+            params![BUDGET_OVERRIDE_SETTING_KEY],
+        )
+        .map_err(|e| e.to_string())?;
+        return Ok(None);
+    };
+
+    let until = (Utc::now() + chrono::Duration::hours(hours as i64)).to_rfc3339();
+    conn.execute(
+        "INSERT INTO app_settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = ?2",
+        params![BUDGET_OVERRIDE_SETTING_KEY, until],
+    )
+    .map_err(|e| format!("Failed to save budget override: {}", e))?;
+    info!("Hard budgets overridden until {}", until);
+    Ok(Some(until))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(project_path: Option<&str>, limit_usd: f64) -> Budget {
+        Budget {
+            id: Some(1),
+            project_path: project_path.map(String::from),
+            period: BudgetPeriod::Daily,
+            limit_usd,
+            thresholds: default_thresholds(),
+            hard_limit: true,
+            enabled: true,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_budget_statuses_and_alerts() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_budget_tables(&conn).unwrap();
+        conn.execute(
+            "CREATE TABLE usage_entries (timestamp TEXT, cost REAL, project_path TEXT)",
+            [],
+        )
+        .unwrap();
+        let now = Local::now();
+        let today = session_timestamp(now.with_timezone(&Utc));
+        let last_month = session_timestamp((now - chrono::Duration::days(40)).with_timezone(&Utc));
+        for (timestamp, cost, project) in [(&today, 6.0, "/work/app"), (&today, 2.0, "/work/other"), (&last_month, 50.0, "/work/app")] {
+            conn.execute(
+                "INSERT INTO usage_entries VALUES (?1, ?2, ?3)",
+                params![timestamp, cost, project],
+            )
+            .unwrap();
+        }
+
+        let statuses = compute_statuses(
+            &conn,
+            vec![budget(None, 10.0), budget(Some("/work/app"), 5.0)],
+            now,
+            false,
+        )
+        .unwrap();
+        assert_eq!(statuses[0].spent_usd, 8.0);
+        assert!(!statuses[0].blocking);
+        assert_eq!(statuses[1].spent_usd, 6.0);
+        assert!(statuses[1].blocking);
+
+        // Only the highest threshold crossed fires, and only once
+        conn.execute("INSERT INTO usage_budgets (id, period, limit_usd) VALUES (1, 'daily', 10)", [])
+            .unwrap();
+        let alerts = take_new_alerts(&conn, &statuses[..1]).unwrap();
+        assert_eq!(alerts.iter().map(|a| a.threshold).collect::<Vec<_>>(), vec![80]);
+        assert!(take_new_alerts(&conn, &statuses[..1]).unwrap().is_empty());
+
+        let overridden = compute_statuses(&conn, vec![budget(Some("/work/app"), 5.0)], now, true).unwrap();
+        assert!(overridden[0].exceeded && !overridden[0].blocking);
+    }
+}
@@ -0,0 +1,420 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::State;
+
+use crate::commands::agents::AgentDb;
+use crate::commands::usage::{indexed_usage_db, parse_date_arg};
+
+/// Columns of a raw usage export, in order. Append new columns at the end so
+/// spreadsheets built on earlier exports keep working
+const ENTRY_COLUMNS: &[&str] = &[
+    "timestamp",
+    "session_id",
+    "project_path",
+    "model",
+    "input_tokens",
+    "output_tokens",
+    "cache_creation_tokens",
+    "cache_read_tokens",
+    "total_tokens",
+    "cost_usd",
+    "cost_source",
+    "pricing_version",
+];
+
+/// Columns of an aggregated usage export, following the grouping's key columns
+const AGGREGATE_COLUMNS: &[&str] = &[
+    "entries",
+    "sessions",
+    "input_tokens",
+    "output_tokens",
+    "cache_creation_tokens",
+    "cache_read_tokens",
+    "total_tokens",
+    "cost_usd",
+    "pricing_version",
+];
+
+/// File format of a usage export
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageExportFormat {
+    Csv,
+    Json,
+}
+
+/// What usage entries are aggregated by
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageGrouping {
+    Day,
+    Model,
+    Project,
+    Session,
+}
+
+impl UsageGrouping {
+    /// Key columns of the grouping and the SQL expressions they're computed from
+    fn keys(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            UsageGrouping::Day => &[("date", "substr(timestamp, 1, 10)")],
+            UsageGrouping::Model => &[("model", "model")],
+            UsageGrouping::Project => &[("project_path", "project_path")],
+            UsageGrouping::Session => &[("session_id", "session_id"), ("project_path", "project_path")],
+        }
+    }
+}
+
+/// What a usage export contains
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UsageExportOptions {
+    pub format: UsageExportFormat,
+    /// Aggregate entries by this key; raw entries are exported when unset
+    #[serde(default)]
+    pub group_by: Option<UsageGrouping>,
+    /// First day to include, as `YYYY-MM-DD` or an RFC 3339 timestamp
+    #[serde(default)]
+    pub start_date: Option<String>,
+    /// Last day to include, as `YYYY-MM-DD` or an RFC 3339 timestamp
+    #[serde(default)]
+    pub end_date: Option<String>,
+}
+
+/// A usage entry as exported
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportedUsageEntry {
+    pub timestamp: String,
+    pub session_id: String,
+    pub project_path: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    pub cost_usd: f64,
+    /// `recorded` when the session recorded the cost, `priced` when it was computed
+    /// from the pricing table and `unpriced` when the model has no price
+    pub cost_source: String,
+    /// Version of the prices a computed cost was taken from
+    pub pricing_version: Option<String>,
+}
+
+/// Usage totals for one key of a grouping, as exported
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ExportedUsageAggregate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_path: Option<String>,
+    pub entries: u64,
+    pub sessions: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    pub cost_usd: f64,
+    /// Versions of the prices the computed costs were taken from, comma separated
+    pub pricing_version: Option<String>,
+}
+
+impl ExportedUsageAggregate {
+    fn key(&self, column: &str) -> Option<&str> {
+        match column {
+            "date" => self.date.as_deref(),
+            "model" => self.model.as_deref(),
+            "session_id" => self.session_id.as_deref(),
+            "project_path" => self.project_path.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_record<S: AsRef<str>>(fields: &[S]) -> String {
+    let fields: Vec<String> = fields.iter().map(|field| csv_field(field.as_ref())).collect();
+    format!("{}\r\n", fields.join(","))
+}
+
+fn format_cost(cost: f64) -> String {
+    format!("{:.6}", cost)
+}
+
+fn query_entries(conn: &Connection, start: Option<&str>, end: Option<&str>) -> Result<Vec<ExportedUsageEntry>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT timestamp, session_id, project_path, model, input_tokens, output_tokens,
+                    cache_creation_tokens, cache_read_tokens, cost, cost_recorded, priced, pricing_version
+             FROM usage_entries
+             WHERE (?1 IS NULL OR substr(timestamp, 1, 10) >= ?1)
+               AND (?2 IS NULL OR substr(timestamp, 1, 10) <= ?2)
+             ORDER BY timestamp, id",
+        )
+        .map_err(|e| e.to_string())?;
+    let entries = stmt
+        .query_map(params![start, end], |row| {
+            let input_tokens = row.get::<_, i64>(4)? as u64;
+            let output_tokens = row.get::<_, i64>(5)? as u64;
+            let cache_creation_tokens = row.get::<_, i64>(6)? as u64;
+            let cache_read_tokens = row.get::<_, i64>(7)? as u64;
+            let recorded: bool = row.get(9)?;
+            let priced: bool = row.get(10)?;
+            Ok(ExportedUsageEntry {
+                timestamp: row.get(0)?,
+                session_id: row.get(1)?,
+                project_path: row.get(2)?,
+                model: row.get(3)?,
+                input_tokens,
+                output_tokens,
+                cache_creation_tokens,
+                cache_read_tokens,
+                total_tokens: input_tokens + output_tokens + cache_creation_tokens + cache_read_tokens,
+                cost_usd: row.get(8)?,
+                cost_source: match (recorded, priced) {
+                    (true, _) => "recorded",
+                    (false, true) => "priced",
+                    (false, false) => "unpriced",
+                }
+                .to_string(),
+                pricing_version: if recorded { None } else { row.get(11)? },
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(entries)
+}
+
+fn query_aggregates(
+    conn: &Connection,
+    group_by: UsageGrouping,
+    start: Option<&str>,
+    end: Option<&str>,
+) -> Result<Vec<ExportedUsageAggregate>, String> {
+    let keys = group_by.keys();
+    let key_exprs = keys
+        .iter()
+        .map(|(column, expr)| format!("{} AS {}", expr, column))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let key_columns = keys.iter().map(|(column, _)| *column).collect::<Vec<_>>().join(", ");
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {}, COUNT(*), COUNT(DISTINCT session_id), SUM(input_tokens), SUM(output_tokens),
+                    SUM(cache_creation_tokens), SUM(cache_read_tokens), SUM(cost),
+                    group_concat(DISTINCT CASE WHEN cost_recorded = 0 THEN pricing_version END)
+             FROM usage_entries
+             WHERE (?1 IS NULL OR substr(timestamp, 1, 10) >= ?1)
+               AND (?2 IS NULL OR substr(timestamp, 1, 10) <= ?2)
+             GROUP BY {} ORDER BY {}",
+            key_exprs, key_columns, key_columns
+        ))
+        .map_err(|e| e.to_string())?;
+    let aggregates = stmt
+        .query_map(params![start, end], |row| {
+            let mut aggregate = ExportedUsageAggregate::default();
+            for (i, (column, _)) in keys.iter().enumerate() {
+                let value: String = row.get(i)?;
+                match *column {
+                    "date" => aggregate.date = Some(value),
+                    "model" => aggregate.model = Some(value),
+                    "session_id" => aggregate.session_id = Some(value),
+                    _ => aggregate.project_path = Some(value),
+                }
+            }
+            let n = keys.len();
+            aggregate.entries = row.get::<_, i64>(n)? as u64;
+            aggregate.sessions = row.get::<_, i64>(n + 1)? as u64;
+            aggregate.input_tokens = row.get::<_, i64>(n + 2)? as u64;
+            aggregate.output_tokens = row.get::<_, i64>(n + 3)? as u64;
+            aggregate.cache_creation_tokens = row.get::<_, i64>(n + 4)? as u64;
+            aggregate.cache_read_tokens = row.get::<_, i64>(n + 5)? as u64;
+            aggregate.total_tokens = aggregate.input_tokens
+                + aggregate.output_tokens
+                + aggregate.cache_creation_tokens
+                + aggregate.cache_read_tokens;
+            aggregate.cost_usd = row.get(n + 6)?;
+            aggregate.pricing_version = row.get(n + 7)?;
+            Ok(aggregate)
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(aggregates)
+}
+
+/// Render the indexed usage as CSV or JSON, returning the output and its row count
+pub fn render_usage_export(conn: &Connection, options: &UsageExportOptions) -> Result<(String, usize), String> {
+    let start = options
+        .start_date
+        .as_deref()
+        .map(|value| parse_date_arg(value, "start"))
+        .transpose()?
+        .map(|date| date.format("%Y-%m-%d").to_string());
+    let end = options
+        .end_date
+        .as_deref()
+        .map(|value| parse_date_arg(value, "end"))
+        .transpose()?
+        .map(|date| date.format("%Y-%m-%d").to_string());
+
+    match options.group_by {
+        None => {
+            let entries = query_entries(conn, start.as_deref(), end.as_deref())?;
+            let output = match options.format {
+                UsageExportFormat::Json => serde_json::to_string_pretty(&entries)
+                    .map_err(|e| format!("Failed to serialize usage: {}", e))?,
+                UsageExportFormat::Csv => {
+                    let mut out = csv_record(ENTRY_COLUMNS);
+                    for entry in &entries {
+                        out.push_str(&csv_record(&[
+                            entry.timestamp.clone(),
+                            entry.session_id.clone(),
+                            entry.project_path.clone(),
+                            entry.model.clone(),
+                            entry.input_tokens.to_string(),
+                            entry.output_tokens.to_string(),
+                            entry.cache_creation_tokens.to_string(),
+                            entry.cache_read_tokens.to_string(),
+                            entry.total_tokens.to_string(),
+                            format_cost(entry.cost_usd),
+                            entry.cost_source.clone(),
+                            entry.pricing_version.clone().unwrap_or_default(),
+                        ]));
+                    }
+                    out
+                }
+            };
+            Ok((output, entries.len()))
+        }
+        Some(group_by) => {
+            let aggregates = query_aggregates(conn, group_by, start.as_deref(), end.as_deref())?;
+            let output = match options.format {
+                UsageExportFormat::Json => serde_json::to_string_pretty(&aggregates)
+                    .map_err(|e| format!("Failed to serialize usage: {}", e))?,
+                UsageExportFormat::Csv => {
+                    let keys = group_by.keys();
+                    let header: Vec<&str> = keys
+                        .iter()
+                        .map(|(column, _)| *column)
+                        .chain(AGGREGATE_COLUMNS.iter().copied())
+                        .collect();
+                    let mut out = csv_record(&header);
+                    for aggregate in &aggregates {
+                        let mut fields: Vec<String> = keys
+                            .iter()
+                            .map(|(column, _)| aggregate.key(column).unwrap_or_default().to_string())
+                            .collect();
+                        fields.extend([
+                            aggregate.entries.to_string(),
+                            aggregate.sessions.to_string(),
+                            aggregate.input_tokens.to_string(),
+                            aggregate.output_tokens.to_string(),
+                            aggregate.cache_creation_tokens.to_string(),
+                            aggregate.cache_read_tokens.to_string(),
+                            aggregate.total_tokens.to_string(),
+                            format_cost(aggregate.cost_usd),
+                            aggregate.pricing_version.clone().unwrap_or_default(),
+                        ]);
+                        out.push_str(&csv_record(&fields));
+                    }
+                    out
+                }
+            };
+            Ok((output, aggregates.len()))
+        }
+    }
+}
+
+/// Write usage entries, or their totals by day, model, project or session, to a
+/// CSV or JSON file. Returns the number of rows written
+#[tauri::command]
+pub async fn export_usage(
+    db: State<'_, AgentDb>,
+    file_path: String,
+    options: UsageExportOptions,
+) -> Result<usize, String> {
+    let (output, rows) = {
+        let conn = indexed_usage_db(&db).await?;
+        render_usage_export(&conn, &options)?
+    };
+    fs::write(&file_path, output).map_err(|e| format!("Failed to write file: {}", e))?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::usage_index::init_usage_index_tables;
+
+    #[test]
+    fn test_usage_export_columns_and_grouping() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_usage_index_tables(&conn).unwrap();
+        for (timestamp, session, model, cost, recorded) in [
+            ("2025-03-01T10:00:00Z", "s1", "claude-sonnet-4", 1.5, false),
+            ("2025-03-01T11:00:00Z", "s2", "claude-sonnet-4", 0.25, true),
+            ("2025-03-02T09:00:00Z", "s2", "claude-opus-4", 2.0, false),
+        ] {
+            conn.execute(
+                "INSERT INTO usage_entries (file_path, timestamp, model, input_tokens, output_tokens, cache_creation_tokens, cache_read_tokens, cost, session_id, project_path, cost_recorded, pricing_version, priced)
+                 VALUES ('f', ?1, ?2, 10, 20, 30, 40, ?3, ?4, '/work/app, inc', ?5, '2025-11-24', 1)",
+                params![timestamp, model, cost, session, recorded],
+            )
+            .unwrap();
+        }
+
+        let raw = UsageExportOptions {
+            format: UsageExportFormat::Csv,
+            group_by: None,
+            start_date: None,
+            end_date: Some("2025-03-01".to_string()),
+        };
+        let (csv, rows) = render_usage_export(&conn, &raw).unwrap();
+        assert_eq!(rows, 2);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], ENTRY_COLUMNS.join(","));
+        assert_eq!(
+            lines[2],
+            "2025-03-01T11:00:00Z,s2,\"/work/app, inc\",claude-sonnet-4,10,20,30,40,100,0.250000,recorded,"
+        );
+
+        let by_day = UsageExportOptions {
+            format: UsageExportFormat::Csv,
+            group_by: Some(UsageGrouping::Day),
+            ..raw.clone()
+        };
+        let (csv, _) = render_usage_export(&conn, &UsageExportOptions { end_date: None, ..by_day }).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], format!("date,{}", AGGREGATE_COLUMNS.join(",")));
+        assert_eq!(lines[1], "2025-03-01,2,2,20,40,60,80,200,1.750000,2025-11-24");
+
+        let by_session = UsageExportOptions {
+            format: UsageExportFormat::Json,
+            group_by: Some(UsageGrouping::Session),
+            start_date: None,
+            end_date: None,
+        };
+        let (json, rows) = render_usage_export(&conn, &by_session).unwrap();
+        assert_eq!(rows, 2);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[1]["session_id"], "s2");
+        assert_eq!(value[1]["cost_usd"], 2.25);
+        assert!(value[1].get("date").is_none());
+    }
+}
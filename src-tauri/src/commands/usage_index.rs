@@ -0,0 +1,542 @@
+use log::{debug, info, warn};
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::commands::agents::{read_complete_lines, AgentDb};
+use crate::commands::usage_pricing::{load_pricing, PricingTable};
+use crate::project_paths::{claude_projects_dir, resolve_project_path};
+use crate::stream_json::{parse_line, StreamLine, Usage};
+
+/// Counts from one pass of the usage indexer
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UsageIndexStats {
+    pub files_scanned: usize,
+    pub files_updated: usize,
+    pub files_removed: usize,
+    pub entries_added: usize,
+    /// Entries whose cost was recomputed after the prices changed
+    pub entries_repriced: usize,
+}
+
+/// A usage record read from a session line
+#[derive(Debug, PartialEq)]
+struct IndexedUsage {
+    dedup_hash: Option<String>,
+    timestamp: String,
+    model: String,
+    usage: Usage,
+    /// Cost recorded in the session, if any
+    recorded_cost: Option<f64>,
+    session_id: String,
+}
+
+/// Create the usage index tables
+pub fn init_usage_index_tables(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS usage_index_files (
+            path TEXT PRIMARY KEY,
+            byte_offset INTEGER NOT NULL DEFAULT 0,
+            cwd TEXT,
+            indexed_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Costs not recorded in the sessions are computed from the prices of `pricing_version`,
+    // and entries whose model has no price are counted at no cost but not `priced`
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS usage_entries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_path TEXT NOT NULL,
+            timestamp TEXT NOT NULL,
+            model TEXT NOT NULL,
+            input_tokens INTEGER NOT NULL DEFAULT 0,
+            output_tokens INTEGER NOT NULL DEFAULT 0,
+            cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+            cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+            cost REAL NOT NULL DEFAULT 0,
+            session_id TEXT NOT NULL,
+            project_path TEXT NOT NULL,
+            cost_recorded BOOLEAN NOT NULL DEFAULT 0,
+            pricing_version TEXT,
+            priced BOOLEAN NOT NULL DEFAULT 1
+        )",
+        [],
+    )?;
+
+    // Message ID and request ID pairs already counted, so copies of a message in other
+    // files (resumed sessions repeat their history) are counted once
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS usage_dedup_hashes (
+            hash TEXT PRIMARY KEY,
+            file_path TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // Copies of counted messages skipped in other files, so that those files can be
+    // ingested again to count the messages when the file counting them goes away
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS usage_dedup_copies (
+            hash TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            PRIMARY KEY (hash, file_path)
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_usage_entries_timestamp ON usage_entries(timestamp)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_usage_entries_project ON usage_entries(project_path)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_usage_entries_file ON usage_entries(file_path)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_usage_dedup_hashes_file ON usage_dedup_hashes(file_path)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_usage_dedup_copies_file ON usage_dedup_copies(file_path)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// The usage a session line records, if it is a message with a timestamp
+fn line_usage(line: &StreamLine, fallback_session_id: &str) -> Option<IndexedUsage> {
+    let message = line.message()?;
+    let timestamp = line.meta.timestamp.clone()?;
+    let dedup_hash = match (&message.id, &line.meta.request_id) {
+        (Some(msg_id), Some(req_id)) => Some(format!("{}:{}", msg_id, req_id)),
+        _ => None,
+    };
+
+    Some(IndexedUsage {
+        dedup_hash,
+        timestamp,
+        model: message.model.clone().unwrap_or_else(|| "unknown".to_string()),
+        usage: message.usage.unwrap_or_default(),
+        recorded_cost: line.meta.cost_usd,
+        session_id: line
+            .meta
+            .session_id
+            .clone()
+            .unwrap_or_else(|| fallback_session_id.to_string()),
+    })
+}
+
+fn clear_usage_index(conn: &Connection) -> SqliteResult<()> {
+    conn.execute("DELETE FROM usage_entries", [])?;
+    conn.execute("DELETE FROM usage_dedup_hashes", [])?;
+    conn.execute("DELETE FROM usage_dedup_copies", [])?;
+    conn.execute("DELETE FROM usage_index_files", [])?;
+    Ok(())
+}
+
+/// Drop a file from the index, returning the other indexed files holding copies of
+/// the messages it counted
+fn remove_file(conn: &Connection, path: &str) -> SqliteResult<Vec<String>> {
+    let holders = {
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT c.file_path FROM usage_dedup_copies c
+             JOIN usage_dedup_hashes h ON h.hash = c.hash
+             WHERE h.file_path = ?1 AND c.file_path != ?1",
+        )?;
+        let holders = stmt
+            .query_map(params![path], |row| row.get(0))?
+            .collect::<SqliteResult<Vec<String>>>()?;
+        holders
+    };
+    conn.execute("DELETE FROM usage_entries WHERE file_path = ?1", params![path])?;
+    conn.execute("DELETE FROM usage_dedup_hashes WHERE file_path = ?1", params![path])?;
+    conn.execute("DELETE FROM usage_dedup_copies WHERE file_path = ?1", params![path])?;
+    conn.execute("DELETE FROM usage_index_files WHERE path = ?1", params![path])?;
+    Ok(holders)
+}
+
+/// The earliest timestamp in a session file, for ordering files new to the index
+fn get_earliest_timestamp(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    content
+        .lines()
+        .filter_map(parse_line)
+        .filter_map(|line| line.meta.timestamp)
+        .min()
+}
+
+/// Ingest the lines appended to a session file since it was last indexed, returning
+/// the number of usage entries added. Files that shrank are left to `update_usage_index`
+/// to drop first.
+fn index_file(
+    conn: &mut Connection,
+    path: &Path,
+    resolved_project_path: &str,
+    pricing: &PricingTable,
+) -> Result<usize, String> {
+    let key = path.to_string_lossy().to_string();
+    // Lines without a session ID are attributed to the file's directory
+    let fallback_session_id = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+    let len = fs::metadata(path).map_err(|e| e.to_string())?.len();
+
+    let indexed: Option<(u64, Option<String>)> = conn
+        .query_row(
+            "SELECT byte_offset, cwd FROM usage_index_files WHERE path = ?1",
+            params![key],
+            |row| Ok((row.get::<_, i64>(0)? as u64, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    let (offset, mut cwd) = match indexed {
+        Some((offset, _)) if len <= offset => return Ok(0),
+        Some(indexed) => indexed,
+        None => (0, None),
+    };
+
+    let (lines, new_offset) = read_complete_lines(path, offset).map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut added = 0;
+    for line in &lines {
+        let Some(line) = parse_line(line) else {
+            continue;
+        };
+        // The file's first recorded cwd is its project path from then on
+        if cwd.is_none() {
+            cwd = line.meta.cwd.clone();
+        }
+        let Some(entry) = line_usage(&line, &fallback_session_id) else {
+            continue;
+        };
+
+        if let Some(hash) = &entry.dedup_hash {
+            let inserted = tx
+                .execute(
+                    "INSERT OR IGNORE INTO usage_dedup_hashes (hash, file_path) VALUES (?1, ?2)",
+                    params![hash, key],
+                )
+                .map_err(|e| e.to_string())?;
+            if inserted == 0 {
+                tx.execute(
+                    "INSERT OR IGNORE INTO usage_dedup_copies (hash, file_path)
+                     SELECT ?1, ?2 WHERE EXISTS (SELECT 1 FROM usage_dedup_hashes WHERE hash = ?1 AND file_path != ?2)",
+                    params![hash, key],
+                )
+                .map_err(|e| e.to_string())?;
+                continue;
+            }
+        }
+
+        // Skip entries without meaningful token usage
+        if entry.usage.is_empty() {
+            continue;
+        }
+
+        let computed_cost = pricing.cost(&entry.model, Some(&entry.timestamp), &entry.usage);
+        tx.execute(
+            "INSERT INTO usage_entries (file_path, timestamp, model, input_tokens, output_tokens, cache_creation_tokens, cache_read_tokens, cost, session_id, project_path, cost_recorded, pricing_version, priced)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                key,
+                entry.timestamp,
+                entry.model,
+                entry.usage.input_tokens as i64,
+                entry.usage.output_tokens as i64,
+                entry.usage.cache_creation_input_tokens as i64,
+                entry.usage.cache_read_input_tokens as i64,
+                entry.recorded_cost.or(computed_cost).unwrap_or(0.0),
+                entry.session_id,
+                cwd.as_deref().unwrap_or(resolved_project_path),
+                entry.recorded_cost.is_some(),
+                pricing.version,
+                entry.recorded_cost.is_some() || computed_cost.is_some(),
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        added += 1;
+    }
+    tx.execute(
+        "INSERT INTO usage_index_files (path, byte_offset, cwd, indexed_at)
+         VALUES (?1, ?2, ?3, CURRENT_TIMESTAMP)
+         ON CONFLICT(path) DO UPDATE SET byte_offset = ?2, cwd = ?3, indexed_at = CURRENT_TIMESTAMP",
+        params![key, new_offset as i64, cwd],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(added)
+}
+
+/// Recompute the costs that were computed with other prices, returning how many
+fn reprice_entries(conn: &mut Connection, pricing: &PricingTable) -> Result<usize, String> {
+    let stale: Vec<(i64, String, String, Usage)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, model, timestamp, input_tokens, output_tokens, cache_creation_tokens, cache_read_tokens
+                 FROM usage_entries
+                 WHERE cost_recorded = 0 AND (pricing_version IS NULL OR pricing_version != ?1)",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![pricing.version], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    Usage {
+                        input_tokens: row.get::<_, i64>(3)? as u64,
+                        output_tokens: row.get::<_, i64>(4)? as u64,
+                        cache_creation_input_tokens: row.get::<_, i64>(5)? as u64,
+                        cache_read_input_tokens: row.get::<_, i64>(6)? as u64,
+                    },
+                ))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+    if stale.is_empty() {
+        return Ok(0);
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for (id, model, timestamp, usage) in &stale {
+        let cost = pricing.cost(model, Some(timestamp), usage);
+        tx.execute(
+            "UPDATE usage_entries SET cost = ?1, priced = ?2, pricing_version = ?3 WHERE id = ?4",
+            params![cost.unwrap_or(0.0), cost.is_some(), pricing.version, id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    info!("Repriced {} usage entries with prices {}", stale.len(), pricing.version);
+    Ok(stale.len())
+}
+
+/// Bring the usage index up to date with the session files under `projects_dir`.
+///
+/// Files that vanished or shrank are dropped first, so sessions moved elsewhere in the
+/// tree are counted from their new file. Files holding copies of the messages a dropped
+/// file counted are ingested again from the start to count those messages instead.
+/// Files new to the index are ingested in order of their earliest timestamp, so the
+/// original of a repeated message is the one counted.
+/// Entries costed with prices other than the current ones are repriced.
+pub fn update_usage_index(conn: &mut Connection, projects_dir: &Path) -> Result<UsageIndexStats, String> {
+    let mut stats = UsageIndexStats::default();
+    let pricing = load_pricing(conn)?;
+    stats.entries_repriced = reprice_entries(conn, &pricing)?;
+    let mut files: Vec<(PathBuf, String)> = Vec::new();
+
+    if let Ok(projects) = fs::read_dir(projects_dir) {
+        for project in projects.flatten() {
+            if !project.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                continue;
+            }
+            let project_dir = project.path();
+            let project_path = resolve_project_path(&project_dir);
+
+            walkdir::WalkDir::new(&project_dir)
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("jsonl"))
+                .for_each(|entry| files.push((entry.path().to_path_buf(), project_path.clone())));
+        }
+    }
+    stats.files_scanned = files.len();
+
+    let indexed: HashMap<String, u64> = {
+        let mut stmt = conn
+            .prepare("SELECT path, byte_offset FROM usage_index_files")
+            .map_err(|e| e.to_string())?;
+        let paths = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| e.to_string())?;
+        paths
+    };
+    let seen: HashSet<String> = files
+        .iter()
+        .map(|(path, _)| path.to_string_lossy().to_string())
+        .collect();
+
+    let mut to_drop: Vec<String> = indexed
+        .keys()
+        .filter(|path| !seen.contains(*path))
+        .cloned()
+        .collect();
+    stats.files_removed = to_drop.len();
+    for (path, _) in &files {
+        let key = path.to_string_lossy();
+        let shrank = indexed.get(key.as_ref()).is_some_and(|offset| {
+            fs::metadata(path).map(|m| m.len() < *offset).unwrap_or(false)
+        });
+        if shrank {
+            debug!("Session file {} shrank, reindexing its usage", key);
+            to_drop.push(key.to_string());
+        }
+    }
+    let mut dropped: HashSet<String> = HashSet::new();
+    while let Some(path) = to_drop.pop() {
+        if dropped.insert(path.clone()) {
+            to_drop.extend(remove_file(conn, &path).map_err(|e| e.to_string())?);
+        }
+    }
+
+    let (known, mut new): (Vec<_>, Vec<_>) = files.into_iter().partition(|(path, _)| {
+        let key = path.to_string_lossy();
+        indexed.contains_key(key.as_ref()) && !dropped.contains(key.as_ref())
+    });
+    new.sort_by_cached_key(|(path, _)| get_earliest_timestamp(path));
+
+    for (path, project_path) in known.into_iter().chain(new) {
+        match index_file(conn, &path, &project_path, &pricing) {
+            Ok(0) => {}
+            Ok(added) => {
+                stats.files_updated += 1;
+                stats.entries_added += added;
+            }
+            Err(e) => warn!("Failed to index usage of {:?}: {}", path, e),
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Run `update` on a connection of its own to the app database, on a blocking thread
+/// and one at a time, so reading session files doesn't hold the shared connection
+async fn run_index_update<F>(db: &AgentDb, update: F) -> Result<UsageIndexStats, String>
+where
+    F: FnOnce(&mut Connection, &Path) -> Result<UsageIndexStats, String> + Send + 'static,
+{
+    static UPDATING: Mutex<()> = Mutex::new(());
+
+    let db_path = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.path()
+            .map(PathBuf::from)
+            .ok_or("The usage index needs a database file")?
+    };
+    let projects_dir = claude_projects_dir().ok_or("Failed to get home directory")?;
+    tokio::task::spawn_blocking(move || {
+        let _updating = UPDATING.lock().map_err(|e| e.to_string())?;
+        let mut conn = Connection::open(&db_path).map_err(|e| e.to_string())?;
+        update(&mut conn, &projects_dir)
+    })
+    .await
+    .map_err(|e| format!("Usage index update failed: {}", e))?
+}
+
+/// Bring the usage index up to date without holding the shared database connection
+pub async fn refresh_usage_index(db: &AgentDb) -> Result<UsageIndexStats, String> {
+    run_index_update(db, update_usage_index).await
+}
+
+/// Drop the usage index and ingest every session file again
+#[tauri::command]
+pub async fn rebuild_usage_index(db: State<'_, AgentDb>) -> Result<UsageIndexStats, String> {
+    let stats = run_index_update(&db, |conn, projects_dir| {
+        clear_usage_index(conn).map_err(|e| e.to_string())?;
+        update_usage_index(conn, projects_dir)
+    })
+    .await?;
+    info!(
+        "Usage index rebuilt: {} files, {} entries",
+        stats.files_scanned, stats.entries_added
+    );
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assistant_line(msg_id: &str, request_id: &str, timestamp: &str, output_tokens: u64) -> String {
+        format!(
+            r#"{{"type":"assistant","sessionId":"s1","cwd":"/work/app","requestId":"{}","timestamp":"{}","message":{{"id":"{}","model":"claude-sonnet-4","usage":{{"input_tokens":10,"output_tokens":{}}}}}}}"#,
+            request_id, timestamp, msg_id, output_tokens
+        )
+    }
+
+    #[test]
+    fn test_update_usage_index_is_incremental_and_dedups() {
+        let claude_dir = tempfile::tempdir().unwrap();
+        let projects_dir = claude_dir.path().join("projects");
+        let project_dir = projects_dir.join("-work-app");
+        fs::create_dir_all(&project_dir).unwrap();
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute("CREATE TABLE app_settings (key TEXT PRIMARY KEY, value TEXT NOT NULL)", [])
+            .unwrap();
+        init_usage_index_tables(&conn).unwrap();
+
+        let first = assistant_line("m1", "r1", "2025-01-01T10:00:00Z", 5);
+        fs::write(project_dir.join("a.jsonl"), format!("{}\n", first)).unwrap();
+        // A resumed session repeats m1 and adds m2, the latter not yet fully written
+        let second = assistant_line("m2", "r2", "2025-01-02T10:00:00Z", 7);
+        fs::write(
+            project_dir.join("b.jsonl"),
+            format!("{}\n{}", first, &second[..20]),
+        )
+        .unwrap();
+
+        let stats = update_usage_index(&mut conn, &projects_dir).unwrap();
+        assert_eq!(stats.entries_added, 1);
+
+        fs::write(project_dir.join("b.jsonl"), format!("{}\n{}\n", first, second)).unwrap();
+        let stats = update_usage_index(&mut conn, &projects_dir).unwrap();
+        assert_eq!((stats.files_updated, stats.entries_added), (1, 1));
+
+        let (count, output, project): (i64, i64, String) = conn
+            .query_row(
+                "SELECT COUNT(*), SUM(output_tokens), MAX(project_path) FROM usage_entries",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((count, output, project.as_str()), (2, 12, "/work/app"));
+
+        // New prices apply to the entries already indexed
+        conn.execute(
+            "INSERT INTO app_settings (key, value) VALUES ('pricing_overrides', ?1)",
+            params![r#"[{"patterns":["sonnet"],"input_price":0,"output_price":1000000,"cache_write_price":0,"cache_read_price":0}]"#],
+        )
+        .unwrap();
+        let stats = update_usage_index(&mut conn, &projects_dir).unwrap();
+        assert_eq!(stats.entries_repriced, 2);
+        let cost: f64 = conn
+            .query_row("SELECT SUM(cost) FROM usage_entries", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(cost, 12.0);
+
+        // The copy of m1 in b.jsonl takes over once a.jsonl is gone
+        fs::remove_file(project_dir.join("a.jsonl")).unwrap();
+        let stats = update_usage_index(&mut conn, &projects_dir).unwrap();
+        assert_eq!(stats.files_removed, 1);
+        let (count, output, cost): (i64, i64, f64) = conn
+            .query_row(
+                "SELECT COUNT(*), SUM(output_tokens), SUM(cost) FROM usage_entries",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((count, output, cost), (2, 12, 12.0));
+    }
+}
@@ -0,0 +1,373 @@
+use chrono::NaiveDate;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use tauri::State;
+
+use crate::commands::agents::AgentDb;
+use crate::stream_json::Usage;
+
+/// Version of the bundled price list, the date it was last checked against
+/// https://docs.anthropic.com/en/docs/about-claude/pricing
+pub const BUNDLED_PRICING_VERSION: &str = "2026-10-18";
+
+/// `app_settings` key holding the user's price overrides as JSON
+const PRICING_OVERRIDES_SETTING_KEY: &str = "pricing_overrides";
+
+/// Price of a model in USD per million tokens, over an optional range of dates
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ModelPrice {
+    /// Substrings of the model names the price applies to
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Model names the price applies to exactly, such as the `sonnet` alias
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub input_price: f64,
+    pub output_price: f64,
+    pub cache_write_price: f64,
+    pub cache_read_price: f64,
+    /// First day the price applies, `YYYY-MM-DD`
+    #[serde(default)]
+    pub effective_from: Option<String>,
+    /// First day the price no longer applies, `YYYY-MM-DD`
+    #[serde(default)]
+    pub effective_until: Option<String>,
+}
+
+/// The prices usage is costed with: the user's overrides ahead of the bundled prices
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PricingTable {
+    /// Identifies the prices, changing whenever the overrides do
+    pub version: String,
+    pub overrides: Vec<ModelPrice>,
+    pub bundled: Vec<ModelPrice>,
+}
+
+/// A pricing file to import, either a list of prices or an object holding one
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum PricingFile {
+    Prices(Vec<ModelPrice>),
+    Table { prices: Vec<ModelPrice> },
+}
+
+struct BundledPrice {
+    patterns: &'static [&'static str],
+    aliases: &'static [&'static str],
+    prices: [f64; 4],
+    effective_from: Option<&'static str>,
+    effective_until: Option<&'static str>,
+}
+
+/// Bundled prices as input, output, cache write and cache read per million tokens.
+/// The first price matching a model wins, so more specific names come first.
+const BUNDLED_PRICES: &[BundledPrice] = &[
+    // Claude Opus 4.5
+    BundledPrice {
+        patterns: &["claude-opus-4-5", "opus-4-5"],
+        aliases: &[],
+        prices: [5.0, 25.0, 6.25, 0.50],
+        effective_from: None,
+        effective_until: None,
+    },
+    // Claude Haiku 4.5
+    BundledPrice {
+        patterns: &["claude-haiku-4-5", "haiku-4-5"],
+        aliases: &[],
+        prices: [1.0, 5.0, 1.25, 0.10],
+        effective_from: None,
+        effective_until: None,
+    },
+    // The `haiku` alias points at Claude Haiku 4.5 since its release
+    BundledPrice {
+        patterns: &[],
+        aliases: &["haiku"],
+        prices: [1.0, 5.0, 1.25, 0.10],
+        effective_from: Some("2025-10-15"),
+        effective_until: None,
+    },
+    // Claude 3.5 Haiku
+    BundledPrice {
+        patterns: &["claude-3-5-haiku"],
+        aliases: &["haiku"],
+        prices: [0.8, 4.0, 1.0, 0.08],
+        effective_from: None,
+        effective_until: None,
+    },
+    // Claude 3 Haiku
+    BundledPrice {
+        patterns: &["claude-3-haiku"],
+        aliases: &[],
+        prices: [0.25, 1.25, 0.30, 0.03],
+        effective_from: None,
+        effective_until: None,
+    },
+    // Claude 3.5 Sonnet
+    BundledPrice {
+        patterns: &["claude-3-5-sonnet"],
+        aliases: &["sonnet-3-5"],
+        prices: [3.0, 15.0, 3.75, 0.30],
+        effective_from: None,
+        effective_until: None,
+    },
+    // Claude 3.7 Sonnet
+    BundledPrice {
+        patterns: &["claude-3-7-sonnet"],
+        aliases: &["sonnet-3-7"],
+        prices: [3.0, 15.0, 3.75, 0.30],
+        effective_from: None,
+        effective_until: None,
+    },
+    // Claude 3 Opus (legacy)
+    BundledPrice {
+        patterns: &["claude-3-opus", "opus-3"],
+        aliases: &[],
+        prices: [15.0, 75.0, 18.75, 1.50],
+        effective_from: None,
+        effective_until: None,
+    },
+    // The `opus` alias points at Claude Opus 4.5 since its release
+    BundledPrice {
+        patterns: &[],
+        aliases: &["opus"],
+        prices: [5.0, 25.0, 6.25, 0.50],
+        effective_from: Some("2025-11-24"),
+        effective_until: None,
+    },
+    // Claude Opus 4 and 4.1, matched by their released IDs so later models stay unpriced
+    BundledPrice {
+        patterns: &["claude-opus-4-2025", "claude-opus-4-0", "claude-opus-4-1"],
+        aliases: &["opus"],
+        prices: [15.0, 75.0, 18.75, 1.50],
+        effective_from: None,
+        effective_until: None,
+    },
+    // Claude Sonnet 4 and 4.5, matched by their released IDs so later models stay unpriced
+    BundledPrice {
+        patterns: &["claude-sonnet-4-2025", "claude-sonnet-4-0", "claude-sonnet-4-5"],
+        aliases: &["sonnet"],
+        prices: [3.0, 15.0, 3.75, 0.30],
+        effective_from: None,
+        effective_until: None,
+    },
+];
+
+impl ModelPrice {
+    /// Whether the price applies to `model` on `date`, given as `YYYY-MM-DD` or any
+    /// timestamp starting with it
+    fn applies_to(&self, model: &str, date: Option<&str>) -> bool {
+        let named = self.aliases.iter().any(|alias| alias == model)
+            || self.patterns.iter().any(|pattern| model.contains(pattern.as_str()));
+        let day = date.map(|date| date.get(..10).unwrap_or(date));
+        let in_range = match day {
+            Some(day) => {
+                self.effective_from.as_deref().is_none_or(|from| day >= from)
+                    && self.effective_until.as_deref().is_none_or(|until| day < until)
+            }
+            // Without a date, only prices without an end still apply
+            None => self.effective_until.is_none(),
+        };
+        named && in_range
+    }
+
+    fn cost(&self, usage: &Usage) -> f64 {
+        // Prices are per million tokens
+        (usage.input_tokens as f64 * self.input_price
+            + usage.output_tokens as f64 * self.output_price
+            + usage.cache_creation_input_tokens as f64 * self.cache_write_price
+            + usage.cache_read_input_tokens as f64 * self.cache_read_price)
+            / 1_000_000.0
+    }
+
+    /// Check the price is usable, naming it by its position in the list
+    fn validate(&self, index: usize) -> Result<(), String> {
+        if self.patterns.iter().chain(&self.aliases).all(|name| name.trim().is_empty()) {
+            return Err(format!("Price {} names no models", index + 1));
+        }
+        let prices = [self.input_price, self.output_price, self.cache_write_price, self.cache_read_price];
+        if prices.iter().any(|price| !price.is_finite() || *price < 0.0) {
+            return Err(format!("Price {} has a negative or invalid amount", index + 1));
+        }
+        for date in self.effective_from.iter().chain(&self.effective_until) {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| format!("Price {} has an invalid date: {}", index + 1, date))?;
+        }
+        if let (Some(from), Some(until)) = (&self.effective_from, &self.effective_until) {
+            if from >= until {
+                return Err(format!("Price {} ends before it starts", index + 1));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PricingTable {
+    pub fn new(overrides: Vec<ModelPrice>) -> Self {
+        let bundled = BUNDLED_PRICES
+            .iter()
+            .map(|price| ModelPrice {
+                patterns: price.patterns.iter().map(|s| s.to_string()).collect(),
+                aliases: price.aliases.iter().map(|s| s.to_string()).collect(),
+                input_price: price.prices[0],
+                output_price: price.prices[1],
+                cache_write_price: price.prices[2],
+                cache_read_price: price.prices[3],
+                effective_from: price.effective_from.map(String::from),
+                effective_until: price.effective_until.map(String::from),
+            })
+            .collect();
+
+        let version = if overrides.is_empty() {
+            BUNDLED_PRICING_VERSION.to_string()
+        } else {
+            let mut hasher = Sha256::new();
+            hasher.update(serde_json::to_vec(&overrides).unwrap_or_default());
+            let digest = format!("{:x}", hasher.finalize());
+            format!("{}+{}", BUNDLED_PRICING_VERSION, &digest[..8])
+        };
+
+        Self { version, overrides, bundled }
+    }
+
+    /// The price of `model` on the day of `timestamp`
+    pub fn price(&self, model: &str, timestamp: Option<&str>) -> Option<&ModelPrice> {
+        self.overrides
+            .iter()
+            .chain(&self.bundled)
+            .find(|price| price.applies_to(model, timestamp))
+    }
+
+    /// The cost of `usage` by `model` on the day of `timestamp`, or `None` for a model
+    /// without a price
+    pub fn cost(&self, model: &str, timestamp: Option<&str>, usage: &Usage) -> Option<f64> {
+        self.price(model, timestamp).map(|price| price.cost(usage))
+    }
+}
+
+fn validate_prices(prices: &[ModelPrice]) -> Result<(), String> {
+    prices
+        .iter()
+        .enumerate()
+        .try_for_each(|(index, price)| price.validate(index))
+}
+
+/// Load the bundled prices with the user's overrides
+pub fn load_pricing(conn: &Connection) -> Result<PricingTable, String> {
+    let overrides = match conn.query_row(
+        "SELECT value FROM app_settings WHERE key = ?1",
+        params![PRICING_OVERRIDES_SETTING_KEY],
+        |row| row.get::<_, String>(0),
+    ) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| format!("Invalid price overrides: {}", e))?,
+        Err(rusqlite::Error::QueryReturnedNoRows) => Vec::new(),
+        Err(e) => return Err(format!("Failed to load price overrides: {}", e)),
+    };
+    Ok(PricingTable::new(overrides))
+}
+
+fn save_overrides(conn: &Connection, overrides: &[ModelPrice]) -> Result<(), String> {
+    validate_prices(overrides)?;
+    let json = serde_json::to_string(overrides).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO app_settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = ?2",
+        params![PRICING_OVERRIDES_SETTING_KEY, json],
+    )
+    .map_err(|e| format!("Failed to save price overrides: {}", e))?;
+    Ok(())
+}
+
+/// Get the prices usage is costed with
+#[tauri::command]
+pub async fn get_pricing_table(db: State<'_, AgentDb>) -> Result<PricingTable, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_pricing(&conn)
+}
+
+/// Replace the user's price overrides. Indexed usage is repriced on its next update.
+#[tauri::command]
+pub async fn set_pricing_overrides(
+    db: State<'_, AgentDb>,
+    overrides: Vec<ModelPrice>,
+) -> Result<PricingTable, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    save_overrides(&conn, &overrides)?;
+    Ok(PricingTable::new(overrides))
+}
+
+/// Replace the user's price overrides with the prices in a JSON file
+#[tauri::command]
+pub async fn import_pricing_file(db: State<'_, AgentDb>, path: String) -> Result<PricingTable, String> {
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let overrides = match serde_json::from_str(&content).map_err(|e| format!("Invalid pricing file: {}", e))? {
+        PricingFile::Prices(prices) | PricingFile::Table { prices } => prices,
+    };
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    save_overrides(&conn, &overrides)?;
+    Ok(PricingTable::new(overrides))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prices_follow_overrides_and_dates() {
+        let usage = Usage {
+            input_tokens: 1_000_000,
+            output_tokens: 1_000_000,
+            ..Default::default()
+        };
+        let table = PricingTable::new(Vec::new());
+        assert_eq!(table.version, BUNDLED_PRICING_VERSION);
+        assert_eq!(table.cost("claude-opus-4-1-20250805", None, &usage), Some(90.0));
+        assert_eq!(table.cost("claude-opus-4-5-20251101", None, &usage), Some(30.0));
+        assert_eq!(table.cost("opus", Some("2025-06-01T10:00:00Z"), &usage), Some(90.0));
+        assert_eq!(table.cost("opus", Some("2025-12-01T10:00:00Z"), &usage), Some(30.0));
+        assert_eq!(table.cost("haiku", Some("2025-06-01T10:00:00Z"), &usage), Some(4.8));
+        assert_eq!(table.cost("haiku", Some("2025-12-01T10:00:00Z"), &usage), Some(6.0));
+        assert_eq!(table.cost("claude-opus-4-6", None, &usage), None);
+        assert_eq!(table.cost("claude-sonnet-4-6", None, &usage), None);
+        assert_eq!(table.cost("my-proxy-model", None, &usage), None);
+
+        let proxy = ModelPrice {
+            patterns: vec!["my-proxy".to_string()],
+            aliases: Vec::new(),
+            input_price: 1.0,
+            output_price: 2.0,
+            cache_write_price: 0.0,
+            cache_read_price: 0.0,
+            effective_from: Some("2025-01-01".to_string()),
+            effective_until: None,
+        };
+        validate_prices(std::slice::from_ref(&proxy)).unwrap();
+        let table = PricingTable::new(vec![proxy.clone()]);
+        assert_ne!(table.version, BUNDLED_PRICING_VERSION);
+        assert_eq!(table.cost("my-proxy-model", Some("2025-02-01"), &usage), Some(3.0));
+        assert_eq!(table.cost("my-proxy-model", Some("2024-12-31"), &usage), None);
+
+        let invalid = ModelPrice { effective_until: Some("2024-06-01".to_string()), ..proxy };
+        assert!(validate_prices(&[invalid]).is_err());
+    }
+
+    #[test]
+    fn test_bundled_prices_of_known_models() {
+        let table = PricingTable::new(Vec::new());
+        let prices = |model: &str| {
+            let price = table.price(model, None).unwrap();
+            [price.input_price, price.output_price, price.cache_write_price, price.cache_read_price]
+        };
+        assert_eq!(prices("claude-3-7-sonnet-20250219"), [3.0, 15.0, 3.75, 0.30]);
+        assert_eq!(prices("claude-3-5-sonnet-20241022"), [3.0, 15.0, 3.75, 0.30]);
+        assert_eq!(prices("claude-sonnet-4-5-20250929"), [3.0, 15.0, 3.75, 0.30]);
+        assert_eq!(prices("claude-3-5-haiku-20241022"), [0.8, 4.0, 1.0, 0.08]);
+        assert_eq!(prices("claude-haiku-4-5-20251001"), [1.0, 5.0, 1.25, 0.10]);
+        assert_eq!(prices("claude-opus-4-1-20250805"), [15.0, 75.0, 18.75, 1.50]);
+        assert_eq!(prices("claude-opus-4-20250514"), [15.0, 75.0, 18.75, 1.50]);
+        assert_eq!(prices("claude-sonnet-4-20250514"), [3.0, 15.0, 3.75, 0.30]);
+        assert_eq!(prices("claude-3-opus-20240229"), [15.0, 75.0, 18.75, 1.50]);
+    }
+}
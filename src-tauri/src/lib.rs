@@ -6,6 +6,8 @@ pub mod claude_binary;
 pub mod commands;
 pub mod logger;
 pub mod process;
+pub mod project_paths;
+pub mod stream_json;
 
 // Logger macros are automatically exported to crate root due to #[macro_export]
 // No need to re-export them manually
@@ -6,6 +6,8 @@ mod claude_binary;
 mod commands;
 mod logger;
 mod process;
+mod project_paths;
+mod stream_json;
 
 use checkpoint::state::CheckpointState;
 use commands::agents::{
@@ -16,6 +18,8 @@ use commands::agents::{
     import_agent_from_file, import_agent_from_github, import_native_agents, init_database, kill_agent_session,
     list_agent_runs, list_agent_runs_with_metrics, list_agents, list_claude_installations,
     list_native_agents, list_running_sessions, load_agent_session_history, set_claude_binary_path, stream_session_output, update_agent, AgentDb,
+    list_agent_versions, get_agent_version, diff_agent_versions, rollback_agent_version,
+    check_agent_import_dependencies, check_agent_file_dependencies,
     get_environment_variables, save_environment_variables, get_enabled_environment_variables,
     get_environment_variable_groups, create_environment_variable_group, update_environment_variable_group, delete_environment_variable_group,
 };
@@ -32,15 +36,64 @@ use commands::claude::{
     get_hooks_config, update_hooks_config, validate_hook_command,
     ClaudeProcessState,
 };
+use commands::agent_batches::{
+    cancel_agent_batch, execute_agent_batch, get_agent_batch_summary, list_agent_batches,
+};
+use commands::agent_pipelines::{
+    cancel_pipeline_run, create_agent_pipeline, delete_agent_pipeline, execute_agent_pipeline,
+    get_pipeline_run, list_agent_pipelines, list_pipeline_runs, update_agent_pipeline,
+};
+use commands::agent_registry::{
+    check_agent_updates, fetch_registry_agents, import_agent_from_registry, list_agent_registries,
+    save_agent_registries,
+};
+use commands::agent_results::{
+    get_agent_run_result, list_agent_run_results, read_agent_run_artifact,
+};
+use commands::agent_retries::{
+    get_agent_retry_policy, list_agent_run_attempts, resume_agent_run, retry_agent_run,
+    set_agent_retry_policy,
+};
+use commands::agent_scheduler::{
+    create_agent_schedule, delete_agent_schedule, list_agent_schedules, run_agent_schedule_now,
+    update_agent_schedule,
+};
+use commands::agent_trust::{
+    add_trusted_agent_key, get_agent_import_policy, list_trusted_agent_keys,
+    remove_trusted_agent_key, set_agent_import_policy, verify_agent_file_signature,
+    verify_agent_source_signature,
+};
 use commands::mcp::{
     mcp_add, mcp_add_from_claude_desktop, mcp_add_json, mcp_get, mcp_get_server_status, mcp_list,
     mcp_read_project_config, mcp_remove, mcp_reset_project_choices, mcp_save_project_config,
     mcp_serve, mcp_test_connection,
 };
+use commands::session_export::{export_session, export_session_to_file};
+use commands::session_forks::{fork_session, get_session_tree, list_session_forks};
+use commands::session_meta::{
+    archive_session, get_session_meta, list_session_tags, set_session_meta, unarchive_session,
+};
+use commands::session_search::{rebuild_session_index, search_sessions, update_session_index};
+use commands::session_summaries::{
+    get_session_summarizer_settings, get_session_summary, set_session_summarizer_settings,
+    summarize_session,
+};
+use commands::session_transfer::{export_session_bundle, import_session, preview_session_import};
+use commands::session_trash::{
+    empty_session_trash, get_session_trash_retention, list_trashed_sessions, purge_trashed_session,
+    restore_trashed_session, set_session_trash_retention,
+};
 
 use commands::usage::{
-    get_session_stats, get_usage_by_date_range, get_usage_details, get_usage_stats,
+    get_agent_run_usage, get_session_stats, get_usage_by_date_range, get_usage_details, get_usage_stats,
+};
+use commands::usage_budgets::{
+    delete_budget, get_budget_override, get_budget_statuses, list_budgets, save_budget,
+    set_budget_override,
 };
+use commands::usage_export::export_usage;
+use commands::usage_index::rebuild_usage_index;
+use commands::usage_pricing::{get_pricing_table, import_pricing_file, set_pricing_overrides};
 use commands::storage::{
     storage_list_tables, storage_read_table, storage_update_row, storage_delete_row,
     storage_insert_row, storage_execute_sql, storage_reset_database,
@@ -59,6 +112,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_window_state::Builder::default().build())
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
             // Initialize agents database
             let conn = init_database(&app.handle()).map_err(|e| {
@@ -146,6 +200,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             // Initialize Claude process state
             app.manage(ClaudeProcessState::default());
 
+            // Start firing scheduled agent runs
+            commands::agent_scheduler::start_scheduler(app.handle().clone());
+
+            // Summarize finished sessions in the background when enabled
+            commands::session_summaries::start_summarizer(app.handle().clone());
+
+            // Purge trashed sessions past their retention
+            commands::session_trash::start_trash_purger(app.handle().clone());
+
+            // Notify when spend crosses budget thresholds
+            commands::usage_budgets::start_budget_monitor(app.handle().clone());
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -177,6 +243,46 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             validate_hook_command,
             get_claude_models,
 
+            // Session Search
+            search_sessions,
+            update_session_index,
+            rebuild_session_index,
+
+            // Session Export
+            export_session,
+            export_session_to_file,
+
+            // Session Forks
+            fork_session,
+            list_session_forks,
+            get_session_tree,
+
+            // Session Metadata
+            get_session_meta,
+            set_session_meta,
+            list_session_tags,
+            archive_session,
+            unarchive_session,
+
+            // Session Summaries
+            get_session_summary,
+            summarize_session,
+            get_session_summarizer_settings,
+            set_session_summarizer_settings,
+
+            // Session Transfer
+            preview_session_import,
+            import_session,
+            export_session_bundle,
+
+            // Session Trash
+            list_trashed_sessions,
+            restore_trashed_session,
+            purge_trashed_session,
+            empty_session_trash,
+            get_session_trash_retention,
+            set_session_trash_retention,
+
             // Checkpoint Management
             create_checkpoint,
             restore_checkpoint,
@@ -202,6 +308,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             delete_agent,
             delete_native_agents,
             get_agent,
+            list_agent_versions,
+            get_agent_version,
+            diff_agent_versions,
+            rollback_agent_version,
             execute_agent,
             list_agent_runs,
             get_agent_run,
@@ -223,9 +333,48 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             export_agent_to_file,
             import_agent,
             import_agent_from_file,
+            check_agent_import_dependencies,
+            check_agent_file_dependencies,
             fetch_github_agents,
             fetch_github_agent_content,
             import_agent_from_github,
+            list_agent_registries,
+            save_agent_registries,
+            fetch_registry_agents,
+            import_agent_from_registry,
+            check_agent_updates,
+            list_trusted_agent_keys,
+            add_trusted_agent_key,
+            remove_trusted_agent_key,
+            get_agent_import_policy,
+            set_agent_import_policy,
+            verify_agent_file_signature,
+            verify_agent_source_signature,
+            list_agent_schedules,
+            create_agent_schedule,
+            update_agent_schedule,
+            delete_agent_schedule,
+            run_agent_schedule_now,
+            list_agent_pipelines,
+            create_agent_pipeline,
+            update_agent_pipeline,
+            delete_agent_pipeline,
+            execute_agent_pipeline,
+            list_pipeline_runs,
+            get_pipeline_run,
+            cancel_pipeline_run,
+            execute_agent_batch,
+            list_agent_batches,
+            get_agent_batch_summary,
+            cancel_agent_batch,
+            get_agent_run_result,
+            list_agent_run_results,
+            read_agent_run_artifact,
+            retry_agent_run,
+            resume_agent_run,
+            list_agent_run_attempts,
+            get_agent_retry_policy,
+            set_agent_retry_policy,
 
             // Environment Variables
             get_environment_variables,
@@ -241,6 +390,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             get_usage_by_date_range,
             get_usage_details,
             get_session_stats,
+            get_agent_run_usage,
+            export_usage,
+            rebuild_usage_index,
+            get_pricing_table,
+            set_pricing_overrides,
+            import_pricing_file,
+            list_budgets,
+            save_budget,
+            delete_budget,
+            get_budget_statuses,
+            get_budget_override,
+            set_budget_override,
 
             // MCP (Model Context Protocol)
             mcp_add,
@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use tokio::process::Child;
 
@@ -34,6 +35,8 @@ pub struct ProcessHandle {
     pub info: ProcessInfo,
     pub child: Arc<Mutex<Option<Child>>>,
     pub live_output: Arc<Mutex<String>>,
+    /// Set once a process without a child handle (e.g. a sidecar) reports that it terminated
+    pub exited: Arc<AtomicBool>,
 }
 
 /// Registry for tracking active agent processes
@@ -111,6 +114,7 @@ impl ProcessRegistry {
             info: process_info,
             child: Arc::new(Mutex::new(None)), // No tokio::process::Child handle for sidecar
             live_output: Arc::new(Mutex::new(String::new())),
+            exited: Arc::new(AtomicBool::new(false)),
         };
 
         processes.insert(run_id, process_handle);
@@ -145,6 +149,7 @@ impl ProcessRegistry {
             info: process_info,
             child: Arc::new(Mutex::new(None)), // No child handle for Claude sessions
             live_output: Arc::new(Mutex::new(String::new())),
+            exited: Arc::new(AtomicBool::new(false)),
         };
 
         processes.insert(run_id, process_handle);
@@ -164,6 +169,7 @@ impl ProcessRegistry {
             info: process_info,
             child: Arc::new(Mutex::new(Some(child))),
             live_output: Arc::new(Mutex::new(String::new())),
+            exited: Arc::new(AtomicBool::new(false)),
         };
 
         processes.insert(run_id, process_handle);
@@ -444,26 +450,20 @@ impl ProcessRegistry {
     }
 
     /// Check if a process is still running by trying to get its status
-    #[allow(dead_code)]
     pub async fn is_process_running(&self, run_id: i64) -> Result<bool, String> {
         let processes = self.processes.lock().map_err(|e| e.to_string())?;
 
         if let Some(handle) = processes.get(&run_id) {
             let child_arc = handle.child.clone();
+            let exited = handle.exited.clone();
             drop(processes); // Release the lock before async operation
 
             let mut child_guard = child_arc.lock().map_err(|e| e.to_string())?;
             if let Some(ref mut child) = child_guard.as_mut() {
+                // The child is kept after it exits so its exit code can still be read
                 match child.try_wait() {
-                    Ok(Some(_)) => {
-                        // Process has exited
-                        *child_guard = None;
-                        Ok(false)
-                    }
-                    Ok(None) => {
-                        // Process is still running
-                        Ok(true)
-                    }
+                    Ok(Some(_)) => Ok(false), // Process has exited
+                    Ok(None) => Ok(true),     // Process is still running
                     Err(_) => {
                         // Error checking status, assume not running
                         *child_guard = None;
@@ -471,13 +471,49 @@ impl ProcessRegistry {
                     }
                 }
             } else {
-                Ok(false) // No child handle
+                // No child handle, running until it is marked as exited
+                Ok(!exited.load(Ordering::SeqCst))
             }
         } else {
             Ok(false) // Process not found in registry
         }
     }
 
+    /// Mark a process without a child handle as exited
+    pub fn mark_process_exited(&self, run_id: i64) -> Result<(), String> {
+        let processes = self.processes.lock().map_err(|e| e.to_string())?;
+        if let Some(handle) = processes.get(&run_id) {
+            handle.exited.store(true, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    /// Wait up to `timeout` for a registered child to exit and return its exit code
+    pub async fn wait_for_exit_code(&self, run_id: i64, timeout: std::time::Duration) -> Option<i32> {
+        let child_arc = {
+            let processes = self.processes.lock().ok()?;
+            processes.get(&run_id)?.child.clone()
+        };
+
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            {
+                let mut child_guard = child_arc.lock().ok()?;
+                let child = child_guard.as_mut()?;
+                match child.try_wait() {
+                    Ok(Some(status)) => return status.code(),
+                    Ok(None) => {}
+                    Err(_) => return None,
+                }
+            }
+
+            if std::time::Instant::now() >= deadline {
+                return None;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+    }
+
     /// Append to live output for a process
     pub fn append_live_output(&self, run_id: i64, output: &str) -> Result<(), String> {
         let processes = self.processes.lock().map_err(|e| e.to_string())?;
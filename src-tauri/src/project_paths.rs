@@ -0,0 +1,212 @@
+//! Mapping between project paths and their directories under `~/.claude/projects`
+//!
+//! Claude Code names each project directory after the project path with every
+//! character other than ASCII letters and digits replaced by `-`, so the name alone
+//! can't be decoded reliably. Paths are resolved from the `cwd` recorded in the
+//! project's session files, then by probing the filesystem for a path that encodes
+//! to the same name, and only as a last resort by the naive decoding. Results are cached.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::stream_json::parse_line;
+
+/// How many lines of each session file are checked for a `cwd`
+const CWD_SCAN_LINES: usize = 20;
+
+/// Upper bound on filesystem checks when probing for a path
+const MAX_PROBES: usize = 2000;
+
+/// Characters that may have become a `-` inside a single path component
+const COMPONENT_SEPARATORS: &[char] = &['-', '.', '_', ' '];
+
+/// Resolved project paths keyed by project directory name
+fn cache() -> &'static Mutex<HashMap<String, String>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// The `~/.claude` directory
+pub fn claude_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".claude"))
+}
+
+/// The `~/.claude/projects` directory
+pub fn claude_projects_dir() -> Option<PathBuf> {
+    claude_dir().map(|dir| dir.join("projects"))
+}
+
+/// Encode a project path the way Claude Code names its project directory
+pub fn encode_project_path(project_path: &str) -> String {
+    project_path
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Find the project directory holding a project's sessions.
+///
+/// Falls back to a directory whose sessions record the same `cwd`, for directories
+/// named by older Claude Code versions that only replaced path separators.
+pub fn project_dir_for_path(project_path: &str) -> Option<PathBuf> {
+    let projects_dir = claude_projects_dir()?;
+    let project_path = project_path.trim_end_matches(['/', '\\']);
+
+    let encoded = projects_dir.join(encode_project_path(project_path));
+    if encoded.is_dir() {
+        return Some(encoded);
+    }
+
+    let legacy = projects_dir.join(project_path.replace(['/', '\\'], "-"));
+    if legacy.is_dir() {
+        return Some(legacy);
+    }
+
+    fs::read_dir(&projects_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|dir| dir.is_dir())
+        .find(|dir| resolve_project_path(dir) == project_path)
+}
+
+/// Resolve the project path of a project directory under `~/.claude/projects`
+pub fn resolve_project_path(project_dir: &Path) -> String {
+    let project_id = project_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    if let Some(path) = cache().lock().ok().and_then(|c| c.get(&project_id).cloned()) {
+        return path;
+    }
+
+    match cwd_from_sessions(project_dir).or_else(|| probe_filesystem(&project_id)) {
+        Some(path) => {
+            if let Ok(mut cache) = cache().lock() {
+                cache.insert(project_id, path.clone());
+            }
+            path
+        }
+        None => {
+            // Not cached, so it's resolved again once a session records a cwd
+            log::warn!("Could not resolve project path for {}, falling back to decode", project_id);
+            naive_decode(&project_id)
+        }
+    }
+}
+
+/// Read the `cwd` recorded by any session in the project directory
+fn cwd_from_sessions(project_dir: &Path) -> Option<String> {
+    let entries = fs::read_dir(project_dir).ok()?;
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("jsonl") {
+            continue;
+        }
+        let Ok(file) = fs::File::open(&path) else {
+            continue;
+        };
+
+        let cwd = BufReader::new(file)
+            .lines()
+            .take(CWD_SCAN_LINES)
+            .map_while(Result::ok)
+            .find_map(|line| parse_line(&line).and_then(|line| line.meta.cwd));
+        if cwd.is_some() {
+            return cwd;
+        }
+    }
+
+    None
+}
+
+/// Split an encoded name into its filesystem root and the rest of the name
+fn split_root(encoded: &str) -> (String, &str) {
+    let bytes = encoded.as_bytes();
+    // Windows drive, e.g. `C--Users-me` for `C:\Users\me`
+    if bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && &bytes[1..3] == b"--" {
+        return (format!("{}:\\", &encoded[..1]), &encoded[3..]);
+    }
+    ("/".to_string(), encoded.strip_prefix('-').unwrap_or(encoded))
+}
+
+/// Find an existing directory whose path encodes to the given name
+fn probe_filesystem(encoded: &str) -> Option<String> {
+    let (root, rest) = split_root(encoded);
+    if rest.is_empty() {
+        return None;
+    }
+
+    let mut probes = 0;
+    probe(Path::new(&root), String::new(), rest, &mut probes)
+        .map(|path| path.to_string_lossy().to_string())
+}
+
+/// Try each reading of the next `-` in `rest`, either as a path separator or as a
+/// character inside the current component, and return the first path that exists
+fn probe(base: &Path, component: String, rest: &str, probes: &mut usize) -> Option<PathBuf> {
+    if *probes >= MAX_PROBES {
+        return None;
+    }
+
+    let Some(dash) = rest.find('-') else {
+        let component = component + rest;
+        *probes += 1;
+        let path = base.join(&component);
+        return (!component.is_empty() && path.is_dir()).then_some(path);
+    };
+    let current = component + &rest[..dash];
+    let rest = &rest[dash + 1..];
+
+    if !current.is_empty() {
+        let next = base.join(&current);
+        *probes += 1;
+        if next.is_dir() {
+            if let Some(path) = probe(&next, String::new(), rest, probes) {
+                return Some(path);
+            }
+        }
+    }
+
+    COMPONENT_SEPARATORS
+        .iter()
+        .find_map(|sep| probe(base, format!("{}{}", current, sep), rest, probes))
+}
+
+/// Decode by treating every `-` as a path separator; wrong for names containing hyphens
+fn naive_decode(encoded: &str) -> String {
+    let (root, rest) = split_root(encoded);
+    let separator = if root.ends_with('\\') { "\\" } else { "/" };
+    format!("{}{}", root, rest.replace('-', separator))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_project_path() {
+        assert_eq!(encode_project_path("/home/me/my_app.v2"), "-home-me-my-app-v2");
+        assert_eq!(encode_project_path("C:\\Users\\me"), "C--Users-me");
+    }
+
+    #[test]
+    fn test_probe_filesystem_finds_hyphenated_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("dev").join("jsonl-viewer").join(".config");
+        fs::create_dir_all(&project).unwrap();
+        fs::create_dir_all(root.path().join("dev").join("jsonl")).unwrap();
+
+        let encoded = encode_project_path(&project.to_string_lossy());
+        assert_eq!(probe_filesystem(&encoded), Some(project.to_string_lossy().to_string()));
+
+        // Directories named by other tools may hold any characters
+        assert_eq!(split_root("Cé-x"), ("/".to_string(), "Cé-x"));
+        assert_eq!(split_root("C--Users-me"), ("C:\\".to_string(), "Users-me"));
+    }
+}
@@ -0,0 +1,382 @@
+//! Typed model of Claude's stream-json output and session JSONL files
+//!
+//! Both formats share one line shape: a `type` tag, the event's own fields, and
+//! session metadata. Parsing is tolerant: unknown fields are ignored, unknown event
+//! and content block types parse as `Unknown`, and missing or null fields fall back to defaults.
+//! Lines sent on to the frontend go through [`StreamLine::to_json_with_raw`], which puts
+//! back whatever the typed model dropped.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value as JsonValue;
+
+/// One parsed line of stream-json output or a session JSONL file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StreamLine {
+    #[serde(flatten)]
+    pub event: StreamEvent,
+    #[serde(flatten)]
+    pub meta: LineMeta,
+}
+
+/// Session metadata carried alongside events
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LineMeta {
+    #[serde(default, alias = "sessionId", skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    #[serde(default, alias = "parentUuid", skip_serializing_if = "Option::is_none")]
+    pub parent_uuid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(default, alias = "requestId", skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Cost recorded per message by older Claude Code versions
+    #[serde(default, alias = "costUSD", skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+/// Kind of line, tagged by its `type` field
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    System(SystemEvent),
+    Assistant(MessageEvent),
+    User(MessageEvent),
+    Result(ResultEvent),
+    /// Session title written by Claude Code into session files
+    Summary(SummaryEvent),
+    #[serde(other)]
+    Unknown,
+}
+
+/// A `system` line, e.g. the `init` line that carries the session ID
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SystemEvent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subtype: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, deserialize_with = "null_as_default", skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+}
+
+/// An `assistant` or `user` line wrapping an API message
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MessageEvent {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub message: Message,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_tool_use_id: Option<String>,
+    /// Set on user lines Claude Code injects rather than the user typed
+    #[serde(default, alias = "isMeta", deserialize_with = "null_as_default", skip_serializing_if = "std::ops::Not::not")]
+    pub is_meta: bool,
+}
+
+/// An API message
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Message {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Plain string content is read as a single text block
+    #[serde(default, deserialize_with = "deserialize_content")]
+    pub content: Vec<ContentBlock>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+}
+
+/// A block of message content
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        #[serde(default, deserialize_with = "null_as_default")]
+        text: String,
+    },
+    Thinking {
+        #[serde(default, deserialize_with = "null_as_default")]
+        thinking: String,
+    },
+    ToolUse {
+        #[serde(default, deserialize_with = "null_as_default")]
+        id: String,
+        #[serde(default, deserialize_with = "null_as_default")]
+        name: String,
+        #[serde(default)]
+        input: JsonValue,
+    },
+    ToolResult {
+        #[serde(default, deserialize_with = "null_as_default")]
+        tool_use_id: String,
+        /// A string or a list of content blocks, kept as sent
+        #[serde(default)]
+        content: JsonValue,
+        #[serde(default, deserialize_with = "null_as_default", skip_serializing_if = "std::ops::Not::not")]
+        is_error: bool,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+/// Token usage of a message or a whole run
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct Usage {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub input_tokens: u64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub output_tokens: u64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub cache_creation_input_tokens: u64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub cache_read_input_tokens: u64,
+}
+
+/// The final `result` line of a run
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ResultEvent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subtype: Option<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub is_error: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_turns: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+    #[serde(default, alias = "cost_usd", skip_serializing_if = "Option::is_none")]
+    pub total_cost_usd: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+}
+
+/// A `summary` line naming a conversation
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SummaryEvent {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub summary: String,
+    #[serde(default, alias = "leafUuid", skip_serializing_if = "Option::is_none")]
+    pub leaf_uuid: Option<String>,
+}
+
+/// Read `null` as the type's default, like a missing field
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+fn deserialize_content<'de, D>(deserializer: D) -> Result<Vec<ContentBlock>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Content {
+        Text(String),
+        Blocks(Vec<ContentBlock>),
+        Other(serde::de::IgnoredAny),
+    }
+
+    Ok(match Content::deserialize(deserializer)? {
+        Content::Text(text) => vec![ContentBlock::Text { text }],
+        Content::Blocks(blocks) => blocks,
+        Content::Other(_) => Vec::new(),
+    })
+}
+
+/// Fill in what typed serialization dropped from the original JSON. Objects gain the
+/// fields they lack, `unknown` types get their original tag back, and arrays of the same
+/// length merge item by item; where both sides have a value the typed one wins.
+fn merge_raw(typed: &mut JsonValue, raw: JsonValue) {
+    match (typed, raw) {
+        (JsonValue::Object(typed), JsonValue::Object(raw)) => {
+            if typed.get("type").and_then(JsonValue::as_str) == Some("unknown") {
+                if let Some(tag) = raw.get("type") {
+                    typed.insert("type".to_string(), tag.clone());
+                }
+            }
+            for (key, raw_value) in raw {
+                match typed.get_mut(&key) {
+                    Some(typed_value) => merge_raw(typed_value, raw_value),
+                    None => {
+                        typed.insert(key, raw_value);
+                    }
+                }
+            }
+        }
+        (JsonValue::Array(typed), JsonValue::Array(raw)) if typed.len() == raw.len() => {
+            for (typed_item, raw_item) in typed.iter_mut().zip(raw) {
+                merge_raw(typed_item, raw_item);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Parse one line, returning `None` for blank lines and lines that aren't JSON objects with a `type`
+pub fn parse_line(line: &str) -> Option<StreamLine> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    serde_json::from_str(line).ok()
+}
+
+impl StreamLine {
+    /// Session ID announced by a `system` `init` line
+    pub fn init_session_id(&self) -> Option<&str> {
+        match &self.event {
+            StreamEvent::System(system) if system.subtype.as_deref() == Some("init") => {
+                self.meta.session_id.as_deref()
+            }
+            _ => None,
+        }
+    }
+
+    /// The wrapped message of an `assistant` or `user` line
+    pub fn message(&self) -> Option<&Message> {
+        match &self.event {
+            StreamEvent::Assistant(event) | StreamEvent::User(event) => Some(&event.message),
+            _ => None,
+        }
+    }
+
+    /// Token usage of a message, or of the whole run for a `result` line
+    pub fn usage(&self) -> Option<&Usage> {
+        match &self.event {
+            StreamEvent::Result(result) => result.usage.as_ref(),
+            _ => self.message().and_then(|m| m.usage.as_ref()),
+        }
+    }
+
+    /// Cost reported by the line itself, if any
+    pub fn cost_usd(&self) -> Option<f64> {
+        match &self.event {
+            StreamEvent::Result(result) => result.total_cost_usd,
+            _ => self.meta.cost_usd,
+        }
+    }
+
+    /// Tool calls in the line's message as `(name, input)` pairs
+    pub fn tool_uses(&self) -> impl Iterator<Item = (&str, &JsonValue)> {
+        self.message()
+            .into_iter()
+            .flat_map(|m| m.content.iter())
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse { name, input, .. } => Some((name.as_str(), input)),
+                _ => None,
+            })
+    }
+
+    /// The line as JSON for the frontend: the typed fields under their normalized names,
+    /// laid over the original line so unknown fields, events and content blocks survive
+    pub fn to_json_with_raw(&self, raw: &str) -> JsonValue {
+        let mut value = serde_json::to_value(self).unwrap_or(JsonValue::Null);
+        if let Ok(raw) = serde_json::from_str::<JsonValue>(raw.trim()) {
+            merge_raw(&mut value, raw);
+        }
+        value
+    }
+
+    /// Text blocks of the line's message joined by newlines, `None` if it has none
+    pub fn text(&self) -> Option<String> {
+        let texts: Vec<&str> = self
+            .message()?
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        (!texts.is_empty()).then(|| texts.join("\n"))
+    }
+}
+
+impl Usage {
+    /// Input and output tokens, excluding cache reads and writes
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    /// Whether any tokens were counted at all
+    pub fn is_empty(&self) -> bool {
+        self.input_tokens == 0
+            && self.output_tokens == 0
+            && self.cache_creation_input_tokens == 0
+            && self.cache_read_input_tokens == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stream_and_session_lines() {
+        let init = parse_line(
+            r#"{"type":"system","subtype":"init","session_id":"abc","model":"sonnet","extra":1}"#,
+        )
+        .unwrap();
+        assert_eq!(init.init_session_id(), Some("abc"));
+
+        let assistant = parse_line(
+            r#"{"type":"assistant","sessionId":"abc","timestamp":"2025-01-01T00:00:00Z","requestId":"r1",
+                "message":{"id":"m1","model":"claude-sonnet-4","content":[
+                    {"type":"text","text":"hi"},
+                    {"type":"tool_use","id":"t1","name":"Write","input":{"file_path":"a.rs"}},
+                    {"type":"server_tool_use","id":"t2"}],
+                "usage":{"input_tokens":3,"output_tokens":4}}}"#,
+        )
+        .unwrap();
+        assert_eq!(assistant.meta.session_id.as_deref(), Some("abc"));
+        assert_eq!(assistant.text().as_deref(), Some("hi"));
+        assert_eq!(assistant.tool_uses().map(|(name, _)| name).collect::<Vec<_>>(), vec!["Write"]);
+        assert_eq!(assistant.usage().map(Usage::total_tokens), Some(7));
+
+        let user = parse_line(r#"{"type":"user","message":{"role":"user","content":"do it"}}"#).unwrap();
+        assert_eq!(user.text().as_deref(), Some("do it"));
+
+        let nulls = parse_line(
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":null}],
+                "usage":{"input_tokens":5,"output_tokens":null,"cache_read_input_tokens":null}}}"#,
+        )
+        .unwrap();
+        assert_eq!(nulls.text().as_deref(), Some(""));
+        assert_eq!(nulls.usage().map(Usage::total_tokens), Some(5));
+
+        let unknown = parse_line(r#"{"type":"progress","data":{}}"#).unwrap();
+        assert_eq!(unknown.event, StreamEvent::Unknown);
+        assert!(parse_line("not json").is_none());
+    }
+
+    #[test]
+    fn test_to_json_with_raw_keeps_unknown_content() {
+        let raw = r#"{"type":"assistant","sessionId":"abc","isSidechain":false,
+            "message":{"content":[
+                {"type":"text","text":"hi","citations":[]},
+                {"type":"server_tool_use","id":"t2","name":"web_search"}]}}"#;
+        let value = parse_line(raw).unwrap().to_json_with_raw(raw);
+
+        assert_eq!(value["session_id"], "abc");
+        assert_eq!(value["isSidechain"], false);
+        let blocks = &value["message"]["content"];
+        assert_eq!(blocks[0]["citations"], serde_json::json!([]));
+        assert_eq!(blocks[1], serde_json::json!({"type":"server_tool_use","id":"t2","name":"web_search"}));
+
+        let raw = r#"{"type":"progress","data":{"step":1}}"#;
+        let value = parse_line(raw).unwrap().to_json_with_raw(raw);
+        assert_eq!(value, serde_json::json!({"type":"progress","data":{"step":1}}));
+    }
+}
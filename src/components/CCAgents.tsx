@@ -247,6 +247,29 @@ export const CCAgents: React.FC<CCAgentsProps> = ({ onBack, className }) => {
         return;
       }
 
+      // Ask before importing an agent whose MCP servers or env vars are missing
+      const report = await api.checkAgentFileDependencies(filePath as string);
+      const missing = [
+        ...report.missing_mcp_servers.map((name) => `MCP: ${name}`),
+        ...report.missing_env.map((key) => `ENV: ${key}`),
+      ];
+      if (
+        missing.length > 0 &&
+        !globalThis.confirm(t.agents.missingDependenciesConfirm.replace("{missing}", missing.join("\n")))
+      ) {
+        return;
+      }
+
+      // Under the "warn" policy, ask before importing an agent without a trusted signature
+      const trust = await api.verifyAgentFileSignature(filePath as string);
+      if (
+        trust.policy === "warn" &&
+        trust.signature.status !== "verified" &&
+        !globalThis.confirm(t.agents.unsignedAgentConfirm)
+      ) {
+        return;
+      }
+
       // Import the agent from the selected file (default to claudia)
       await api.importAgentFromFile(filePath as string, 'claudia');
 
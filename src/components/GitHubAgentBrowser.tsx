@@ -10,6 +10,7 @@ import { api, type GitHubAgentFile, type AgentExport, type Agent } from "@/lib/a
 import { AGENT_ICONS } from "@/constants/agentIcons";
 import { open } from "@tauri-apps/plugin-shell";
 import { handleError } from "@/lib/errorHandler";
+import { useI18n } from "@/lib/i18n";
 /**
  * Props interface for the GitHubAgentBrowser component
  */
@@ -53,6 +54,7 @@ export const GitHubAgentBrowser: React.FC<GitHubAgentBrowserProps> = ({
   onClose,
   onImportSuccess,
 }) => {
+  const { t } = useI18n();
   const [agents, setAgents] = useState<GitHubAgentFile[]>([]);
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
@@ -128,7 +130,37 @@ export const GitHubAgentBrowser: React.FC<GitHubAgentBrowserProps> = ({
 
     try {
       setImporting(true);
-      await api.importAgentFromGitHub(selectedAgent.file.download_url);
+
+      // Ask before importing an agent whose MCP servers or env vars are missing
+      if (selectedAgent.data) {
+        const report = await api.checkAgentImportDependencies(JSON.stringify(selectedAgent.data));
+        const missing = [
+          ...report.missing_mcp_servers.map((name) => `MCP: ${name}`),
+          ...report.missing_env.map((key) => `ENV: ${key}`),
+        ];
+        if (
+          missing.length > 0 &&
+          !globalThis.confirm(t.agents.missingDependenciesConfirm.replace("{missing}", missing.join("\n")))
+        ) {
+          return;
+        }
+      }
+
+      // Under the "warn" policy, ask before importing an agent without a trusted signature
+      const trust = await api.verifyAgentSourceSignature(selectedAgent.file.download_url);
+      if (
+        trust.policy === "warn" &&
+        trust.signature.status !== "verified" &&
+        !globalThis.confirm(t.agents.unsignedAgentConfirm)
+      ) {
+        return;
+      }
+
+      await api.importAgentFromGitHub(
+        selectedAgent.file.download_url,
+        selectedAgent.file.path,
+        selectedAgent.file.sha
+      );
 
       // Refresh existing agents list
       await fetchExistingAgents();
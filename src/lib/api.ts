@@ -59,10 +59,273 @@ export interface Session {
   todo_data?: unknown;
   /** Unix timestamp when the session file was created */
   created_at: number;
+  /** Unix timestamp when the session file was last written */
+  modified_at: number;
   /** First user message content (if available) */
   first_message?: string;
   /** Timestamp of the first user message (if available) */
   message_timestamp?: string;
+  /** User-assigned title */
+  title?: string;
+  /** User-assigned tags */
+  tags: string[];
+  /** Whether the session is pinned to the top of the listing */
+  pinned: boolean;
+  /** Whether the session has been moved out of the active listing */
+  archived: boolean;
+  /** User notes */
+  notes?: string;
+  /** Title generated by the session summarizer */
+  summary_title?: string;
+  /** Paragraph summary generated by the session summarizer */
+  summary?: string;
+}
+
+/**
+ * A generated title and summary of a session
+ */
+export interface SessionSummary {
+  session_id: string;
+  project_id: string;
+  project_path: string;
+  title: string;
+  summary: string;
+  /** Model that wrote the summary */
+  model: string;
+  /** Size of the session file when summarized */
+  source_size: number;
+  started_at?: string;
+  ended_at?: string;
+  /** Models the session used */
+  models: string[];
+  created_at: string;
+}
+
+/**
+ * A session forked from a message of another session
+ */
+export interface SessionFork {
+  session_id: string;
+  project_id: string;
+  project_path: string;
+  parent_session_id: string;
+  /** Index of the last message copied from the parent */
+  fork_message_index: number;
+  /** `uuid` of that message in the parent */
+  fork_message_uuid?: string;
+  created_at: string;
+}
+
+/**
+ * A session and the sessions forked from it
+ */
+export interface SessionTreeNode {
+  session_id: string;
+  /** Where the session was forked from its parent, if it is a fork */
+  fork_message_index?: number;
+  fork_message_uuid?: string;
+  created_at?: string;
+  /** False once the session was deleted */
+  exists: boolean;
+  children: SessionTreeNode[];
+}
+
+/**
+ * What to do when an imported session's ID is already taken
+ */
+export type SessionIdConflict = "fail" | "rename" | "replace";
+
+/**
+ * Options of a session import
+ */
+export interface SessionImportOptions {
+  /** Local project path to remap the recorded `cwd` to; defaults to the recorded path */
+  project_path?: string;
+  on_conflict?: SessionIdConflict;
+}
+
+/**
+ * What a session file would import
+ */
+export interface SessionImportPreview {
+  session_id: string;
+  /** Project path the session was recorded in */
+  recorded_project_path?: string;
+  message_count: number;
+  has_todos: boolean;
+  /** Project already holding a session with the same ID */
+  existing_project_id?: string;
+}
+
+/**
+ * A session placed under ~/.claude/projects by an import
+ */
+export interface SessionImportResult {
+  session_id: string;
+  /** ID the session had in the imported file */
+  original_session_id: string;
+  project_id: string;
+  project_path: string;
+  recorded_project_path?: string;
+  message_count: number;
+  has_todos: boolean;
+}
+
+/**
+ * A deleted session waiting in the trash
+ */
+export interface TrashedSession {
+  /** ID of the trash entry */
+  id: string;
+  session_id: string;
+  project_id: string;
+  first_message?: string;
+  has_todos: boolean;
+  has_timeline: boolean;
+  deleted_at: string;
+  /** When the session is purged for good */
+  expires_at: string;
+}
+
+/**
+ * Settings of the background session summarizer
+ */
+export interface SessionSummarizerSettings {
+  /** Whether finished sessions are summarized in the background */
+  enabled: boolean;
+  /** Model passed to claude -p, e.g. "haiku" */
+  model: string;
+  /** Minutes without writes after which a session counts as finished */
+  idle_minutes: number;
+}
+
+/**
+ * User-assigned metadata of a session
+ */
+export interface SessionMeta {
+  session_id: string;
+  project_id: string;
+  title?: string;
+  tags: string[];
+  pinned: boolean;
+  /** Set by archiveSession, ignored by setSessionMeta */
+  archived: boolean;
+  notes?: string;
+  updated_at?: string;
+}
+
+/**
+ * Filters and ordering for getProjectSessions; pinned sessions always come first
+ */
+export interface SessionListQuery {
+  /** Which sessions to include by archive state (defaults to "active") */
+  archived?: "active" | "archived" | "all";
+  /** Only sessions carrying all of these tags */
+  tags?: string[];
+  pinned_only?: boolean;
+  /** Case-insensitive match against title, first message and notes */
+  text?: string;
+  /** Defaults to "created_at" */
+  sort_by?: "created_at" | "last_activity" | "title";
+  /** Reverse the default order of newest first, or A–Z for titles */
+  reverse?: boolean;
+}
+
+/**
+ * A search over all indexed Claude sessions
+ */
+export interface SessionSearchQuery {
+  /** Terms to match; every term must appear, the last one as a prefix */
+  query: string;
+  /** Only sessions of this project path */
+  project_path?: string;
+  /** Only messages at or after this RFC 3339 timestamp or date */
+  from?: string;
+  /** Only messages at or before this RFC 3339 timestamp or date */
+  to?: string;
+  /** Only sessions that used a model whose name contains this */
+  model?: string;
+  limit?: number;
+}
+
+/**
+ * A session message matching a search
+ */
+export interface SessionSearchHit {
+  project_id: string;
+  project_path: string;
+  session_id: string;
+  /** Index of the message in the array returned by loadSessionHistory; 0 for summary hits */
+  message_index: number;
+  /** "summary" for matches in a generated session summary */
+  role: "user" | "assistant" | "summary";
+  model?: string;
+  timestamp?: string;
+  /** Matched text with terms wrapped in <mark></mark>; the rest is unescaped text */
+  snippet: string;
+  tool_names: string[];
+  file_paths: string[];
+}
+
+/**
+ * Outcome of updating the session search index
+ */
+export interface SessionIndexStats {
+  files_scanned: number;
+  files_updated: number;
+  files_removed: number;
+  messages_added: number;
+}
+
+/**
+ * Outcome of updating the usage index
+ */
+export interface UsageIndexStats {
+  files_scanned: number;
+  files_updated: number;
+  files_removed: number;
+  entries_added: number;
+  /** Entries whose cost was recomputed after the prices changed */
+  entries_repriced: number;
+}
+
+/**
+ * What usage entries are totalled by in a usage export
+ */
+export type UsageGrouping = "day" | "model" | "project" | "session";
+
+/**
+ * What a usage export contains
+ */
+export interface UsageExportOptions {
+  format: "csv" | "json";
+  /** Aggregate entries by this key; raw entries are exported when unset */
+  group_by?: UsageGrouping;
+  /** First day to include, as YYYY-MM-DD */
+  start_date?: string;
+  /** Last day to include, as YYYY-MM-DD */
+  end_date?: string;
+}
+
+/**
+ * Output format of a session export
+ */
+export type SessionExportFormat = "markdown" | "html" | "json";
+
+/**
+ * What a session export renders and what it redacts
+ */
+export interface SessionExportOptions {
+  format: SessionExportFormat;
+  /** Include tool calls and their results (defaults to true) */
+  include_tool_calls?: boolean;
+  include_thinking?: boolean;
+  /** Replace file contents written by edit tools and returned by read tools */
+  redact_file_contents?: boolean;
+  /** Replace API keys, tokens, private keys and password assignments */
+  redact_secrets?: boolean;
+  /** Replace values of configured and sensitive environment variables with ${NAME} */
+  redact_env?: boolean;
 }
 
 /**
@@ -135,10 +398,37 @@ export interface Agent {
   source?: string; // 'claudia', 'native', 'user', etc.
   created_at: string;
   updated_at: string;
+  allowed_tools?: string; // JSON array of tool names
+  dependencies?: string; // JSON string of AgentDependencies
+  parameters?: string; // JSON array of AgentParameter declarations
+  collect_artifacts?: boolean; // Point runs at a per-run artifacts directory
+}
+
+export type AgentParameterType = "string" | "number" | "boolean" | "enum" | "path";
+
+export interface AgentParameter {
+  name: string;
+  type?: AgentParameterType;
+  default?: string | number | boolean;
+  description?: string;
+  required?: boolean;
+  /** Allowed values for enum parameters */
+  options?: string[];
+}
+
+export interface AgentPermissions {
+  enable_file_read: boolean;
+  enable_file_write: boolean;
+  enable_network: boolean;
+}
+
+export interface AgentDependencies {
+  mcp_servers: Record<string, MCPServerConfig>;
+  env: string[];
 }
 
 export interface AgentExport {
-  version: number;
+  version: number; // 1 or 2
   exported_at: string;
   agent: {
     name: string;
@@ -147,9 +437,25 @@ export interface AgentExport {
     default_task?: string;
     model: string;
     hooks?: string;
+    // Version 2 fields
+    permissions?: AgentPermissions;
+    allowed_tools?: string[];
+    required_mcp_servers?: Record<string, MCPServerConfig>;
+    required_env?: string[];
+    parameters?: AgentParameter[];
+    collect_artifacts?: boolean;
   };
 }
 
+export interface AgentDependencyReport {
+  agent_name: string;
+  export_version: number;
+  required_mcp_servers: Record<string, MCPServerConfig>;
+  required_env: string[];
+  missing_mcp_servers: string[];
+  missing_env: string[];
+}
+
 export interface GitHubAgentFile {
   name: string;
   path: string;
@@ -158,6 +464,58 @@ export interface GitHubAgentFile {
   sha: string;
 }
 
+export type RegistrySource =
+  | { type: "github"; owner: string; repo: string; path: string; branch?: string }
+  | { type: "http_index"; url: string }
+  | { type: "local"; path: string };
+
+export interface AgentRegistry {
+  id: string;
+  name: string;
+  enabled: boolean;
+  source: RegistrySource;
+}
+
+export interface RegistryAgentFile {
+  registry_id: string;
+  name: string;
+  path: string;
+  download_url: string;
+  size: number;
+  sha: string;
+}
+
+export interface AgentUpdateInfo {
+  agent_id: number;
+  agent_name: string;
+  registry_id: string;
+  path: string;
+  installed_sha: string;
+  latest_sha: string;
+  download_url: string;
+}
+
+export interface TrustedKey {
+  id: string;
+  name: string;
+  /** Base64-encoded ed25519 public key */
+  public_key: string;
+  added_at: string;
+}
+
+export type AgentImportPolicy = "allow_unsigned" | "warn" | "require_trusted";
+
+export type AgentSignatureStatus =
+  | { status: "unsigned" }
+  | { status: "verified"; key_id: string; key_name: string }
+  | { status: "unverified" };
+
+export interface AgentTrustReport {
+  signature: AgentSignatureStatus;
+  policy: AgentImportPolicy;
+  allowed: boolean;
+}
+
 export interface AgentRun {
   id?: number;
   agent_id: number;
@@ -172,115 +530,546 @@ export interface AgentRun {
   process_started_at?: string;
   created_at: string;
   completed_at?: string;
+  agent_version?: number; // Agent definition version used for this run
+  schedule_id?: number; // Schedule that started this run, if any
+  pipeline_run_id?: number; // Pipeline run this run is a step of, if any
+  pipeline_step?: number;
+  batch_id?: string; // Batch this run belongs to, if any
+  parameters?: string; // JSON object of resolved template parameters
+  retry_of?: number; // Original run this run retries or resumes, if any
+  attempt?: number; // 1 for an original run, 2+ for its retries
 }
 
-export interface AgentRunMetrics {
-  duration_ms?: number;
-  total_tokens?: number;
-  cost_usd?: number;
-  message_count?: number;
-}
+export type MissedRunPolicy = "run_once" | "skip";
 
-export interface AgentRunWithMetrics {
+export interface AgentSchedule {
   id?: number;
   agent_id: number;
-  agent_name: string;
-  agent_icon: string;
-  task: string;
-  model: string;
+  name: string;
+  /** Cron expression in local time; 5-field expressions are accepted */
+  cron_expression?: string;
+  /** Fixed interval in seconds, used when no cron expression is set */
+  interval_seconds?: number;
   project_path: string;
-  session_id: string;
-  status: string; // 'pending', 'running', 'completed', 'failed', 'cancelled'
-  pid?: number;
-  process_started_at?: string;
+  task?: string;
+  model?: string;
+  /** Values for the agent's declared template parameters */
+  parameters?: Record<string, string | number | boolean>;
+  enabled: boolean;
+  missed_run_policy: MissedRunPolicy;
+  next_run_at?: string;
+  last_run_at?: string;
+  last_run_id?: number;
+  last_error?: string;
+  created_at: string;
+  updated_at: string;
+}
+
+export type PipelineStepInput =
+  | { type: "none" }
+  | { type: "previous_output" }
+  | { type: "artifact"; path: string };
+
+export type PipelineStepCondition = "on_success" | "on_failure" | "always";
+
+export interface PipelineStep {
+  agent_id: number;
+  /** Task override; `{{input}}` marks where the step input goes */
+  task?: string;
+  model?: string;
+  input?: PipelineStepInput;
+  run_if?: PipelineStepCondition;
+  /** Values for the agent's declared template parameters */
+  parameters?: Record<string, string | number | boolean>;
+}
+
+export interface AgentPipeline {
+  id?: number;
+  name: string;
+  description?: string;
+  steps: PipelineStep[];
+  created_at: string;
+  updated_at: string;
+}
+
+export interface PipelineRun {
+  id?: number;
+  pipeline_id: number;
+  pipeline_name: string;
+  project_path: string;
+  status: string; // 'running', 'completed', 'failed', 'cancelled'
+  current_step?: number;
+  error?: string;
   created_at: string;
   completed_at?: string;
-  metrics?: AgentRunMetrics;
-  output?: string; // Real-time JSONL content
 }
 
-// Usage Dashboard types
-export interface UsageEntry {
-  project: string;
-  timestamp: string;
-  model: string;
-  input_tokens: number;
-  output_tokens: number;
-  cache_write_tokens: number;
-  cache_read_tokens: number;
-  cost: number;
+export interface PipelineRunWithSteps extends PipelineRun {
+  step_runs: AgentRun[];
 }
 
-export interface ModelUsage {
-  model: string;
-  total_cost: number;
-  total_tokens: number;
-  input_tokens: number;
-  output_tokens: number;
-  cache_creation_tokens: number;
-  cache_read_tokens: number;
-  session_count: number;
+export interface AgentBatchRequest {
+  agent_id: number;
+  task: string;
+  model?: string;
+  project_paths?: string[];
+  /** Glob whose matching directories are added to project_paths */
+  project_glob?: string;
+  max_concurrency?: number;
+  /** Values for the agent's declared template parameters, shared by every project */
+  parameters?: Record<string, string | number | boolean>;
 }
 
-export interface DailyUsage {
-  date: string;
-  total_cost: number;
-  total_tokens: number;
-  models_used: string[];
+export interface AgentBatch {
+  id: string;
+  agent_id: number;
+  agent_name: string;
+  task: string;
+  model?: string;
+  max_concurrency: number;
+  status: string; // 'running', 'completed', 'cancelled'
+  created_at: string;
+  completed_at?: string;
+  parameters?: Record<string, string | number | boolean>;
 }
 
-export interface ProjectUsage {
+export interface BatchProjectSummary {
   project_path: string;
-  project_name: string;
-  total_cost: number;
-  total_tokens: number;
-  session_count: number;
-  last_used: string;
+  run_id: number;
+  status: string;
+  session_id: string;
+  cost_usd?: number;
+  duration_ms?: number;
+  total_tokens?: number;
 }
 
-export interface UsageStats {
-  total_cost: number;
+export interface AgentBatchSummary extends AgentBatch {
+  total: number;
+  pending: number;
+  running: number;
+  completed: number;
+  failed: number;
+  cancelled: number;
+  total_cost_usd: number;
+  total_duration_ms: number;
   total_tokens: number;
-  total_input_tokens: number;
-  total_output_tokens: number;
-  total_cache_creation_tokens: number;
-  total_cache_read_tokens: number;
-  total_sessions: number;
-  by_model: ModelUsage[];
-  by_date: DailyUsage[];
-  by_project: ProjectUsage[];
+  projects: BatchProjectSummary[];
 }
 
 /**
- * Represents a checkpoint in the session timeline
+ * A named file an agent run wrote to its artifact directory
  */
-export interface Checkpoint {
-  id: string;
-  sessionId: string;
-  projectId: string;
-  messageIndex: number;
-  timestamp: string;
-  description?: string;
-  parentCheckpointId?: string;
-  metadata: CheckpointMetadata;
+export interface AgentRunArtifact {
+  name: string;
+  path: string;
+  size: number;
 }
 
 /**
- * Metadata associated with a checkpoint
+ * Structured outcome of a finished agent run
  */
-export interface CheckpointMetadata {
-  totalTokens: number;
-  modelUsed: string;
-  userPrompt: string;
-  fileChanges: number;
-  snapshotSize: number;
+export interface AgentRunResult {
+  run_id: number;
+  result_message?: string;
+  is_error: boolean;
+  result_subtype?: string;
+  num_turns?: number;
+  exit_code?: number;
+  files_changed: string[];
+  artifacts: AgentRunArtifact[];
+  created_at: string;
 }
 
 /**
- * Represents a file snapshot at a checkpoint
+ * Token usage of a message or a whole run
  */
-export interface FileSnapshot {
-  checkpointId: string;
+export interface StreamUsage {
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_input_tokens: number;
+  cache_read_input_tokens: number;
+}
+
+/**
+ * A block of message content; unrecognised block types arrive with their own type and fields
+ */
+export type StreamContentBlock =
+  | { type: "text"; text: string }
+  | { type: "thinking"; thinking: string }
+  | { type: "tool_use"; id: string; name: string; input: unknown }
+  | { type: "tool_result"; tool_use_id: string; content: unknown; is_error?: boolean }
+  | { type: string; [key: string]: unknown };
+
+/**
+ * An API message wrapped by assistant and user lines
+ */
+export interface StreamMessage {
+  id?: string;
+  role?: string;
+  model?: string;
+  content: StreamContentBlock[];
+  stop_reason?: string;
+  usage?: StreamUsage;
+}
+
+/**
+ * Session metadata carried by every line
+ */
+export interface StreamLineMeta {
+  session_id?: string;
+  uuid?: string;
+  parent_uuid?: string;
+  timestamp?: string;
+  cwd?: string;
+  request_id?: string;
+  cost_usd?: number;
+}
+
+/**
+ * One typed line of Claude's stream-json output or a session JSONL file.
+ * Emitted as `claude-event:{sessionId}` and `agent-event:{runId}` alongside the raw output events.
+ * Fields and types not listed here are passed through as Claude wrote them.
+ */
+export type StreamLine = StreamLineMeta &
+  (
+    | { type: "system"; subtype?: string; model?: string; tools?: string[] }
+    | {
+        type: "assistant" | "user";
+        message: StreamMessage;
+        parent_tool_use_id?: string;
+        is_meta?: boolean;
+      }
+    | {
+        type: "result";
+        subtype?: string;
+        is_error: boolean;
+        result?: string;
+        num_turns?: number;
+        duration_ms?: number;
+        total_cost_usd?: number;
+        usage?: StreamUsage;
+      }
+    | { type: "summary"; summary: string; leaf_uuid?: string }
+    | { type: string; [key: string]: unknown }
+  );
+
+/**
+ * New complete lines appended to a running session's JSONL file
+ */
+export interface SessionOutputChunk {
+  run_id: number;
+  offset: number; // Byte offset just past the last line, to resume streaming from
+  lines: StreamLine[];
+}
+
+/**
+ * Why an agent run failed
+ */
+export type AgentFailureClass =
+  | "api_error"
+  | "rate_limit"
+  | "killed"
+  | "timeout"
+  | "max_turns"
+  | "cancelled"
+  | "error";
+
+/**
+ * Automatic retry settings of an agent
+ */
+export interface AgentRetryPolicy {
+  max_attempts: number;
+  backoff_seconds: number;
+  backoff_multiplier?: number;
+  retry_on?: AgentFailureClass[];
+}
+
+export interface AgentScheduleInput {
+  agent_id: number;
+  name: string;
+  cron_expression?: string;
+  interval_seconds?: number;
+  project_path: string;
+  task?: string;
+  model?: string;
+  parameters?: Record<string, string | number | boolean>;
+  enabled?: boolean;
+  missed_run_policy?: MissedRunPolicy;
+}
+
+export interface AgentVersion {
+  id?: number;
+  agent_id: number;
+  version: number;
+  name: string;
+  icon: string;
+  system_prompt: string;
+  default_task?: string;
+  model: string;
+  enable_file_read: boolean;
+  enable_file_write: boolean;
+  enable_network: boolean;
+  hooks?: string;
+  change_note?: string;
+  created_at: string;
+  collect_artifacts?: boolean;
+}
+
+export interface AgentFieldChange {
+  field: string;
+  from?: string;
+  to?: string;
+}
+
+export interface AgentVersionDiff {
+  agent_id: number;
+  from_version: number;
+  to_version: number;
+  changed_fields: AgentFieldChange[];
+  system_prompt_diff?: string;
+  hooks_diff?: string;
+}
+
+export interface AgentRunMetrics {
+  duration_ms?: number;
+  total_tokens?: number;
+  cost_usd?: number;
+  message_count?: number;
+}
+
+export interface AgentRunWithMetrics {
+  id?: number;
+  agent_id: number;
+  agent_name: string;
+  agent_icon: string;
+  task: string;
+  model: string;
+  project_path: string;
+  session_id: string;
+  status: string; // 'pending', 'running', 'completed', 'failed', 'cancelled'
+  pid?: number;
+  process_started_at?: string;
+  created_at: string;
+  completed_at?: string;
+  agent_version?: number;
+  schedule_id?: number;
+  pipeline_run_id?: number;
+  pipeline_step?: number;
+  batch_id?: string;
+  parameters?: string;
+  retry_of?: number;
+  attempt?: number;
+  metrics?: AgentRunMetrics;
+  output?: string; // Real-time JSONL content
+}
+
+// Usage Dashboard types
+export interface UsageEntry {
+  project: string;
+  timestamp: string;
+  model: string;
+  input_tokens: number;
+  output_tokens: number;
+  cache_write_tokens: number;
+  cache_read_tokens: number;
+  cost: number;
+}
+
+export interface ModelUsage {
+  model: string;
+  total_cost: number;
+  total_tokens: number;
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_tokens: number;
+  cache_read_tokens: number;
+  session_count: number;
+}
+
+export interface DailyUsage {
+  date: string;
+  total_cost: number;
+  total_tokens: number;
+  models_used: string[];
+}
+
+export interface ProjectUsage {
+  project_path: string;
+  project_name: string;
+  total_cost: number;
+  total_tokens: number;
+  session_count: number;
+  last_used: string;
+}
+
+export interface AgentUsage {
+  agent_id: number;
+  agent_name: string;
+  agent_icon: string;
+  total_cost: number;
+  total_tokens: number;
+  run_count: number;
+  avg_cost_per_run: number;
+  last_used: string;
+}
+
+export interface SourceUsage {
+  /** "manual" for sessions started by the user, "agent" for agent runs */
+  source: "manual" | "agent";
+  total_cost: number;
+  total_tokens: number;
+  session_count: number;
+}
+
+/**
+ * Usage spent by one agent run
+ */
+export interface AgentRunUsage {
+  run_id: number;
+  agent_id: number;
+  agent_name: string;
+  agent_icon: string;
+  task: string;
+  model: string;
+  status: string;
+  project_path: string;
+  session_id: string;
+  created_at: string;
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_tokens: number;
+  cache_read_tokens: number;
+  total_tokens: number;
+  total_cost: number;
+}
+
+export interface UsageStats {
+  total_cost: number;
+  total_tokens: number;
+  total_input_tokens: number;
+  total_output_tokens: number;
+  total_cache_creation_tokens: number;
+  total_cache_read_tokens: number;
+  total_sessions: number;
+  by_model: ModelUsage[];
+  by_date: DailyUsage[];
+  by_project: ProjectUsage[];
+  by_agent: AgentUsage[];
+  /** Usage of manual sessions and of agent runs, in that order */
+  by_source: SourceUsage[];
+  /** Models without a price, whose usage is counted at no cost */
+  unknown_models: string[];
+  /** Version of the prices the costs were computed with */
+  pricing_version: string;
+}
+
+/**
+ * Price of a model in USD per million tokens, over an optional range of dates
+ */
+export interface ModelPrice {
+  /** Substrings of the model names the price applies to */
+  patterns: string[];
+  /** Model names the price applies to exactly, such as the `sonnet` alias */
+  aliases: string[];
+  input_price: number;
+  output_price: number;
+  cache_write_price: number;
+  cache_read_price: number;
+  /** First day the price applies, YYYY-MM-DD */
+  effective_from?: string;
+  /** First day the price no longer applies, YYYY-MM-DD */
+  effective_until?: string;
+}
+
+/**
+ * The period a budget's spend is counted over, in local time
+ */
+export type BudgetPeriod = "daily" | "monthly";
+
+/**
+ * A spend limit over a day or month, for all usage or one project's
+ */
+export interface Budget {
+  id?: number;
+  /** Project the budget applies to, or all projects when unset */
+  project_path?: string;
+  period: BudgetPeriod;
+  limit_usd: number;
+  /** Percentages of the limit at which to notify */
+  thresholds: number[];
+  /** Block new Claude Code and agent launches once the limit is reached */
+  hard_limit: boolean;
+  enabled: boolean;
+  created_at?: string;
+  updated_at?: string;
+}
+
+/**
+ * A budget with its spend over the current period
+ */
+export interface BudgetStatus {
+  budget: Budget;
+  /** Start of the current period, in UTC */
+  period_start: string;
+  spent_usd: number;
+  percent_used: number;
+  exceeded: boolean;
+  /** Whether the budget currently blocks launches */
+  blocking: boolean;
+}
+
+/**
+ * Payload of the `budget-alert` event, sent when a budget threshold is first crossed in a period
+ */
+export interface BudgetAlert {
+  budget_id: number;
+  project_path?: string;
+  period: BudgetPeriod;
+  threshold: number;
+  spent_usd: number;
+  limit_usd: number;
+}
+
+/**
+ * The prices usage is costed with: the user's overrides ahead of the bundled prices
+ */
+export interface PricingTable {
+  /** Identifies the prices, changing whenever the overrides do */
+  version: string;
+  overrides: ModelPrice[];
+  bundled: ModelPrice[];
+}
+
+/**
+ * Represents a checkpoint in the session timeline
+ */
+export interface Checkpoint {
+  id: string;
+  sessionId: string;
+  projectId: string;
+  messageIndex: number;
+  timestamp: string;
+  description?: string;
+  parentCheckpointId?: string;
+  metadata: CheckpointMetadata;
+}
+
+/**
+ * Metadata associated with a checkpoint
+ */
+export interface CheckpointMetadata {
+  totalTokens: number;
+  modelUsed: string;
+  userPrompt: string;
+  fileChanges: number;
+  snapshotSize: number;
+}
+
+/**
+ * Represents a file snapshot at a checkpoint
+ */
+export interface FileSnapshot {
+  checkpointId: string;
   filePath: string;
   content: string;
   hash: string;
@@ -462,407 +1251,1022 @@ export interface AddServerResult {
   server_name?: string;
 }
 
-/**
- * Import result for multiple servers
- */
-export interface ImportResult {
-  imported_count: number;
-  failed_count: number;
-  servers: ImportServerResult[];
-}
+/**
+ * Import result for multiple servers
+ */
+export interface ImportResult {
+  imported_count: number;
+  failed_count: number;
+  servers: ImportServerResult[];
+}
+
+/**
+ * Result for individual server import
+ */
+export interface ImportServerResult {
+  name: string;
+  success: boolean;
+  error?: string;
+}
+
+/**
+ * API client for interacting with the Rust backend
+ */
+export const api = {
+  /**
+   * Lists all projects in the ~/.claude/projects directory
+   * @returns Promise resolving to an array of projects
+   */
+  async listProjects(): Promise<Project[]> {
+    try {
+      return await invoke<Project[]>("list_projects");
+    } catch (error) {
+      await handleApiError(error as Error, { operation: "listProjects" });
+      throw error;
+    }
+  },
+
+  /**
+   * Retrieves sessions for a specific project
+   * @param projectId - The ID of the project to retrieve sessions for
+   * @param query - Optional filters and ordering; active sessions, newest first by default
+   * @returns Promise resolving to an array of sessions
+   */
+  async getProjectSessions(projectId: string, query?: SessionListQuery): Promise<Session[]> {
+    try {
+      return await invoke<Session[]>("get_project_sessions", { projectId, query });
+    } catch (error) {
+      await handleApiError(error as Error, { operation: "getProjectSessions", projectId });
+      throw error;
+    }
+  },
+
+  /**
+   * Fetch list of agents from GitHub repository
+   * @returns Promise resolving to list of available agents on GitHub
+   */
+  async fetchGitHubAgents(): Promise<GitHubAgentFile[]> {
+    try {
+      return await invoke<GitHubAgentFile[]>("fetch_github_agents");
+    } catch (error) {
+      logger.error("Failed to fetch GitHub agents:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Fetch and preview a specific agent from GitHub
+   * @param downloadUrl - The download URL for the agent file
+   * @returns Promise resolving to the agent export data
+   */
+  async fetchGitHubAgentContent(downloadUrl: string): Promise<AgentExport> {
+    try {
+      return await invoke<AgentExport>("fetch_github_agent_content", { downloadUrl });
+    } catch (error) {
+      logger.error("Failed to fetch GitHub agent content:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Import an agent directly from GitHub
+   * @param downloadUrl - The download URL for the agent file
+   * @param path - Optional repository path, used to track upstream updates
+   * @param sha - Optional file sha, used to track upstream updates
+   * @returns Promise resolving to the imported agent
+   */
+  async importAgentFromGitHub(downloadUrl: string, path?: string, sha?: string): Promise<Agent> {
+    try {
+      return await invoke<Agent>("import_agent_from_github", { downloadUrl, path, sha });
+    } catch (error) {
+      logger.error("Failed to import agent from GitHub:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists the configured agent registries
+   * @returns Promise resolving to the registries
+   */
+  async listAgentRegistries(): Promise<AgentRegistry[]> {
+    try {
+      return await invoke<AgentRegistry[]>("list_agent_registries");
+    } catch (error) {
+      logger.error("Failed to list agent registries:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Replaces the configured agent registries
+   * @param registries - The full list of registries
+   * @returns Promise resolving when saved
+   */
+  async saveAgentRegistries(registries: AgentRegistry[]): Promise<void> {
+    try {
+      return await invoke("save_agent_registries", { registries });
+    } catch (error) {
+      logger.error("Failed to save agent registries:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Fetches agents from one registry, or from all enabled registries
+   * @param registryId - Optional registry to fetch from
+   * @param forceRefresh - Bypass the listing cache
+   * @returns Promise resolving to the listed agent files
+   */
+  async fetchRegistryAgents(registryId?: string, forceRefresh?: boolean): Promise<RegistryAgentFile[]> {
+    try {
+      return await invoke<RegistryAgentFile[]>("fetch_registry_agents", { registryId, forceRefresh });
+    } catch (error) {
+      logger.error("Failed to fetch registry agents:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Imports an agent from a registry entry
+   * @param file - The registry entry to import
+   * @returns Promise resolving to the imported agent
+   */
+  async importAgentFromRegistry(file: RegistryAgentFile): Promise<Agent> {
+    try {
+      return await invoke<Agent>("import_agent_from_registry", { file });
+    } catch (error) {
+      logger.error("Failed to import agent from registry:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Checks imported agents for newer versions in their registries
+   * @param forceRefresh - Bypass the listing cache
+   * @returns Promise resolving to the agents with available updates
+   */
+  async checkAgentUpdates(forceRefresh?: boolean): Promise<AgentUpdateInfo[]> {
+    try {
+      return await invoke<AgentUpdateInfo[]>("check_agent_updates", { forceRefresh });
+    } catch (error) {
+      logger.error("Failed to check agent updates:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists the public keys trusted to sign agents
+   * @returns Promise resolving to the trusted keys
+   */
+  async listTrustedAgentKeys(): Promise<TrustedKey[]> {
+    try {
+      return await invoke<TrustedKey[]>("list_trusted_agent_keys");
+    } catch (error) {
+      logger.error("Failed to list trusted agent keys:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Trusts a public key for agent signatures
+   * @param name - Display name for the key
+   * @param publicKey - Base64-encoded ed25519 public key
+   * @returns Promise resolving to the stored key
+   */
+  async addTrustedAgentKey(name: string, publicKey: string): Promise<TrustedKey> {
+    try {
+      return await invoke<TrustedKey>("add_trusted_agent_key", { name, publicKey });
+    } catch (error) {
+      logger.error("Failed to add trusted agent key:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Removes a trusted key
+   * @param id - The key ID
+   */
+  async removeTrustedAgentKey(id: string): Promise<void> {
+    try {
+      await invoke("remove_trusted_agent_key", { id });
+    } catch (error) {
+      logger.error("Failed to remove trusted agent key:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the policy applied to unsigned or unverified agent imports
+   * @returns Promise resolving to the import policy
+   */
+  async getAgentImportPolicy(): Promise<AgentImportPolicy> {
+    try {
+      return await invoke<AgentImportPolicy>("get_agent_import_policy");
+    } catch (error) {
+      logger.error("Failed to get agent import policy:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Sets the policy applied to unsigned or unverified agent imports
+   * @param policy - The new import policy
+   */
+  async setAgentImportPolicy(policy: AgentImportPolicy): Promise<void> {
+    try {
+      await invoke("set_agent_import_policy", { policy });
+    } catch (error) {
+      logger.error("Failed to set agent import policy:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Checks the detached signature of a local agent file
+   * @param filePath - The path to the agent file
+   * @returns Promise resolving to the trust report
+   */
+  async verifyAgentFileSignature(filePath: string): Promise<AgentTrustReport> {
+    try {
+      return await invoke<AgentTrustReport>("verify_agent_file_signature", { filePath });
+    } catch (error) {
+      logger.error("Failed to verify agent file signature:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Checks the detached signature of a remote agent file
+   * @param downloadUrl - The agent file URL
+   * @returns Promise resolving to the trust report
+   */
+  async verifyAgentSourceSignature(downloadUrl: string): Promise<AgentTrustReport> {
+    try {
+      return await invoke<AgentTrustReport>("verify_agent_source_signature", { downloadUrl });
+    } catch (error) {
+      logger.error("Failed to verify agent signature:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads the Claude settings file
+   * @returns Promise resolving to the settings object
+   */
+  async getClaudeSettings(): Promise<ClaudeSettings> {
+    try {
+      const result = await invoke<{ data: ClaudeSettings }>("get_claude_settings");
+      logger.debug("Raw result from get_claude_settings:", result);
+
+      // The Rust backend returns ClaudeSettings { data: ... }
+      // We need to extract the data field
+      if (result && typeof result === "object" && "data" in result) {
+        return result.data;
+      }
+
+      // If the result is already the settings object, return it
+      return result as ClaudeSettings;
+    } catch (error) {
+      logger.error("Failed to get Claude settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Opens a new Claude Code session
+   * @param path - Optional path to open the session in
+   * @returns Promise resolving when the session is opened
+   */
+  async openNewSession(path?: string): Promise<string> {
+    try {
+      return await invoke<string>("open_new_session", { path });
+    } catch (error) {
+      logger.error("Failed to open new session:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads the CLAUDE.md system prompt file
+   * @returns Promise resolving to the system prompt content
+   */
+  async getSystemPrompt(): Promise<string> {
+    try {
+      return await invoke<string>("get_system_prompt");
+    } catch (error) {
+      logger.error("Failed to get system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Checks if Claude Code is installed and gets its version
+   * @returns Promise resolving to the version status
+   */
+  async checkClaudeVersion(): Promise<ClaudeVersionStatus> {
+    try {
+      return await invoke<ClaudeVersionStatus>("check_claude_version");
+    } catch (error) {
+      logger.error("Failed to check Claude version:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the CLAUDE.md system prompt file
+   * @param content - The new content for the system prompt
+   * @returns Promise resolving when the file is saved
+   */
+  async saveSystemPrompt(content: string): Promise<string> {
+    try {
+      return await invoke<string>("save_system_prompt", { content });
+    } catch (error) {
+      logger.error("Failed to save system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the Claude settings file
+   * @param settings - The settings object to save
+   * @returns Promise resolving when the settings are saved
+   */
+  async saveClaudeSettings(settings: ClaudeSettings): Promise<string> {
+    try {
+      return await invoke<string>("save_claude_settings", { settings });
+    } catch (error) {
+      logger.error("Failed to save Claude settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Get Claude models by invoking /model from the CLI
+   */
+  async getClaudeModels(): Promise<ClaudeModelInfo[]> {
+    // Return cached result if available
+    if (claudeModelsCache) {
+      return claudeModelsCache;
+    }
+
+    // If a request is already in flight, reuse it
+    if (claudeModelsPromise) {
+      return claudeModelsPromise;
+    }
+
+    try {
+      claudeModelsPromise = invoke<ClaudeModelInfo[]>("get_claude_models");
+      const models = await claudeModelsPromise;
+      claudeModelsCache = models;
+      return models;
+    } catch (error) {
+      logger.error("Failed to get Claude models:", error);
+      throw error;
+    } finally {
+      claudeModelsPromise = null;
+    }
+  },
+
+  /**
+   * Finds all CLAUDE.md files in a project directory
+   * @param projectPath - The absolute path to the project
+   * @returns Promise resolving to an array of CLAUDE.md files
+   */
+  async findClaudeMdFiles(projectPath: string): Promise<ClaudeMdFile[]> {
+    try {
+      return await invoke<ClaudeMdFile[]>("find_claude_md_files", { projectPath });
+    } catch (error) {
+      logger.error("Failed to find CLAUDE.md files:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads a specific CLAUDE.md file
+   * @param filePath - The absolute path to the file
+   * @returns Promise resolving to the file content
+   */
+  async readClaudeMdFile(filePath: string): Promise<string> {
+    try {
+      return await invoke<string>("read_claude_md_file", { filePath });
+    } catch (error) {
+      logger.error("Failed to read CLAUDE.md file:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves a specific CLAUDE.md file
+   * @param filePath - The absolute path to the file
+   * @param content - The new content for the file
+   * @returns Promise resolving when the file is saved
+   */
+  async saveClaudeMdFile(filePath: string, content: string): Promise<string> {
+    try {
+      return await invoke<string>("save_claude_md_file", { filePath, content });
+    } catch (error) {
+      logger.error("Failed to save CLAUDE.md file:", error);
+      throw error;
+    }
+  },
+
+  // Agent API methods
+
+  /**
+   * Lists all CC agents
+   * @returns Promise resolving to an array of agents
+   */
+  async listAgents(): Promise<Agent[]> {
+    try {
+      return await invoke<Agent[]>("list_agents");
+    } catch (error) {
+      logger.error("Failed to list agents:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists native agents directly from .claude/agents directory
+   * @returns Promise resolving to an array of native agents
+   */
+  async listNativeAgents(): Promise<Agent[]> {
+    try {
+      return await invoke<Agent[]>("list_native_agents");
+    } catch (error) {
+      logger.error("Failed to list native agents:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Import native agents from .claude/agents directory to database
+   * @returns Promise resolving to the number of agents imported
+   */
+  async importNativeAgents(): Promise<number> {
+    try {
+      return await invoke<number>("import_native_agents");
+    } catch (error) {
+      logger.error("Failed to import native agents:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Creates a new agent
+   * @param name - The agent name
+   * @param icon - The icon identifier
+   * @param system_prompt - The system prompt for the agent
+   * @param default_task - Optional default task
+   * @param model - Optional model (defaults to 'sonnet')
+   * @param hooks - Optional hooks configuration as JSON string
+   * @param source - Optional source type (defaults to 'claudia')
+   * @param allowed_tools - Optional JSON array of allowed tool names
+   * @param dependencies - Optional JSON string of AgentDependencies
+   * @param parameters - Optional JSON array of AgentParameter declarations
+   * @param collect_artifacts - Optional flag to point runs at an artifacts directory (defaults to false)
+   * @returns Promise resolving to the created agent
+   */
+  async createAgent(
+    name: string,
+    icon: string,
+    system_prompt: string,
+    default_task?: string,
+    model?: string,
+    hooks?: string,
+    source?: string,
+    allowed_tools?: string,
+    dependencies?: string,
+    parameters?: string,
+    collect_artifacts?: boolean
+  ): Promise<Agent> {
+    try {
+      return await invoke<Agent>("create_agent", {
+        name,
+        icon,
+        systemPrompt: system_prompt,
+        defaultTask: default_task,
+        model,
+        hooks,
+        source: source || 'claudia',
+        allowedTools: allowed_tools,
+        dependencies,
+        parameters,
+        collectArtifacts: collect_artifacts,
+      });
+    } catch (error) {
+      logger.error("Failed to create agent:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Updates an existing agent
+   * @param id - The agent ID
+   * @param name - The updated name
+   * @param icon - The updated icon
+   * @param system_prompt - The updated system prompt
+   * @param default_task - Optional default task
+   * @param model - Optional model
+   * @param hooks - Optional hooks configuration as JSON string
+   * @param allowed_tools - Optional JSON array of allowed tool names (unchanged if omitted)
+   * @param dependencies - Optional JSON string of AgentDependencies (unchanged if omitted)
+   * @param parameters - Optional JSON array of AgentParameter declarations (unchanged if omitted)
+   * @param collect_artifacts - Optional flag to point runs at an artifacts directory (unchanged if omitted)
+   * @returns Promise resolving to the updated agent
+   */
+  async updateAgent(
+    id: number,
+    name: string,
+    icon: string,
+    system_prompt: string,
+    default_task?: string,
+    model?: string,
+    hooks?: string,
+    allowed_tools?: string,
+    dependencies?: string,
+    parameters?: string,
+    collect_artifacts?: boolean
+  ): Promise<Agent> {
+    try {
+      return await invoke<Agent>("update_agent", {
+        id,
+        name,
+        icon,
+        systemPrompt: system_prompt,
+        defaultTask: default_task,
+        model,
+        hooks,
+        allowedTools: allowed_tools,
+        dependencies,
+        parameters,
+        collectArtifacts: collect_artifacts,
+      });
+    } catch (error) {
+      logger.error("Failed to update agent:", error);
+      throw error;
+    }
+  },
 
-/**
- * Result for individual server import
- */
-export interface ImportServerResult {
-  name: string;
-  success: boolean;
-  error?: string;
-}
+  /**
+   * Deletes an agent
+   * @param id - The agent ID to delete
+   * @returns Promise resolving when the agent is deleted
+   */
+  async deleteAgent(id: number): Promise<void> {
+    try {
+      return await invoke("delete_agent", { id });
+    } catch (error) {
+      logger.error("Failed to delete agent:", error);
+      throw error;
+    }
+  },
 
-/**
- * API client for interacting with the Rust backend
- */
-export const api = {
   /**
-   * Lists all projects in the ~/.claude/projects directory
-   * @returns Promise resolving to an array of projects
+   * Deletes all native agents from database (keeping .claude/agents files intact)
+   * @returns Promise resolving to the number of agents deleted
    */
-  async listProjects(): Promise<Project[]> {
+  async deleteNativeAgents(): Promise<number> {
     try {
-      return await invoke<Project[]>("list_projects");
+      return await invoke<number>("delete_native_agents");
     } catch (error) {
-      await handleApiError(error as Error, { operation: "listProjects" });
+      logger.error("Failed to delete native agents:", error);
       throw error;
     }
   },
 
   /**
-   * Retrieves sessions for a specific project
-   * @param projectId - The ID of the project to retrieve sessions for
-   * @returns Promise resolving to an array of sessions
+   * Gets a single agent by ID
+   * @param id - The agent ID
+   * @returns Promise resolving to the agent
    */
-  async getProjectSessions(projectId: string): Promise<Session[]> {
+  async getAgent(id: number): Promise<Agent> {
     try {
-      return await invoke<Session[]>("get_project_sessions", { projectId });
+      return await invoke<Agent>("get_agent", { id });
     } catch (error) {
-      await handleApiError(error as Error, { operation: "getProjectSessions", projectId });
+      logger.error("Failed to get agent:", error);
       throw error;
     }
   },
 
   /**
-   * Fetch list of agents from GitHub repository
-   * @returns Promise resolving to list of available agents on GitHub
+   * Lists all recorded versions of an agent, newest first
+   * @param agentId - The agent ID
+   * @returns Promise resolving to the agent's version history
    */
-  async fetchGitHubAgents(): Promise<GitHubAgentFile[]> {
+  async listAgentVersions(agentId: number): Promise<AgentVersion[]> {
     try {
-      return await invoke<GitHubAgentFile[]>("fetch_github_agents");
+      return await invoke<AgentVersion[]>("list_agent_versions", { agentId });
     } catch (error) {
-      logger.error("Failed to fetch GitHub agents:", error);
+      logger.error("Failed to list agent versions:", error);
       throw error;
     }
   },
 
   /**
-   * Fetch and preview a specific agent from GitHub
-   * @param downloadUrl - The download URL for the agent file
-   * @returns Promise resolving to the agent export data
+   * Gets a single version of an agent
+   * @param agentId - The agent ID
+   * @param version - The version number
+   * @returns Promise resolving to the agent version
    */
-  async fetchGitHubAgentContent(downloadUrl: string): Promise<AgentExport> {
+  async getAgentVersion(agentId: number, version: number): Promise<AgentVersion> {
     try {
-      return await invoke<AgentExport>("fetch_github_agent_content", { downloadUrl });
+      return await invoke<AgentVersion>("get_agent_version", { agentId, version });
     } catch (error) {
-      logger.error("Failed to fetch GitHub agent content:", error);
+      logger.error("Failed to get agent version:", error);
       throw error;
     }
   },
 
   /**
-   * Import an agent directly from GitHub
-   * @param downloadUrl - The download URL for the agent file
-   * @returns Promise resolving to the imported agent
+   * Compares two versions of an agent
+   * @param agentId - The agent ID
+   * @param fromVersion - The base version
+   * @param toVersion - The version to compare against
+   * @returns Promise resolving to the changed fields and prompt/hooks diffs
    */
-  async importAgentFromGitHub(downloadUrl: string): Promise<Agent> {
+  async diffAgentVersions(agentId: number, fromVersion: number, toVersion: number): Promise<AgentVersionDiff> {
     try {
-      return await invoke<Agent>("import_agent_from_github", { downloadUrl });
+      return await invoke<AgentVersionDiff>("diff_agent_versions", { agentId, fromVersion, toVersion });
     } catch (error) {
-      logger.error("Failed to import agent from GitHub:", error);
+      logger.error("Failed to diff agent versions:", error);
       throw error;
     }
   },
 
   /**
-   * Reads the Claude settings file
-   * @returns Promise resolving to the settings object
+   * Rolls an agent back to a previous version (recorded as a new version)
+   * @param agentId - The agent ID
+   * @param version - The version to restore
+   * @returns Promise resolving to the updated agent
    */
-  async getClaudeSettings(): Promise<ClaudeSettings> {
+  async rollbackAgentVersion(agentId: number, version: number): Promise<Agent> {
     try {
-      const result = await invoke<{ data: ClaudeSettings }>("get_claude_settings");
-      logger.debug("Raw result from get_claude_settings:", result);
+      return await invoke<Agent>("rollback_agent_version", { agentId, version });
+    } catch (error) {
+      logger.error("Failed to rollback agent version:", error);
+      throw error;
+    }
+  },
 
-      // The Rust backend returns ClaudeSettings { data: ... }
-      // We need to extract the data field
-      if (result && typeof result === "object" && "data" in result) {
-        return result.data;
-      }
+  /**
+   * Lists agent schedules
+   * @param agentId - Optional agent ID to filter by
+   * @returns Promise resolving to the schedules
+   */
+  async listAgentSchedules(agentId?: number): Promise<AgentSchedule[]> {
+    try {
+      return await invoke<AgentSchedule[]>("list_agent_schedules", { agentId });
+    } catch (error) {
+      logger.error("Failed to list agent schedules:", error);
+      throw error;
+    }
+  },
 
-      // If the result is already the settings object, return it
-      return result as ClaudeSettings;
+  /**
+   * Creates a recurring agent run
+   * @param schedule - The schedule definition
+   * @returns Promise resolving to the created schedule
+   */
+  async createAgentSchedule(schedule: AgentScheduleInput): Promise<AgentSchedule> {
+    try {
+      return await invoke<AgentSchedule>("create_agent_schedule", { schedule });
     } catch (error) {
-      logger.error("Failed to get Claude settings:", error);
+      logger.error("Failed to create agent schedule:", error);
       throw error;
     }
   },
 
   /**
-   * Opens a new Claude Code session
-   * @param path - Optional path to open the session in
-   * @returns Promise resolving when the session is opened
+   * Updates a schedule; its next run time is recomputed from now
+   * @param id - The schedule ID
+   * @param schedule - The new schedule definition
+   * @returns Promise resolving to the updated schedule
    */
-  async openNewSession(path?: string): Promise<string> {
+  async updateAgentSchedule(id: number, schedule: AgentScheduleInput): Promise<AgentSchedule> {
     try {
-      return await invoke<string>("open_new_session", { path });
+      return await invoke<AgentSchedule>("update_agent_schedule", { id, schedule });
     } catch (error) {
-      logger.error("Failed to open new session:", error);
+      logger.error("Failed to update agent schedule:", error);
       throw error;
     }
   },
 
   /**
-   * Reads the CLAUDE.md system prompt file
-   * @returns Promise resolving to the system prompt content
+   * Deletes a schedule
+   * @param id - The schedule ID
    */
-  async getSystemPrompt(): Promise<string> {
+  async deleteAgentSchedule(id: number): Promise<void> {
     try {
-      return await invoke<string>("get_system_prompt");
+      await invoke("delete_agent_schedule", { id });
     } catch (error) {
-      logger.error("Failed to get system prompt:", error);
+      logger.error("Failed to delete agent schedule:", error);
       throw error;
     }
   },
 
   /**
-   * Checks if Claude Code is installed and gets its version
-   * @returns Promise resolving to the version status
+   * Runs a schedule immediately without changing its next run time
+   * @param id - The schedule ID
+   * @returns Promise resolving to the run ID
    */
-  async checkClaudeVersion(): Promise<ClaudeVersionStatus> {
+  async runAgentScheduleNow(id: number): Promise<number> {
     try {
-      return await invoke<ClaudeVersionStatus>("check_claude_version");
+      return await invoke<number>("run_agent_schedule_now", { id });
     } catch (error) {
-      logger.error("Failed to check Claude version:", error);
+      logger.error("Failed to run agent schedule:", error);
       throw error;
     }
   },
 
   /**
-   * Saves the CLAUDE.md system prompt file
-   * @param content - The new content for the system prompt
-   * @returns Promise resolving when the file is saved
+   * Lists agent pipelines
+   * @returns Promise resolving to the pipelines
    */
-  async saveSystemPrompt(content: string): Promise<string> {
+  async listAgentPipelines(): Promise<AgentPipeline[]> {
     try {
-      return await invoke<string>("save_system_prompt", { content });
+      return await invoke<AgentPipeline[]>("list_agent_pipelines");
     } catch (error) {
-      logger.error("Failed to save system prompt:", error);
+      logger.error("Failed to list agent pipelines:", error);
       throw error;
     }
   },
 
   /**
-   * Saves the Claude settings file
-   * @param settings - The settings object to save
-   * @returns Promise resolving when the settings are saved
+   * Creates a pipeline that chains agent runs
+   * @param name - The pipeline name
+   * @param description - Optional description
+   * @param steps - The ordered steps
+   * @returns Promise resolving to the created pipeline
    */
-  async saveClaudeSettings(settings: ClaudeSettings): Promise<string> {
+  async createAgentPipeline(name: string, description: string | undefined, steps: PipelineStep[]): Promise<AgentPipeline> {
     try {
-      return await invoke<string>("save_claude_settings", { settings });
+      return await invoke<AgentPipeline>("create_agent_pipeline", { name, description, steps });
     } catch (error) {
-      logger.error("Failed to save Claude settings:", error);
+      logger.error("Failed to create agent pipeline:", error);
       throw error;
     }
   },
 
   /**
-   * Get Claude models by invoking /model from the CLI
+   * Updates a pipeline
+   * @param id - The pipeline ID
+   * @param name - The pipeline name
+   * @param description - Optional description
+   * @param steps - The ordered steps
+   * @returns Promise resolving to the updated pipeline
    */
-  async getClaudeModels(): Promise<ClaudeModelInfo[]> {
-    // Return cached result if available
-    if (claudeModelsCache) {
-      return claudeModelsCache;
+  async updateAgentPipeline(id: number, name: string, description: string | undefined, steps: PipelineStep[]): Promise<AgentPipeline> {
+    try {
+      return await invoke<AgentPipeline>("update_agent_pipeline", { id, name, description, steps });
+    } catch (error) {
+      logger.error("Failed to update agent pipeline:", error);
+      throw error;
     }
+  },
 
-    // If a request is already in flight, reuse it
-    if (claudeModelsPromise) {
-      return claudeModelsPromise;
+  /**
+   * Deletes a pipeline
+   * @param id - The pipeline ID
+   */
+  async deleteAgentPipeline(id: number): Promise<void> {
+    try {
+      await invoke("delete_agent_pipeline", { id });
+    } catch (error) {
+      logger.error("Failed to delete agent pipeline:", error);
+      throw error;
     }
+  },
 
+  /**
+   * Starts a pipeline in a project
+   * @param pipelineId - The pipeline ID
+   * @param projectPath - The project to run every step in
+   * @returns Promise resolving to the pipeline run ID
+   */
+  async executeAgentPipeline(pipelineId: number, projectPath: string): Promise<number> {
     try {
-      claudeModelsPromise = invoke<ClaudeModelInfo[]>("get_claude_models");
-      const models = await claudeModelsPromise;
-      claudeModelsCache = models;
-      return models;
+      return await invoke<number>("execute_agent_pipeline", { pipelineId, projectPath });
     } catch (error) {
-      logger.error("Failed to get Claude models:", error);
+      logger.error("Failed to execute agent pipeline:", error);
       throw error;
-    } finally {
-      claudeModelsPromise = null;
     }
   },
 
   /**
-   * Finds all CLAUDE.md files in a project directory
-   * @param projectPath - The absolute path to the project
-   * @returns Promise resolving to an array of CLAUDE.md files
+   * Lists pipeline runs
+   * @param pipelineId - Optional pipeline ID to filter by
+   * @returns Promise resolving to the pipeline runs
    */
-  async findClaudeMdFiles(projectPath: string): Promise<ClaudeMdFile[]> {
+  async listPipelineRuns(pipelineId?: number): Promise<PipelineRun[]> {
     try {
-      return await invoke<ClaudeMdFile[]>("find_claude_md_files", { projectPath });
+      return await invoke<PipelineRun[]>("list_pipeline_runs", { pipelineId });
     } catch (error) {
-      logger.error("Failed to find CLAUDE.md files:", error);
+      logger.error("Failed to list pipeline runs:", error);
       throw error;
     }
   },
 
   /**
-   * Reads a specific CLAUDE.md file
-   * @param filePath - The absolute path to the file
-   * @returns Promise resolving to the file content
+   * Gets a pipeline run with the agent runs of its steps
+   * @param id - The pipeline run ID
+   * @returns Promise resolving to the pipeline run
    */
-  async readClaudeMdFile(filePath: string): Promise<string> {
+  async getPipelineRun(id: number): Promise<PipelineRunWithSteps> {
     try {
-      return await invoke<string>("read_claude_md_file", { filePath });
+      return await invoke<PipelineRunWithSteps>("get_pipeline_run", { id });
     } catch (error) {
-      logger.error("Failed to read CLAUDE.md file:", error);
+      logger.error("Failed to get pipeline run:", error);
       throw error;
     }
   },
 
   /**
-   * Saves a specific CLAUDE.md file
-   * @param filePath - The absolute path to the file
-   * @param content - The new content for the file
-   * @returns Promise resolving when the file is saved
+   * Cancels a pipeline run after its current step
+   * @param id - The pipeline run ID
    */
-  async saveClaudeMdFile(filePath: string, content: string): Promise<string> {
+  async cancelPipelineRun(id: number): Promise<void> {
     try {
-      return await invoke<string>("save_claude_md_file", { filePath, content });
+      await invoke("cancel_pipeline_run", { id });
     } catch (error) {
-      logger.error("Failed to save CLAUDE.md file:", error);
+      logger.error("Failed to cancel pipeline run:", error);
       throw error;
     }
   },
 
-  // Agent API methods
+  /**
+   * Runs one agent and task across many projects with bounded concurrency
+   * @param request - The agent, task and projects to run in
+   * @returns Promise resolving to the batch ID
+   */
+  async executeAgentBatch(request: AgentBatchRequest): Promise<string> {
+    try {
+      return await invoke<string>("execute_agent_batch", { request });
+    } catch (error) {
+      logger.error("Failed to execute agent batch:", error);
+      throw error;
+    }
+  },
 
   /**
-   * Lists all CC agents
-   * @returns Promise resolving to an array of agents
+   * Lists agent batches
+   * @returns Promise resolving to the batches
    */
-  async listAgents(): Promise<Agent[]> {
+  async listAgentBatches(): Promise<AgentBatch[]> {
     try {
-      return await invoke<Agent[]>("list_agents");
+      return await invoke<AgentBatch[]>("list_agent_batches");
     } catch (error) {
-      logger.error("Failed to list agents:", error);
+      logger.error("Failed to list agent batches:", error);
       throw error;
     }
   },
 
   /**
-   * Lists native agents directly from .claude/agents directory
-   * @returns Promise resolving to an array of native agents
+   * Gets status, cost and duration per project for a batch
+   * @param batchId - The batch ID
+   * @returns Promise resolving to the batch summary
    */
-  async listNativeAgents(): Promise<Agent[]> {
+  async getAgentBatchSummary(batchId: string): Promise<AgentBatchSummary> {
     try {
-      return await invoke<Agent[]>("list_native_agents");
+      return await invoke<AgentBatchSummary>("get_agent_batch_summary", { batchId });
     } catch (error) {
-      logger.error("Failed to list native agents:", error);
+      logger.error("Failed to get agent batch summary:", error);
       throw error;
     }
   },
 
   /**
-   * Import native agents from .claude/agents directory to database
-   * @returns Promise resolving to the number of agents imported
+   * Cancels a batch, killing its running runs and dropping queued ones
+   * @param batchId - The batch ID
    */
-  async importNativeAgents(): Promise<number> {
+  async cancelAgentBatch(batchId: string): Promise<void> {
     try {
-      return await invoke<number>("import_native_agents");
+      await invoke("cancel_agent_batch", { batchId });
     } catch (error) {
-      logger.error("Failed to import native agents:", error);
+      logger.error("Failed to cancel agent batch:", error);
       throw error;
     }
   },
 
   /**
-   * Creates a new agent
-   * @param name - The agent name
-   * @param icon - The icon identifier
-   * @param system_prompt - The system prompt for the agent
-   * @param default_task - Optional default task
-   * @param model - Optional model (defaults to 'sonnet')
-   * @param hooks - Optional hooks configuration as JSON string
-   * @param source - Optional source type (defaults to 'claudia')
-   * @returns Promise resolving to the created agent
+   * Gets the structured result of a finished agent run
+   * @param runId - The run ID
+   * @returns Promise resolving to the result, or null if the run has none yet
    */
-  async createAgent(
-    name: string,
-    icon: string,
-    system_prompt: string,
-    default_task?: string,
-    model?: string,
-    hooks?: string,
-    source?: string
-  ): Promise<Agent> {
+  async getAgentRunResult(runId: number): Promise<AgentRunResult | null> {
+    try {
+      return await invoke<AgentRunResult | null>("get_agent_run_result", { runId });
+    } catch (error) {
+      logger.error("Failed to get agent run result:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists structured run results, optionally for one agent or runs that changed a file
+   * @param agentId - Optional agent ID to filter by
+   * @param changedFile - Optional file path the runs must have changed
+   * @param limit - Optional maximum number of results
+   * @returns Promise resolving to the results, most recent first
+   */
+  async listAgentRunResults(
+    agentId?: number,
+    changedFile?: string,
+    limit?: number
+  ): Promise<AgentRunResult[]> {
     try {
-      return await invoke<Agent>("create_agent", {
-        name,
-        icon,
-        systemPrompt: system_prompt,
-        defaultTask: default_task,
-        model,
-        hooks,
-        source: source || 'claudia',
+      return await invoke<AgentRunResult[]>("list_agent_run_results", {
+        agentId,
+        changedFile,
+        limit,
       });
     } catch (error) {
-      logger.error("Failed to create agent:", error);
+      logger.error("Failed to list agent run results:", error);
       throw error;
     }
   },
 
   /**
-   * Updates an existing agent
-   * @param id - The agent ID
-   * @param name - The updated name
-   * @param icon - The updated icon
-   * @param system_prompt - The updated system prompt
-   * @param default_task - Optional default task
-   * @param model - Optional model
-   * @param hooks - Optional hooks configuration as JSON string
-   * @returns Promise resolving to the updated agent
+   * Reads the contents of a named artifact of an agent run
+   * @param runId - The run ID
+   * @param name - The artifact name
+   * @returns Promise resolving to the artifact contents
    */
-  async updateAgent(
-    id: number,
-    name: string,
-    icon: string,
-    system_prompt: string,
-    default_task?: string,
-    model?: string,
-    hooks?: string
-  ): Promise<Agent> {
+  async readAgentRunArtifact(runId: number, name: string): Promise<string> {
     try {
-      return await invoke<Agent>("update_agent", {
-        id,
-        name,
-        icon,
-        systemPrompt: system_prompt,
-        defaultTask: default_task,
-        model,
-        hooks,
-      });
+      return await invoke<string>("read_agent_run_artifact", { runId, name });
     } catch (error) {
-      logger.error("Failed to update agent:", error);
+      logger.error("Failed to read agent run artifact:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes an agent
-   * @param id - The agent ID to delete
-   * @returns Promise resolving when the agent is deleted
+   * Runs a finished agent run again with the same task in a new session
+   * @param runId - The run ID to retry
+   * @returns Promise resolving to the new run ID
    */
-  async deleteAgent(id: number): Promise<void> {
+  async retryAgentRun(runId: number): Promise<number> {
     try {
-      return await invoke("delete_agent", { id });
+      return await invoke<number>("retry_agent_run", { runId });
     } catch (error) {
-      logger.error("Failed to delete agent:", error);
+      logger.error("Failed to retry agent run:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes all native agents from database (keeping .claude/agents files intact)
-   * @returns Promise resolving to the number of agents deleted
+   * Continues a finished agent run's Claude session with a follow-up prompt
+   * @param runId - The run ID to resume
+   * @param prompt - Optional follow-up prompt
+   * @returns Promise resolving to the new run ID
    */
-  async deleteNativeAgents(): Promise<number> {
+  async resumeAgentRun(runId: number, prompt?: string): Promise<number> {
     try {
-      return await invoke<number>("delete_native_agents");
+      return await invoke<number>("resume_agent_run", { runId, prompt });
     } catch (error) {
-      logger.error("Failed to delete native agents:", error);
+      logger.error("Failed to resume agent run:", error);
       throw error;
     }
   },
 
   /**
-   * Gets a single agent by ID
-   * @param id - The agent ID
-   * @returns Promise resolving to the agent
+   * Lists a run's original run and all of its retries
+   * @param runId - Any run ID in the chain
+   * @returns Promise resolving to the runs, oldest attempt first
    */
-  async getAgent(id: number): Promise<Agent> {
+  async listAgentRunAttempts(runId: number): Promise<AgentRun[]> {
     try {
-      return await invoke<Agent>("get_agent", { id });
+      return await invoke<AgentRun[]>("list_agent_run_attempts", { runId });
     } catch (error) {
-      logger.error("Failed to get agent:", error);
+      logger.error("Failed to list agent run attempts:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets an agent's automatic retry policy
+   * @param agentId - The agent ID
+   * @returns Promise resolving to the policy, or null if retries are off
+   */
+  async getAgentRetryPolicy(agentId: number): Promise<AgentRetryPolicy | null> {
+    try {
+      return await invoke<AgentRetryPolicy | null>("get_agent_retry_policy", { agentId });
+    } catch (error) {
+      logger.error("Failed to get agent retry policy:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Sets or clears an agent's automatic retry policy
+   * @param agentId - The agent ID
+   * @param policy - The policy, or null to turn retries off
+   */
+  async setAgentRetryPolicy(agentId: number, policy: AgentRetryPolicy | null): Promise<void> {
+    try {
+      await invoke("set_agent_retry_policy", { agentId, policy });
+    } catch (error) {
+      logger.error("Failed to set agent retry policy:", error);
       throw error;
     }
   },
@@ -884,11 +2288,12 @@ export const api = {
   /**
    * Imports an agent from JSON data
    * @param jsonData - The JSON string containing the agent export
+   * @param signature - Optional base64 detached signature over jsonData
    * @returns Promise resolving to the imported agent
    */
-  async importAgent(jsonData: string): Promise<Agent> {
+  async importAgent(jsonData: string, signature?: string): Promise<Agent> {
     try {
-      return await invoke<Agent>("import_agent", { jsonData });
+      return await invoke<Agent>("import_agent", { jsonData, signature });
     } catch (error) {
       logger.error("Failed to import agent:", error);
       throw error;
@@ -910,24 +2315,55 @@ export const api = {
     }
   },
 
+  /**
+   * Checks which MCP servers and environment variables an agent export needs
+   * that are not configured locally
+   * @param jsonData - The JSON string containing the agent export
+   * @returns Promise resolving to the dependency report
+   */
+  async checkAgentImportDependencies(jsonData: string): Promise<AgentDependencyReport> {
+    try {
+      return await invoke<AgentDependencyReport>("check_agent_import_dependencies", { jsonData });
+    } catch (error) {
+      logger.error("Failed to check agent dependencies:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Checks the dependencies of an agent export file
+   * @param filePath - The path to the JSON file
+   * @returns Promise resolving to the dependency report
+   */
+  async checkAgentFileDependencies(filePath: string): Promise<AgentDependencyReport> {
+    try {
+      return await invoke<AgentDependencyReport>("check_agent_file_dependencies", { filePath });
+    } catch (error) {
+      logger.error("Failed to check agent file dependencies:", error);
+      throw error;
+    }
+  },
+
   /**
    * Executes an agent
    * @param agentId - The agent ID to execute
    * @param projectPath - The project path to run the agent in
    * @param task - The task description
    * @param model - Optional model override
+   * @param parameters - Values for the agent's template parameters
    * @returns Promise resolving to the run ID when execution starts
    */
   async executeAgent(
     agentId: number,
     projectPath: string,
     task: string,
-    model?: string
+    model?: string,
+    parameters?: Record<string, string | number | boolean>
   ): Promise<number> {
     try {
       // Map shorthand model names to API model identifiers
       const apiModel = model ? getApiModel(model as ClaudeModel) : undefined;
-      return await invoke<number>("execute_agent", { agentId, projectPath, task, model: apiModel });
+      return await invoke<number>("execute_agent", { agentId, projectPath, task, model: apiModel, parameters });
     } catch (error) {
       logger.error("Failed to execute agent:", error);
       // Return a sentinel value to indicate error
@@ -1079,13 +2515,16 @@ export const api = {
   },
 
   /**
-   * Start streaming real-time output for a running session
+   * Start streaming real-time output for a running session.
+   * New lines arrive as `session-output-update:{runId}` events carrying a SessionOutputChunk,
+   * followed by `session-output-complete:{runId}` once the run exits.
    * @param runId - The run ID to stream output for
+   * @param offset - Optional byte offset in the session file to start from
    * @returns Promise that resolves when streaming starts
    */
-  async streamSessionOutput(runId: number): Promise<void> {
+  async streamSessionOutput(runId: number, offset?: number): Promise<void> {
     try {
-      return await invoke<void>("stream_session_output", { runId });
+      return await invoke<void>("stream_session_output", { runId, offset });
     } catch (error) {
       logger.error("Failed to start streaming session output:", error);
       throw new Error(
@@ -1101,6 +2540,84 @@ export const api = {
     return invoke("load_session_history", { sessionId, projectId });
   },
 
+  /**
+   * Searches prompts, assistant text, tool names and file paths across all sessions.
+   * Searches what is already indexed; new and grown session files are indexed in the background
+   * @param query - Search terms and filters
+   * @returns Promise resolving to matching messages, best matches first
+   */
+  async searchSessions(query: SessionSearchQuery): Promise<SessionSearchHit[]> {
+    try {
+      return await invoke<SessionSearchHit[]>("search_sessions", { query });
+    } catch (error) {
+      logger.error("Failed to search sessions:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Indexes session files that are new or have grown since the last update
+   * @returns Promise resolving to what the update indexed
+   */
+  async updateSessionIndex(): Promise<SessionIndexStats> {
+    try {
+      return await invoke<SessionIndexStats>("update_session_index");
+    } catch (error) {
+      logger.error("Failed to update session index:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Drops the session search index and builds it again from all session files
+   * @returns Promise resolving to what the rebuild indexed
+   */
+  async rebuildSessionIndex(): Promise<SessionIndexStats> {
+    try {
+      return await invoke<SessionIndexStats>("rebuild_session_index");
+    } catch (error) {
+      logger.error("Failed to rebuild session index:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Renders a session as Markdown, self-contained HTML or normalized JSON
+   * @param sessionId - The session ID
+   * @param projectId - The project ID
+   * @param options - Format and redaction options
+   * @returns Promise resolving to the rendered session
+   */
+  async exportSession(sessionId: string, projectId: string, options: SessionExportOptions): Promise<string> {
+    try {
+      return await invoke<string>("export_session", { sessionId, projectId, options });
+    } catch (error) {
+      logger.error("Failed to export session:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Renders a session and writes it to a file
+   * @param sessionId - The session ID
+   * @param projectId - The project ID
+   * @param options - Format and redaction options
+   * @param filePath - Path of the file to write
+   */
+  async exportSessionToFile(
+    sessionId: string,
+    projectId: string,
+    options: SessionExportOptions,
+    filePath: string
+  ): Promise<void> {
+    try {
+      return await invoke<void>("export_session_to_file", { sessionId, projectId, options, filePath });
+    } catch (error) {
+      logger.error("Failed to export session to file:", error);
+      throw error;
+    }
+  },
+
   /**
    * Loads the JSONL history for a specific agent session
    * Similar to loadSessionHistory but searches across all project directories
@@ -1146,106 +2663,270 @@ export const api = {
   },
 
   /**
-   * Cancels the currently running Claude Code execution
-   * @param sessionId - Optional session ID to cancel a specific session
+   * Cancels the currently running Claude Code execution
+   * @param sessionId - Optional session ID to cancel a specific session
+   */
+  async cancelClaudeExecution(sessionId?: string): Promise<void> {
+    return invoke("cancel_claude_execution", { sessionId });
+  },
+
+  /**
+   * Lists all currently running Claude sessions
+   * @returns Promise resolving to list of running Claude sessions
+   */
+  async listRunningClaudeSessions(): Promise<unknown[]> {
+    return invoke("list_running_claude_sessions");
+  },
+
+  /**
+   * Gets live output from a Claude session
+   * @param sessionId - The session ID to get output for
+   * @returns Promise resolving to the current live output
+   */
+  async getClaudeSessionOutput(sessionId: string): Promise<string> {
+    return invoke("get_claude_session_output", { sessionId });
+  },
+
+  /**
+   * Lists files and directories in a given path
+   */
+  async listDirectoryContents(directoryPath: string): Promise<FileEntry[]> {
+    return invoke("list_directory_contents", { directoryPath });
+  },
+
+  /**
+   * Searches for files and directories matching a pattern
+   */
+  async searchFiles(basePath: string, query: string): Promise<FileEntry[]> {
+    return invoke("search_files", { basePath, query });
+  },
+
+  /**
+   * Gets overall usage statistics
+   * @returns Promise resolving to usage statistics
+   */
+  async getUsageStats(): Promise<UsageStats> {
+    try {
+      return await invoke<UsageStats>("get_usage_stats");
+    } catch (error) {
+      logger.error("Failed to get usage stats:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets usage statistics filtered by date range
+   * @param startDate - Start date (ISO format)
+   * @param endDate - End date (ISO format)
+   * @returns Promise resolving to usage statistics
+   */
+  async getUsageByDateRange(startDate: string, endDate: string): Promise<UsageStats> {
+    try {
+      return await invoke<UsageStats>("get_usage_by_date_range", { startDate, endDate });
+    } catch (error) {
+      logger.error("Failed to get usage by date range:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets usage statistics grouped by session
+   * @param since - Optional start date (YYYYMMDD)
+   * @param until - Optional end date (YYYYMMDD)
+   * @param order - Optional sort order ('asc' or 'desc')
+   * @returns Promise resolving to an array of session usage data
+   */
+  async getSessionStats(
+    since?: string,
+    until?: string,
+    order?: "asc" | "desc"
+  ): Promise<ProjectUsage[]> {
+    try {
+      return await invoke<ProjectUsage[]>("get_session_stats", {
+        since,
+        until,
+        order,
+      });
+    } catch (error) {
+      logger.error("Failed to get session stats:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the cost of each agent run with usage, latest first
+   * @param agentId - Optional agent to list the runs of
+   * @param days - Optional number of days to look back
+   * @returns Promise resolving to the usage of each run
+   */
+  async getAgentRunUsage(agentId?: number, days?: number): Promise<AgentRunUsage[]> {
+    try {
+      return await invoke<AgentRunUsage[]>("get_agent_run_usage", { agentId, days });
+    } catch (error) {
+      logger.error("Failed to get agent run usage:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets detailed usage entries with optional filtering
+   * @param limit - Optional limit for number of entries
+   * @returns Promise resolving to array of usage entries
+   */
+  async getUsageDetails(limit?: number): Promise<UsageEntry[]> {
+    try {
+      return await invoke<UsageEntry[]>("get_usage_details", { limit });
+    } catch (error) {
+      logger.error("Failed to get usage details:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Writes usage entries, or their totals by day, model, project or session, to a CSV or JSON file
+   * @param filePath - Where to write the export
+   * @param options - Format, grouping and date range of the export
+   * @returns Promise resolving to the number of rows written
+   */
+  async exportUsage(filePath: string, options: UsageExportOptions): Promise<number> {
+    try {
+      return await invoke<number>("export_usage", { filePath, options });
+    } catch (error) {
+      logger.error("Failed to export usage:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Drops the usage index and ingests every session file again
+   * @returns Promise resolving to the indexing counts
+   */
+  async rebuildUsageIndex(): Promise<UsageIndexStats> {
+    try {
+      return await invoke<UsageIndexStats>("rebuild_usage_index");
+    } catch (error) {
+      logger.error("Failed to rebuild usage index:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the prices usage is costed with
+   * @returns Promise resolving to the bundled prices and the user's overrides
    */
-  async cancelClaudeExecution(sessionId?: string): Promise<void> {
-    return invoke("cancel_claude_execution", { sessionId });
+  async getPricingTable(): Promise<PricingTable> {
+    try {
+      return await invoke<PricingTable>("get_pricing_table");
+    } catch (error) {
+      logger.error("Failed to get pricing table:", error);
+      throw error;
+    }
   },
 
   /**
-   * Lists all currently running Claude sessions
-   * @returns Promise resolving to list of running Claude sessions
+   * Replaces the user's price overrides; indexed usage is repriced on its next update
+   * @param overrides - Prices checked ahead of the bundled ones
+   * @returns Promise resolving to the updated pricing table
    */
-  async listRunningClaudeSessions(): Promise<unknown[]> {
-    return invoke("list_running_claude_sessions");
+  async setPricingOverrides(overrides: ModelPrice[]): Promise<PricingTable> {
+    try {
+      return await invoke<PricingTable>("set_pricing_overrides", { overrides });
+    } catch (error) {
+      logger.error("Failed to set pricing overrides:", error);
+      throw error;
+    }
   },
 
   /**
-   * Gets live output from a Claude session
-   * @param sessionId - The session ID to get output for
-   * @returns Promise resolving to the current live output
+   * Replaces the user's price overrides with the prices in a JSON file
+   * @param path - Path of a JSON list of prices, or an object with a `prices` list
+   * @returns Promise resolving to the updated pricing table
    */
-  async getClaudeSessionOutput(sessionId: string): Promise<string> {
-    return invoke("get_claude_session_output", { sessionId });
+  async importPricingFile(path: string): Promise<PricingTable> {
+    try {
+      return await invoke<PricingTable>("import_pricing_file", { path });
+    } catch (error) {
+      logger.error("Failed to import pricing file:", error);
+      throw error;
+    }
   },
 
   /**
-   * Lists files and directories in a given path
+   * Lists all budgets, global ones first
+   * @returns Promise resolving to the budgets
    */
-  async listDirectoryContents(directoryPath: string): Promise<FileEntry[]> {
-    return invoke("list_directory_contents", { directoryPath });
+  async listBudgets(): Promise<Budget[]> {
+    try {
+      return await invoke<Budget[]>("list_budgets");
+    } catch (error) {
+      logger.error("Failed to list budgets:", error);
+      throw error;
+    }
   },
 
   /**
-   * Searches for files and directories matching a pattern
+   * Creates a budget, or updates the one with the same ID
+   * @param budget - The budget to save
+   * @returns Promise resolving to the saved budget
    */
-  async searchFiles(basePath: string, query: string): Promise<FileEntry[]> {
-    return invoke("search_files", { basePath, query });
+  async saveBudget(budget: Budget): Promise<Budget> {
+    try {
+      return await invoke<Budget>("save_budget", { budget });
+    } catch (error) {
+      logger.error("Failed to save budget:", error);
+      throw error;
+    }
   },
 
   /**
-   * Gets overall usage statistics
-   * @returns Promise resolving to usage statistics
+   * Deletes a budget
+   * @param id - The budget ID
    */
-  async getUsageStats(): Promise<UsageStats> {
+  async deleteBudget(id: number): Promise<void> {
     try {
-      return await invoke<UsageStats>("get_usage_stats");
+      await invoke("delete_budget", { id });
     } catch (error) {
-      logger.error("Failed to get usage stats:", error);
+      logger.error("Failed to delete budget:", error);
       throw error;
     }
   },
 
   /**
-   * Gets usage statistics filtered by date range
-   * @param startDate - Start date (ISO format)
-   * @param endDate - End date (ISO format)
-   * @returns Promise resolving to usage statistics
+   * Gets the spend of every budget over its current period
+   * @returns Promise resolving to the budget statuses
    */
-  async getUsageByDateRange(startDate: string, endDate: string): Promise<UsageStats> {
+  async getBudgetStatuses(): Promise<BudgetStatus[]> {
     try {
-      return await invoke<UsageStats>("get_usage_by_date_range", { startDate, endDate });
+      return await invoke<BudgetStatus[]>("get_budget_statuses");
     } catch (error) {
-      logger.error("Failed to get usage by date range:", error);
+      logger.error("Failed to get budget statuses:", error);
       throw error;
     }
   },
 
   /**
-   * Gets usage statistics grouped by session
-   * @param since - Optional start date (YYYYMMDD)
-   * @param until - Optional end date (YYYYMMDD)
-   * @param order - Optional sort order ('asc' or 'desc')
-   * @returns Promise resolving to an array of session usage data
+   * Gets when the override of hard budgets ends
+   * @returns Promise resolving to the end of the override, or null if none is active
    */
-  async getSessionStats(
-    since?: string,
-    until?: string,
-    order?: "asc" | "desc"
-  ): Promise<ProjectUsage[]> {
+  async getBudgetOverride(): Promise<string | null> {
     try {
-      return await invoke<ProjectUsage[]>("get_session_stats", {
-        since,
-        until,
-        order,
-      });
+      return await invoke<string | null>("get_budget_override");
     } catch (error) {
-      logger.error("Failed to get session stats:", error);
+      logger.error("Failed to get budget override:", error);
       throw error;
     }
   },
 
   /**
-   * Gets detailed usage entries with optional filtering
-   * @param limit - Optional limit for number of entries
-   * @returns Promise resolving to array of usage entries
+   * Lets launches through exceeded hard budgets for a while, or stops letting them through
+   * @param hours - How long to override hard budgets; omit to end the override
+   * @returns Promise resolving to the end of the override, or null once ended
    */
-  async getUsageDetails(limit?: number): Promise<UsageEntry[]> {
+  async setBudgetOverride(hours?: number): Promise<string | null> {
     try {
-      return await invoke<UsageEntry[]>("get_usage_details", { limit });
+      return await invoke<string | null>("set_budget_override", { hours });
     } catch (error) {
-      logger.error("Failed to get usage details:", error);
+      logger.error("Failed to set budget override:", error);
       throw error;
     }
   },
@@ -2032,7 +3713,7 @@ export const api = {
   },
 
   /**
-   * Deletes a session and its associated data
+   * Moves a session, its todos and its checkpoint timeline to the trash
    * @param sessionId - The session ID to delete
    * @param projectId - The project ID containing the session
    */
@@ -2045,6 +3726,319 @@ export const api = {
     }
   },
 
+  /**
+   * Gets a session's title, tags, pinned flag and notes
+   * @param sessionId - The session ID
+   * @returns Promise resolving to the metadata, or null if none was set
+   */
+  async getSessionMeta(sessionId: string): Promise<SessionMeta | null> {
+    try {
+      return await invoke<SessionMeta | null>("get_session_meta", { sessionId });
+    } catch (error) {
+      logger.error("Failed to get session metadata:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Sets a session's title, tags, pinned flag and notes
+   * @param meta - The metadata to store; archived is ignored
+   * @returns Promise resolving to the stored metadata
+   */
+  async setSessionMeta(meta: SessionMeta): Promise<SessionMeta> {
+    try {
+      return await invoke<SessionMeta>("set_session_meta", { meta });
+    } catch (error) {
+      logger.error("Failed to set session metadata:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists every session tag in use
+   * @returns Promise resolving to the tags, alphabetically
+   */
+  async listSessionTags(): Promise<string[]> {
+    try {
+      return await invoke<string[]>("list_session_tags");
+    } catch (error) {
+      logger.error("Failed to list session tags:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Moves a session and its timeline out of the active listing without deleting them
+   * @param sessionId - The session ID to archive
+   * @param projectId - The project ID containing the session
+   */
+  async archiveSession(sessionId: string, projectId: string): Promise<void> {
+    try {
+      await invoke("archive_session", { sessionId, projectId });
+    } catch (error) {
+      await handleApiError(error as Error, { operation: 'archiveSession', sessionId, projectId });
+      throw error;
+    }
+  },
+
+  /**
+   * Moves an archived session back into the active listing
+   * @param sessionId - The session ID to unarchive
+   * @param projectId - The project ID containing the session
+   */
+  async unarchiveSession(sessionId: string, projectId: string): Promise<void> {
+    try {
+      await invoke("unarchive_session", { sessionId, projectId });
+    } catch (error) {
+      await handleApiError(error as Error, { operation: 'unarchiveSession', sessionId, projectId });
+      throw error;
+    }
+  },
+
+  /**
+   * Lists sessions in the trash, most recently deleted first
+   * @returns Promise resolving to the trashed sessions
+   */
+  async listTrashedSessions(): Promise<TrashedSession[]> {
+    try {
+      return await invoke<TrashedSession[]>("list_trashed_sessions");
+    } catch (error) {
+      logger.error("Failed to list trashed sessions:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Moves a trashed session back into its project
+   * @param trashId - ID of the trash entry
+   * @returns Promise resolving to the restored entry
+   */
+  async restoreTrashedSession(trashId: string): Promise<TrashedSession> {
+    try {
+      return await invoke<TrashedSession>("restore_trashed_session", { trashId });
+    } catch (error) {
+      logger.error("Failed to restore trashed session:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deletes a trashed session for good
+   * @param trashId - ID of the trash entry
+   */
+  async purgeTrashedSession(trashId: string): Promise<void> {
+    try {
+      await invoke("purge_trashed_session", { trashId });
+    } catch (error) {
+      logger.error("Failed to purge trashed session:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deletes every trashed session for good
+   * @returns Promise resolving to the number of sessions purged
+   */
+  async emptySessionTrash(): Promise<number> {
+    try {
+      return await invoke<number>("empty_session_trash");
+    } catch (error) {
+      logger.error("Failed to empty session trash:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets how many days trashed sessions are kept
+   */
+  async getSessionTrashRetention(): Promise<number> {
+    try {
+      return await invoke<number>("get_session_trash_retention");
+    } catch (error) {
+      logger.error("Failed to get trash retention:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Sets how many days newly trashed sessions are kept
+   * @param days - Retention in days, at least 1
+   */
+  async setSessionTrashRetention(days: number): Promise<void> {
+    try {
+      await invoke("set_session_trash_retention", { days });
+    } catch (error) {
+      logger.error("Failed to set trash retention:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Forks a session from one of its messages into a new session of the same project
+   * @param sessionId - The session to fork
+   * @param projectId - The project ID
+   * @param messageIndex - Index of the last message to copy, as in the session history
+   * @returns Promise resolving to the new session's fork record
+   */
+  async forkSession(sessionId: string, projectId: string, messageIndex: number): Promise<SessionFork> {
+    try {
+      return await invoke<SessionFork>("fork_session", { sessionId, projectId, messageIndex });
+    } catch (error) {
+      logger.error("Failed to fork session:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Forks a session from one of its messages and continues the fork with a new prompt
+   * @param sessionId - The session to fork
+   * @param projectId - The project ID
+   * @param messageIndex - Index of the last message to copy, as in the session history
+   * @param prompt - The prompt to continue the fork with
+   * @param model - The model to use
+   * @returns Promise resolving to the new session's fork record
+   */
+  async forkAndResumeSession(
+    sessionId: string,
+    projectId: string,
+    messageIndex: number,
+    prompt: string,
+    model: string
+  ): Promise<SessionFork> {
+    const fork = await this.forkSession(sessionId, projectId, messageIndex);
+    await this.resumeClaudeCode(fork.project_path, fork.session_id, prompt, model);
+    return fork;
+  },
+
+  /**
+   * Lists the sessions of a project that were forked from another session
+   * @param projectId - The project ID
+   * @returns Promise resolving to the fork records
+   */
+  async listSessionForks(projectId: string): Promise<SessionFork[]> {
+    try {
+      return await invoke<SessionFork[]>("list_session_forks", { projectId });
+    } catch (error) {
+      logger.error("Failed to list session forks:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the tree of forks a session belongs to, from the session all of them descend from
+   * @param sessionId - The session ID
+   * @param projectId - The project ID
+   * @returns Promise resolving to the root of the tree
+   */
+  async getSessionTree(sessionId: string, projectId: string): Promise<SessionTreeNode> {
+    try {
+      return await invoke<SessionTreeNode>("get_session_tree", { sessionId, projectId });
+    } catch (error) {
+      logger.error("Failed to get session tree:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Shows what a session JSONL or session bundle would import
+   * @param path - Path of the file to import
+   * @returns Promise resolving to the preview
+   */
+  async previewSessionImport(path: string): Promise<SessionImportPreview> {
+    try {
+      return await invoke<SessionImportPreview>("preview_session_import", { path });
+    } catch (error) {
+      logger.error("Failed to preview session import:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Imports a session JSONL or session bundle into the project it belongs to locally
+   * @param path - Path of the file to import
+   * @param options - Optional local project path and ID conflict handling
+   * @returns Promise resolving to the imported session
+   */
+  async importSession(path: string, options?: SessionImportOptions): Promise<SessionImportResult> {
+    try {
+      return await invoke<SessionImportResult>("import_session", { path, options });
+    } catch (error) {
+      logger.error("Failed to import session:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Packs a session and its todos into a compressed bundle for importing elsewhere
+   * @param sessionId - The session ID
+   * @param projectId - The project ID
+   * @param path - Path to write the bundle to
+   */
+  async exportSessionBundle(sessionId: string, projectId: string, path: string): Promise<void> {
+    try {
+      await invoke("export_session_bundle", { sessionId, projectId, path });
+    } catch (error) {
+      logger.error("Failed to export session bundle:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets a session's generated title and summary
+   * @param sessionId - The session ID
+   * @returns Promise resolving to the summary, or null if none was generated yet
+   */
+  async getSessionSummary(sessionId: string): Promise<SessionSummary | null> {
+    try {
+      return await invoke<SessionSummary | null>("get_session_summary", { sessionId });
+    } catch (error) {
+      logger.error("Failed to get session summary:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Generates or regenerates a session's title and summary with Claude
+   * @param sessionId - The session ID
+   * @param projectId - The project ID containing the session
+   * @param model - Model to use instead of the summarizer setting
+   * @returns Promise resolving to the new summary
+   */
+  async summarizeSession(sessionId: string, projectId: string, model?: string): Promise<SessionSummary> {
+    try {
+      return await invoke<SessionSummary>("summarize_session", { sessionId, projectId, model });
+    } catch (error) {
+      logger.error("Failed to summarize session:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the background session summarizer settings
+   * @returns Promise resolving to the settings
+   */
+  async getSessionSummarizerSettings(): Promise<SessionSummarizerSettings> {
+    try {
+      return await invoke<SessionSummarizerSettings>("get_session_summarizer_settings");
+    } catch (error) {
+      logger.error("Failed to get session summarizer settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the background session summarizer settings
+   * @param settings - The settings to save
+   */
+  async setSessionSummarizerSettings(settings: SessionSummarizerSettings): Promise<void> {
+    try {
+      await invoke("set_session_summarizer_settings", { settings });
+    } catch (error) {
+      logger.error("Failed to save session summarizer settings:", error);
+      throw error;
+    }
+  },
+
   /**
    * Gets checkpoint state statistics (for debugging/monitoring)
    */
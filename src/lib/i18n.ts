@@ -672,6 +672,8 @@ export interface Translations {
     agentExportedSuccessfully: string;
     agentImportedSuccessfully: string;
     agentImportedFromGitHub: string;
+    missingDependenciesConfirm: string;
+    unsignedAgentConfirm: string;
     deleteAgentTitle: string;
     deleteAgentConfirm: string;
     deleteAgentDesc: string;
@@ -104,7 +104,7 @@ export const ar: Translations = {
     sessionName: "اسم الجلسة",
     deleteSession: "حذف الجلسة",
     deleteSessionConfirm: "تأكيد الحذف",
-    deleteSessionDesc: "هل أنت متأكد من أنك تريد حذف الجلسة \"{sessionId}\"؟ لا يمكن التراجع عن هذا الإجراء.",
+    deleteSessionDesc: "هل أنت متأكد من أنك تريد حذف الجلسة \"{sessionId}\"؟ سيتم نقلها إلى سلة المهملات، حيث يمكن استعادتها حتى انتهاء صلاحيتها.",
     deletingSession: "جاري الحذف...",
     sessionDeleted: "تم حذف الجلسة",
     failedToDeleteSession: "فشل في حذف الجلسة",
@@ -640,6 +640,8 @@ export const ar: Translations = {
     agentExportedSuccessfully: "تم تصدير الوكيل بنجاح",
     agentImportedSuccessfully: "تم استيراد الوكيل بنجاح",
     agentImportedFromGitHub: "تم استيراد الوكيل من GitHub بنجاح",
+    missingDependenciesConfirm: "يتطلب هذا الوكيل تبعيات غير مهيأة:\n{missing}\n\nهل تريد الاستيراد على أي حال؟",
+    unsignedAgentConfirm: "هذا الوكيل غير موقّع بمفتاح موثوق. هل تريد الاستيراد على أي حال؟",
     deleteAgentTitle: "حذف الوكيل",
     deleteAgentConfirm: "هل أنت متأكد أنك تريد حذف هذا الوكيل؟",
     deleteAgentDesc: "لا يمكن التراجع عن هذا الإجراء. سيتم حذف الوكيل بشكل دائم.",
@@ -106,7 +106,7 @@ export const de: Translations = {
     sessionName: "Sitzungsname",
     deleteSession: "Sitzung löschen",
     deleteSessionConfirm: "Löschung bestätigen",
-    deleteSessionDesc: "Sind Sie sicher, dass Sie die Sitzung \"{sessionId}\" löschen möchten? Diese Aktion kann nicht rückgängig gemacht werden.",
+    deleteSessionDesc: "Sind Sie sicher, dass Sie die Sitzung \"{sessionId}\" löschen möchten? Sie wird in den Papierkorb verschoben und kann dort bis zum Ablauf wiederhergestellt werden.",
     deletingSession: "Wird gelöscht...",
     sessionDeleted: "Sitzung gelöscht",
     failedToDeleteSession: "Fehler beim Löschen der Sitzung",
@@ -671,6 +671,8 @@ export const de: Translations = {
     agentExportedSuccessfully: "Agent erfolgreich exportiert",
     agentImportedSuccessfully: "Agent erfolgreich importiert",
     agentImportedFromGitHub: "Agent erfolgreich von GitHub importiert",
+    missingDependenciesConfirm: "Dieser Agent benötigt Abhängigkeiten, die nicht konfiguriert sind:\n{missing}\n\nTrotzdem importieren?",
+    unsignedAgentConfirm: "Dieser Agent ist nicht mit einem vertrauenswürdigen Schlüssel signiert. Trotzdem importieren?",
     deleteAgentTitle: "Agent löschen",
     deleteAgentConfirm: "Sind Sie sicher, dass Sie diesen Agenten löschen möchten?",
     deleteAgentDesc:
@@ -105,7 +105,7 @@ export const en: Translations = {
     sessionName: "Session Name",
     deleteSession: "Delete Session",
     deleteSessionConfirm: "Confirm Deletion",
-    deleteSessionDesc: "Are you sure you want to delete session \"{sessionId}\"? This action cannot be undone.",
+    deleteSessionDesc: "Are you sure you want to delete session \"{sessionId}\"? It will be moved to the trash, where it can be restored until it expires.",
     deletingSession: "Deleting...",
     sessionDeleted: "Session deleted",
     failedToDeleteSession: "Failed to delete session",
@@ -654,6 +654,8 @@ export const en: Translations = {
     agentExportedSuccessfully: "Agent exported successfully",
     agentImportedSuccessfully: "Agent imported successfully",
     agentImportedFromGitHub: "Agent imported from GitHub successfully",
+    missingDependenciesConfirm: "This agent requires dependencies that are not configured:\n{missing}\n\nImport anyway?",
+    unsignedAgentConfirm: "This agent is not signed by a trusted key. Import anyway?",
     deleteAgentTitle: "Delete Agent",
     deleteAgentConfirm: "Are you sure you want to delete this agent?",
     deleteAgentDesc: "This action cannot be undone. The agent will be permanently deleted.",
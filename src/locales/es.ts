@@ -105,7 +105,7 @@ export const es: Translations = {
     sessionName: "Nombre de la sesión",
     deleteSession: "Eliminar Sesión",
     deleteSessionConfirm: "Confirmar Eliminación",
-    deleteSessionDesc: "¿Estás seguro de que quieres eliminar la sesión \"{sessionId}\"? Esta acción no se puede deshacer.",
+    deleteSessionDesc: "¿Estás seguro de que quieres eliminar la sesión \"{sessionId}\"? Se moverá a la papelera, donde podrá restaurarse hasta que caduque.",
     deletingSession: "Eliminando...",
     sessionDeleted: "Sesión eliminada",
     failedToDeleteSession: "Error al eliminar la sesión",
@@ -665,6 +665,8 @@ export const es: Translations = {
     agentExportedSuccessfully: "Agente exportado exitosamente",
     agentImportedSuccessfully: "Agente importado exitosamente",
     agentImportedFromGitHub: "Agente importado desde GitHub exitosamente",
+    missingDependenciesConfirm: "Este agente requiere dependencias que no están configuradas:\n{missing}\n\n¿Importar de todos modos?",
+    unsignedAgentConfirm: "Este agente no está firmado con una clave de confianza. ¿Importar de todos modos?",
     deleteAgentTitle: "Eliminar agente",
     deleteAgentConfirm: "¿Estás seguro de que quieres eliminar este agente?",
     deleteAgentDesc: "Esta acción no se puede deshacer. El agente será eliminado permanentemente.",
@@ -106,7 +106,7 @@ export const fr: Translations = {
     sessionName: "Nom de la session",
     deleteSession: "Supprimer la Session",
     deleteSessionConfirm: "Confirmer la Suppression",
-    deleteSessionDesc: "Êtes-vous sûr de vouloir supprimer la session \"{sessionId}\" ? Cette action ne peut pas être annulée.",
+    deleteSessionDesc: "Êtes-vous sûr de vouloir supprimer la session \"{sessionId}\" ? Elle sera placée dans la corbeille, d'où elle pourra être restaurée jusqu'à son expiration.",
     deletingSession: "Suppression...",
     sessionDeleted: "Session supprimée",
     failedToDeleteSession: "Échec de la suppression de la session",
@@ -670,6 +670,8 @@ export const fr: Translations = {
     agentExportedSuccessfully: "Agent exporté avec succès",
     agentImportedSuccessfully: "Agent importé avec succès",
     agentImportedFromGitHub: "Agent importé depuis GitHub avec succès",
+    missingDependenciesConfirm: "Cet agent nécessite des dépendances qui ne sont pas configurées :\n{missing}\n\nImporter quand même ?",
+    unsignedAgentConfirm: "Cet agent n'est pas signé par une clé de confiance. Importer quand même ?",
     deleteAgentTitle: "Supprimer l'agent",
     deleteAgentConfirm: "Êtes-vous sûr de vouloir supprimer cet agent ?",
     deleteAgentDesc: "Cette action ne peut pas être annulée. L'agent sera définitivement supprimé.",
@@ -105,7 +105,7 @@ export const hi: Translations = {
     sessionName: "सेशन का नाम",
     deleteSession: "सेशन हटाएं",
     deleteSessionConfirm: "हटाने की पुष्टि करें",
-    deleteSessionDesc: "क्या आप वाकई सेशन \"{sessionId}\" को हटाना चाहते हैं? यह क्रिया पूर्ववत नहीं की जा सकती।",
+    deleteSessionDesc: "क्या आप वाकई सेशन \"{sessionId}\" को हटाना चाहते हैं? इसे ट्रैश में ले जाया जाएगा, जहाँ से समय-सीमा समाप्त होने तक इसे पुनर्स्थापित किया जा सकता है।",
     deletingSession: "हटा रहा है...",
     sessionDeleted: "सेशन हटा दिया गया",
     failedToDeleteSession: "सेशन हटाने में विफल",
@@ -658,6 +658,8 @@ export const hi: Translations = {
     agentExportedSuccessfully: "एजेंट सफलतापूर्वक निर्यात किया गया",
     agentImportedSuccessfully: "एजेंट सफलतापूर्वक आयात किया गया",
     agentImportedFromGitHub: "GitHub से एजेंट सफलतापूर्वक आयात किया गया",
+    missingDependenciesConfirm: "इस एजेंट को ऐसी निर्भरताएँ चाहिए जो कॉन्फ़िगर नहीं हैं:\n{missing}\n\nफिर भी आयात करें?",
+    unsignedAgentConfirm: "यह एजेंट किसी विश्वसनीय कुंजी से हस्ताक्षरित नहीं है। फिर भी आयात करें?",
     deleteAgentTitle: "एजेंट हटाएं",
     deleteAgentConfirm: "क्या आप वाकई इस एजेंट को हटाना चाहते हैं?",
     deleteAgentDesc: "यह क्रिया पूर्ववत नहीं की जा सकती। एजेंट स्थायी रूप से हटा दिया जाएगा।",
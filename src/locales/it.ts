@@ -104,7 +104,7 @@ export const it: Translations = {
     sessionName: "Nome sessione",
     deleteSession: "Elimina Sessione",
     deleteSessionConfirm: "Conferma Eliminazione",
-    deleteSessionDesc: "Sei sicuro di voler eliminare la sessione \"{sessionId}\"? Questa azione non può essere annullata.",
+    deleteSessionDesc: "Sei sicuro di voler eliminare la sessione \"{sessionId}\"? Verrà spostata nel cestino, dove potrà essere ripristinata fino alla scadenza.",
     deletingSession: "Eliminazione...",
     sessionDeleted: "Sessione eliminata",
     failedToDeleteSession: "Impossibile eliminare la sessione",
@@ -652,6 +652,8 @@ export const it: Translations = {
     agentExportedSuccessfully: "Agente esportato con successo",
     agentImportedSuccessfully: "Agente importato con successo",
     agentImportedFromGitHub: "Agente importato da GitHub con successo",
+    missingDependenciesConfirm: "Questo agente richiede dipendenze non configurate:\n{missing}\n\nImportare comunque?",
+    unsignedAgentConfirm: "Questo agente non è firmato con una chiave attendibile. Importare comunque?",
     deleteAgentTitle: "Elimina agente",
     deleteAgentConfirm: "Sei sicuro di voler eliminare questo agente?",
     deleteAgentDesc:
@@ -105,7 +105,7 @@ export const ja: Translations = {
     sessionName: "セッション名",
     deleteSession: "セッションを削除",
     deleteSessionConfirm: "削除の確認",
-    deleteSessionDesc: "セッション「{sessionId}」を削除してもよろしいですか？この操作は元に戻せません。",
+    deleteSessionDesc: "セッション「{sessionId}」を削除してもよろしいですか？ゴミ箱に移動され、期限が切れるまで復元できます。",
     deletingSession: "削除中...",
     sessionDeleted: "セッションが削除されました",
     failedToDeleteSession: "セッションの削除に失敗しました",
@@ -657,6 +657,8 @@ export const ja: Translations = {
     agentExportedSuccessfully: "エージェントのエクスポートが成功しました",
     agentImportedSuccessfully: "エージェントのインポートが成功しました",
     agentImportedFromGitHub: "GitHubからエージェントのインポートが成功しました",
+    missingDependenciesConfirm: "このエージェントには未設定の依存関係が必要です：\n{missing}\n\nそれでもインポートしますか？",
+    unsignedAgentConfirm: "このエージェントは信頼されたキーで署名されていません。それでもインポートしますか？",
     deleteAgentTitle: "エージェントを削除",
     deleteAgentConfirm: "このエージェントを削除してもよろしいですか？",
     deleteAgentDesc: "この操作は元に戻せません。エージェントは完全に削除されます。",
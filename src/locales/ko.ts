@@ -105,7 +105,7 @@ export const ko: Translations = {
     sessionName: "세션 이름",
     deleteSession: "세션 삭제",
     deleteSessionConfirm: "삭제 확인",
-    deleteSessionDesc: "세션 \"{sessionId}\"을(를) 삭제하시겠습니까? 이 작업은 되돌릴 수 없습니다.",
+    deleteSessionDesc: "세션 \"{sessionId}\"을(를) 삭제하시겠습니까? 휴지통으로 이동되며 만료되기 전까지 복원할 수 있습니다.",
     deletingSession: "삭제 중...",
     sessionDeleted: "세션이 삭제되었습니다",
     failedToDeleteSession: "세션 삭제 실패",
@@ -656,6 +656,8 @@ export const ko: Translations = {
     agentExportedSuccessfully: "에이전트가 성공적으로 내보내졌습니다",
     agentImportedSuccessfully: "에이전트가 성공적으로 가져와졌습니다",
     agentImportedFromGitHub: "GitHub에서 에이전트가 성공적으로 가져와졌습니다",
+    missingDependenciesConfirm: "이 에이전트에는 구성되지 않은 종속성이 필요합니다:\n{missing}\n\n그래도 가져오시겠습니까?",
+    unsignedAgentConfirm: "이 에이전트는 신뢰할 수 있는 키로 서명되지 않았습니다. 그래도 가져오시겠습니까?",
     deleteAgentTitle: "에이전트 삭제",
     deleteAgentConfirm: "이 에이전트를 삭제하시겠습니까?",
     deleteAgentDesc: "이 작업은 취소할 수 없습니다. 에이전트가 영구적으로 삭제됩니다.",
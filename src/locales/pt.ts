@@ -105,7 +105,7 @@ export const pt: Translations = {
     sessionName: "Nome da sessão",
     deleteSession: "Excluir Sessão",
     deleteSessionConfirm: "Confirmar Exclusão",
-    deleteSessionDesc: "Tem certeza de que deseja excluir a sessão \"{sessionId}\"? Esta ação não pode ser desfeita.",
+    deleteSessionDesc: "Tem certeza de que deseja excluir a sessão \"{sessionId}\"? Ela será movida para a lixeira, onde poderá ser restaurada até expirar.",
     deletingSession: "Excluindo...",
     sessionDeleted: "Sessão excluída",
     failedToDeleteSession: "Falha ao excluir sessão",
@@ -662,6 +662,8 @@ export const pt: Translations = {
     agentExportedSuccessfully: "Agente exportado com sucesso",
     agentImportedSuccessfully: "Agente importado com sucesso",
     agentImportedFromGitHub: "Agente importado do GitHub com sucesso",
+    missingDependenciesConfirm: "Este agente requer dependências que não estão configuradas:\n{missing}\n\nImportar mesmo assim?",
+    unsignedAgentConfirm: "Este agente não está assinado por uma chave confiável. Importar mesmo assim?",
     deleteAgentTitle: "Excluir agente",
     deleteAgentConfirm: "Tem certeza que deseja excluir este agente?",
     deleteAgentDesc: "Esta ação não pode ser desfeita. O agente será excluído permanentemente.",
@@ -105,7 +105,7 @@ export const ru: Translations = {
     sessionName: "Название сессии",
     deleteSession: "Удалить Сессию",
     deleteSessionConfirm: "Подтвердить Удаление",
-    deleteSessionDesc: "Вы уверены, что хотите удалить сессию \"{sessionId}\"? Это действие нельзя отменить.",
+    deleteSessionDesc: "Вы уверены, что хотите удалить сессию \"{sessionId}\"? Она будет перемещена в корзину, откуда её можно восстановить до истечения срока хранения.",
     deletingSession: "Удаление...",
     sessionDeleted: "Сессия удалена",
     failedToDeleteSession: "Не удалось удалить сессию",
@@ -664,6 +664,8 @@ export const ru: Translations = {
     agentExportedSuccessfully: "Агент успешно экспортирован",
     agentImportedSuccessfully: "Агент успешно импортирован",
     agentImportedFromGitHub: "Агент успешно импортирован из GitHub",
+    missingDependenciesConfirm: "Этому агенту нужны ненастроенные зависимости:\n{missing}\n\nВсё равно импортировать?",
+    unsignedAgentConfirm: "Этот агент не подписан доверенным ключом. Всё равно импортировать?",
     deleteAgentTitle: "Удалить агента",
     deleteAgentConfirm: "Вы уверены, что хотите удалить этого агента?",
     deleteAgentDesc: "Это действие нельзя отменить. Агент будет удален навсегда.",
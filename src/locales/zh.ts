@@ -105,7 +105,7 @@ export const zh: Translations = {
     sessionName: "会话名称",
     deleteSession: "删除会话",
     deleteSessionConfirm: "确认删除",
-    deleteSessionDesc: "您确定要删除会话 \"{sessionId}\" 吗？此操作无法撤销。",
+    deleteSessionDesc: "您确定要删除会话 \"{sessionId}\" 吗？会话将被移到回收站，在过期前可以恢复。",
     deletingSession: "删除中...",
     sessionDeleted: "会话已删除",
     failedToDeleteSession: "删除会话失败",
@@ -649,6 +649,8 @@ export const zh: Translations = {
     agentExportedSuccessfully: "智能体导出成功",
     agentImportedSuccessfully: "智能体导入成功",
     agentImportedFromGitHub: "从 GitHub 导入智能体成功",
+    missingDependenciesConfirm: "该智能体需要以下尚未配置的依赖：\n{missing}\n\n仍要导入吗？",
+    unsignedAgentConfirm: "该智能体未使用受信任的密钥签名。仍要导入吗？",
     deleteAgentTitle: "删除智能体",
     deleteAgentConfirm: "确定要删除此智能体吗？",
     deleteAgentDesc: "此操作无法撤销。智能体将被永久删除。",